@@ -0,0 +1,33 @@
+//! Generation of the key pairs of existing actors.
+
+use std::sync::Arc;
+
+use adapters::outbound::clock::SystemClock;
+use adapters::outbound::persistence::postgres::actor_key_repository::PgActorKeyRepository;
+use application::ports::inbound::GenerateActorKeys;
+use application::usecases::GenerateActorKeysUseCase;
+use sqlx::PgPool;
+
+use crate::crypto;
+
+pub async fn generate(postgres: PgPool, batch_size: u32) {
+    let generate_uc = GenerateActorKeysUseCase::new(
+        Arc::new(PgActorKeyRepository::new(postgres)),
+        Arc::new(crypto::adapter()),
+        Arc::new(SystemClock::new()),
+    );
+
+    let mut generated = 0;
+    loop {
+        let count = generate_uc
+            .generate_missing(batch_size.max(1))
+            .await
+            .expect("Are tables already created?");
+        if count == 0 {
+            break;
+        }
+        generated += count;
+    }
+
+    println!("{} actor keys generated.", generated);
+}
@@ -0,0 +1,67 @@
+//! Bloom filter of breached passwords, built from Have I Been Pwned data.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use adapters::outbound::password::BloomFilterBuilder;
+
+/// Decode a 40 hexadecimal characters SHA-1 digest.
+fn decode(digest: &str) -> Option<[u8; 20]> {
+    if digest.len() != 40 || !digest.is_ascii() {
+        return None;
+    }
+
+    let mut sha1 = [0; 20];
+    for (i, byte) in sha1.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digest[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(sha1)
+}
+
+/// Files to read with the prefix of their digests: range files of a
+/// directory, or a single file of full digests.
+fn sources(input: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    if !input.is_dir() {
+        return Ok(vec![(String::new(), input.to_path_buf())]);
+    }
+
+    let mut sources = Vec::new();
+    for entry in std::fs::read_dir(input)? {
+        let path = entry?.path();
+        if let Some(prefix) = path.file_stem().and_then(|stem| stem.to_str()) {
+            sources.push((prefix.to_string(), path.clone()));
+        }
+    }
+    sources.sort();
+    Ok(sources)
+}
+
+/// Digests of every line of `sources`, as `<digest>:<count>`.
+fn digests(sources: &[(String, PathBuf)]) -> impl Iterator<Item = [u8; 20]> + '_ {
+    sources.iter().flat_map(|(prefix, path)| {
+        let file = File::open(path).expect("Cannot read breached passwords.");
+        BufReader::new(file).lines().filter_map(move |line| {
+            let line = line.expect("Cannot read breached passwords.");
+            let suffix = line.split(':').next()?.trim();
+            decode(&format!("{prefix}{suffix}"))
+        })
+    })
+}
+
+pub fn build(input: &Path, output: &Path, rate: f64) {
+    let sources = sources(input).expect("Cannot read breached passwords.");
+    let capacity = digests(&sources).count() as u64;
+
+    let mut filter = BloomFilterBuilder::new(capacity, rate);
+    for sha1 in digests(&sources) {
+        filter.insert(&sha1);
+    }
+
+    let file = File::create(output).expect("Cannot create the filter.");
+    filter
+        .write_to(BufWriter::new(file))
+        .expect("Cannot write the filter.");
+
+    println!("{} breached passwords written to {:?}.", capacity, output);
+}
@@ -0,0 +1,63 @@
+//! Cryptography configured from the environment, as on the server.
+
+use std::sync::Arc;
+
+use adapters::outbound::clock::SystemClock;
+use adapters::outbound::crypto::{CryptoAdapter, HmacBlindIndex, Keyring};
+
+/// ID of the key set with `MASTER_KEY`, as on the server.
+const LEGACY_KEY_ID: &str = "default";
+/// Commands never hash passwords, so Argon2 is never used.
+const ARGON2_PARAMS: (u32, u32, u32) = (19_456, 2, 1);
+
+/// Master keys from `MASTER_KEYS` or `MASTER_KEY`. Only the current key is
+/// used, to encrypt.
+fn keyring(salt: &[u8]) -> Keyring {
+    let key = match std::env::var("MASTER_KEYS") {
+        Ok(keys) => keys
+            .split(',')
+            .next()
+            .and_then(|key| key.split_once('='))
+            .map(|(id, key)| (id.to_string(), key.to_string()))
+            .expect("Invalid MASTER_KEYS."),
+        Err(_) => (
+            LEGACY_KEY_ID.to_string(),
+            std::env::var("MASTER_KEY").expect("MASTER_KEY env var is required."),
+        ),
+    };
+
+    Keyring::new(key.0, key.1.as_bytes(), salt).expect("Invalid master key.")
+}
+
+/// Email index from the current secret of `EMAIL_INDEX_KEYS`, or `SALT`.
+fn blind_index(salt: Vec<u8>) -> HmacBlindIndex {
+    match std::env::var("EMAIL_INDEX_KEYS") {
+        Ok(keys) => {
+            let (id, secret) = keys
+                .split(',')
+                .next()
+                .and_then(|key| key.split_once('='))
+                .expect("Invalid EMAIL_INDEX_KEYS.");
+            HmacBlindIndex::new(id, secret.as_bytes()).expect("Invalid EMAIL_INDEX_KEYS.")
+        }
+        Err(_) => HmacBlindIndex::legacy(salt),
+    }
+}
+
+/// Crypto adapter encrypting with the current master key.
+pub fn adapter() -> CryptoAdapter {
+    let salt = std::env::var("SALT")
+        .expect("SALT env var is required.")
+        .into_bytes();
+    let (memory_cost, iterations, parallelism) = ARGON2_PARAMS;
+    CryptoAdapter::new(
+        Arc::new(SystemClock::new()),
+        keyring(&salt),
+        salt.clone(),
+        blind_index(salt),
+        memory_cost,
+        iterations,
+        parallelism,
+    )
+    .expect("Invalid Argon2 parameters.")
+}
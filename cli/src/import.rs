@@ -0,0 +1,133 @@
+//! Import of users exported from another system.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use adapters::outbound::clock::SystemClock;
+use adapters::outbound::persistence::postgres::account_repository::PgAccountRepository;
+use application::dto::ImportedAccountDto;
+use application::ports::inbound::ImportAccounts;
+use application::usecases::ImportAccountsUseCase;
+use sqlx::PgPool;
+
+use crate::crypto;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    fn guess(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "jsonl" | "ndjson" => Some(Format::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// Users read from a file, with their line number.
+type Rows = Box<dyn Iterator<Item = (u64, Result<ImportedAccountDto, String>)>>;
+
+fn read(path: &Path, format: Format) -> std::io::Result<Rows> {
+    let file = File::open(path)?;
+    Ok(match format {
+        Format::Csv => {
+            let mut reader = csv::Reader::from_reader(file);
+            let headers = reader.headers()?.clone();
+            Box::new(reader.into_records().map(move |record| {
+                match record {
+                    Ok(record) => (
+                        record.position().map_or(0, csv::Position::line),
+                        record
+                            .deserialize(Some(&headers))
+                            .map_err(|err| err.to_string()),
+                    ),
+                    Err(err) => (
+                        err.position().map_or(0, csv::Position::line),
+                        Err(err.to_string()),
+                    ),
+                }
+            }))
+        }
+        Format::Jsonl => Box::new(
+            BufReader::new(file)
+                .lines()
+                .zip(1..)
+                .filter(|(line, _)| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+                .map(|(line, number)| {
+                    let account = line.map_err(|err| err.to_string()).and_then(|line| {
+                        serde_json::from_str(&line).map_err(|err| err.to_string())
+                    });
+                    (number, account)
+                }),
+        ),
+    })
+}
+
+pub async fn import(
+    postgres: PgPool,
+    path: &Path,
+    format: Option<Format>,
+    batch_size: usize,
+    dry_run: bool,
+) {
+    let format = format
+        .or_else(|| Format::guess(path))
+        .expect("Cannot guess the file format, use --format.");
+    let rows = read(path, format).expect("Cannot read the file.");
+
+    let import_uc = ImportAccountsUseCase::new(
+        Arc::new(PgAccountRepository::new(postgres)),
+        Arc::new(crypto::adapter()),
+        Arc::new(SystemClock::new()),
+    );
+
+    let (mut imported, mut skipped, mut invalid) = (0, 0, 0);
+    let mut rows = rows.peekable();
+    while rows.peek().is_some() {
+        let mut lines = Vec::with_capacity(batch_size);
+        let mut batch = Vec::with_capacity(batch_size);
+        for (line, account) in rows.by_ref().take(batch_size.max(1)) {
+            match account {
+                Ok(account) => {
+                    lines.push(line);
+                    batch.push(account);
+                }
+                Err(err) => {
+                    eprintln!("Line {}: {}", line, err);
+                    invalid += 1;
+                }
+            }
+        }
+
+        let report = import_uc
+            .import(batch, dry_run)
+            .await
+            .expect("Are tables already created?");
+        for (position, reason) in &report.invalid {
+            eprintln!("Line {}: {}", lines[*position], reason);
+        }
+        for id in &report.skipped {
+            eprintln!(
+                "User {:?} skipped: ID or email already in use.",
+                id.as_str()
+            );
+        }
+        imported += report.imported.len();
+        skipped += report.skipped.len();
+        invalid += report.invalid.len();
+    }
+
+    println!(
+        "{} users {}imported, {} skipped, {} invalid.",
+        imported,
+        if dry_run { "would be " } else { "" },
+        skipped,
+        invalid
+    );
+}
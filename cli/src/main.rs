@@ -1,3 +1,11 @@
+mod actor_keys;
+mod breach;
+mod crypto;
+mod import;
+
+use std::path::PathBuf;
+
+use adapters::outbound::persistence::postgres::audit_log::PgAuditLog;
 use clap::{command, Parser as _};
 use clap_derive::{Parser, Subcommand};
 use rand::{distributions::Alphanumeric, Rng};
@@ -29,19 +37,91 @@ enum Commands {
         /// If code is genereated randomly, what size should it be?
         #[clap(long, short)]
         size: Option<usize>,
+        /// How many accounts can be created with the code.
+        #[clap(long, short, default_value_t = 1)]
+        max_uses: i32,
+        /// Number of days before the code expires.
+        #[clap(long, short)]
+        expires_in: Option<i32>,
+    },
+    /// Grant or revoke a staff role, e.g. to bootstrap the first admin.
+    Role {
+        r#type: Type,
+        /// ID of the user.
+        user: String,
+        #[clap(value_parser = ["moderator", "admin"])]
+        role: String,
+    },
+    /// Inspect the security audit log.
+    Audit {
+        #[command(subcommand)]
+        cmd: Audit,
+    },
+    /// Import users exported from another system, with their password hash.
+    Import {
+        /// CSV or JSON Lines file of users, with `id`, `email`,
+        /// `password_hash` and optionally `locale` fields.
+        file: PathBuf,
+        /// Format of the file, guessed from its extension by default.
+        #[clap(long, short, value_enum)]
+        format: Option<import::Format>,
+        /// Users inserted at once.
+        #[clap(long, short, default_value_t = 500)]
+        batch_size: usize,
+        /// Report what would be imported without inserting anything.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Generate the signing keys of users without one, e.g. imported ones.
+    ActorKeys {
+        /// Keys generated at once.
+        #[clap(long, short, default_value_t = 100)]
+        batch_size: u32,
     },
+    /// Build the Bloom filter of breached passwords read by the server.
+    BreachFilter {
+        /// Directory of Have I Been Pwned range files, or file of SHA-1
+        /// digests as `<digest>:<count>` lines.
+        input: PathBuf,
+        /// Path of the filter.
+        output: PathBuf,
+        /// False positive rate, i.e. share of safe passwords refused.
+        #[clap(long, short, default_value_t = 0.001)]
+        rate: f64,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Audit {
+    /// Check that no security event was altered or removed.
+    Verify,
 }
 
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    if let Commands::BreachFilter {
+        input,
+        output,
+        rate,
+    } = &args.cmd
+    {
+        return breach::build(input, output, *rate);
+    }
+
     let postgres =
         PgPool::connect(&std::env::var("POSTGRES_URL").unwrap_or_else(|_| DEFAULT_PG_URL.into()))
             .await
             .expect("Cannot connect to PostgreSQL database.");
 
-    let args = Args::parse();
     match args.cmd {
-        Commands::Invite { r#type, code, size } => match r#type {
+        Commands::Invite {
+            r#type,
+            code,
+            size,
+            max_uses,
+            expires_in,
+        } => match r#type {
             Type::Add => {
                 let code = match code {
                     Some(code) => code,
@@ -52,10 +132,15 @@ async fn main() {
                         .collect(),
                 };
 
-                sqlx::query!(r#"INSERT INTO "invite_codes" (code) VALUES ($1)"#, code)
-                    .execute(&postgres)
-                    .await
-                    .expect("Are tables already created?");
+                sqlx::query!(
+                    r#"INSERT INTO "invite_codes" (code, max_uses, expires_at) VALUES ($1, $2, NOW() + make_interval(days => $3))"#,
+                    code,
+                    max_uses,
+                    expires_in
+                )
+                .execute(&postgres)
+                .await
+                .expect("Are tables already created?");
 
                 println!("Invite code {:?} has been created!", code);
             }
@@ -69,5 +154,57 @@ async fn main() {
                 println!("Invite code {:?} has been deleted.", code);
             }
         },
+        Commands::Role { r#type, user, role } => match r#type {
+            Type::Add => {
+                sqlx::query!(
+                    r#"INSERT INTO "user_roles" (user_id, role) VALUES ($1, $2) ON CONFLICT DO NOTHING"#,
+                    user,
+                    role
+                )
+                .execute(&postgres)
+                .await
+                .expect("Does the user exist?");
+
+                println!("{:?} is now {}.", user, role);
+            }
+            Type::Remove => {
+                sqlx::query!(
+                    r#"DELETE FROM "user_roles" WHERE user_id = $1 AND role = $2"#,
+                    user,
+                    role
+                )
+                .execute(&postgres)
+                .await
+                .expect("Are tables already created?");
+
+                println!("{:?} is no longer {}.", user, role);
+            }
+        },
+        Commands::Audit { cmd: Audit::Verify } => {
+            let key = std::env::var("AUDIT_LOG_KEY").expect("AUDIT_LOG_KEY env var is required.");
+            let report = PgAuditLog::new(postgres, key.as_bytes())
+                .verify()
+                .await
+                .expect("Are tables already created?");
+
+            if let Some(id) = report.broken_at {
+                eprintln!("Audit log chain is broken at event {}.", id);
+                std::process::exit(1);
+            }
+
+            println!("{} events verified.", report.verifier.count());
+            if let Some(head) = report.verifier.head() {
+                // Keep the head elsewhere to also detect removed trailing events.
+                println!("Head hash: {}", head);
+            }
+        }
+        Commands::Import {
+            file,
+            format,
+            batch_size,
+            dry_run,
+        } => import::import(postgres, &file, format, batch_size, dry_run).await,
+        Commands::ActorKeys { batch_size } => actor_keys::generate(postgres, batch_size).await,
+        Commands::BreachFilter { .. } => unreachable!(),
     }
 }
@@ -0,0 +1,13 @@
+-- Sliding-window rate limit counters.
+
+CREATE TABLE IF NOT EXISTS rate_limits (
+  key           TEXT    NOT NULL,
+  window_size   BIGINT  NOT NULL,
+  window_start  BIGINT  NOT NULL,
+  hits          BIGINT  NOT NULL DEFAULT 0,
+  PRIMARY KEY (key, window_size, window_start)
+);
+
+-- Stale windows of every key are purged by start.
+CREATE INDEX IF NOT EXISTS rate_limits_window_start_idx
+  ON rate_limits (window_start);
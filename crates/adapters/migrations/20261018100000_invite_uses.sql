@@ -0,0 +1,25 @@
+-- Invitation codes with several uses, expiry and attribution.
+
+ALTER TABLE invite_codes
+  ADD COLUMN IF NOT EXISTS created_by TEXT        REFERENCES users(id),
+  ADD COLUMN IF NOT EXISTS max_uses   INTEGER     NOT NULL DEFAULT 1,
+  ADD COLUMN IF NOT EXISTS uses       INTEGER     NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+-- Who joined with which code. `used_by` only keeps the last one.
+CREATE TABLE IF NOT EXISTS invite_uses (
+  code     TEXT       NOT NULL REFERENCES invite_codes(code) ON DELETE CASCADE,
+  user_id  TEXT       PRIMARY KEY REFERENCES users(id),
+  used_at  TIMESTAMP  NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS invite_uses_code_idx ON invite_uses(code);
+
+-- Codes claimed before uses were counted.
+INSERT INTO invite_uses (code, user_id, used_at)
+SELECT code, used_by, COALESCE(used_at, NOW())
+FROM invite_codes
+WHERE used_by IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE invite_codes SET uses = 1 WHERE used_by IS NOT NULL AND uses = 0;
@@ -0,0 +1,8 @@
+-- Invitations created and revoked by users.
+
+ALTER TABLE invite_codes
+  ALTER COLUMN created_at TYPE TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS invite_codes_created_by_idx
+  ON invite_codes(created_by);
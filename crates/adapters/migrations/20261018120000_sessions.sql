@@ -0,0 +1,30 @@
+-- Sessions grouping rotated refresh tokens.
+
+CREATE TABLE IF NOT EXISTS sessions (
+  id            TEXT        PRIMARY KEY,
+  user_id       TEXT        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  device        TEXT,
+  ip            TEXT,
+  created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_used_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  revoked_at    TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id_idx ON sessions(user_id);
+
+ALTER TABLE tokens
+  ADD COLUMN IF NOT EXISTS session_id TEXT
+  REFERENCES sessions(id) ON DELETE CASCADE;
+
+-- Tokens issued before sessions existed each become their own session.
+INSERT INTO sessions (id, user_id, ip, created_at, last_used_at)
+SELECT md5(token), user_id, ip, created_at, created_at
+FROM tokens
+WHERE session_id IS NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE tokens SET session_id = md5(token) WHERE session_id IS NULL;
+
+ALTER TABLE tokens ALTER COLUMN session_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS tokens_session_id_idx ON tokens(session_id);
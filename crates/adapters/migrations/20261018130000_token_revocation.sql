@@ -0,0 +1,13 @@
+-- Access token revocation.
+
+-- Tokens issued with an older generation are revoked.
+ALTER TABLE users
+  ADD COLUMN IF NOT EXISTS token_generation BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+  jti         TEXT        PRIMARY KEY,
+  expires_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS revoked_tokens_expires_at_idx
+  ON revoked_tokens(expires_at);
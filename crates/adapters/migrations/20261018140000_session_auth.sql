@@ -0,0 +1,5 @@
+-- Authentication methods and time carried by sessions.
+
+ALTER TABLE sessions
+  ADD COLUMN IF NOT EXISTS amr TEXT[] NOT NULL DEFAULT '{}',
+  ADD COLUMN IF NOT EXISTS auth_time TIMESTAMPTZ;
@@ -0,0 +1,19 @@
+-- Account suspensions and moderation history.
+
+ALTER TABLE users
+  ADD COLUMN IF NOT EXISTS suspended_until    TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS suspension_reason  TEXT;
+
+CREATE TABLE IF NOT EXISTS moderation_actions (
+  id            BIGSERIAL   PRIMARY KEY,
+  user_id       TEXT        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  -- Kept when the moderator account is deleted.
+  moderator_id  TEXT        NOT NULL,
+  action        TEXT        NOT NULL,
+  reason        TEXT        NOT NULL,
+  until         TIMESTAMPTZ,
+  created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS moderation_actions_user_id_idx
+  ON moderation_actions(user_id);
@@ -0,0 +1,26 @@
+-- Staff roles, forced password resets and administration log.
+
+CREATE TABLE IF NOT EXISTS user_roles (
+  user_id     TEXT        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  role        TEXT        NOT NULL,
+  -- Kept when the granting account is deleted.
+  granted_by  TEXT,
+  granted_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, role)
+);
+
+ALTER TABLE users
+  ADD COLUMN IF NOT EXISTS password_reset_required BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Actions on invitations or searches apply to no single account, and the
+-- log must outlive the accounts it mentions.
+ALTER TABLE moderation_actions
+  ALTER COLUMN user_id DROP NOT NULL,
+  ALTER COLUMN reason DROP NOT NULL,
+  ADD COLUMN IF NOT EXISTS target TEXT,
+  DROP CONSTRAINT IF EXISTS moderation_actions_user_id_fkey,
+  ADD CONSTRAINT moderation_actions_user_id_fkey
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS moderation_actions_moderator_id_idx
+  ON moderation_actions(moderator_id);
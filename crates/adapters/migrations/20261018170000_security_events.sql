@@ -0,0 +1,39 @@
+-- Append-only, hash-chained log of security events.
+
+CREATE TABLE IF NOT EXISTS security_events (
+  id          BIGSERIAL   PRIMARY KEY,
+  -- Events outlive the accounts they concern.
+  user_id     TEXT,
+  kind        TEXT        NOT NULL,
+  -- Encrypted client IP address and user agent.
+  ip_address  TEXT,
+  user_agent  TEXT,
+  details     TEXT,
+  created_at  TIMESTAMPTZ NOT NULL,
+  -- HMAC of the previous row, or 64 zeroes for the first one.
+  prev_hash   TEXT        NOT NULL,
+  hash        TEXT        NOT NULL UNIQUE
+);
+
+CREATE INDEX IF NOT EXISTS security_events_user_idx
+  ON security_events (user_id, id DESC);
+
+-- Hashes are computed and verified by the application, with a key kept
+-- outside the database.
+
+CREATE OR REPLACE FUNCTION security_events_append_only() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+  RAISE EXCEPTION 'security_events is append-only';
+END
+$$;
+
+DROP TRIGGER IF EXISTS security_events_no_change ON security_events;
+CREATE TRIGGER security_events_no_change
+  BEFORE UPDATE OR DELETE ON security_events
+  FOR EACH ROW EXECUTE FUNCTION security_events_append_only();
+
+DROP TRIGGER IF EXISTS security_events_no_truncate ON security_events;
+CREATE TRIGGER security_events_no_truncate
+  BEFORE TRUNCATE ON security_events
+  FOR EACH STATEMENT EXECUTE FUNCTION security_events_append_only();
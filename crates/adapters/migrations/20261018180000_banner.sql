@@ -0,0 +1,3 @@
+-- Profile header image.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS banner TEXT;
@@ -0,0 +1,24 @@
+-- ActivityPub signing keys and received activities.
+
+CREATE TABLE IF NOT EXISTS actor_keys (
+  user_id         TEXT        PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  public_key_pem  TEXT        NOT NULL,
+  -- Encrypted PKCS#8 private key.
+  private_key     TEXT        NOT NULL,
+  created_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS inbox_activities (
+  id           BIGSERIAL   PRIMARY KEY,
+  user_id      TEXT        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  -- Actor whose key signed the delivery.
+  actor        TEXT        NOT NULL,
+  kind         TEXT        NOT NULL,
+  activity_id  TEXT,
+  activity     JSONB       NOT NULL,
+  received_at  TIMESTAMPTZ NOT NULL,
+  UNIQUE (user_id, activity_id)
+);
+
+CREATE INDEX IF NOT EXISTS inbox_activities_user_idx
+  ON inbox_activities (user_id, received_at DESC);
@@ -0,0 +1,12 @@
+-- Trust store of remote instances.
+
+CREATE TABLE IF NOT EXISTS federation_peers (
+  -- Host, with its port if not the default one.
+  host         TEXT        PRIMARY KEY,
+  -- 'allowed', 'denied', or NULL when no decision was taken.
+  status       TEXT        CHECK (status IN ('allowed', 'denied')),
+  -- Last key a request of the instance was verified with.
+  key_id       TEXT,
+  verified_at  TIMESTAMPTZ,
+  updated_at   TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,187 @@
+//! ActivityPub HTTP handlers.
+
+use std::sync::Arc;
+
+use application::dto::{CollectionKind, OrderedCollectionDto};
+use application::ports::inbound::Federation;
+use axum::Json;
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use domain::identity::id::UserId;
+use serde::Serialize;
+
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::signature::VerifiedActivity;
+
+/// Largest accepted activity, in bytes.
+pub const MAX_ACTIVITY_SIZE: usize = 1024 * 1024;
+const ACTIVITY_JSON: &str = "application/activity+json";
+
+/// Whether the client asked for ActivityStreams rather than plain JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    activity: bool,
+}
+
+impl Negotiated {
+    /// Negotiate the media type of an `Accept` header value.
+    pub fn from_accept(accept: &str) -> Self {
+        let activity = accept.split(',').any(|range| {
+            let mut params = range.split(';').map(str::trim);
+            let media_type = params.next().unwrap_or_default();
+            let refused = params.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+
+            !refused &&
+                (media_type.eq_ignore_ascii_case(ACTIVITY_JSON) ||
+                    media_type
+                        .eq_ignore_ascii_case("application/ld+json"))
+        });
+
+        Self { activity }
+    }
+
+    /// Serialize `value` as the negotiated media type.
+    pub fn json<T: Serialize>(self, value: T) -> Response {
+        let mut response = Json(value).into_response();
+        let headers = response.headers_mut();
+
+        if self.activity {
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(ACTIVITY_JSON),
+            );
+        }
+        headers.insert(header::VARY, HeaderValue::from_static("accept"));
+
+        response
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Negotiated {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .map(Self::from_accept)
+            .unwrap_or(Self { activity: false }))
+    }
+}
+
+/// Handler for `GET /actor`, the actor of the instance.
+pub async fn instance_actor_handler(
+    State(service): State<Arc<dyn Federation>>,
+    negotiated: Negotiated,
+) -> Response {
+    negotiated.json(service.instance_actor())
+}
+
+/// Handler for `GET /actor/outbox`.
+pub async fn instance_outbox_handler(
+    State(service): State<Arc<dyn Federation>>,
+    negotiated: Negotiated,
+) -> Response {
+    negotiated.json(service.instance_outbox())
+}
+
+/// Handler for `POST /actor/inbox`.
+///
+/// The instance actor follows no one, so activities are acknowledged and
+/// dropped once their signature is verified.
+pub async fn instance_inbox_handler(_: VerifiedActivity) -> StatusCode {
+    StatusCode::ACCEPTED
+}
+
+async fn collection(
+    service: Arc<dyn Federation>,
+    id: String,
+    kind: CollectionKind,
+    negotiated: Negotiated,
+) -> Result<Response, HttpError> {
+    let collection: OrderedCollectionDto = service
+        .collection(UserId::parse(id)?, kind)
+        .await
+        .into_http_result()?;
+
+    Ok(negotiated.json(collection))
+}
+
+/// Handler for `GET /users/{id}/outbox`.
+pub async fn outbox_handler(
+    State(service): State<Arc<dyn Federation>>,
+    Path(id): Path<String>,
+    negotiated: Negotiated,
+) -> Result<Response, HttpError> {
+    collection(service, id, CollectionKind::Outbox, negotiated).await
+}
+
+/// Handler for `GET /users/{id}/followers`.
+pub async fn followers_handler(
+    State(service): State<Arc<dyn Federation>>,
+    Path(id): Path<String>,
+    negotiated: Negotiated,
+) -> Result<Response, HttpError> {
+    collection(service, id, CollectionKind::Followers, negotiated).await
+}
+
+/// Handler for `GET /users/{id}/following`.
+pub async fn following_handler(
+    State(service): State<Arc<dyn Federation>>,
+    Path(id): Path<String>,
+    negotiated: Negotiated,
+) -> Result<Response, HttpError> {
+    collection(service, id, CollectionKind::Following, negotiated).await
+}
+
+/// Handler for `POST /users/{id}/inbox`.
+pub async fn inbox_handler(
+    State(service): State<Arc<dyn Federation>>,
+    Path(id): Path<String>,
+    VerifiedActivity { actor, activity }: VerifiedActivity,
+) -> Result<StatusCode, HttpError> {
+    service
+        .receive(UserId::parse(id)?, &actor, activity)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiation() {
+        assert!(Negotiated::from_accept("application/activity+json").activity);
+        assert!(
+            Negotiated::from_accept(
+                "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\""
+            )
+            .activity
+        );
+        assert!(
+            Negotiated::from_accept(
+                "text/html, application/activity+json;q=0.9"
+            )
+            .activity
+        );
+        assert!(!Negotiated::from_accept("application/json").activity);
+        assert!(!Negotiated::from_accept("*/*").activity);
+        assert!(
+            !Negotiated::from_accept("application/activity+json;q=0").activity
+        );
+    }
+}
@@ -0,0 +1,567 @@
+//! Administration HTTP handlers.
+//!
+//! Routes are mounted under `/admin`. Role checks and audit logging happen
+//! in the application layer, so handlers only translate requests.
+
+use std::sync::Arc;
+
+use application::dto::{
+    AccountDto, AccountQueryDto, AdminAccountDto, CreateInviteDto, InviteDto,
+    ModerationActionDto, PeerDto, SessionDto, SuspendAccountDto,
+};
+use application::ports::inbound::{
+    AdministerAccounts, ManagePeers, ModerateAccount,
+};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use domain::error::DomainError;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+use domain::identity::suspension::Suspension;
+use serde::{Deserialize, Serialize};
+
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::extractor::Valid;
+use crate::inbound::http::invite::{CreateInviteRequest, InviteResponse};
+
+/// Query string of `GET /admin/accounts`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountQuery {
+    /// Beginning of the user ID.
+    pub id: Option<String>,
+    pub email: Option<String>,
+    pub email_hash: Option<String>,
+    /// Return accounts after this user ID.
+    pub after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Query string of list endpoints.
+#[derive(Debug, Deserialize)]
+pub struct LimitQuery {
+    pub limit: Option<u32>,
+}
+
+/// Optional justification of an administrative action.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReasonRequest {
+    pub reason: Option<String>,
+}
+
+/// Flag change request body, with flags named in `snake_case`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FlagsRequest {
+    pub set: Vec<String>,
+    pub clear: Vec<String>,
+}
+
+/// Flags of an account after a change.
+#[derive(Debug, Serialize)]
+pub struct FlagsResponse {
+    pub flags: UserFlags,
+    pub names: Vec<String>,
+}
+
+/// Suspension request body.
+#[derive(Debug, Deserialize)]
+pub struct SuspendRequest {
+    pub reason: String,
+    /// End of the suspension as a UNIX timestamp, `None` until lifted.
+    pub until: Option<u64>,
+}
+
+/// Suspension lifting request body.
+#[derive(Debug, Deserialize)]
+pub struct UnsuspendRequest {
+    pub reason: String,
+}
+
+/// Trust decision request body.
+#[derive(Debug, Deserialize)]
+pub struct PeerStatusRequest {
+    /// `allowed` or `denied`.
+    pub status: String,
+    pub reason: Option<String>,
+}
+
+/// Remote instance as shown to staff.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerResponse {
+    pub host: String,
+    pub status: Option<&'static str>,
+    pub key_id: Option<String>,
+    pub verified_at: Option<u64>,
+    pub updated_at: u64,
+}
+
+impl From<PeerDto> for PeerResponse {
+    fn from(peer: PeerDto) -> Self {
+        Self {
+            host: peer.host,
+            status: peer.status.map(|status| status.as_str()),
+            key_id: peer.key_id,
+            verified_at: peer.verified_at,
+            updated_at: peer.updated_at,
+        }
+    }
+}
+
+/// Account as listed in search results.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummaryResponse {
+    pub id: String,
+    pub username: String,
+    pub flags: UserFlags,
+    pub created_at: u64,
+    pub deleted_at: Option<u64>,
+    pub suspension: Option<SuspensionResponse>,
+}
+
+impl From<AccountDto> for AccountSummaryResponse {
+    fn from(account: AccountDto) -> Self {
+        Self {
+            id: account.id.to_string(),
+            username: account.username,
+            flags: account.flags,
+            created_at: account.created_at,
+            deleted_at: account.deleted_at,
+            suspension: account.suspension.as_ref().map(Into::into),
+        }
+    }
+}
+
+/// Current or last suspension of an account.
+#[derive(Debug, Serialize)]
+pub struct SuspensionResponse {
+    pub reason: String,
+    pub since: u64,
+    pub until: Option<u64>,
+}
+
+impl From<&Suspension> for SuspensionResponse {
+    fn from(suspension: &Suspension) -> Self {
+        Self {
+            reason: suspension.reason().to_string(),
+            since: suspension.since(),
+            until: suspension.until(),
+        }
+    }
+}
+
+/// Session as shown to staff.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSessionResponse {
+    pub id: String,
+    pub device: Option<String>,
+    pub amr: Vec<String>,
+    pub authenticated_at: u64,
+    pub created_at: u64,
+    pub last_used_at: u64,
+}
+
+impl From<SessionDto> for AdminSessionResponse {
+    fn from(session: SessionDto) -> Self {
+        Self {
+            id: session.id,
+            device: session.device,
+            amr: session.amr,
+            authenticated_at: session.authenticated_at,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+        }
+    }
+}
+
+/// Detailed account state.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminAccountResponse {
+    #[serde(flatten)]
+    pub account: AccountSummaryResponse,
+    pub locale: String,
+    pub totp_enabled: bool,
+    pub password_reset_required: bool,
+    pub public_keys: usize,
+    pub roles: Vec<String>,
+    pub sessions: Vec<AdminSessionResponse>,
+    /// Users who, directly or not, invited this account, closest first.
+    pub invited_by: Vec<String>,
+}
+
+impl From<AdminAccountDto> for AdminAccountResponse {
+    fn from(dto: AdminAccountDto) -> Self {
+        let AdminAccountDto {
+            account,
+            roles,
+            sessions,
+            invited_by,
+        } = dto;
+
+        Self {
+            locale: account.locale.clone(),
+            totp_enabled: account.totp_secret.is_some(),
+            password_reset_required: account.password_reset_required,
+            public_keys: account.public_keys.len(),
+            account: account.into(),
+            roles: roles.iter().map(Role::to_string).collect(),
+            sessions: sessions.into_iter().map(Into::into).collect(),
+            invited_by: invited_by.iter().map(UserId::to_string).collect(),
+        }
+    }
+}
+
+/// Entry of the moderation log.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationActionResponse {
+    pub user_id: Option<String>,
+    pub moderator: String,
+    pub action: &'static str,
+    pub reason: Option<String>,
+    pub target: Option<String>,
+    pub until: Option<u64>,
+    pub created_at: u64,
+}
+
+impl From<ModerationActionDto> for ModerationActionResponse {
+    fn from(action: ModerationActionDto) -> Self {
+        Self {
+            user_id: action.user_id.as_ref().map(UserId::to_string),
+            moderator: action.moderator.to_string(),
+            action: action.action.as_str(),
+            reason: action.reason,
+            target: action.target,
+            until: action.until,
+            created_at: action.created_at,
+        }
+    }
+}
+
+/// Invitation as shown to staff.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminInviteResponse {
+    #[serde(flatten)]
+    pub invite: InviteResponse,
+    /// `None` for invitations created by staff.
+    pub created_by: Option<String>,
+}
+
+impl From<InviteDto> for AdminInviteResponse {
+    fn from(invite: InviteDto) -> Self {
+        Self {
+            created_by: invite.created_by.as_ref().map(UserId::to_string),
+            invite: invite.into(),
+        }
+    }
+}
+
+/// Handler for `GET /admin/accounts`.
+pub async fn search_accounts_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Query(query): Query<AccountQuery>,
+) -> Result<Json<Vec<AccountSummaryResponse>>, HttpError> {
+    let dto = AccountQueryDto {
+        id: query.id,
+        email: query.email,
+        email_hash: query.email_hash,
+        after: query.after,
+        limit: query.limit,
+    };
+
+    let accounts = service
+        .search_accounts(&actor, dto)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(accounts.into_iter().map(Into::into).collect()))
+}
+
+/// Handler for `GET /admin/accounts/{id}`.
+pub async fn get_account_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+) -> Result<Json<AdminAccountResponse>, HttpError> {
+    let account = service
+        .account(&actor, &UserId::parse(id)?)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(account.into()))
+}
+
+/// Handler for `DELETE /admin/accounts/{id}`.
+pub async fn delete_account_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+    request: Option<Json<ReasonRequest>>,
+) -> Result<StatusCode, HttpError> {
+    let Json(request) = request.unwrap_or_default();
+    service
+        .delete_account(&actor, &UserId::parse(id)?, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `POST /admin/accounts/{id}/password-reset`.
+pub async fn force_password_reset_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+    request: Option<Json<ReasonRequest>>,
+) -> Result<StatusCode, HttpError> {
+    let Json(request) = request.unwrap_or_default();
+    service
+        .force_password_reset(&actor, &UserId::parse(id)?, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /admin/accounts/{id}/totp`.
+pub async fn reset_totp_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+    request: Option<Json<ReasonRequest>>,
+) -> Result<StatusCode, HttpError> {
+    let Json(request) = request.unwrap_or_default();
+    service
+        .reset_totp(&actor, &UserId::parse(id)?, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /admin/accounts/{id}/sessions`.
+pub async fn revoke_sessions_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+    request: Option<Json<ReasonRequest>>,
+) -> Result<StatusCode, HttpError> {
+    let Json(request) = request.unwrap_or_default();
+    service
+        .revoke_sessions(&actor, &UserId::parse(id)?, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `PUT /admin/accounts/{id}/roles/{role}`.
+pub async fn grant_role_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path((id, role)): Path<(String, String)>,
+) -> Result<StatusCode, HttpError> {
+    service
+        .grant_role(&actor, &UserId::parse(id)?, role.parse()?)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /admin/accounts/{id}/roles/{role}`.
+pub async fn revoke_role_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path((id, role)): Path<(String, String)>,
+) -> Result<StatusCode, HttpError> {
+    service
+        .revoke_role(&actor, &UserId::parse(id)?, role.parse()?)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `PATCH /admin/accounts/{id}/flags`.
+pub async fn set_flags_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(id): Path<String>,
+    Json(request): Json<FlagsRequest>,
+) -> Result<Json<FlagsResponse>, HttpError> {
+    let parse = |names: &[String]| {
+        names.iter().try_fold(UserFlags::empty(), |flags, name| {
+            Ok::<_, HttpError>(flags | UserFlags::from_flag_name(name)?)
+        })
+    };
+
+    let flags = service
+        .set_flags(
+            &actor,
+            &UserId::parse(id)?,
+            parse(&request.set)?,
+            parse(&request.clear)?,
+        )
+        .await
+        .into_http_result()?;
+
+    Ok(Json(FlagsResponse {
+        names: flags.names(),
+        flags,
+    }))
+}
+
+/// Handler for `POST /admin/accounts/{id}/suspension`.
+pub async fn suspend_handler(
+    State(service): State<Arc<dyn ModerateAccount>>,
+    Extension(moderator): Extension<UserId>,
+    Path(id): Path<String>,
+    Json(request): Json<SuspendRequest>,
+) -> Result<StatusCode, HttpError> {
+    let dto = SuspendAccountDto {
+        user_id: UserId::parse(id)?,
+        moderator,
+        reason: request.reason,
+        until: request.until,
+    };
+
+    service.suspend(dto).await.into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /admin/accounts/{id}/suspension`.
+pub async fn unsuspend_handler(
+    State(service): State<Arc<dyn ModerateAccount>>,
+    Extension(moderator): Extension<UserId>,
+    Path(id): Path<String>,
+    Json(request): Json<UnsuspendRequest>,
+) -> Result<StatusCode, HttpError> {
+    service
+        .unsuspend(&UserId::parse(id)?, &moderator, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `GET /admin/accounts/{id}/actions`.
+pub async fn list_actions_handler(
+    State(service): State<Arc<dyn ModerateAccount>>,
+    Extension(moderator): Extension<UserId>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ModerationActionResponse>>, HttpError> {
+    let actions = service
+        .history(&moderator, &UserId::parse(id)?)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(actions.into_iter().map(Into::into).collect()))
+}
+
+/// Handler for `GET /admin/invites`.
+pub async fn list_invites_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<AdminInviteResponse>>, HttpError> {
+    let invites = service
+        .list_invites(&actor, query.limit)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(invites.into_iter().map(Into::into).collect()))
+}
+
+/// Handler for `POST /admin/invites`.
+pub async fn create_invite_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Valid(request): Valid<CreateInviteRequest>,
+) -> Result<(StatusCode, Json<AdminInviteResponse>), HttpError> {
+    let dto = CreateInviteDto {
+        max_uses: request.max_uses,
+        expires_in: request.expires_in,
+    };
+
+    let invite = service
+        .create_invite(&actor, dto)
+        .await
+        .into_http_result()?;
+
+    Ok((StatusCode::CREATED, Json(invite.into())))
+}
+
+/// Handler for `DELETE /admin/invites/{code}`.
+pub async fn revoke_invite_handler(
+    State(service): State<Arc<dyn AdministerAccounts>>,
+    Extension(actor): Extension<UserId>,
+    Path(code): Path<String>,
+) -> Result<StatusCode, HttpError> {
+    service
+        .revoke_invite(&actor, &code)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `GET /admin/peers`.
+pub async fn list_peers_handler(
+    State(service): State<Arc<dyn ManagePeers>>,
+    Extension(actor): Extension<UserId>,
+) -> Result<Json<Vec<PeerResponse>>, HttpError> {
+    let peers = service.list_peers(&actor).await.into_http_result()?;
+
+    Ok(Json(peers.into_iter().map(Into::into).collect()))
+}
+
+/// Handler for `PUT /admin/peers/{host}`.
+pub async fn set_peer_status_handler(
+    State(service): State<Arc<dyn ManagePeers>>,
+    Extension(actor): Extension<UserId>,
+    Path(host): Path<String>,
+    Json(request): Json<PeerStatusRequest>,
+) -> Result<Json<PeerResponse>, HttpError> {
+    let status =
+        request
+            .status
+            .parse()
+            .map_err(|_| DomainError::ValidationFailed {
+                field: "status".into(),
+                message: "Expected `allowed` or `denied`.".into(),
+            })?;
+
+    let peer = service
+        .set_peer_status(&actor, &host, Some(status), request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(peer.into()))
+}
+
+/// Handler for `DELETE /admin/peers/{host}`, clearing the decision about an
+/// instance.
+pub async fn reset_peer_status_handler(
+    State(service): State<Arc<dyn ManagePeers>>,
+    Extension(actor): Extension<UserId>,
+    Path(host): Path<String>,
+    request: Option<Json<ReasonRequest>>,
+) -> Result<Json<PeerResponse>, HttpError> {
+    let Json(request) = request.unwrap_or_default();
+    let peer = service
+        .set_peer_status(&actor, &host, None, request.reason)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(peer.into()))
+}
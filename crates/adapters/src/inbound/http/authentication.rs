@@ -0,0 +1,212 @@
+//! Authentication of requests through their bearer access token.
+
+use application::error::ApplicationError;
+use application::ports::outbound::{TokenClaims, TokenDenylist, TokenSigner};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use domain::identity::id::UserId;
+
+const BEARER: &str = "Bearer ";
+
+/// Verify the bearer token of `headers` and returns its user and claims.
+///
+/// Tokens are rejected once denied by `jti`, once their session is revoked
+/// or once the token generation of their user is bumped.
+pub async fn authenticate(
+    signer: &dyn TokenSigner,
+    denylist: &dyn TokenDenylist,
+    headers: &HeaderMap,
+) -> Result<(UserId, TokenClaims), Response> {
+    let token = match headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix(BEARER))
+    {
+        Some(token) => token,
+        None => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid token",
+            )
+                .into_response());
+        },
+    };
+
+    let claims = signer.verify_token(token).map_err(|_| {
+        (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response()
+    })?;
+
+    let user_id = UserId::parse(&claims.sub).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Invalid user ID in token",
+        )
+            .into_response()
+    })?;
+
+    let unavailable = |err: ApplicationError| match err {
+        // The account was purged since the token was issued.
+        ApplicationError::UserNotFound => {
+            (StatusCode::UNAUTHORIZED, "Token has been revoked")
+                .into_response()
+        },
+        err => {
+            tracing::error!(%err, "token denylist unavailable");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        },
+    };
+    let mut denied =
+        denylist.is_denied(&claims.jti).await.map_err(unavailable)?;
+    if let Some(session_id) = &claims.sid {
+        denied |= denylist
+            .is_session_denied(session_id)
+            .await
+            .map_err(unavailable)?;
+    }
+    let generation =
+        denylist.generation(&user_id).await.map_err(unavailable)?;
+
+    if denied || claims.generation < generation {
+        return Err((StatusCode::UNAUTHORIZED, "Token has been revoked")
+            .into_response());
+    }
+
+    Ok((user_id, claims))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use application::error::Result;
+    use async_trait::async_trait;
+    use domain::auth::factor::{FactorMethod, FactorType, VerifiedFactor};
+    use domain::auth::proof::AuthenticationProofBuilder;
+
+    use super::*;
+    use crate::outbound::clock::FixedClock;
+    use crate::outbound::denylist::LruTokenDenylist;
+    use crate::outbound::kms::{FileKeystore, KeyKind};
+    use crate::outbound::token::AdapterTokenSigner;
+
+    async fn signer() -> AdapterTokenSigner {
+        let mut keystore = FileKeystore::new();
+        keystore.ensure("jwt", KeyKind::EcdsaP256).unwrap();
+        AdapterTokenSigner::managed(
+            "1",
+            "https://auth",
+            Arc::new(keystore),
+            "jwt",
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn bearer(
+        signer: &AdapterTokenSigner,
+        sid: &str,
+        now: u64,
+    ) -> HeaderMap {
+        let user_id = UserId::parse("alice").unwrap();
+        let factor = VerifiedFactor::new(
+            FactorType::Knowledge,
+            FactorMethod::Password,
+            now,
+        );
+        let proof = AuthenticationProofBuilder::default()
+            .user_id(&user_id)
+            .session_id(sid)
+            .authenticated_at(now)
+            .add_factor(factor)
+            .build()
+            .unwrap();
+        let token = signer.create_access_token(&proof).await.unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("{BEARER}{token}").parse().unwrap(),
+        );
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session() {
+        let now = jsonwebtoken::get_current_timestamp();
+        let signer = signer().await;
+        let denylist = LruTokenDenylist::new(Arc::new(FixedClock::new(now)));
+        let revoked = bearer(&signer, "revoked", now).await;
+        let other = bearer(&signer, "other", now).await;
+
+        let (user_id, claims) =
+            authenticate(&signer, &denylist, &revoked).await.unwrap();
+        assert_eq!(user_id.as_str(), "alice");
+        assert_eq!(claims.sid.as_deref(), Some("revoked"));
+
+        denylist.deny_session("revoked", now + 900).await.unwrap();
+        let response = authenticate(&signer, &denylist, &revoked)
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(authenticate(&signer, &denylist, &other).await.is_ok());
+
+        denylist
+            .bump_generation(&UserId::parse("alice").unwrap())
+            .await
+            .unwrap();
+        let response =
+            authenticate(&signer, &denylist, &other).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_missing_token() {
+        let signer = signer().await;
+        let denylist = LruTokenDenylist::new(Arc::new(FixedClock::new(0)));
+        let mut headers = HeaderMap::new();
+
+        let response = authenticate(&signer, &denylist, &headers)
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        headers.insert(header::AUTHORIZATION, "Basic abc".parse().unwrap());
+        let response = authenticate(&signer, &denylist, &headers)
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// Denylist of a database whose users were all purged.
+    struct Purged;
+
+    #[async_trait]
+    impl TokenDenylist for Purged {
+        async fn deny(&self, _jti: &str, _expires_at: u64) -> Result<()> {
+            Ok(())
+        }
+
+        async fn is_denied(&self, _jti: &str) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn generation(&self, _user_id: &UserId) -> Result<u64> {
+            Err(ApplicationError::UserNotFound)
+        }
+
+        async fn bump_generation(&self, _user_id: &UserId) -> Result<u64> {
+            Err(ApplicationError::UserNotFound)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purged_user() {
+        let now = jsonwebtoken::get_current_timestamp();
+        let signer = signer().await;
+        let headers = bearer(&signer, "session", now).await;
+
+        let response =
+            authenticate(&signer, &Purged, &headers).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
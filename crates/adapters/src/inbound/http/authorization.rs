@@ -0,0 +1,263 @@
+//! Scope- and factor-aware authorization of authenticated requests.
+//!
+//! Both the [`AccessToken`] extractor and the [`RequireScopes`] layer read
+//! the [`TokenClaims`] inserted by the authentication middleware, which must
+//! therefore run first.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use application::error::ApplicationError;
+use application::ports::outbound::TokenClaims;
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use domain::error::DomainError;
+use tower::{Layer, Service};
+
+use crate::inbound::http::errors::HttpError;
+
+/// RFC 8176 method reference for multi-factor authentication.
+const MFA: &str = "mfa";
+
+/// Requirements a token must meet to access a route.
+#[derive(Debug, Default)]
+struct Requirements {
+    scopes: Vec<String>,
+    mfa: bool,
+}
+
+impl Requirements {
+    fn check(&self, claims: Option<&TokenClaims>) -> Result<(), HttpError> {
+        let claims = claims.ok_or(DomainError::InvalidCredentials)?;
+
+        let missing: Vec<String> = self
+            .scopes
+            .iter()
+            .filter(|scope| !claims.has_scope(scope))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(ApplicationError::InsufficientScope {
+                scopes: missing,
+            }
+            .into());
+        }
+
+        if self.mfa && !claims.has_amr(MFA) {
+            return Err(ApplicationError::MfaRequired.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Claims of the access token authenticating the request.
+#[derive(Debug, Clone)]
+pub struct AccessToken(pub TokenClaims);
+
+impl AccessToken {
+    /// Reject the request unless the token was granted every `scopes`.
+    pub fn require_scopes<I, T>(&self, scopes: I) -> Result<(), HttpError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Requirements {
+            scopes: scopes.into_iter().map(Into::into).collect(),
+            mfa: false,
+        }
+        .check(Some(&self.0))
+    }
+
+    /// Reject the request unless the user authenticated with several
+    /// factors.
+    pub fn require_mfa(&self) -> Result<(), HttpError> {
+        Requirements {
+            scopes: Vec::new(),
+            mfa: true,
+        }
+        .check(Some(&self.0))
+    }
+}
+
+impl<S> FromRequestParts<S> for AccessToken
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<TokenClaims>()
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| DomainError::InvalidCredentials.into())
+    }
+}
+
+/// Layer rejecting requests with `403 Forbidden` when the access token lacks
+/// a scope or an authentication factor.
+#[derive(Clone)]
+pub struct RequireScopes {
+    requirements: Arc<Requirements>,
+}
+
+impl RequireScopes {
+    /// Create a new [`RequireScopes`] demanding every `scopes`.
+    pub fn new<I, T>(scopes: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            requirements: Arc::new(Requirements {
+                scopes: scopes.into_iter().map(Into::into).collect(),
+                mfa: false,
+            }),
+        }
+    }
+
+    /// Also demand that the user authenticated with several factors.
+    pub fn with_mfa(mut self) -> Self {
+        Arc::get_mut(&mut self.requirements)
+            .expect("authorization layer configured after being cloned")
+            .mfa = true;
+        self
+    }
+}
+
+impl<S> Layer<S> for RequireScopes {
+    type Service = RequireScopesService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireScopesService {
+            inner,
+            requirements: Arc::clone(&self.requirements),
+        }
+    }
+}
+
+/// Service produced by [`RequireScopes`].
+#[derive(Clone)]
+pub struct RequireScopesService<S> {
+    inner: S,
+    requirements: Arc<Requirements>,
+}
+
+impl<S> Service<Request> for RequireScopesService<S>
+where
+    S: Service<Request, Error = Infallible> + Clone + Send + 'static,
+    S::Response: IntoResponse,
+    S::Future: Send + 'static,
+{
+    type Error = Infallible;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+    type Response = Response;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if let Err(err) = self
+            .requirements
+            .check(req.extensions().get::<TokenClaims>())
+        {
+            return Box::pin(async move { Ok(err.into_response()) });
+        }
+
+        // Take the service that was driven to readiness.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move { Ok(inner.call(req).await?.into_response()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    fn claims(scope: &str, amr: &[&str]) -> TokenClaims {
+        TokenClaims {
+            sub: "user123".into(),
+            iss: "https://account.gravitalia.com".into(),
+            aud: "account.gravitalia.com".into(),
+            exp: 1900,
+            iat: 1000,
+            jti: "jti".into(),
+            scope: scope.into(),
+            sid: None,
+            generation: 0,
+            amr: amr.iter().map(|a| a.to_string()).collect(),
+            auth_time: 1000,
+        }
+    }
+
+    fn status(result: Result<(), HttpError>) -> Option<StatusCode> {
+        result.err().map(|err| err.into_response().status())
+    }
+
+    #[test]
+    fn test_scopes_granted() {
+        let token = AccessToken(claims("read:account write:account", &[]));
+        assert!(token.require_scopes(["write:account"]).is_ok());
+        assert!(
+            token
+                .require_scopes(["read:account", "write:account"])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_scope_missing() {
+        let token = AccessToken(claims("read:account", &["pwd"]));
+        assert_eq!(
+            status(token.require_scopes(["write:public_keys"])),
+            Some(StatusCode::FORBIDDEN)
+        );
+        // Scopes must match exactly, not as prefixes.
+        let token = AccessToken(claims("write:accounts", &["pwd"]));
+        assert!(token.require_scopes(["write:account"]).is_err());
+    }
+
+    #[test]
+    fn test_mfa_required() {
+        let requirements = Requirements {
+            scopes: vec!["write:account".into()],
+            mfa: true,
+        };
+
+        let single = claims("write:account", &["pwd"]);
+        assert_eq!(
+            status(requirements.check(Some(&single))),
+            Some(StatusCode::FORBIDDEN)
+        );
+
+        let multi = claims("write:account", &["pwd", "otp", "mfa"]);
+        assert!(requirements.check(Some(&multi)).is_ok());
+    }
+
+    #[test]
+    fn test_missing_claims_unauthorized() {
+        let requirements = Requirements::default();
+        assert_eq!(
+            status(requirements.check(None)),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Client IP extraction behind trusted reverse proxies.
+//!
+//! Forwarding headers are only honoured when the hop which appended them is
+//! a trusted proxy. The chain is walked from the right (closest hop) until
+//! an untrusted address is found: that address is the client.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use application::dto::ClientContextDto;
+use application::error::ApplicationError;
+use application::ports::outbound::CryptoPort;
+use axum::extract::{ConnectInfo, FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use domain::identity::ip::EncryptedIp;
+use ipnet::IpNet;
+
+use crate::inbound::http::device::EncryptedUserAgent;
+use crate::inbound::http::errors::HttpError;
+
+const FORWARDED: &str = "forwarded";
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// List of proxy networks allowed to set forwarding headers.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Arc<[IpNet]>);
+
+impl TrustedProxies {
+    /// Parse a list of CIDRs (e.g. `10.0.0.0/8`) or single addresses.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the first entry which is neither.
+    pub fn parse<I, T>(networks: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        networks
+            .into_iter()
+            .map(|network| {
+                let network = network.as_ref().trim();
+                network
+                    .parse::<IpNet>()
+                    .or_else(|_| network.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| network.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|networks| Self(networks.into()))
+    }
+
+    /// Returns `true` if `ip` belongs to a trusted network.
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|network| network.contains(&ip))
+    }
+
+    /// Resolve the client address of a request.
+    ///
+    /// `peer` is the socket address, or `None` for a Unix socket, whose
+    /// only peer is a local proxy and is therefore trusted.
+    pub fn resolve(
+        &self,
+        peer: Option<IpAddr>,
+        headers: &HeaderMap,
+    ) -> Option<IpAddr> {
+        let peer = peer.map(|ip| ip.to_canonical());
+        if let Some(ip) = peer &&
+            !self.is_trusted(ip)
+        {
+            return Some(ip);
+        }
+
+        let mut client = peer;
+        for hop in forwarded_chain(headers).into_iter().rev() {
+            // An obfuscated or malformed hop hides the real client.
+            let ip = hop?.to_canonical();
+            client = Some(ip);
+
+            if !self.is_trusted(ip) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+/// Resolve the client address using request extensions.
+///
+/// [`TrustedProxies`] are read from extensions and nothing is trusted if
+/// they are missing.
+pub fn resolve_client_ip(
+    extensions: &Extensions,
+    headers: &HeaderMap,
+) -> Option<IpAddr> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    match extensions.get::<TrustedProxies>() {
+        Some(trusted) => trusted.resolve(peer, headers),
+        None => TrustedProxies::default().resolve(peer, headers),
+    }
+}
+
+/// Returns forwarded hops from the farthest to the closest.
+///
+/// `Forwarded` (RFC 7239) takes precedence over `X-Forwarded-For`.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let forwarded: Vec<_> = headers
+        .get_all(FORWARDED)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|element| {
+            // Elements without `for` do not describe a hop.
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("for"))
+                .map(|(_, node)| parse_node(node))
+        })
+        .collect();
+
+    if !forwarded.is_empty() {
+        return forwarded;
+    }
+
+    headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(parse_node)
+        .collect()
+}
+
+/// Parse a node such as `192.0.2.1`, `192.0.2.1:80`, `"[2001:db8::1]:80"`
+/// or `2001:db8::1`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// Extracts the client IP address.
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(resolve_client_ip(&parts.extensions, &parts.headers)))
+    }
+}
+
+/// Extracts the client IP address encrypted for storage.
+pub struct EncryptedClientIp(pub Option<EncryptedIp>);
+
+impl<S> FromRequestParts<S> for EncryptedClientIp
+where
+    Arc<dyn CryptoPort>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(ip) = resolve_client_ip(&parts.extensions, &parts.headers)
+        else {
+            return Ok(Self(None));
+        };
+
+        let crypto = Arc::<dyn CryptoPort>::from_ref(state);
+        let cipher = crypto
+            .symmetric_encryption()
+            .encrypt_to_hex(ip.to_string().as_bytes())
+            .await
+            .map_err(|_| HttpError(ApplicationError::Unknown))?;
+
+        Ok(Self(Some(EncryptedIp::new(cipher))))
+    }
+}
+
+/// Extracts the encrypted IP address and user agent of the client.
+pub struct ClientContext(pub ClientContextDto);
+
+impl<S> FromRequestParts<S> for ClientContext
+where
+    Arc<dyn CryptoPort>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let EncryptedClientIp(ip_address) =
+            EncryptedClientIp::from_request_parts(parts, state).await?;
+        let EncryptedUserAgent(user_agent) =
+            EncryptedUserAgent::from_request_parts(parts, state).await?;
+
+        Ok(Self(ClientContextDto {
+            ip_address,
+            user_agent,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_trusted_proxies() {
+        let trusted =
+            TrustedProxies::parse(["10.0.0.0/8", "::1", "fd00::/8"]).unwrap();
+        assert!(trusted.is_trusted(ip("10.1.2.3")));
+        assert!(trusted.is_trusted(ip("::1")));
+        assert!(!trusted.is_trusted(ip("192.0.2.1")));
+
+        assert_eq!(
+            TrustedProxies::parse(["10.0.0.0/33"]).unwrap_err(),
+            "10.0.0.0/33"
+        );
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let trusted = TrustedProxies::parse(["10.0.0.0/8"]).unwrap();
+        let headers = headers(&[("x-forwarded-for", "203.0.113.9")]);
+
+        assert_eq!(
+            trusted.resolve(Some(ip("192.0.2.1")), &headers),
+            Some(ip("192.0.2.1"))
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_for_skips_trusted_hops() {
+        let trusted = TrustedProxies::parse(["10.0.0.0/8"]).unwrap();
+        let headers = headers(&[(
+            "x-forwarded-for",
+            "198.51.100.7, 203.0.113.9, 10.0.0.2",
+        )]);
+
+        // 198.51.100.7 was written by the client itself: not trusted.
+        assert_eq!(
+            trusted.resolve(Some(ip("10.0.0.1")), &headers),
+            Some(ip("203.0.113.9"))
+        );
+    }
+
+    #[test]
+    fn test_forwarded_takes_precedence() {
+        let trusted = TrustedProxies::parse(["10.0.0.0/8"]).unwrap();
+        let headers = headers(&[
+            ("x-forwarded-for", "198.51.100.7"),
+            (
+                "forwarded",
+                r#"for="[2001:db8:cafe::17]:4711";proto=https, for=10.0.0.2"#,
+            ),
+        ]);
+
+        assert_eq!(
+            trusted.resolve(Some(ip("10.0.0.1")), &headers),
+            Some(ip("2001:db8:cafe::17"))
+        );
+    }
+
+    #[test]
+    fn test_obfuscated_hop_hides_client() {
+        let trusted = TrustedProxies::parse(["10.0.0.0/8"]).unwrap();
+        let headers = headers(&[("forwarded", "for=_hidden, for=10.0.0.2")]);
+
+        assert_eq!(trusted.resolve(Some(ip("10.0.0.1")), &headers), None);
+    }
+
+    #[test]
+    fn test_unix_socket_peer_is_trusted() {
+        let trusted = TrustedProxies::default();
+
+        assert_eq!(trusted.resolve(None, &HeaderMap::new()), None);
+        assert_eq!(
+            trusted.resolve(
+                None,
+                &headers(&[("x-forwarded-for", "192.0.2.1:5120")])
+            ),
+            Some(ip("192.0.2.1"))
+        );
+    }
+
+    #[test]
+    fn test_ipv4_mapped_peer() {
+        let trusted = TrustedProxies::parse(["127.0.0.1"]).unwrap();
+        let headers = headers(&[("x-forwarded-for", "192.0.2.1")]);
+
+        assert_eq!(
+            trusted.resolve(Some(ip("::ffff:127.0.0.1")), &headers),
+            Some(ip("192.0.2.1"))
+        );
+    }
+}
@@ -13,11 +13,11 @@ use domain::identity::id::UserId;
 use serde::Deserialize;
 use validator::Validate;
 
+use crate::inbound::http::client_ip::EncryptedClientIp;
+use crate::inbound::http::device::DeviceLabel;
 use crate::inbound::http::errors::{HttpError, IntoHttpResult};
 use crate::inbound::http::extractor::Valid;
-use crate::inbound::http::validation::{
-    validate_locale, validate_password_strength, validate_user_id,
-};
+use crate::inbound::http::validation::{validate_locale, validate_user_id};
 
 #[derive(Debug, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -26,19 +26,19 @@ pub struct CreateAccountRequest {
     pub id: String,
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
-    #[validate(
-        length(min = 8, max = 255),
-        custom(function = "validate_password_strength")
-    )]
+    #[validate(length(min = 8, max = 255))]
     pub password: String,
     #[validate(custom(function = "validate_locale"))]
     pub locale: Option<String>,
+    #[validate(length(min = 1, max = 64))]
     pub invite: Option<String>,
 }
 
 /// Create a new user account.
 pub async fn create_account_handler(
     State(service): State<Arc<dyn CreateAccount>>,
+    EncryptedClientIp(ip_address): EncryptedClientIp,
+    DeviceLabel(device): DeviceLabel,
     Valid(request): Valid<CreateAccountRequest>,
 ) -> Result<(StatusCode, Json<AuthResponseDto>), HttpError> {
     let dto = CreateAccountRequestDto {
@@ -47,7 +47,8 @@ pub async fn create_account_handler(
         password: Password::new(request.password)?,
         locale: request.locale,
         invite_code: request.invite,
-        ip_address: None,
+        ip_address,
+        device,
     };
 
     let response = service.execute(dto).await.into_http_result()?;
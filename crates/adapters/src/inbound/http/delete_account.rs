@@ -0,0 +1,28 @@
+//! Account deletion HTTP handler.
+
+use std::sync::Arc;
+
+use application::ports::inbound::DeleteAccount;
+use axum::Extension;
+use axum::extract::State;
+use axum::http::StatusCode;
+use domain::identity::id::UserId;
+
+use crate::inbound::http::authorization::AccessToken;
+use crate::inbound::http::client_ip::ClientContext;
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+
+/// Handler for `DELETE /users/@me`.
+pub async fn delete_account_handler(
+    State(service): State<Arc<dyn DeleteAccount>>,
+    Extension(user_id): Extension<UserId>,
+    AccessToken(claims): AccessToken,
+    ClientContext(client): ClientContext,
+) -> Result<StatusCode, HttpError> {
+    service
+        .execute(&user_id, &claims, &client)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
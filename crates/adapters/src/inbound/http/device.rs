@@ -0,0 +1,176 @@
+//! Device label extraction from the `User-Agent` header.
+//!
+//! Sessions only keep a coarse label. The full user agent is only kept,
+//! encrypted, in the security audit log.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use application::error::ApplicationError;
+use application::ports::outbound::CryptoPort;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+
+use crate::inbound::http::errors::HttpError;
+
+const MAX_PRODUCT_LENGTH: usize = 32;
+/// Maximum number of characters of user agent kept.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Known browsers, most specific first.
+const BROWSERS: [(&str, &str); 6] = [
+    ("Edg/", "Edge"),
+    ("OPR/", "Opera"),
+    ("Firefox/", "Firefox"),
+    ("CriOS/", "Chrome"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+];
+
+/// Known operating systems, most specific first.
+const SYSTEMS: [(&str, &str); 7] = [
+    ("Android", "Android"),
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Windows", "Windows"),
+    ("Macintosh", "macOS"),
+    ("CrOS", "ChromeOS"),
+    ("Linux", "Linux"),
+];
+
+/// Returns a label such as `Firefox on Linux` for a user agent.
+///
+/// Unknown agents are labelled with their first product token, e.g. `curl`.
+pub fn device_label(user_agent: &str) -> Option<String> {
+    let find = |table: &[(&str, &'static str)]| {
+        table
+            .iter()
+            .find(|(pattern, _)| user_agent.contains(pattern))
+            .map(|(_, label)| *label)
+    };
+
+    match (find(&BROWSERS), find(&SYSTEMS)) {
+        (Some(browser), Some(system)) => {
+            Some(format!("{browser} on {system}"))
+        },
+        (Some(label), None) | (None, Some(label)) => Some(label.to_string()),
+        (None, None) => {
+            let product = user_agent
+                .split(['/', ' '])
+                .next()?
+                .chars()
+                .filter(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+                })
+                .take(MAX_PRODUCT_LENGTH)
+                .collect::<String>();
+
+            (!product.is_empty()).then_some(product)
+        },
+    }
+}
+
+/// Extracts the device label of the client.
+pub struct DeviceLabel(pub Option<String>);
+
+impl<S> FromRequestParts<S> for DeviceLabel
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            parts
+                .headers
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .and_then(device_label),
+        ))
+    }
+}
+
+/// Extracts the user agent of the client encrypted for storage.
+pub struct EncryptedUserAgent(pub Option<String>);
+
+impl<S> FromRequestParts<S> for EncryptedUserAgent
+where
+    Arc<dyn CryptoPort>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(user_agent) = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        else {
+            return Ok(Self(None));
+        };
+        let user_agent = user_agent
+            .chars()
+            .take(MAX_USER_AGENT_LENGTH)
+            .collect::<String>();
+
+        let crypto = Arc::<dyn CryptoPort>::from_ref(state);
+        let cipher = crypto
+            .symmetric_encryption()
+            .encrypt_to_hex(user_agent.as_bytes())
+            .await
+            .map_err(|_| HttpError(ApplicationError::Unknown))?;
+
+        Ok(Self(Some(cipher)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_on_system() {
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 \
+                 Firefox/131.0"
+            )
+            .as_deref(),
+            Some("Firefox on Linux")
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+                 AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 \
+                 Safari/537.36 Edg/129.0.0.0"
+            )
+            .as_deref(),
+            Some("Edge on Windows")
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) \
+                 AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 \
+                 Mobile/15E148 Safari/604.1"
+            )
+            .as_deref(),
+            Some("Safari on iOS")
+        );
+    }
+
+    #[test]
+    fn test_unknown_agent() {
+        assert_eq!(device_label("curl/8.10.1").as_deref(), Some("curl"));
+        assert_eq!(device_label("").as_deref(), None);
+        assert_eq!(device_label("/").as_deref(), None);
+    }
+}
@@ -1,10 +1,12 @@
 //! RFC 7807 problem details for HTTP APIs.
 
 use application::error::ApplicationError;
+use application::ports::outbound::PasswordViolation;
 use axum::Json;
-use axum::http::StatusCode;
+use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use domain::error::DomainError;
+use domain::key::public_key::KeyError;
 use serde::{Deserialize, Serialize};
 
 /// RFC 7807 problem details.
@@ -34,6 +36,37 @@ pub struct FieldError {
     pub code: String,
 }
 
+impl FieldError {
+    /// Describe why the password sent as `field` is refused.
+    fn password(field: String, violation: PasswordViolation) -> Self {
+        let (message, code) = match violation {
+            PasswordViolation::TooWeak { .. } => (
+                "Password is too easy to guess. Add words or symbols."
+                    .to_string(),
+                "weak_password",
+            ),
+            PasswordViolation::ContainsUserInput => (
+                "Password must not contain your ID or username.".to_string(),
+                "password_contains_user_input",
+            ),
+            PasswordViolation::Breached { count: Some(count) } => (
+                format!("Password appeared {count} times in data breaches."),
+                "breached_password",
+            ),
+            PasswordViolation::Breached { count: None } => (
+                "Password appeared in data breaches.".to_string(),
+                "breached_password",
+            ),
+        };
+
+        Self {
+            field,
+            message,
+            code: code.to_string(),
+        }
+    }
+}
+
 impl ProblemDetails {
     /// Create a new [`ProblemDetails`].
     pub fn new(
@@ -88,6 +121,219 @@ impl ProblemDetails {
                     "This account was deleted.",
                 ),
             ),
+            ApplicationError::AccountSuspended { until } => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Account Suspended",
+                    match until {
+                        Some(until) => format!(
+                            "This account is suspended until {}.",
+                            chrono::DateTime::from_timestamp(until as i64, 0)
+                                .map(|date| date.to_rfc3339())
+                                .unwrap_or_default()
+                        ),
+                        None => "This account is suspended.".to_string(),
+                    },
+                ),
+            ),
+            ApplicationError::AccountNotSuspended => (
+                StatusCode::CONFLICT,
+                Self::new(
+                    StatusCode::CONFLICT,
+                    "Account Not Suspended",
+                    "This account is not suspended.",
+                ),
+            ),
+            ApplicationError::PasswordResetRequired => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Password Reset Required",
+                    "A new password must be chosen to sign in.",
+                )
+                .with_errors(vec![FieldError {
+                    field: "newPassword".to_string(),
+                    message: "New password is required".to_string(),
+                    code: "password_reset_required".to_string(),
+                }]),
+            ),
+            ApplicationError::PasswordRejected { field, violations } => (
+                StatusCode::BAD_REQUEST,
+                Self::new(
+                    StatusCode::BAD_REQUEST,
+                    "Password Rejected",
+                    "The password does not follow the password policy.",
+                )
+                .with_errors(
+                    violations
+                        .into_iter()
+                        .map(|violation| {
+                            FieldError::password(field.clone(), violation)
+                        })
+                        .collect(),
+                ),
+            ),
+            ApplicationError::PasswordLoginDisabled => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Password Login Disabled",
+                    "This account cannot sign in with a password.",
+                ),
+            ),
+            ApplicationError::AccountLocked => (
+                StatusCode::LOCKED,
+                Self::new(
+                    StatusCode::LOCKED,
+                    "Account Locked",
+                    "This account was locked by staff and cannot be changed.",
+                ),
+            ),
+            ApplicationError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Forbidden",
+                    "You are not allowed to perform this action.",
+                ),
+            ),
+            ApplicationError::InvalidSignature => (
+                StatusCode::UNAUTHORIZED,
+                Self::new(
+                    StatusCode::UNAUTHORIZED,
+                    "Invalid Signature",
+                    "The request is not signed by a key of its actor.",
+                ),
+            ),
+            ApplicationError::PeerNotTrusted => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Peer Not Trusted",
+                    "This instance does not federate with yours.",
+                ),
+            ),
+            ApplicationError::SessionNotFound => (
+                StatusCode::NOT_FOUND,
+                Self::new(
+                    StatusCode::NOT_FOUND,
+                    "Session Not Found",
+                    "The requested session does not exist or has ended.",
+                ),
+            ),
+            ApplicationError::InsufficientScope { scopes } => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Insufficient Scope",
+                    format!(
+                        "The access token lacks the required scopes: {}.",
+                        scopes.join(", ")
+                    ),
+                ),
+            ),
+            ApplicationError::MfaRequired => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Multi-Factor Authentication Required",
+                    "This action requires signing in with a second factor.",
+                ),
+            ),
+            ApplicationError::ReauthenticationRequired { max_age } => (
+                StatusCode::UNAUTHORIZED,
+                Self::new(
+                    StatusCode::UNAUTHORIZED,
+                    "Reauthentication Required",
+                    format!(
+                        "This action requires having reauthenticated with a second factor in the last {} seconds.",
+                        max_age
+                    ),
+                ),
+            ),
+            ApplicationError::InviteRequired => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Invitation Required",
+                    "This instance requires an invitation code to register.",
+                ),
+            ),
+            ApplicationError::InvalidInvite => (
+                StatusCode::BAD_REQUEST,
+                Self::new(
+                    StatusCode::BAD_REQUEST,
+                    "Invalid Invitation",
+                    "The invitation code does not exist or has expired.",
+                ),
+            ),
+            ApplicationError::InviteNotFound => (
+                StatusCode::NOT_FOUND,
+                Self::new(
+                    StatusCode::NOT_FOUND,
+                    "Invitation Not Found",
+                    "The invitation does not exist or was already revoked.",
+                ),
+            ),
+            ApplicationError::InviteExhausted => (
+                StatusCode::GONE,
+                Self::new(
+                    StatusCode::GONE,
+                    "Invitation Exhausted",
+                    "The invitation code has already been used.",
+                ),
+            ),
+            ApplicationError::InviteQuotaExceeded { limit } => (
+                StatusCode::FORBIDDEN,
+                Self::new(
+                    StatusCode::FORBIDDEN,
+                    "Invitation Quota Exceeded",
+                    format!(
+                        "You cannot have more than {} active invitations.",
+                        limit
+                    ),
+                ),
+            ),
+            ApplicationError::RateLimited { retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Self::new(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too Many Requests",
+                    format!(
+                        "Too many attempts. Retry in {} seconds.",
+                        retry_after
+                    ),
+                ),
+            ),
+            ApplicationError::Overloaded { retry_after } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Self::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Service Unavailable",
+                    format!(
+                        "Too many requests are being processed. Retry in {} \
+                         seconds.",
+                        retry_after
+                    ),
+                ),
+            ),
+            ApplicationError::UnsupportedMediaType => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Self::new(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "Unsupported Media Type",
+                    "Only PNG, JPEG, GIF and WebP images are accepted.",
+                ),
+            ),
+            ApplicationError::PayloadTooLarge { limit } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Self::new(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "Payload Too Large",
+                    format!("Uploads are limited to {limit} bytes."),
+                ),
+            ),
             ApplicationError::TooSmall { expected } => (
                 StatusCode::BAD_REQUEST,
                 Self::new(
@@ -188,6 +434,31 @@ impl ProblemDetails {
                     code: "validation_failed".to_string(),
                 }]),
             ),
+            DomainError::PublicKey(err) => {
+                let code = match err {
+                    KeyError::InvalidFormat => "invalid_key_format",
+                    KeyError::UnsupportedAlgorithm => "unsupported_algorithm",
+                    KeyError::AlgorithmNotAllowed(_) => {
+                        "algorithm_not_allowed"
+                    },
+                    KeyError::WeakKey { .. } => "weak_key",
+                    KeyError::KeyTooLarge { .. } => "key_too_large",
+                    KeyError::InvalidSignature => "invalid_signature",
+                };
+                (
+                    StatusCode::BAD_REQUEST,
+                    Self::new(
+                        StatusCode::BAD_REQUEST,
+                        "Invalid Public Key",
+                        "The public key is refused.",
+                    )
+                    .with_errors(vec![FieldError {
+                        field: "publicKeys".to_string(),
+                        message: err.to_string(),
+                        code: code.to_string(),
+                    }]),
+                )
+            },
             DomainError::WeakPassword { min_length } => (
                 StatusCode::BAD_REQUEST,
                 Self::new(
@@ -236,8 +507,48 @@ impl From<DomainError> for HttpError {
 
 impl IntoResponse for HttpError {
     fn into_response(self) -> Response {
+        let retry_after = match self.0 {
+            ApplicationError::RateLimited { retry_after } |
+            ApplicationError::Overloaded { retry_after } => Some(retry_after),
+            _ => None,
+        };
+        let challenge = match &self.0 {
+            // RFC 6750 section 3.1.
+            ApplicationError::InsufficientScope { scopes } => Some(format!(
+                r#"Bearer error="insufficient_scope", scope="{}""#,
+                scopes.join(" ")
+            )),
+            // RFC 9470 section 3.
+            ApplicationError::ReauthenticationRequired { max_age } => {
+                Some(format!(
+                    r#"Bearer error="insufficient_user_authentication", max_age={max_age}"#
+                ))
+            },
+            ApplicationError::InvalidSignature => Some(
+                r#"Signature headers="(request-target) host date digest""#
+                    .to_string(),
+            ),
+            _ => None,
+        };
+
         let (status, problem) = ProblemDetails::from_application_error(self.0);
-        (status, Json(problem)).into_response()
+        let mut response = (status, Json(problem)).into_response();
+
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        if let Some(value) =
+            challenge.and_then(|c| HeaderValue::from_str(&c).ok())
+        {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, value);
+        }
+
+        response
     }
 }
 
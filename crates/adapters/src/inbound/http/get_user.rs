@@ -3,21 +3,23 @@
 use std::sync::Arc;
 
 use application::ports::inbound::GetUser;
-use axum::Json;
 use axum::extract::{Path, State};
+use axum::response::Response;
 use domain::identity::id::UserId;
 
+use crate::inbound::http::activitypub::Negotiated;
 use crate::inbound::http::errors::{HttpError, IntoHttpResult};
 
-/// Gets a user.
+/// Gets a user, as an ActivityPub actor if requested.
 pub async fn get_user_handler(
     State(service): State<Arc<dyn GetUser>>,
     Path(id): Path<String>,
-) -> Result<Json<application::dto::UserResponseDto>, HttpError> {
+    negotiated: Negotiated,
+) -> Result<Response, HttpError> {
     let response = service
         .execute(UserId::parse(id)?)
         .await
         .into_http_result()?;
 
-    Ok(Json(response))
+    Ok(negotiated.json(response))
 }
@@ -0,0 +1,92 @@
+//! Invitation management HTTP handlers.
+
+use std::sync::Arc;
+
+use application::dto::{CreateInviteDto, InviteDto};
+use application::ports::inbound::ManageInvites;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use domain::identity::id::UserId;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::extractor::Valid;
+
+/// Invitation creation request body.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInviteRequest {
+    /// Accounts allowed to join with the code.
+    #[validate(range(min = 1))]
+    pub max_uses: Option<u32>,
+    /// Seconds before the code expires.
+    #[validate(range(min = 1))]
+    pub expires_in: Option<u64>,
+}
+
+/// Invitation as returned to its owner.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteResponse {
+    pub code: String,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    pub created_at: u64,
+    /// Users who joined with this code.
+    pub used_by: Vec<String>,
+}
+
+impl From<InviteDto> for InviteResponse {
+    fn from(invite: InviteDto) -> Self {
+        Self {
+            code: invite.code,
+            max_uses: invite.max_uses,
+            uses: invite.uses,
+            expires_at: invite.expires_at,
+            revoked_at: invite.revoked_at,
+            created_at: invite.created_at,
+            used_by: invite.used_by.iter().map(UserId::to_string).collect(),
+        }
+    }
+}
+
+/// Handler for `POST /users/@me/invites`.
+pub async fn create_invite_handler(
+    State(service): State<Arc<dyn ManageInvites>>,
+    Extension(user_id): Extension<UserId>,
+    Valid(request): Valid<CreateInviteRequest>,
+) -> Result<(StatusCode, Json<InviteResponse>), HttpError> {
+    let dto = CreateInviteDto {
+        max_uses: request.max_uses,
+        expires_in: request.expires_in,
+    };
+
+    let invite = service.create(&user_id, dto).await.into_http_result()?;
+
+    Ok((StatusCode::CREATED, Json(invite.into())))
+}
+
+/// Handler for `GET /users/@me/invites`.
+pub async fn list_invites_handler(
+    State(service): State<Arc<dyn ManageInvites>>,
+    Extension(user_id): Extension<UserId>,
+) -> Result<Json<Vec<InviteResponse>>, HttpError> {
+    let invites = service.list(&user_id).await.into_http_result()?;
+
+    Ok(Json(invites.into_iter().map(Into::into).collect()))
+}
+
+/// Handler for `DELETE /users/@me/invites/{code}`.
+pub async fn revoke_invite_handler(
+    State(service): State<Arc<dyn ManageInvites>>,
+    Extension(user_id): Extension<UserId>,
+    Path(code): Path<String>,
+) -> Result<StatusCode, HttpError> {
+    service.revoke(&user_id, &code).await.into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
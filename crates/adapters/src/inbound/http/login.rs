@@ -3,14 +3,16 @@
 use std::sync::Arc;
 
 use application::dto::{AuthRequestDto, AuthResponseDto};
-use application::error::ApplicationError;
 use application::ports::inbound::Authenticate;
 use axum::Json;
 use axum::extract::State;
+use domain::error::DomainError;
 use domain::identity::email::EmailAddress;
 use serde::Deserialize;
 use validator::Validate;
 
+use crate::inbound::http::client_ip::EncryptedClientIp;
+use crate::inbound::http::device::{DeviceLabel, EncryptedUserAgent};
 use crate::inbound::http::errors::{HttpError, IntoHttpResult};
 use crate::inbound::http::extractor::Valid;
 
@@ -29,11 +31,18 @@ pub struct LoginRequest {
     /// TOTP code (required if MFA is enabled).
     #[serde(rename = "totpCode")]
     pub totp_code: Option<String>,
+    /// Replacement password, required when a reset was forced.
+    #[serde(rename = "newPassword")]
+    #[validate(length(min = 8, max = 128))]
+    pub new_password: Option<String>,
 }
 
 /// Authenticates a user.
 pub async fn login_handler(
     State(service): State<Arc<dyn Authenticate>>,
+    EncryptedClientIp(ip_address): EncryptedClientIp,
+    DeviceLabel(device): DeviceLabel,
+    EncryptedUserAgent(user_agent): EncryptedUserAgent,
     Valid(request): Valid<LoginRequest>,
 ) -> Result<Json<AuthResponseDto>, HttpError> {
     let email = request
@@ -41,13 +50,16 @@ pub async fn login_handler(
         .as_deref()
         .map(EmailAddress::parse)
         .transpose()
-        .map_err(|_| HttpError::from(ApplicationError::UserNotFound))?;
+        .map_err(|_| HttpError::from(DomainError::InvalidCredentials))?;
     let dto = AuthRequestDto {
         email,
         user_id: request.id,
         password: request.password,
         totp_code: request.totp_code,
-        ip_address: None, // TODO: extract from X-Forwarded-For.
+        new_password: request.new_password,
+        ip_address,
+        device,
+        user_agent,
     };
 
     let response = service.execute(dto).await.into_http_result()?;
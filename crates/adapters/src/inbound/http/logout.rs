@@ -0,0 +1,52 @@
+//! Logout HTTP handler.
+
+use std::sync::Arc;
+
+use application::dto::LogoutRequestDto;
+use application::ports::inbound::Logout;
+use application::ports::outbound::TokenClaims;
+use axum::Extension;
+use axum::extract::State;
+use axum::http::StatusCode;
+use domain::identity::id::UserId;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::inbound::http::client_ip::ClientContext;
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::extractor::Valid;
+
+/// Logout request body.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutRequest {
+    /// Refresh token to revoke.
+    #[validate(length(min = 1, max = 512))]
+    pub refresh_token: Option<String>,
+    /// Revoke every session and token of the user.
+    #[serde(default)]
+    pub everywhere: bool,
+}
+
+/// Handler for `POST /logout`.
+pub async fn logout_handler(
+    State(service): State<Arc<dyn Logout>>,
+    Extension(user_id): Extension<UserId>,
+    Extension(claims): Extension<TokenClaims>,
+    ClientContext(client): ClientContext,
+    Valid(request): Valid<LogoutRequest>,
+) -> Result<StatusCode, HttpError> {
+    let dto = LogoutRequestDto {
+        user_id,
+        jti: claims.jti,
+        expires_at: claims.exp,
+        session_id: claims.sid,
+        refresh_token: request.refresh_token,
+        everywhere: request.everywhere,
+        client,
+    };
+
+    service.execute(dto).await.into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
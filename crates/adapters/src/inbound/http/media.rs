@@ -0,0 +1,115 @@
+//! Profile media HTTP handlers.
+
+use std::sync::Arc;
+
+use application::dto::{ImageDto, MediaKind};
+use application::error::ApplicationError;
+use application::ports::inbound::ManageMedia;
+use axum::extract::multipart::MultipartError;
+use axum::extract::{Multipart, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+
+/// Largest accepted upload, in bytes.
+pub const MAX_UPLOAD_SIZE: usize = 8 * 1024 * 1024;
+/// Name of the multipart field holding the image.
+const FIELD: &str = "file";
+
+fn multipart_error(err: MultipartError) -> HttpError {
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        return HttpError(ApplicationError::PayloadTooLarge {
+            limit: MAX_UPLOAD_SIZE,
+        });
+    }
+
+    HttpError(
+        DomainError::ValidationFailed {
+            field: FIELD.into(),
+            message: err.body_text(),
+        }
+        .into(),
+    )
+}
+
+/// Read the `file` field of a multipart body.
+async fn read_file(mut multipart: Multipart) -> Result<Vec<u8>, HttpError> {
+    while let Some(field) =
+        multipart.next_field().await.map_err(multipart_error)?
+    {
+        if field.name() == Some(FIELD) {
+            let data = field.bytes().await.map_err(multipart_error)?;
+            return Ok(data.to_vec());
+        }
+    }
+
+    Err(HttpError(
+        DomainError::ValidationFailed {
+            field: FIELD.into(),
+            message: "Missing file.".into(),
+        }
+        .into(),
+    ))
+}
+
+async fn upload(
+    service: Arc<dyn ManageMedia>,
+    user_id: UserId,
+    kind: MediaKind,
+    multipart: Multipart,
+) -> Result<Json<ImageDto>, HttpError> {
+    let data = read_file(multipart).await?;
+    let image = service
+        .upload(&user_id, kind, data)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(image))
+}
+
+async fn remove(
+    service: Arc<dyn ManageMedia>,
+    user_id: UserId,
+    kind: MediaKind,
+) -> Result<StatusCode, HttpError> {
+    service.remove(&user_id, kind).await.into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `PUT /users/@me/avatar`.
+pub async fn upload_avatar_handler(
+    State(service): State<Arc<dyn ManageMedia>>,
+    Extension(user_id): Extension<UserId>,
+    multipart: Multipart,
+) -> Result<Json<ImageDto>, HttpError> {
+    upload(service, user_id, MediaKind::Avatar, multipart).await
+}
+
+/// Handler for `DELETE /users/@me/avatar`.
+pub async fn remove_avatar_handler(
+    State(service): State<Arc<dyn ManageMedia>>,
+    Extension(user_id): Extension<UserId>,
+) -> Result<StatusCode, HttpError> {
+    remove(service, user_id, MediaKind::Avatar).await
+}
+
+/// Handler for `PUT /users/@me/banner`.
+pub async fn upload_banner_handler(
+    State(service): State<Arc<dyn ManageMedia>>,
+    Extension(user_id): Extension<UserId>,
+    multipart: Multipart,
+) -> Result<Json<ImageDto>, HttpError> {
+    upload(service, user_id, MediaKind::Banner, multipart).await
+}
+
+/// Handler for `DELETE /users/@me/banner`.
+pub async fn remove_banner_handler(
+    State(service): State<Arc<dyn ManageMedia>>,
+    Extension(user_id): Extension<UserId>,
+) -> Result<StatusCode, HttpError> {
+    remove(service, user_id, MediaKind::Banner).await
+}
@@ -1,10 +1,26 @@
 //! HTTP inbound adapter using Axum.
 
+pub mod activitypub;
+pub mod admin;
+pub mod authentication;
+pub mod authorization;
+pub mod client_ip;
 pub mod create;
+pub mod delete_account;
+pub mod device;
 pub mod errors;
 pub mod extractor;
 pub mod get_user;
+pub mod invite;
 pub mod login;
+pub mod logout;
+pub mod media;
+pub mod rate_limit;
+pub mod reauthenticate;
+pub mod refresh;
+pub mod security_events;
+pub mod session;
+pub mod signature;
 pub mod status;
 pub mod update_user;
 pub mod validation;
@@ -0,0 +1,173 @@
+//! Tower layer throttling requests by client IP and globally.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use application::error::ApplicationError;
+use application::ports::outbound::{
+    RateLimitDecision, RateLimitKey, RateLimitPolicy, RateLimiter,
+    TelemetryPort,
+};
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use tower::{Layer, Service};
+
+use crate::inbound::http::client_ip::resolve_client_ip;
+use crate::inbound::http::errors::HttpError;
+
+struct RateLimitSettings {
+    limiter: Arc<dyn RateLimiter>,
+    telemetry: Arc<dyn TelemetryPort>,
+    scope: String,
+    per_ip: Option<RateLimitPolicy>,
+    global: Option<RateLimitPolicy>,
+}
+
+impl RateLimitSettings {
+    /// Returns the number of seconds to wait if any limit is exceeded.
+    async fn check(&self, ip: Option<String>) -> Option<u64> {
+        let checks = [
+            (
+                self.per_ip,
+                ip.map(|ip| RateLimitKey::Ip(format!("{}:{ip}", self.scope))),
+                "ip_rate_limited",
+            ),
+            (
+                self.global,
+                Some(RateLimitKey::Global(self.scope.clone())),
+                "global_rate_limited",
+            ),
+        ];
+
+        for (policy, key, reason) in checks {
+            let (Some(policy), Some(key)) = (policy, key) else {
+                continue;
+            };
+
+            match self.limiter.hit(&key, &policy).await {
+                Ok(RateLimitDecision::Limited { retry_after }) => {
+                    self.telemetry.record_auth_failure(reason);
+                    return Some(retry_after);
+                },
+                Ok(RateLimitDecision::Allowed { .. }) => {},
+                Err(err) => {
+                    // Fail open: an unavailable backend must not lock
+                    // everyone out.
+                    tracing::warn!(
+                        %err,
+                        scope = %self.scope,
+                        "rate limiter unavailable"
+                    );
+                },
+            }
+        }
+
+        None
+    }
+}
+
+/// Layer rejecting requests with `429 Too Many Requests` once a limit is
+/// reached.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    settings: Arc<RateLimitSettings>,
+}
+
+impl RateLimitLayer {
+    /// Create a new [`RateLimitLayer`].
+    ///
+    /// `scope` separates the counters of each protected route.
+    pub fn new(
+        limiter: Arc<dyn RateLimiter>,
+        telemetry: Arc<dyn TelemetryPort>,
+        scope: impl Into<String>,
+    ) -> Self {
+        Self {
+            settings: Arc::new(RateLimitSettings {
+                limiter,
+                telemetry,
+                scope: scope.into(),
+                per_ip: None,
+                global: None,
+            }),
+        }
+    }
+
+    /// Limit requests coming from a single client IP.
+    pub fn per_ip(mut self, policy: RateLimitPolicy) -> Self {
+        self.settings_mut().per_ip = Some(policy);
+        self
+    }
+
+    /// Limit requests across every client.
+    pub fn global(mut self, policy: RateLimitPolicy) -> Self {
+        self.settings_mut().global = Some(policy);
+        self
+    }
+
+    fn settings_mut(&mut self) -> &mut RateLimitSettings {
+        Arc::get_mut(&mut self.settings)
+            .expect("rate limit layer configured after being cloned")
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            settings: Arc::clone(&self.settings),
+        }
+    }
+}
+
+/// Service produced by [`RateLimitLayer`].
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    settings: Arc<RateLimitSettings>,
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Error = Infallible> + Clone + Send + 'static,
+    S::Response: IntoResponse,
+    S::Future: Send + 'static,
+{
+    type Error = Infallible;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+    type Response = Response;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // Take the service that was driven to readiness.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let settings = Arc::clone(&self.settings);
+
+        let ip = resolve_client_ip(req.extensions(), req.headers())
+            .map(|ip| ip.to_string());
+
+        Box::pin(async move {
+            if let Some(retry_after) = settings.check(ip).await {
+                return Ok(HttpError(ApplicationError::RateLimited {
+                    retry_after,
+                })
+                .into_response());
+            }
+
+            Ok(inner.call(req).await?.into_response())
+        })
+    }
+}
@@ -0,0 +1,52 @@
+//! Reauthentication HTTP handler.
+
+use std::sync::Arc;
+
+use application::dto::{ElevatedTokenDto, ReauthenticateRequestDto};
+use application::ports::inbound::Reauthenticate;
+use axum::extract::State;
+use axum::{Extension, Json};
+use domain::identity::id::UserId;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::inbound::http::authorization::AccessToken;
+use crate::inbound::http::client_ip::ClientContext;
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::extractor::Valid;
+
+/// Reauthentication request body.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ReauthenticateRequest {
+    /// User password.
+    #[validate(length(min = 8, max = 128))]
+    pub password: String,
+    /// TOTP code (required if MFA is enabled).
+    pub totp_code: Option<String>,
+    /// Refresh token of the current session (required without MFA).
+    #[validate(length(min = 1, max = 512))]
+    pub refresh_token: Option<String>,
+}
+
+/// Exchange fresh credentials for a short-lived elevated access token.
+pub async fn reauthenticate_handler(
+    State(service): State<Arc<dyn Reauthenticate>>,
+    Extension(user_id): Extension<UserId>,
+    AccessToken(claims): AccessToken,
+    ClientContext(client): ClientContext,
+    Valid(request): Valid<ReauthenticateRequest>,
+) -> Result<Json<ElevatedTokenDto>, HttpError> {
+    let dto = ReauthenticateRequestDto {
+        user_id,
+        session_id: claims.sid,
+        password: request.password,
+        totp_code: request.totp_code,
+        refresh_token: request.refresh_token,
+        client,
+    };
+
+    let response = service.execute(dto).await.into_http_result()?;
+
+    Ok(Json(response))
+}
@@ -0,0 +1,39 @@
+//! Token refresh HTTP handler.
+
+use std::sync::Arc;
+
+use application::dto::{AuthResponseDto, RefreshTokenRequestDto};
+use application::ports::inbound::RefreshAccessToken;
+use axum::Json;
+use axum::extract::State;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::inbound::http::client_ip::EncryptedClientIp;
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+use crate::inbound::http::extractor::Valid;
+
+/// Refresh request body.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenRequest {
+    /// Refresh token received on login.
+    #[validate(length(min = 1, max = 512))]
+    pub refresh_token: String,
+}
+
+/// Exchange a refresh token for a new token pair.
+pub async fn refresh_token_handler(
+    State(service): State<Arc<dyn RefreshAccessToken>>,
+    EncryptedClientIp(ip_address): EncryptedClientIp,
+    Valid(request): Valid<RefreshTokenRequest>,
+) -> Result<Json<AuthResponseDto>, HttpError> {
+    let dto = RefreshTokenRequestDto {
+        refresh_token: request.refresh_token,
+        ip_address,
+    };
+
+    let response = service.execute(dto).await.into_http_result()?;
+
+    Ok(Json(response))
+}
@@ -0,0 +1,21 @@
+//! Security events HTTP handler.
+
+use std::sync::Arc;
+
+use application::dto::SecurityEventInfoDto;
+use application::ports::inbound::ListSecurityEvents;
+use axum::extract::State;
+use axum::{Extension, Json};
+use domain::identity::id::UserId;
+
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+
+/// Handler for `GET /users/@me/security-events`.
+pub async fn list_security_events_handler(
+    State(service): State<Arc<dyn ListSecurityEvents>>,
+    Extension(user_id): Extension<UserId>,
+) -> Result<Json<Vec<SecurityEventInfoDto>>, HttpError> {
+    let events = service.list(&user_id).await.into_http_result()?;
+
+    Ok(Json(events))
+}
@@ -0,0 +1,60 @@
+//! Session management HTTP handlers.
+
+use std::sync::Arc;
+
+use application::dto::{AccessTokenDto, SessionInfoDto};
+use application::ports::inbound::ManageSessions;
+use application::ports::outbound::TokenClaims;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use domain::identity::id::UserId;
+
+use crate::inbound::http::client_ip::ClientContext;
+use crate::inbound::http::errors::{HttpError, IntoHttpResult};
+
+/// Handler for `GET /users/@me/sessions`.
+pub async fn list_sessions_handler(
+    State(service): State<Arc<dyn ManageSessions>>,
+    Extension(user_id): Extension<UserId>,
+    Extension(claims): Extension<TokenClaims>,
+) -> Result<Json<Vec<SessionInfoDto>>, HttpError> {
+    let sessions = service
+        .list(&user_id, claims.sid.as_deref())
+        .await
+        .into_http_result()?;
+
+    Ok(Json(sessions))
+}
+
+/// Handler for `DELETE /users/@me/sessions/{id}`.
+pub async fn revoke_session_handler(
+    State(service): State<Arc<dyn ManageSessions>>,
+    Extension(user_id): Extension<UserId>,
+    Path(session_id): Path<String>,
+    ClientContext(client): ClientContext,
+) -> Result<StatusCode, HttpError> {
+    service
+        .revoke(&user_id, &session_id, &client)
+        .await
+        .into_http_result()?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /users/@me/sessions`, logging out everywhere else.
+///
+/// Returns the access token replacing the one of the request.
+pub async fn revoke_other_sessions_handler(
+    State(service): State<Arc<dyn ManageSessions>>,
+    Extension(user_id): Extension<UserId>,
+    Extension(claims): Extension<TokenClaims>,
+    ClientContext(client): ClientContext,
+) -> Result<Json<AccessTokenDto>, HttpError> {
+    let token = service
+        .revoke_others(&user_id, &claims, &client)
+        .await
+        .into_http_result()?;
+
+    Ok(Json(token))
+}
@@ -0,0 +1,460 @@
+//! Verification of signed server-to-server requests.
+//!
+//! Requests signed with HTTP Message Signatures (RFC 9421) must cover
+//! `@method`, their target and, when they have a body, `content-digest`.
+//! In compatibility mode, draft-cavage signatures must cover
+//! `(request-target)`, `host`, `date` and, with a body, `digest`. Either way
+//! a captured request can neither be replayed later nor carry another body.
+
+use std::sync::Arc;
+
+use application::error::ApplicationError;
+use application::ports::inbound::PeerTrust;
+use application::ports::outbound::{ActorResolver, CryptoPort};
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequest, Request};
+use axum::http::request::Parts;
+use chrono::{DateTime, Utc};
+use domain::error::DomainError;
+use url::Url;
+
+use crate::inbound::http::activitypub::MAX_ACTIVITY_SIZE;
+use crate::inbound::http::errors::HttpError;
+use crate::outbound::activitypub::signature::{
+    Message, RSA_V1_5_SHA256, SignatureHeader, SignatureInput, authority,
+    content_digest_matches, digest_matches, is_date_fresh, is_fresh,
+    parse_signatures, signing_string,
+};
+
+/// Key which signed a request, set as an extension by
+/// [`SignatureVerifier::verify`].
+#[derive(Debug, Clone)]
+pub struct SignedBy {
+    pub key_id: String,
+    /// URL of the actor owning the key.
+    pub owner: String,
+}
+
+/// Signature covering a request, and what it was computed over.
+struct Signed {
+    key_id: String,
+    base: String,
+    signature: Vec<u8>,
+}
+
+/// Verifier of the signatures of server-to-server requests.
+pub struct SignatureVerifier {
+    resolver: Arc<dyn ActorResolver>,
+    crypto: Arc<dyn CryptoPort>,
+    peer_trust: Arc<dyn PeerTrust>,
+    /// Scheme of the public URL of the instance, which requests were sent
+    /// with.
+    scheme: &'static str,
+    cavage: bool,
+}
+
+impl SignatureVerifier {
+    /// Create a new [`SignatureVerifier`] accepting RFC 9421 signatures.
+    pub fn new(
+        resolver: Arc<dyn ActorResolver>,
+        crypto: Arc<dyn CryptoPort>,
+        peer_trust: Arc<dyn PeerTrust>,
+        base_url: &str,
+    ) -> Self {
+        let scheme = if base_url.starts_with("http://") {
+            "http"
+        } else {
+            "https"
+        };
+
+        Self {
+            resolver,
+            crypto,
+            peer_trust,
+            scheme,
+            cavage: false,
+        }
+    }
+
+    /// Also accept draft-cavage signatures.
+    pub fn with_cavage(mut self) -> Self {
+        self.cavage = true;
+        self
+    }
+
+    /// Verify the signature of a request, if it has one.
+    ///
+    /// Signed requests get a [`SignedBy`] extension; unsigned ones are
+    /// returned unchanged.
+    pub async fn verify(
+        &self,
+        request: Request,
+    ) -> Result<Request, HttpError> {
+        let (mut parts, body) = request.into_parts();
+        let signed_9421 = parts.headers.contains_key("signature-input");
+        let signed_cavage = SignatureHeader::from_headers(&parts.headers);
+        if !signed_9421 && signed_cavage.is_none() {
+            return Ok(Request::from_parts(parts, body));
+        }
+
+        let body = axum::body::to_bytes(body, MAX_ACTIVITY_SIZE)
+            .await
+            .map_err(|_| ApplicationError::PayloadTooLarge {
+                limit: MAX_ACTIVITY_SIZE,
+            })?;
+        let now = Utc::now();
+
+        let signed = match signed_cavage {
+            _ if signed_9421 => self.rfc9421(&parts, &body, now),
+            Some(header) if self.cavage => cavage(&parts, &body, header, now),
+            _ => None,
+        }
+        .ok_or(ApplicationError::InvalidSignature)?;
+
+        let host = Url::parse(&signed.key_id)
+            .ok()
+            .as_ref()
+            .and_then(authority)
+            .ok_or(ApplicationError::InvalidSignature)?;
+        self.peer_trust.authorize(&host).await?;
+
+        let key = self.resolver.public_key(&signed.key_id).await?;
+        let verified = self.crypto.signature_keys().verify(
+            &key.public_key_pem,
+            signed.base.as_bytes(),
+            &signed.signature,
+        )?;
+        if !verified {
+            return Err(ApplicationError::InvalidSignature.into());
+        }
+        self.peer_trust.verified(&host, &signed.key_id).await?;
+
+        parts.extensions.insert(SignedBy {
+            key_id: signed.key_id,
+            owner: key.owner,
+        });
+        Ok(Request::from_parts(parts, Body::from(body)))
+    }
+
+    /// Absolute URI a request was sent to.
+    fn target_uri(&self, parts: &Parts) -> Option<Url> {
+        let host = parts
+            .headers
+            .get(axum::http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| parts.uri.authority().map(|a| a.as_str()))?;
+        let path = parts
+            .uri
+            .path_and_query()
+            .map_or(parts.uri.path(), |p| p.as_str());
+
+        Url::parse(&format!("{}://{host}{path}", self.scheme)).ok()
+    }
+
+    fn rfc9421(
+        &self,
+        parts: &Parts,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> Option<Signed> {
+        let signatures = parts
+            .headers
+            .get("signature")
+            .and_then(|value| value.to_str().ok())
+            .map(parse_signatures)?;
+        let input = parts.headers.get("signature-input")?.to_str().ok()?;
+        let (input, signature) = SignatureInput::parse_all(input)
+            .into_iter()
+            .find_map(|input| {
+                let (_, signature) = signatures
+                    .iter()
+                    .find(|(label, _)| *label == input.label)?;
+                Some((input, signature.clone()))
+            })?;
+
+        let fresh =
+            input.created.is_some_and(|created| is_fresh(created, now)) &&
+                input
+                    .expires
+                    .is_none_or(|expires| expires >= now.timestamp());
+        let body_covered = body.is_empty() ||
+            (input.covers("content-digest") &&
+                content_digest_matches(&parts.headers, body));
+        if !fresh ||
+            !body_covered ||
+            !input.covers("@method") ||
+            !input.covers_target() ||
+            !matches!(input.alg.as_deref(), None | Some(RSA_V1_5_SHA256))
+        {
+            return None;
+        }
+
+        let target_uri = self.target_uri(parts)?;
+        let base = Message {
+            method: Some(&parts.method),
+            target_uri: Some(&target_uri),
+            status: None,
+            headers: &parts.headers,
+        }
+        .signature_base(&input)?;
+
+        Some(Signed {
+            key_id: input.key_id?,
+            base,
+            signature,
+        })
+    }
+}
+
+fn cavage(
+    parts: &Parts,
+    body: &[u8],
+    header: SignatureHeader,
+    now: DateTime<Utc>,
+) -> Option<Signed> {
+    let covers = |name: &str| header.headers.iter().any(|h| h == name);
+    let body_covered = body.is_empty() ||
+        (covers("digest") && digest_matches(&parts.headers, body));
+    if !covers("(request-target)") ||
+        !covers("host") ||
+        !covers("date") ||
+        !body_covered ||
+        !is_date_fresh(&parts.headers, now)
+    {
+        return None;
+    }
+
+    let base = signing_string(
+        &parts.method,
+        &parts.uri,
+        &parts.headers,
+        &header.headers,
+    )?;
+
+    Some(Signed {
+        key_id: header.key_id,
+        base,
+        signature: header.signature,
+    })
+}
+
+/// JSON body of a request signed by a remote actor.
+pub struct VerifiedActivity {
+    /// URL of the actor owning the signing key.
+    pub actor: String,
+    pub activity: serde_json::Value,
+}
+
+impl<S> FromRequest<S> for VerifiedActivity
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, HttpError> {
+        let signed_by = req
+            .extensions()
+            .get::<SignedBy>()
+            .cloned()
+            .ok_or(ApplicationError::InvalidSignature)?;
+
+        let body = Bytes::from_request(req, state).await.map_err(|_| {
+            ApplicationError::PayloadTooLarge {
+                limit: MAX_ACTIVITY_SIZE,
+            }
+        })?;
+        let activity = serde_json::from_slice(&body).map_err(|err| {
+            DomainError::ValidationFailed {
+                field: "activity".into(),
+                message: err.to_string(),
+            }
+        })?;
+
+        Ok(Self {
+            actor: signed_by.owner,
+            activity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use application::dto::ActorPublicKeyDto;
+    use axum::http::{HeaderValue, Method};
+    use zeroize::Zeroizing;
+
+    use super::*;
+    use crate::outbound::activitypub::{RequestSigner, SignatureStyle};
+    use crate::outbound::clock::SystemClock;
+    use crate::outbound::crypto::{CryptoAdapter, HmacBlindIndex, Keyring};
+
+    struct Resolver(String);
+
+    #[async_trait::async_trait]
+    impl ActorResolver for Resolver {
+        async fn public_key(
+            &self,
+            key_id: &str,
+        ) -> application::error::Result<ActorPublicKeyDto> {
+            Ok(ActorPublicKeyDto {
+                id: key_id.into(),
+                owner: "https://remote.example/actor".into(),
+                public_key_pem: self.0.clone(),
+            })
+        }
+    }
+
+    struct Trusted;
+
+    #[async_trait::async_trait]
+    impl PeerTrust for Trusted {
+        async fn authorize(
+            &self,
+            host: &str,
+        ) -> application::error::Result<()> {
+            assert_eq!(host, "remote.example");
+            Ok(())
+        }
+
+        async fn verified(
+            &self,
+            _host: &str,
+            _key_id: &str,
+        ) -> application::error::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn parts(headers: &[(&str, &str)]) -> Parts {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri("/users/alice/inbox");
+        for (name, value) in headers {
+            request =
+                request.header(*name, HeaderValue::from_str(value).unwrap());
+        }
+
+        request.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_cavage_coverage() {
+        let now = Utc::now();
+        let date = now.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let body = br#"{"type":"Follow"}"#;
+        let digest = crate::outbound::activitypub::signature::digest(body);
+        let header = |headers: &str| SignatureHeader {
+            key_id: "https://example.com/actor#main-key".into(),
+            headers: headers.split(' ').map(String::from).collect(),
+            signature: vec![0; 256],
+        };
+        let parts = parts(&[
+            ("host", "auth.example.com"),
+            ("date", &date),
+            ("digest", &digest),
+        ]);
+
+        let signed = cavage(
+            &parts,
+            body,
+            header("(request-target) host date digest"),
+            now,
+        )
+        .unwrap();
+        assert!(
+            signed
+                .base
+                .starts_with("(request-target): post /users/alice/inbox\n")
+        );
+
+        // The body must be covered.
+        assert!(
+            cavage(&parts, body, header("(request-target) host date"), now)
+                .is_none()
+        );
+        assert!(
+            cavage(
+                &parts,
+                b"{}",
+                header("(request-target) host date digest"),
+                now
+            )
+            .is_none()
+        );
+        // Old requests cannot be replayed.
+        assert!(
+            cavage(
+                &parts,
+                body,
+                header("(request-target) host date digest"),
+                now + chrono::Duration::hours(2)
+            )
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify() {
+        let crypto: Arc<dyn CryptoPort> = Arc::new(
+            CryptoAdapter::new(
+                Arc::new(SystemClock),
+                Keyring::new("1", b"key", &[0; 16]).unwrap(),
+                vec![0; 16],
+                HmacBlindIndex::legacy(vec![0; 16]),
+                8,
+                1,
+                1,
+            )
+            .unwrap(),
+        );
+        let (public_key, private_key) =
+            crypto.signature_keys().generate().await.unwrap();
+        let verifier = SignatureVerifier::new(
+            Arc::new(Resolver(public_key)),
+            Arc::clone(&crypto),
+            Arc::new(Trusted),
+            "https://auth.example.com",
+        )
+        .with_cavage();
+
+        for style in [SignatureStyle::Rfc9421, SignatureStyle::Cavage] {
+            let signer = RequestSigner::new(
+                "https://remote.example/actor#main-key",
+                Zeroizing::new(private_key.clone()),
+                Arc::clone(&crypto),
+            )
+            .with_style(style);
+            let body = br#"{"type":"Follow"}"#;
+            let mut outbound = reqwest::Client::new()
+                .post("https://auth.example.com/users/alice/inbox")
+                .body(body.to_vec())
+                .build()
+                .unwrap();
+            signer.sign_request(&mut outbound).unwrap();
+
+            let request = |body: &[u8]| {
+                let mut request = Request::builder()
+                    .method(Method::POST)
+                    .uri("/users/alice/inbox")
+                    .header("host", "auth.example.com");
+                for (name, value) in outbound.headers() {
+                    if name != "host" {
+                        request = request.header(name, value);
+                    }
+                }
+                request.body(Body::from(body.to_vec())).unwrap()
+            };
+
+            let verified = verifier
+                .verify(request(body))
+                .await
+                .map_err(|err| err.0)
+                .unwrap();
+            let signed_by = verified.extensions().get::<SignedBy>().unwrap();
+            assert_eq!(signed_by.owner, "https://remote.example/actor");
+
+            // Another body does not match the signature.
+            assert!(verifier.verify(request(b"{}")).await.is_err());
+        }
+    }
+}
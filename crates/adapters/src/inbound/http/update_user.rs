@@ -5,20 +5,108 @@ use std::sync::Arc;
 use application::dto::UpdateUserDto;
 use application::ports::inbound::UpdateUser;
 use axum::extract::State;
+use axum::response::{IntoResponse, Response};
 use axum::{Extension, Json};
 use domain::identity::id::UserId;
 
+use crate::inbound::http::authorization::AccessToken;
+use crate::inbound::http::client_ip::ClientContext;
+use crate::inbound::http::errors::HttpError;
+
 /// Handler for `PATCH /users/@me`
 pub async fn handler(
     State(service): State<Arc<dyn UpdateUser>>,
     Extension(user_id): Extension<UserId>,
+    token: AccessToken,
+    ClientContext(client): ClientContext,
     Json(payload): Json<UpdateUserDto>,
-) -> Result<Json<Vec<String>>, axum::http::StatusCode> {
-    match service.update(&user_id, payload).await {
-        Ok(keys) => Ok(Json(keys)),
-        Err(err) => {
-            tracing::error!(%err, "failed to update user");
-            Err(axum::http::StatusCode::BAD_REQUEST)
-        },
+) -> Result<Json<Vec<String>>, Response> {
+    if payload.public_keys.is_some() {
+        token
+            .require_scopes(["write:public_keys"])
+            .map_err(IntoResponse::into_response)?;
+    }
+
+    service
+        .update(&user_id, &token.0, payload, &client)
+        .await
+        .map(Json)
+        .map_err(|err| HttpError(err).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use application::dto::{ClientContextDto, TypedKeyDto};
+    use application::error::Result;
+    use application::ports::outbound::TokenClaims;
+    use async_trait::async_trait;
+    use axum::http::StatusCode;
+    use domain::key::algorithm::KeyPolicy;
+    use domain::key::pem::PemPublicKey;
+
+    use super::*;
+
+    const RSA_1024: &str = "-----BEGIN PUBLIC KEY-----\nMIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC1QHr7VshXkPRmEkcoH1wXCA9l\nWn0yg8mGC5ZgDJD4r8jAxAI4rUmIYZygaFd6ga/00Oka+qUmaSZEofKH9bYPmY6X\nCNrhAfmi8LHHH9YAO4eTRhLqaX/Ka/naMZ8/qA9shd5BNgYZBWs6Dc3n3S/c68Xf\nJSE6eX1aLA321yjXsQIDAQAB\n-----END PUBLIC KEY-----\n";
+
+    /// Service checking keys against the default policy.
+    struct KeyChecker;
+
+    #[async_trait]
+    impl UpdateUser for KeyChecker {
+        async fn update(
+            &self,
+            _user_id: &UserId,
+            _claims: &TokenClaims,
+            payload: UpdateUserDto,
+            _client: &ClientContextDto,
+        ) -> Result<Vec<String>> {
+            let Some(TypedKeyDto::One(key)) = payload.public_keys else {
+                return Ok(Vec::new());
+            };
+            let pem = PemPublicKey::parse(key)?;
+            KeyPolicy::default().check(pem.algorithm(), pem.bits())?;
+            Ok(vec![pem.fingerprint()?.to_string()])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_weak_key() {
+        let user_id = UserId::parse("alice").unwrap();
+        let token = AccessToken(TokenClaims {
+            sub: user_id.to_string(),
+            iss: "https://auth".into(),
+            aud: "https://auth".into(),
+            exp: 900,
+            iat: 0,
+            jti: "jti".into(),
+            scope: "write:public_keys".into(),
+            sid: None,
+            generation: 0,
+            amr: vec!["pwd".into()],
+            auth_time: 0,
+        });
+        let payload = serde_json::from_value(serde_json::json!({
+            "publicKeys": RSA_1024,
+        }))
+        .unwrap();
+
+        let response = handler(
+            State(Arc::new(KeyChecker)),
+            Extension(user_id),
+            token,
+            ClientContext(ClientContextDto::default()),
+            Json(payload),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: serde_json::Value =
+            serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["errors"][0]["field"], "publicKeys");
+        assert_eq!(problem["errors"][0]["code"], "weak_key");
     }
 }
@@ -22,21 +22,3 @@ pub fn validate_locale(locale: &str) -> Result<(), ValidationError> {
 
     Ok(())
 }
-
-/// Validate password strength.
-pub fn validate_password_strength(
-    password: &str,
-) -> Result<(), ValidationError> {
-    use passwords::{analyzer, scorer};
-
-    let analysis = analyzer::analyze(password);
-    let score = scorer::score(&analysis);
-
-    if score < 80.0 {
-        return Err(ValidationError::new("weak_password").with_message(
-            "Password is too weak. Use a mix of letters, numbers, and symbols.".into(),
-        ));
-    }
-
-    Ok(())
-}
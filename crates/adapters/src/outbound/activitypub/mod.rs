@@ -0,0 +1,381 @@
+//! Resolution of remote ActivityPub actors.
+//!
+//! With a [`RequestSigner`], key fetches are signed with the instance key, as
+//! servers enforcing authorized fetch require, and responses signed by
+//! another instance are verified before being trusted.
+//!
+//! Keys are only fetched from global addresses, see [`network`].
+
+pub mod network;
+pub mod signature;
+pub mod signer;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use application::dto::ActorPublicKeyDto;
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::ActorResolver;
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::Client;
+use reqwest::header::ACCEPT;
+use serde_json::Value;
+use url::Url;
+
+use self::network::{GlobalResolver, is_allowed_url, redirect_policy};
+pub use self::signature::SignatureStyle;
+use self::signature::{
+    Message, SignatureInput, content_digest_matches, is_fresh,
+    parse_signatures,
+};
+pub use self::signer::RequestSigner;
+
+const ACTIVITY_JSON: &str = "application/activity+json, application/ld+json; \
+                             profile=\"https://www.w3.org/ns/activitystreams\"";
+/// Response signature asked to other instances (RFC 9421, section 5).
+const ACCEPT_SIGNATURE: &str = r#"sig1=("@status" "content-digest")"#;
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Actor documents are small; anything larger is not worth parsing.
+const MAX_DOCUMENT_SIZE: usize = 256 * 1024;
+
+/// Resolver fetching actor documents over HTTP.
+pub struct HttpActorResolver {
+    client: Client,
+    signer: Option<Arc<RequestSigner>>,
+    allow_http: bool,
+    allow_private: bool,
+}
+
+impl HttpActorResolver {
+    /// Create a new [`HttpActorResolver`].
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: client(false, false)?,
+            signer: None,
+            allow_http: false,
+            allow_private: false,
+        })
+    }
+
+    /// Sign requests with the instance key and verify signed responses.
+    pub fn with_signer(mut self, signer: Arc<RequestSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Also resolve keys served over plain HTTP, for development only.
+    pub fn allow_http(mut self) -> Result<Self> {
+        self.allow_http = true;
+        self.client = client(self.allow_http, self.allow_private)?;
+        Ok(self)
+    }
+
+    /// Also resolve keys hosted on loopback and private networks, for
+    /// development only.
+    pub fn allow_private_addresses(mut self) -> Result<Self> {
+        self.allow_private = true;
+        self.client = client(self.allow_http, self.allow_private)?;
+        Ok(self)
+    }
+
+    /// Parse `key_id` and ensure it uses an allowed scheme and host.
+    fn parse_url(&self, key_id: &str) -> Result<Url> {
+        let url = Url::parse(key_id)
+            .map_err(|_| ApplicationError::InvalidSignature)?;
+
+        if is_allowed_url(&url, self.allow_http, self.allow_private) {
+            Ok(url)
+        } else {
+            Err(ApplicationError::InvalidSignature)
+        }
+    }
+
+    /// Fetch the key `key_id`, verifying the response signature if
+    /// `verify` is set.
+    async fn key(
+        &self,
+        key_id: &str,
+        verify: bool,
+    ) -> Result<ActorPublicKeyDto> {
+        let mut url = self.parse_url(key_id)?;
+        url.set_fragment(None);
+        let origin = url.origin();
+
+        let key = find_public_key(&self.fetch(url, verify).await?, key_id)
+            .ok_or(ApplicationError::InvalidSignature)?;
+
+        // A server may only vouch for its own actors.
+        let owner = Url::parse(&key.owner)
+            .map_err(|_| ApplicationError::InvalidSignature)?;
+        if owner.origin() != origin {
+            return Err(ApplicationError::InvalidSignature);
+        }
+
+        Ok(key)
+    }
+
+    /// Download the JSON document at `url`.
+    async fn fetch(&self, url: Url, verify: bool) -> Result<Value> {
+        let mut request = self
+            .client
+            .get(url.clone())
+            .header(ACCEPT, ACTIVITY_JSON)
+            .build()
+            .map_err(|_| ApplicationError::InvalidSignature)?;
+        if let Some(signer) = &self.signer {
+            signer.sign_request(&mut request)?;
+            request.headers_mut().insert(
+                "accept-signature",
+                reqwest::header::HeaderValue::from_static(ACCEPT_SIGNATURE),
+            );
+        }
+
+        let mut response = self
+            .client
+            .execute(request)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|_| ApplicationError::InvalidSignature)?;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|_| ApplicationError::InvalidSignature)?
+        {
+            if body.len() + chunk.len() > MAX_DOCUMENT_SIZE {
+                return Err(ApplicationError::InvalidSignature);
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        if verify {
+            self.verify_response(
+                &url,
+                response.status().as_u16(),
+                response.headers(),
+                &body,
+            )
+            .await?;
+        }
+
+        serde_json::from_slice(&body)
+            .map_err(|_| ApplicationError::InvalidSignature)
+    }
+
+    /// Verify the RFC 9421 signature of a response, if it has one.
+    ///
+    /// Unsigned responses are accepted, as most servers do not sign them.
+    async fn verify_response(
+        &self,
+        url: &Url,
+        status: u16,
+        headers: &reqwest::header::HeaderMap,
+        body: &[u8],
+    ) -> Result<()> {
+        let Some(signer) = &self.signer else {
+            return Ok(());
+        };
+        let Some(input) = headers
+            .get("signature-input")
+            .and_then(|value| value.to_str().ok())
+        else {
+            return Ok(());
+        };
+
+        let signatures = headers
+            .get("signature")
+            .and_then(|value| value.to_str().ok())
+            .map(parse_signatures)
+            .unwrap_or_default();
+        let (input, signature) = SignatureInput::parse_all(input)
+            .into_iter()
+            .find_map(|input| {
+                let (_, signature) = signatures
+                    .iter()
+                    .find(|(label, _)| *label == input.label)?;
+                Some((input, signature.clone()))
+            })
+            .ok_or(ApplicationError::InvalidSignature)?;
+
+        let key_id = input
+            .key_id
+            .as_deref()
+            .ok_or(ApplicationError::InvalidSignature)?;
+        let fresh = input
+            .created
+            .is_some_and(|created| is_fresh(created, Utc::now()));
+        if !fresh ||
+            !input.covers("@status") ||
+            !input.covers("content-digest") ||
+            !content_digest_matches(headers, body) ||
+            self.parse_url(key_id)?.origin() != url.origin()
+        {
+            return Err(ApplicationError::InvalidSignature);
+        }
+
+        let base = Message {
+            method: None,
+            target_uri: None,
+            status: Some(status),
+            headers,
+        }
+        .signature_base(&input)
+        .ok_or(ApplicationError::InvalidSignature)?;
+        let key = Box::pin(self.key(key_id, false)).await?;
+
+        if signer.verify(&key.public_key_pem, base.as_bytes(), &signature)? {
+            Ok(())
+        } else {
+            Err(ApplicationError::InvalidSignature)
+        }
+    }
+}
+
+/// Build the HTTP client, only connecting to global addresses unless
+/// `allow_private` is set.
+fn client(allow_http: bool, allow_private: bool) -> Result<Client> {
+    let builder = Client::builder()
+        .timeout(TIMEOUT)
+        .redirect(redirect_policy(allow_http, allow_private))
+        .user_agent(concat!("Autha/", env!("CARGO_PKG_VERSION")));
+    let builder = if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(GlobalResolver))
+    };
+
+    builder
+        .build()
+        .map_err(|err| ApplicationError::Internal(Box::new(err)))
+}
+
+/// Find the key `key_id` in an actor or key document.
+fn find_public_key(
+    document: &Value,
+    key_id: &str,
+) -> Option<ActorPublicKeyDto> {
+    let candidates = match document.get("publicKey") {
+        Some(Value::Array(keys)) => keys.iter().collect(),
+        Some(key) => vec![key],
+        None => vec![document],
+    };
+
+    candidates
+        .into_iter()
+        .filter_map(|key| {
+            serde_json::from_value::<ActorPublicKeyDto>(key.clone()).ok()
+        })
+        .find(|key| key.id == key_id)
+}
+
+#[async_trait]
+impl ActorResolver for HttpActorResolver {
+    async fn public_key(&self, key_id: &str) -> Result<ActorPublicKeyDto> {
+        self.key(key_id, true).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+
+    const PEM: &str =
+        "-----BEGIN PUBLIC KEY-----\n...\n-----END PUBLIC KEY-----";
+
+    #[test]
+    fn test_find_public_key() {
+        let key = json!({
+            "id": "https://example.com/users/a#main-key",
+            "owner": "https://example.com/users/a",
+            "publicKeyPem": PEM,
+        });
+
+        let actor =
+            json!({ "id": "https://example.com/users/a", "publicKey": key });
+        assert!(
+            find_public_key(&actor, "https://example.com/users/a#main-key")
+                .is_some()
+        );
+        assert!(
+            find_public_key(&actor, "https://example.com/users/a#other")
+                .is_none()
+        );
+
+        let actor = json!({ "publicKey": [{ "id": "x" }, key] });
+        assert!(
+            find_public_key(&actor, "https://example.com/users/a#main-key")
+                .is_some()
+        );
+        assert!(
+            find_public_key(&key, "https://example.com/users/a#main-key")
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let actor = json!({
+            "id": format!("{base}/users/a"),
+            "publicKey": {
+                "id": format!("{base}/users/a#main-key"),
+                "owner": format!("{base}/users/a"),
+                "publicKeyPem": PEM,
+            },
+        });
+        let spoofed = json!({
+            "publicKey": {
+                "id": format!("{base}/users/b#main-key"),
+                "owner": "https://victim.example/users/b",
+                "publicKeyPem": PEM,
+            },
+        });
+        let app = Router::new()
+            .route("/users/a", get(move || async move { Json(actor) }))
+            .route("/users/b", get(move || async move { Json(spoofed) }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let key_id = format!("{base}/users/a#main-key");
+        assert!(
+            HttpActorResolver::new()
+                .unwrap()
+                .public_key(&key_id)
+                .await
+                .is_err()
+        );
+        // Loopback is refused, whether as an IP literal or a host name.
+        let public = HttpActorResolver::new().unwrap().allow_http().unwrap();
+        assert!(public.public_key(&key_id).await.is_err());
+        assert!(
+            public
+                .public_key(&key_id.replace("127.0.0.1", "localhost"))
+                .await
+                .is_err()
+        );
+
+        let resolver = HttpActorResolver::new()
+            .unwrap()
+            .allow_http()
+            .unwrap()
+            .allow_private_addresses()
+            .unwrap();
+        let key = resolver.public_key(&key_id).await.unwrap();
+        assert_eq!(key.owner, format!("{base}/users/a"));
+        assert_eq!(key.public_key_pem, PEM);
+
+        assert!(
+            resolver
+                .public_key(&format!("{base}/users/b#main-key"))
+                .await
+                .is_err()
+        );
+    }
+}
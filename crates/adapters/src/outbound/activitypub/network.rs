@@ -0,0 +1,261 @@
+//! Restriction of actor fetches to the public internet.
+//!
+//! A `keyId` is chosen by whoever signs the request, so fetching it must not
+//! reach the instance's own network. Host names are resolved by
+//! [`GlobalResolver`], which drops non-global addresses, and IP literals are
+//! checked by [`is_allowed_url`], both for the `keyId` and on every redirect.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect;
+use url::{Host, Url};
+
+/// Redirects followed before giving up.
+const MAX_REDIRECTS: usize = 3;
+
+/// Whether `ip` is reachable on the public internet.
+pub fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_global_v4(ip),
+        IpAddr::V6(ip) => is_global_v6(ip),
+    }
+}
+
+fn is_global_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+
+    !(a == 0 ||
+        ip.is_private() ||
+        ip.is_loopback() ||
+        ip.is_link_local() ||
+        // Shared address space (RFC 6598).
+        (a == 100 && (b & 0xc0) == 64) ||
+        // IETF protocol assignments (RFC 6890).
+        (a == 192 && b == 0 && c == 0) ||
+        ip.is_documentation() ||
+        // 6to4 relay anycast (RFC 7526).
+        (a == 192 && b == 88 && c == 99) ||
+        // Benchmarking (RFC 2544).
+        (a == 198 && (b & 0xfe) == 18) ||
+        ip.is_multicast() ||
+        // Reserved, including broadcast.
+        a >= 240)
+}
+
+fn is_global_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    let embedded_v4 = |high: u16, low: u16| {
+        Ipv4Addr::from((u32::from(high) << 16) | u32::from(low))
+    };
+
+    match segments {
+        // IPv4-mapped.
+        [0, 0, 0, 0, 0, 0xffff, high, low] => {
+            is_global_v4(embedded_v4(high, low))
+        },
+        // NAT64 (RFC 6052).
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => {
+            is_global_v4(embedded_v4(high, low))
+        },
+        // 6to4 (RFC 3056).
+        [0x2002, high, low, ..] => is_global_v4(embedded_v4(high, low)),
+        // Unspecified, loopback and IPv4-compatible.
+        [0, 0, 0, 0, 0, 0, ..] => false,
+        // Discard-only (RFC 6666).
+        [0x100, 0, 0, 0, ..] => false,
+        // IETF protocol assignments, including Teredo.
+        [0x2001, b, ..] if b < 0x200 => false,
+        // Documentation.
+        [0x2001, 0xdb8, ..] => false,
+        // Unique local, link-local, site-local and multicast.
+        [a, ..] => {
+            (a & 0xfe00) != 0xfc00 &&
+                (a & 0xffc0) != 0xfe80 &&
+                (a & 0xffc0) != 0xfec0 &&
+                (a & 0xff00) != 0xff00
+        },
+    }
+}
+
+/// Whether `url` may be fetched, judging by its scheme and, for IP
+/// literals, its host.
+///
+/// Host names are checked once resolved, by [`GlobalResolver`].
+pub fn is_allowed_url(
+    url: &Url,
+    allow_http: bool,
+    allow_private: bool,
+) -> bool {
+    let scheme = match url.scheme() {
+        "https" => true,
+        "http" => allow_http,
+        _ => false,
+    };
+
+    scheme &&
+        match url.host() {
+            Some(Host::Domain(_)) => true,
+            Some(Host::Ipv4(ip)) => allow_private || is_global_v4(ip),
+            Some(Host::Ipv6(ip)) => allow_private || is_global_v6(ip),
+            None => false,
+        }
+}
+
+/// Redirect policy applying [`is_allowed_url`] to every hop.
+pub fn redirect_policy(
+    allow_http: bool,
+    allow_private: bool,
+) -> redirect::Policy {
+    redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if is_allowed_url(attempt.url(), allow_http, allow_private) {
+            attempt.follow()
+        } else {
+            attempt.error("redirect to a forbidden address")
+        }
+    })
+}
+
+/// DNS resolver only returning global addresses.
+///
+/// Each connection resolves the host again, so a record changed after the
+/// first check still cannot point to an internal address.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalResolver;
+
+impl Resolve for GlobalResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_global(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "host has no global address",
+                )
+                .into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::response::Redirect;
+    use axum::routing::get;
+
+    use super::*;
+
+    #[test]
+    fn test_is_global() {
+        for ip in [
+            "0.0.0.0",
+            "10.1.2.3",
+            "100.64.0.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "172.16.0.1",
+            "192.0.0.8",
+            "192.0.2.1",
+            "192.168.1.1",
+            "198.18.0.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "2002:7f00:1::",
+            "2001:db8::1",
+            "2001::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+        ] {
+            assert!(!is_global(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in [
+            "1.1.1.1",
+            "93.184.216.34",
+            "100.128.0.1",
+            "172.32.0.1",
+            "::ffff:1.1.1.1",
+            "2002:101:101::",
+            "2606:4700:4700::1111",
+            "2a01:4f8::1",
+        ] {
+            assert!(is_global(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_is_allowed_url() {
+        let allowed = |url: &str, allow_http, allow_private| {
+            is_allowed_url(
+                &Url::parse(url).unwrap(),
+                allow_http,
+                allow_private,
+            )
+        };
+
+        assert!(allowed("https://example.com/users/a", false, false));
+        assert!(allowed("https://1.1.1.1/users/a", false, false));
+        assert!(!allowed("http://example.com/users/a", false, false));
+        assert!(allowed("http://example.com/users/a", true, false));
+        assert!(!allowed("ftp://example.com/users/a", true, true));
+
+        assert!(!allowed("https://127.0.0.1/users/a", false, false));
+        assert!(!allowed("https://[::1]/users/a", false, false));
+        assert!(!allowed("https://169.254.169.254/latest", false, false));
+        assert!(!allowed("https://[::ffff:a00:1]/users/a", false, false));
+        assert!(allowed("https://127.0.0.1/users/a", false, true));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy() {
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new()
+            .route(
+                "/metadata",
+                get(|| async {
+                    Redirect::temporary("http://169.254.169.254/latest")
+                }),
+            )
+            .route("/loop", get(|| async { Redirect::temporary("/loop") }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::builder()
+            .redirect(redirect_policy(true, false))
+            .build()
+            .unwrap();
+        for path in ["/metadata", "/loop"] {
+            let err = client
+                .get(format!("{base}{path}"))
+                .send()
+                .await
+                .unwrap_err();
+            assert!(err.is_redirect(), "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolver() {
+        let name = |host: &str| host.parse::<Name>().unwrap();
+
+        assert!(GlobalResolver.resolve(name("localhost")).await.is_err());
+    }
+}
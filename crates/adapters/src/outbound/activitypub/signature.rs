@@ -0,0 +1,598 @@
+//! HTTP message signature primitives.
+//!
+//! Autha instances sign with HTTP Message Signatures (RFC 9421) and content
+//! digests (RFC 9530). Most of the fediverse still only understands
+//! draft-cavage-http-signatures-12, which is supported as a compatibility
+//! mode. Both only use RSASSA-PKCS1-v1_5 with SHA-256.
+
+use axum::http::{HeaderMap, Method, Uri};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// RFC 9421 name of RSASSA-PKCS1-v1_5 with SHA-256.
+pub const RSA_V1_5_SHA256: &str = "rsa-v1_5-sha256";
+/// Tolerated difference between the signature date and our clock.
+pub const MAX_CLOCK_SKEW: i64 = 60 * 60;
+
+/// Style of signature put on outbound requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignatureStyle {
+    /// HTTP Message Signatures (RFC 9421).
+    #[default]
+    Rfc9421,
+    /// draft-cavage-http-signatures-12, as used by the fediverse.
+    Cavage,
+}
+
+/// `Content-Digest` header value of `body`.
+pub fn content_digest(body: &[u8]) -> String {
+    format!("sha-256=:{}:", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// Returns `true` if a `Content-Digest` header holds the SHA-256 of `body`.
+pub fn content_digest_matches(headers: &HeaderMap, body: &[u8]) -> bool {
+    let expected = STANDARD.encode(Sha256::digest(body));
+
+    header_values(headers, "content-digest").is_some_and(|value| {
+        split_members(&value).into_iter().any(|member| {
+            member.split_once('=').is_some_and(|(alg, hash)| {
+                alg.trim() == "sha-256" &&
+                    hash.trim()
+                        .strip_prefix(':')
+                        .and_then(|h| h.strip_suffix(':')) ==
+                        Some(expected.as_str())
+            })
+        })
+    })
+}
+
+/// `Digest` header value of `body`, for draft-cavage signatures.
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// Returns `true` if a `Digest` header holds the SHA-256 of `body`.
+pub fn digest_matches(headers: &HeaderMap, body: &[u8]) -> bool {
+    let expected = STANDARD.encode(Sha256::digest(body));
+
+    header_values(headers, "digest").is_some_and(|value| {
+        value.split(',').any(|digest| {
+            digest.trim().split_once('=').is_some_and(|(alg, hash)| {
+                alg.eq_ignore_ascii_case("sha-256") && hash == expected
+            })
+        })
+    })
+}
+
+/// Returns `true` if `timestamp` is close enough to `now`.
+pub fn is_fresh(timestamp: i64, now: DateTime<Utc>) -> bool {
+    (now.timestamp() - timestamp).abs() <= MAX_CLOCK_SKEW
+}
+
+/// Returns `true` if the `Date` header is close enough to `now`.
+pub fn is_date_fresh(headers: &HeaderMap, now: DateTime<Utc>) -> bool {
+    headers
+        .get(axum::http::header::DATE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|date| is_fresh(date.timestamp(), now))
+}
+
+/// `host[:port]` of a URL, without the default port.
+pub fn authority(url: &Url) -> Option<String> {
+    let host = url.host_str()?.to_ascii_lowercase();
+
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
+/// Values of a header, trimmed and joined as RFC 9110 allows.
+fn header_values(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values = headers
+        .get_all(name)
+        .iter()
+        .map(|value| value.to_str().map(str::trim))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(", "))
+    }
+}
+
+/// Split a structured field on commas outside of strings, byte sequences
+/// and inner lists.
+fn split_members(value: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut quoted = false;
+    let mut bytes = false;
+    let mut depth = 0usize;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' if !bytes => quoted = !quoted,
+            ':' if !quoted => bytes = !bytes,
+            '(' if !quoted && !bytes => depth += 1,
+            ')' if !quoted && !bytes => depth = depth.saturating_sub(1),
+            ',' if !quoted && !bytes && depth == 0 => {
+                members.push(value[start..index].trim());
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    members.push(value[start..].trim());
+
+    members.into_iter().filter(|m| !m.is_empty()).collect()
+}
+
+/// Parameters of a draft-cavage `Signature` header.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SignatureHeader {
+    pub key_id: String,
+    pub headers: Vec<String>,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    /// Parse the parameters of a `Signature` header.
+    ///
+    /// Only `rsa-sha256` and `hs2019`, whose algorithm is then given by the
+    /// key, are accepted.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut key_id = None;
+        let mut algorithm = None;
+        let mut headers = None;
+        let mut signature = None;
+
+        for param in split_members(value) {
+            let (name, value) = param.split_once('=')?;
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+            match name.trim() {
+                "keyId" => key_id = Some(value.to_string()),
+                "algorithm" => algorithm = Some(value.to_ascii_lowercase()),
+                "headers" => {
+                    headers = Some(
+                        value
+                            .split_ascii_whitespace()
+                            .map(str::to_ascii_lowercase)
+                            .collect(),
+                    )
+                },
+                "signature" => signature = STANDARD.decode(value).ok(),
+                _ => {},
+            }
+        }
+
+        if !matches!(
+            algorithm.as_deref(),
+            None | Some("rsa-sha256" | "hs2019")
+        ) {
+            return None;
+        }
+
+        Some(Self {
+            key_id: key_id?,
+            // The specification defaults to `date` alone.
+            headers: headers.unwrap_or_else(|| vec!["date".to_string()]),
+            signature: signature?,
+        })
+    }
+
+    /// Returns the `Signature` header of a request, also accepted as an
+    /// `Authorization: Signature` scheme.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        if let Some(value) = headers.get("signature") {
+            return Self::parse(value.to_str().ok()?);
+        }
+
+        let value = headers.get(axum::http::header::AUTHORIZATION)?;
+        Self::parse(value.to_str().ok()?.strip_prefix("Signature ")?)
+    }
+
+    /// Serialize as a `Signature` header value.
+    pub fn to_header(&self) -> String {
+        format!(
+            r#"keyId="{}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+            self.key_id,
+            self.headers.join(" "),
+            STANDARD.encode(&self.signature)
+        )
+    }
+}
+
+/// Build the draft-cavage string covered by the signature.
+///
+/// Returns `None` if a signed header is missing.
+pub fn signing_string(
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    components: &[String],
+) -> Option<String> {
+    components
+        .iter()
+        .map(|name| {
+            if name == "(request-target)" {
+                let target = uri
+                    .path_and_query()
+                    .map(|p| p.as_str())
+                    .unwrap_or_else(|| uri.path());
+                return Some(format!(
+                    "(request-target): {} {target}",
+                    method.as_str().to_ascii_lowercase()
+                ));
+            }
+
+            Some(format!("{name}: {}", header_values(headers, name)?))
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// A member of a `Signature-Input` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInput {
+    pub label: String,
+    /// Covered component identifiers, such as `@method` or `content-type`.
+    pub components: Vec<String>,
+    pub key_id: Option<String>,
+    pub alg: Option<String>,
+    pub created: Option<i64>,
+    pub expires: Option<i64>,
+    /// Serialized inner list and parameters, signed as
+    /// `@signature-params`.
+    pub params: String,
+}
+
+impl SignatureInput {
+    /// Create the input of a signature covering `components`.
+    pub fn new(
+        label: &str,
+        components: &[&str],
+        key_id: &str,
+        created: i64,
+    ) -> Self {
+        let items = components
+            .iter()
+            .map(|c| format!("\"{c}\""))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            label: label.to_string(),
+            components: components.iter().map(|c| c.to_string()).collect(),
+            key_id: Some(key_id.to_string()),
+            alg: Some(RSA_V1_5_SHA256.to_string()),
+            created: Some(created),
+            expires: None,
+            params: format!(
+                "({items});created={created};keyid=\"{key_id}\";alg=\"{RSA_V1_5_SHA256}\""
+            ),
+        }
+    }
+
+    /// Parse every member of a `Signature-Input` header.
+    ///
+    /// Members using features we do not support, such as component
+    /// parameters, are skipped.
+    pub fn parse_all(value: &str) -> Vec<Self> {
+        split_members(value)
+            .into_iter()
+            .filter_map(Self::parse_member)
+            .collect()
+    }
+
+    fn parse_member(member: &str) -> Option<Self> {
+        let (label, params) = member.split_once('=')?;
+        let params = params.trim();
+        let (items, rest) = params.strip_prefix('(')?.split_once(')')?;
+
+        let components = items
+            .split_ascii_whitespace()
+            .map(|item| {
+                item.strip_prefix('"')?
+                    .strip_suffix('"')
+                    .filter(|c| !c.contains('"'))
+                    .map(str::to_string)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut input = Self {
+            label: label.trim().to_string(),
+            components,
+            key_id: None,
+            alg: None,
+            created: None,
+            expires: None,
+            params: params.to_string(),
+        };
+        for param in rest.split(';').filter(|p| !p.trim().is_empty()) {
+            let (name, value) = param.split_once('=')?;
+            let string = || {
+                value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .map(str::to_string)
+            };
+            match name.trim() {
+                "keyid" => input.key_id = Some(string()?),
+                "alg" => input.alg = Some(string()?),
+                "created" => input.created = Some(value.parse().ok()?),
+                "expires" => input.expires = Some(value.parse().ok()?),
+                _ => {},
+            }
+        }
+
+        Some(input)
+    }
+
+    /// Returns `true` if `component` is covered.
+    pub fn covers(&self, component: &str) -> bool {
+        self.components.iter().any(|c| c == component)
+    }
+
+    /// Returns `true` if the request target is covered.
+    pub fn covers_target(&self) -> bool {
+        self.covers("@target-uri") ||
+            (self.covers("@authority") &&
+                (self.covers("@request-target") || self.covers("@path")))
+    }
+}
+
+/// Parse the signatures of a `Signature` header, by label.
+pub fn parse_signatures(value: &str) -> Vec<(String, Vec<u8>)> {
+    split_members(value)
+        .into_iter()
+        .filter_map(|member| {
+            let (label, value) = member.split_once('=')?;
+            let value = value.trim().strip_prefix(':')?.strip_suffix(':')?;
+            Some((label.trim().to_string(), STANDARD.decode(value).ok()?))
+        })
+        .collect()
+}
+
+/// Message whose components can be signed.
+pub struct Message<'a> {
+    pub method: Option<&'a Method>,
+    /// Absolute target URI of a request.
+    pub target_uri: Option<&'a Url>,
+    /// Status code of a response.
+    pub status: Option<u16>,
+    pub headers: &'a HeaderMap,
+}
+
+impl Message<'_> {
+    /// Value of a component, or `None` if the message has none.
+    fn component(&self, name: &str) -> Option<String> {
+        let target = || self.target_uri;
+        match name {
+            "@method" => Some(self.method?.as_str().to_string()),
+            "@target-uri" => Some(target()?.to_string()),
+            "@authority" => authority(target()?),
+            "@scheme" => Some(target()?.scheme().to_string()),
+            "@path" => Some(target()?.path().to_string()),
+            "@query" => {
+                Some(format!("?{}", target()?.query().unwrap_or_default()))
+            },
+            "@request-target" => {
+                let url = target()?;
+                Some(match url.query() {
+                    Some(query) => format!("{}?{query}", url.path()),
+                    None => url.path().to_string(),
+                })
+            },
+            "@status" => Some(format!("{:03}", self.status?)),
+            name if name.starts_with('@') => None,
+            name => header_values(self.headers, name),
+        }
+    }
+
+    /// Build the RFC 9421 signature base of `input`.
+    ///
+    /// Returns `None` if a covered component is missing.
+    pub fn signature_base(&self, input: &SignatureInput) -> Option<String> {
+        let mut base = String::new();
+        for name in &input.components {
+            base.push_str(&format!("\"{name}\": {}\n", self.component(name)?));
+        }
+        base.push_str(&format!("\"@signature-params\": {}", input.params));
+
+        Some(base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const SIGNATURE: &str = r#"keyId="https://example.com/users/a#main-key",algorithm="rsa-sha256",headers="(request-target) host date digest",signature="c2lnbmF0dXJl""#;
+
+    #[test]
+    fn test_parse_cavage() {
+        let header = SignatureHeader::parse(SIGNATURE).unwrap();
+        assert_eq!(header.key_id, "https://example.com/users/a#main-key");
+        assert_eq!(
+            header.headers,
+            ["(request-target)", "host", "date", "digest"]
+        );
+        assert_eq!(header.signature, b"signature");
+        assert_eq!(SignatureHeader::parse(&header.to_header()), Some(header));
+
+        let header =
+            SignatureHeader::parse(r#"keyId="a,b", signature="c2lnbmF0dXJl""#)
+                .unwrap();
+        assert_eq!(header.key_id, "a,b");
+        assert_eq!(header.headers, ["date"]);
+
+        assert!(
+            SignatureHeader::parse(
+                r#"keyId="a",algorithm="hmac-sha256",signature="c2lnbmF0dXJl""#
+            )
+            .is_none()
+        );
+        assert!(
+            SignatureHeader::parse(r#"signature="c2lnbmF0dXJl""#).is_none()
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_str(&format!("Signature {SIGNATURE}")).unwrap(),
+        );
+        assert!(SignatureHeader::from_headers(&headers).is_some());
+    }
+
+    #[test]
+    fn test_signing_string() {
+        // Example from draft-cavage-http-signatures-12, section C.3.
+        let mut headers = HeaderMap::new();
+        headers.insert("host", HeaderValue::from_static("example.com"));
+        headers.insert(
+            "date",
+            HeaderValue::from_static("Sun, 05 Jan 2014 21:31:40 GMT"),
+        );
+        headers.insert(
+            "content-type",
+            HeaderValue::from_static("application/json"),
+        );
+        let components = ["(request-target)", "host", "date", "content-type"]
+            .map(String::from);
+
+        assert_eq!(
+            signing_string(
+                &Method::POST,
+                &"/foo?param=value&pet=dog".parse().unwrap(),
+                &headers,
+                &components,
+            )
+            .unwrap(),
+            "(request-target): post /foo?param=value&pet=dog\n\
+             host: example.com\n\
+             date: Sun, 05 Jan 2014 21:31:40 GMT\n\
+             content-type: application/json"
+        );
+
+        headers.remove("host");
+        assert!(
+            signing_string(
+                &Method::POST,
+                &"/".parse().unwrap(),
+                &headers,
+                &components
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_digests_and_dates() {
+        let body = br#"{"hello": "world"}"#;
+        let mut headers = HeaderMap::new();
+        headers.insert("digest", digest(body).parse().unwrap());
+        headers
+            .insert("content-digest", content_digest(body).parse().unwrap());
+        headers.insert(
+            "date",
+            HeaderValue::from_static("Sun, 05 Jan 2014 21:31:40 GMT"),
+        );
+
+        // Example from RFC 9530, section B.2.
+        assert_eq!(
+            content_digest(body),
+            "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:"
+        );
+        assert!(digest_matches(&headers, body));
+        assert!(!digest_matches(&headers, br#"{"hello": "there"}"#));
+        assert!(content_digest_matches(&headers, body));
+        assert!(!content_digest_matches(&headers, b""));
+
+        let date =
+            DateTime::parse_from_rfc2822("Sun, 05 Jan 2014 21:31:40 GMT")
+                .unwrap()
+                .to_utc();
+        assert!(is_date_fresh(
+            &headers,
+            date + chrono::Duration::minutes(30)
+        ));
+        assert!(!is_date_fresh(&headers, date + chrono::Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_signature_base() {
+        // Example from RFC 9421, section 2.5.
+        let input = SignatureInput::parse_all(
+            r#"sig1=("@method" "@authority" "@path" "content-digest" "content-length" "content-type");created=1618884473;keyid="test-key-rsa-pss""#,
+        )
+        .pop()
+        .unwrap();
+        assert_eq!(input.label, "sig1");
+        assert_eq!(input.key_id.as_deref(), Some("test-key-rsa-pss"));
+        assert_eq!(input.created, Some(1618884473));
+        assert!(input.covers_target());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "content-digest",
+            HeaderValue::from_static(
+                "sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:",
+            ),
+        );
+        headers.insert("content-length", HeaderValue::from_static("18"));
+        headers.insert(
+            "content-type",
+            HeaderValue::from_static("application/json"),
+        );
+        let url =
+            Url::parse("https://example.com/foo?param=Value&Pet=dog").unwrap();
+        let message = Message {
+            method: Some(&Method::POST),
+            target_uri: Some(&url),
+            status: None,
+            headers: &headers,
+        };
+
+        assert_eq!(
+            message.signature_base(&input).unwrap(),
+            "\"@method\": POST\n\
+             \"@authority\": example.com\n\
+             \"@path\": /foo\n\
+             \"content-digest\": sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:\n\
+             \"content-length\": 18\n\
+             \"content-type\": application/json\n\
+             \"@signature-params\": (\"@method\" \"@authority\" \"@path\" \"content-digest\" \"content-length\" \"content-type\");created=1618884473;keyid=\"test-key-rsa-pss\""
+        );
+
+        let input = SignatureInput::new("sig1", &["@status"], "key", 1);
+        assert_eq!(
+            SignatureInput::parse_all(&format!("sig1={}", input.params)),
+            std::slice::from_ref(&input)
+        );
+        assert!(!input.covers_target());
+        assert!(message.signature_base(&input).is_none());
+    }
+
+    #[test]
+    fn test_parse_signatures() {
+        assert_eq!(
+            parse_signatures("sig1=:c2lnbmF0dXJl:, sig2=:YQ==:"),
+            [
+                ("sig1".to_string(), b"signature".to_vec()),
+                ("sig2".to_string(), b"a".to_vec())
+            ]
+        );
+    }
+}
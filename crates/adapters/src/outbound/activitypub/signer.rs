@@ -0,0 +1,251 @@
+//! Signature of server-to-server messages with the instance key.
+
+use std::sync::Arc;
+
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::CryptoPort;
+use axum::http::{HeaderMap, HeaderValue, Uri};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::Utc;
+use zeroize::Zeroizing;
+
+use crate::outbound::activitypub::signature::{
+    Message, SignatureHeader, SignatureInput, SignatureStyle, authority,
+    content_digest, digest, signing_string,
+};
+
+/// Label of the signatures we create.
+const LABEL: &str = "sig1";
+
+/// Signer of requests and responses exchanged with other instances.
+///
+/// The instance key is distinct from the key signing access tokens.
+pub struct RequestSigner {
+    key_id: String,
+    /// PKCS#8 DER private key.
+    private_key: Zeroizing<Vec<u8>>,
+    crypto: Arc<dyn CryptoPort>,
+    style: SignatureStyle,
+}
+
+impl RequestSigner {
+    /// Create a new [`RequestSigner`] signing with RFC 9421.
+    pub fn new(
+        key_id: impl Into<String>,
+        private_key: Zeroizing<Vec<u8>>,
+        crypto: Arc<dyn CryptoPort>,
+    ) -> Self {
+        Self {
+            key_id: key_id.into(),
+            private_key,
+            crypto,
+            style: SignatureStyle::default(),
+        }
+    }
+
+    /// Create a new [`RequestSigner`] from a PKCS#8 PEM private key.
+    pub fn from_pem(
+        key_id: impl Into<String>,
+        private_key_pem: &str,
+        crypto: Arc<dyn CryptoPort>,
+    ) -> Result<Self> {
+        let (label, der) = spki::der::pem::decode_vec(
+            private_key_pem.as_bytes(),
+        )
+        .map_err(|err| {
+            ApplicationError::Internal(Box::new(spki::der::Error::from(err)))
+        })?;
+        if label != "PRIVATE KEY" {
+            return Err(ApplicationError::Unknown);
+        }
+
+        Ok(Self::new(key_id, Zeroizing::new(der), crypto))
+    }
+
+    /// Set the style of signatures put on requests.
+    pub fn with_style(mut self, style: SignatureStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// ID of the instance key.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// SPKI PEM public key of the instance.
+    pub fn public_key_pem(&self) -> Result<String> {
+        self.crypto.signature_keys().public_key(&self.private_key)
+    }
+
+    fn sign(&self, message: &str) -> Result<Vec<u8>> {
+        self.crypto
+            .signature_keys()
+            .sign(&self.private_key, message.as_bytes())
+    }
+
+    /// Verify a signature made by another instance.
+    pub fn verify(
+        &self,
+        public_key_pem: &str,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        self.crypto
+            .signature_keys()
+            .verify(public_key_pem, message, signature)
+    }
+
+    /// Sign an outbound request, covering its target and body.
+    pub fn sign_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        let body = request
+            .body()
+            .map(|body| body.as_bytes().ok_or(ApplicationError::Unknown))
+            .transpose()?
+            .map(<[u8]>::to_vec);
+        let method = request.method().clone();
+        let url = request.url().clone();
+
+        match self.style {
+            SignatureStyle::Rfc9421 => {
+                let mut components = vec!["@method", "@target-uri"];
+                if let Some(body) = &body {
+                    insert(
+                        request.headers_mut(),
+                        "content-digest",
+                        content_digest(body),
+                    )?;
+                    components.push("content-digest");
+                }
+
+                let input = SignatureInput::new(
+                    LABEL,
+                    &components,
+                    &self.key_id,
+                    Utc::now().timestamp(),
+                );
+                let base = Message {
+                    method: Some(&method),
+                    target_uri: Some(&url),
+                    status: None,
+                    headers: request.headers(),
+                }
+                .signature_base(&input)
+                .ok_or(ApplicationError::Unknown)?;
+                let signature = self.sign(&base)?;
+
+                let headers = request.headers_mut();
+                insert(
+                    headers,
+                    "signature-input",
+                    format!("{LABEL}={}", input.params),
+                )?;
+                insert(
+                    headers,
+                    "signature",
+                    format!("{LABEL}=:{}:", STANDARD.encode(signature)),
+                )?;
+            },
+            SignatureStyle::Cavage => {
+                let mut components = vec!["(request-target)", "host", "date"];
+                let headers = request.headers_mut();
+                insert(
+                    headers,
+                    "host",
+                    authority(&url).ok_or(ApplicationError::Unknown)?,
+                )?;
+                insert(
+                    headers,
+                    "date",
+                    Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+                )?;
+                if let Some(body) = &body {
+                    insert(headers, "digest", digest(body))?;
+                    components.push("digest");
+                }
+
+                let components = components
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                let uri = url[url::Position::BeforePath..]
+                    .parse::<Uri>()
+                    .map_err(|_| ApplicationError::Unknown)?;
+                let message = signing_string(
+                    &method,
+                    &uri,
+                    request.headers(),
+                    &components,
+                )
+                .ok_or(ApplicationError::Unknown)?;
+                let header = SignatureHeader {
+                    key_id: self.key_id.clone(),
+                    headers: components,
+                    signature: self.sign(&message)?,
+                };
+
+                insert(
+                    request.headers_mut(),
+                    "signature",
+                    header.to_header(),
+                )?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Sign a response with RFC 9421, covering its status, type and body.
+    pub fn sign_response(
+        &self,
+        status: u16,
+        headers: &mut HeaderMap,
+        body: &[u8],
+    ) -> Result<()> {
+        insert(headers, "content-digest", content_digest(body))?;
+
+        let mut components = vec!["@status", "content-digest"];
+        if headers.contains_key("content-type") {
+            components.push("content-type");
+        }
+        let input = SignatureInput::new(
+            LABEL,
+            &components,
+            &self.key_id,
+            Utc::now().timestamp(),
+        );
+        let base = Message {
+            method: None,
+            target_uri: None,
+            status: Some(status),
+            headers,
+        }
+        .signature_base(&input)
+        .ok_or(ApplicationError::Unknown)?;
+        let signature = self.sign(&base)?;
+
+        insert(
+            headers,
+            "signature-input",
+            format!("{LABEL}={}", input.params),
+        )?;
+        insert(
+            headers,
+            "signature",
+            format!("{LABEL}=:{}:", STANDARD.encode(signature)),
+        )
+    }
+}
+
+fn insert(
+    headers: &mut HeaderMap,
+    name: &'static str,
+    value: String,
+) -> Result<()> {
+    let value = HeaderValue::from_str(&value)
+        .map_err(|err| ApplicationError::Internal(Box::new(err)))?;
+    headers.insert(name, value);
+
+    Ok(())
+}
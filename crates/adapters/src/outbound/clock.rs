@@ -37,23 +37,31 @@ impl Clock for SystemClock {
 
 #[cfg(test)]
 pub struct FixedClock {
-    timestamp: u64,
+    timestamp: std::sync::atomic::AtomicU64,
 }
 
 #[cfg(test)]
 impl FixedClock {
     pub fn new(timestamp: u64) -> Self {
-        Self { timestamp }
+        Self {
+            timestamp: timestamp.into(),
+        }
+    }
+
+    /// Move the clock `seconds` forward.
+    pub fn advance(&self, seconds: u64) {
+        self.timestamp
+            .fetch_add(seconds, std::sync::atomic::Ordering::Relaxed);
     }
 }
 
 #[cfg(test)]
 impl Clock for FixedClock {
     fn now(&self) -> u64 {
-        self.timestamp
+        self.timestamp.load(std::sync::atomic::Ordering::Relaxed)
     }
 
     fn now_millis(&self) -> u128 {
-        (self.timestamp * 1000) as u128
+        (self.now() * 1000) as u128
     }
 }
@@ -1,93 +1,445 @@
 //! AES-256-GCM symmetric encryption implementation.
+//!
+//! Each ciphertext is encrypted with its own data key (DEK), which is
+//! wrapped by a master key (KEK) of the [`Keyring`]. The ciphertext starts
+//! with a header naming the algorithm and the master key:
+//! ```text
+//! magic (1) | version (1) | algorithm (1) | key ID length (1) | key ID
+//! | wrapped DEK | nonce (12) | ciphertext and tag
+//! ```
+//! A DEK wrapped locally is 60 bytes long; one wrapped by a key management
+//! system is prefixed with its length on 2 bytes.
+//! The header is authenticated along with the data. Ciphertexts without
+//! header are decrypted with the legacy master key.
+
+use std::collections::HashMap;
+use std::sync::Arc;
 
 use aes_gcm::aead::generic_array::GenericArray;
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use application::error::{ApplicationError, Result, ToInternal};
-use application::ports::outbound::SymmetricEncryption;
+use application::ports::outbound::{KeyManagement, SymmetricEncryption};
+use async_trait::async_trait;
 use rand::RngCore;
 use zeroize::Zeroizing;
 
 const KEY_LENGTH: usize = 32;
 const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+const WRAPPED_KEY_SIZE: usize = NONCE_SIZE + KEY_LENGTH + TAG_SIZE;
 
-/// AES-256-GCM encryption adapter.
-pub struct AesGcmEncryption(Zeroizing<[u8; KEY_LENGTH]>);
+const MAGIC: u8 = 0xae;
+const VERSION: u8 = 1;
+/// AES-256-GCM data key wrapped with AES-256-GCM.
+const ALGORITHM_AES_256_GCM: u8 = 1;
+/// AES-256-GCM data key wrapped by a key management system.
+const ALGORITHM_AES_256_GCM_KMS: u8 = 2;
 
-impl AesGcmEncryption {
-    /// Create a new [`AesGcmEncryption`].
-    /// Derives a 256-bit key from password and salt using Argon2id.
-    pub fn new(master_key: Zeroizing<Vec<u8>>, salt: &[u8]) -> Result<Self> {
-        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
-        Self::derive_key(&master_key, salt, &mut *key)?;
-        Ok(Self(Zeroizing::new(*key)))
+enum MasterKey {
+    Local(Zeroizing<[u8; KEY_LENGTH]>),
+    Managed(Arc<dyn KeyManagement>),
+}
+
+/// Master keys, by ID.
+pub struct Keyring {
+    primary: String,
+    keys: HashMap<String, MasterKey>,
+    /// ID of the key which encrypted ciphertexts without header.
+    legacy: Option<String>,
+}
+
+impl Keyring {
+    /// Create a new [`Keyring`] whose primary key is derived from
+    /// `master_key` and `salt`.
+    pub fn new(
+        id: impl Into<String>,
+        master_key: &[u8],
+        salt: &[u8],
+    ) -> Result<Self> {
+        let key = MasterKey::Local(derive_key(master_key, salt)?);
+        Self::with_primary(id.into(), key)
+    }
+
+    /// Create a new [`Keyring`] whose primary key is the key `id` of a key
+    /// management system.
+    pub fn managed(
+        id: impl Into<String>,
+        key_management: Arc<dyn KeyManagement>,
+    ) -> Result<Self> {
+        Self::with_primary(id.into(), MasterKey::Managed(key_management))
+    }
+
+    fn with_primary(id: String, key: MasterKey) -> Result<Self> {
+        let keyring = Self {
+            primary: id.clone(),
+            keys: HashMap::new(),
+            legacy: None,
+        };
+
+        keyring.insert(id, key)
+    }
+
+    fn insert(mut self, id: String, key: MasterKey) -> Result<Self> {
+        if id.is_empty() || id.len() > u8::MAX as usize {
+            return Err(ApplicationError::Unknown);
+        }
+
+        self.keys.insert(id, key);
+        Ok(self)
     }
 
-    fn derive_key(
-        password: &[u8],
+    /// Add a retired master key, still used to decrypt.
+    pub fn with_key(
+        self,
+        id: impl Into<String>,
+        master_key: &[u8],
         salt: &[u8],
-        output: &mut [u8],
-    ) -> Result<()> {
-        use argon2::Argon2;
-
-        let params = argon2::Params::default();
-        let argon2 = Argon2::new(
-            argon2::Algorithm::Argon2id,
-            argon2::Version::V0x13,
-            params,
-        );
+    ) -> Result<Self> {
+        let key = MasterKey::Local(derive_key(master_key, salt)?);
+        self.insert(id.into(), key)
+    }
+
+    /// Add a retired key of a key management system, still used to decrypt.
+    pub fn with_managed_key(
+        self,
+        id: impl Into<String>,
+        key_management: Arc<dyn KeyManagement>,
+    ) -> Result<Self> {
+        self.insert(id.into(), MasterKey::Managed(key_management))
+    }
 
-        argon2.hash_password_into(password, salt, output).catch()?;
+    /// Decrypt ciphertexts without header with the key `id`.
+    pub fn with_legacy(mut self, id: impl Into<String>) -> Self {
+        self.legacy = Some(id.into());
+        self
+    }
 
-        Ok(())
+    /// ID of the key encrypting new data.
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    fn key(&self, id: &str) -> Result<&MasterKey> {
+        self.keys.get(id).ok_or(ApplicationError::Unknown)
     }
 }
 
-impl SymmetricEncryption for AesGcmEncryption {
-    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
-        let key = Key::<Aes256Gcm>::from_slice(self.0.as_slice());
-        let cipher = Aes256Gcm::new(key);
+/// Derives a 256-bit key from password and salt using Argon2id.
+fn derive_key(
+    password: &[u8],
+    salt: &[u8],
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>> {
+    use argon2::Argon2;
+
+    let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+    let params = argon2::Params::default();
+    let argon2 = Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        params,
+    );
+
+    argon2
+        .hash_password_into(password, salt, &mut *key)
+        .catch()?;
+
+    Ok(key)
+}
 
-        let mut nonce_bytes = [0u8; NONCE_SIZE];
-        rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = GenericArray::from_slice(&nonce_bytes);
+/// Header of a ciphertext.
+struct Header<'a> {
+    algorithm: u8,
+    key_id: &'a str,
+    wrapped_key: &'a [u8],
+    /// Bytes of the header, authenticated with the data.
+    raw: &'a [u8],
+}
 
-        let ciphertext = cipher
-            .encrypt(nonce, plaintext)
-            .map_err(|_| ApplicationError::Unknown)?;
+impl<'a> Header<'a> {
+    /// Split a ciphertext into its header and the encrypted data.
+    fn parse(ciphertext: &'a [u8]) -> Option<(Self, &'a [u8])> {
+        let [MAGIC, VERSION, algorithm, length, rest @ ..] = ciphertext else {
+            return None;
+        };
+        let key_id =
+            std::str::from_utf8(rest.get(..*length as usize)?).ok()?;
+        let rest = &rest[key_id.len()..];
 
-        let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
-        result.extend_from_slice(&nonce_bytes);
-        result.extend_from_slice(&ciphertext);
+        let (offset, wrapped_length) = match *algorithm {
+            ALGORITHM_AES_256_GCM => (0, WRAPPED_KEY_SIZE),
+            ALGORITHM_AES_256_GCM_KMS => {
+                let length = rest.get(..2)?;
+                (2, u16::from_be_bytes([length[0], length[1]]) as usize)
+            },
+            _ => return None,
+        };
+        let wrapped_key = rest.get(offset..offset + wrapped_length)?;
+        let end = 4 + key_id.len() + offset + wrapped_length;
+        if ciphertext.len() < end + NONCE_SIZE {
+            return None;
+        }
 
-        Ok(result)
+        Some((
+            Self {
+                algorithm: *algorithm,
+                key_id,
+                wrapped_key,
+                raw: &ciphertext[..end],
+            },
+            &ciphertext[end..],
+        ))
     }
+}
 
-    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
-        if ciphertext.len() < NONCE_SIZE {
-            return Err(ApplicationError::TooSmall {
-                expected: NONCE_SIZE,
-            });
+fn seal(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = GenericArray::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(
+            nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| ApplicationError::Unknown)?;
+
+    let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    result.extend_from_slice(&nonce_bytes);
+    result.extend_from_slice(&ciphertext);
+
+    Ok(result)
+}
+
+fn open(key: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < NONCE_SIZE {
+        return Err(ApplicationError::TooSmall {
+            expected: NONCE_SIZE,
+        });
+    }
+
+    let (nonce_bytes, ciphertext) = ciphertext.split_at(NONCE_SIZE);
+    let nonce = Nonce::from_slice(nonce_bytes);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| ApplicationError::Unknown)
+}
+
+/// AES-256-GCM encryption adapter.
+pub struct AesGcmEncryption(Keyring);
+
+impl AesGcmEncryption {
+    /// Create a new [`AesGcmEncryption`].
+    pub fn new(keyring: Keyring) -> Self {
+        Self(keyring)
+    }
+}
+
+#[async_trait]
+impl SymmetricEncryption for AesGcmEncryption {
+    async fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let key_id = self.0.primary();
+        let mut data_key = Zeroizing::new([0u8; KEY_LENGTH]);
+        rand::rngs::OsRng.fill_bytes(&mut *data_key);
+
+        let mut header = vec![MAGIC, VERSION];
+        match self.0.key(key_id)? {
+            MasterKey::Local(key) => {
+                let wrapped_key = seal(&**key, &*data_key, &[])?;
+                header.extend_from_slice(&[
+                    ALGORITHM_AES_256_GCM,
+                    key_id.len() as u8,
+                ]);
+                header.extend_from_slice(key_id.as_bytes());
+                header.extend_from_slice(&wrapped_key);
+            },
+            MasterKey::Managed(key_management) => {
+                let wrapped_key =
+                    key_management.wrap(key_id, &*data_key).await?;
+                let length = u16::try_from(wrapped_key.len())
+                    .map_err(|_| ApplicationError::Unknown)?;
+                header.extend_from_slice(&[
+                    ALGORITHM_AES_256_GCM_KMS,
+                    key_id.len() as u8,
+                ]);
+                header.extend_from_slice(key_id.as_bytes());
+                header.extend_from_slice(&length.to_be_bytes());
+                header.extend_from_slice(&wrapped_key);
+            },
         }
 
-        let (nonce_bytes, ciphertext) = ciphertext.split_at(NONCE_SIZE);
-        let nonce = Nonce::from_slice(nonce_bytes);
+        let ciphertext = seal(&*data_key, plaintext, &header)?;
+        header.extend_from_slice(&ciphertext);
+
+        Ok(header)
+    }
 
-        let key = Key::<Aes256Gcm>::from_slice(self.0.as_slice());
-        let cipher = Aes256Gcm::new(key);
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if let Some((header, data)) = Header::parse(ciphertext) &&
+            let Ok(key) = self.0.key(header.key_id)
+        {
+            let data_key = Zeroizing::new(match (header.algorithm, key) {
+                (ALGORITHM_AES_256_GCM, MasterKey::Local(key)) => {
+                    open(&**key, header.wrapped_key, &[])?
+                },
+                (ALGORITHM_AES_256_GCM_KMS, MasterKey::Managed(kms)) => {
+                    kms.unwrap(header.key_id, header.wrapped_key).await?
+                },
+                _ => return Err(ApplicationError::Unknown),
+            });
+            return open(&data_key, data, header.raw);
+        }
 
-        cipher
-            .decrypt(nonce, ciphertext)
-            .map_err(|_| ApplicationError::Unknown)
+        let legacy =
+            self.0.legacy.as_deref().ok_or(ApplicationError::Unknown)?;
+        match self.0.key(legacy)? {
+            MasterKey::Local(key) => open(&**key, ciphertext, &[]),
+            MasterKey::Managed(_) => Err(ApplicationError::Unknown),
+        }
     }
 
-    fn encrypt_to_hex(&self, plaintext: &[u8]) -> Result<String> {
-        let ciphertext = self.encrypt(plaintext)?;
+    async fn encrypt_to_hex(&self, plaintext: &[u8]) -> Result<String> {
+        let ciphertext = self.encrypt(plaintext).await?;
         Ok(hex::encode(ciphertext))
     }
 
-    fn decrypt_from_hex(&self, hex_ciphertext: &str) -> Result<Vec<u8>> {
+    async fn decrypt_from_hex(&self, hex_ciphertext: &str) -> Result<Vec<u8>> {
         let ciphertext = hex::decode(hex_ciphertext).catch()?;
-        self.decrypt(&ciphertext)
+        self.decrypt(&ciphertext).await
+    }
+
+    fn needs_reencryption(&self, hex_ciphertext: &str) -> bool {
+        let Ok(ciphertext) = hex::decode(hex_ciphertext) else {
+            return false;
+        };
+        if ciphertext.is_empty() {
+            return false;
+        }
+
+        Header::parse(&ciphertext)
+            .is_none_or(|(header, _)| header.key_id != self.0.primary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbound::kms::{FileKeystore, KeyKind};
+
+    const SALT: &[u8] = b"0123456789abcdef";
+
+    /// Ciphertext of the former format, without header.
+    fn legacy_encrypt(master_key: &[u8], plaintext: &[u8]) -> String {
+        let key = derive_key(master_key, SALT).unwrap();
+        hex::encode(seal(&*key, plaintext, &[]).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt() {
+        let aes =
+            AesGcmEncryption::new(Keyring::new("1", b"key", SALT).unwrap());
+
+        let ciphertext =
+            aes.encrypt_to_hex(b"alice@example.com").await.unwrap();
+        assert_eq!(
+            aes.decrypt_from_hex(&ciphertext).await.unwrap(),
+            b"alice@example.com"
+        );
+        assert!(!aes.needs_reencryption(&ciphertext));
+
+        // Data keys are never reused.
+        assert_ne!(
+            aes.encrypt_to_hex(b"alice@example.com").await.unwrap(),
+            ciphertext
+        );
+
+        // The header is authenticated.
+        let mut tampered = hex::decode(&ciphertext).unwrap();
+        tampered[1] = 2;
+        assert!(aes.decrypt(&tampered).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let legacy = legacy_encrypt(b"old", b"secret");
+        let old = AesGcmEncryption::new(
+            Keyring::new("1", b"old", SALT).unwrap().with_legacy("1"),
+        );
+        let ciphertext = old.encrypt_to_hex(b"secret").await.unwrap();
+        assert_eq!(old.decrypt_from_hex(&legacy).await.unwrap(), b"secret");
+        assert!(old.needs_reencryption(&legacy));
+
+        let new = AesGcmEncryption::new(
+            Keyring::new("2", b"new", SALT)
+                .unwrap()
+                .with_key("1", b"old", SALT)
+                .unwrap()
+                .with_legacy("1"),
+        );
+        assert_eq!(new.decrypt_from_hex(&legacy).await.unwrap(), b"secret");
+        assert_eq!(
+            new.decrypt_from_hex(&ciphertext).await.unwrap(),
+            b"secret"
+        );
+        assert!(new.needs_reencryption(&ciphertext));
+
+        let rotated = new.encrypt_to_hex(b"secret").await.unwrap();
+        assert!(!new.needs_reencryption(&rotated));
+
+        // Retired keys cannot decrypt data of newer ones.
+        assert!(old.decrypt_from_hex(&rotated).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_managed_key() {
+        let mut keystore = FileKeystore::new();
+        keystore.ensure("kms", KeyKind::Aes256Gcm).unwrap();
+        let keystore: Arc<dyn KeyManagement> = Arc::new(keystore);
+
+        let local =
+            AesGcmEncryption::new(Keyring::new("1", b"key", SALT).unwrap());
+        let ciphertext = local.encrypt_to_hex(b"secret").await.unwrap();
+
+        let aes = AesGcmEncryption::new(
+            Keyring::managed("kms", keystore.clone())
+                .unwrap()
+                .with_key("1", b"key", SALT)
+                .unwrap(),
+        );
+        assert_eq!(
+            aes.decrypt_from_hex(&ciphertext).await.unwrap(),
+            b"secret"
+        );
+        assert!(aes.needs_reencryption(&ciphertext));
+
+        let managed = aes.encrypt_to_hex(b"secret").await.unwrap();
+        assert_eq!(aes.decrypt_from_hex(&managed).await.unwrap(), b"secret");
+        assert!(!aes.needs_reencryption(&managed));
+
+        // Retiring the managed key keeps its data readable.
+        let rotated = AesGcmEncryption::new(
+            Keyring::new("2", b"new", SALT)
+                .unwrap()
+                .with_managed_key("kms", keystore)
+                .unwrap(),
+        );
+        assert_eq!(
+            rotated.decrypt_from_hex(&managed).await.unwrap(),
+            b"secret"
+        );
+        assert!(rotated.needs_reencryption(&managed));
     }
 }
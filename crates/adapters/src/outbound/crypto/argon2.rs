@@ -1,4 +1,7 @@
 //! Argon2id password hasher implementation.
+//!
+//! Hashes of other algorithms, imported from other systems, are verified
+//! but never produced.
 
 use application::error::{Result, ToInternal};
 use application::ports::outbound::PasswordHasher;
@@ -7,19 +10,31 @@ use argon2::password_hash::{
     PasswordVerifier, SaltString,
 };
 use argon2::{Argon2, Params, Version};
+use async_trait::async_trait;
 use domain::auth::password::{Password, PasswordHash as DomainPasswordHash};
 use domain::error::DomainError;
+use rand::RngCore;
 use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::outbound::crypto::legacy;
+use crate::outbound::crypto::pool::HashingPool;
 
 const OUTPUT_LENGTH: usize = 32;
 
 /// Argon2id password hasher adapter.
 pub struct Argon2PasswordHasher {
     params: Params,
+    pool: HashingPool,
+    /// Hash of a random password, with the current parameters.
+    dummy_hash: String,
 }
 
 impl Argon2PasswordHasher {
     /// Create a new Argon2 hasher with custom parameters.
+    ///
+    /// Hashing runs on a [`HashingPool`] sized for `memory_cost`. A dummy
+    /// hash is computed once, on the current thread.
     pub fn new(
         memory_cost: u32,
         iterations: u32,
@@ -33,43 +48,208 @@ impl Argon2PasswordHasher {
         )
         .catch()?;
 
-        Ok(Self { params })
+        let mut dummy_password = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(dummy_password.as_mut());
+        let dummy_hash = Self::argon2(params.clone())
+            .hash_password(
+                dummy_password.as_ref(),
+                &SaltString::generate(&mut OsRng),
+            )
+            .catch()?
+            .to_string();
+
+        Ok(Self {
+            params,
+            pool: HashingPool::sized_for(memory_cost),
+            dummy_hash,
+        })
+    }
+
+    /// Run hashing on `pool`.
+    pub fn with_pool(mut self, pool: HashingPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    fn argon2(params: Params) -> Argon2<'static> {
+        Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params)
+    }
+
+    /// Whether `password` matches `hash`, computed on the pool.
+    async fn matches(
+        &self,
+        password: &Password,
+        hash: String,
+    ) -> Result<bool> {
+        let params = self.params.clone();
+        let password = Zeroizing::new(password.as_bytes().to_vec());
+
+        self.pool
+            .run(move || {
+                if !hash.starts_with("$argon2") {
+                    return legacy::verify(&password, &hash);
+                }
+
+                PasswordHash::new(&hash).is_ok_and(|parsed_hash| {
+                    Self::argon2(params)
+                        .verify_password(&password, &parsed_hash)
+                        .is_ok()
+                })
+            })
+            .await
     }
 }
 
+#[async_trait]
 impl PasswordHasher for Argon2PasswordHasher {
-    fn hash(&self, password: &Password) -> Result<DomainPasswordHash> {
-        let argon2 = Argon2::new(
-            argon2::Algorithm::Argon2id,
-            Version::V0x13,
-            self.params.clone(),
-        );
+    async fn hash(&self, password: &Password) -> Result<DomainPasswordHash> {
+        let params = self.params.clone();
+        let password = Zeroizing::new(password.as_bytes().to_vec());
 
-        let salt = SaltString::generate(&mut OsRng);
+        let hash = self
+            .pool
+            .run(move || {
+                let salt = SaltString::generate(&mut OsRng);
+                Self::argon2(params)
+                    .hash_password(&password, &salt)
+                    .map(|hash| hash.to_string())
+            })
+            .await?
+            .catch()?;
 
-        let hash = argon2.hash_password(password.as_bytes(), &salt).catch()?;
-
-        Ok(DomainPasswordHash::parse(hash.to_string())?)
+        Ok(DomainPasswordHash::parse(hash)?)
     }
 
-    fn verify(
+    async fn verify(
         &self,
         password: &Password,
         hash: &DomainPasswordHash,
     ) -> Result<()> {
-        let argon2 = Argon2::new(
-            argon2::Algorithm::Argon2id,
-            Version::V0x13,
-            self.params.clone(),
+        if self.matches(password, hash.as_str().to_string()).await? {
+            Ok(())
+        } else {
+            Err(DomainError::InvalidCredentials.into())
+        }
+    }
+
+    async fn verify_dummy(&self, password: &Password) -> Result<()> {
+        self.matches(password, self.dummy_hash.clone()).await?;
+        Ok(())
+    }
+
+    fn supports(&self, hash: &DomainPasswordHash) -> bool {
+        PasswordHash::new(hash.as_str()).is_ok_and(|parsed_hash| {
+            parsed_hash.algorithm.as_str().starts_with("argon2")
+        }) || legacy::supports(hash.as_str())
+    }
+
+    fn needs_rehash(&self, hash: &DomainPasswordHash) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hash.as_str()) else {
+            return true;
+        };
+        if parsed_hash.algorithm != argon2::Algorithm::Argon2id.ident() ||
+            parsed_hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+
+        match Params::try_from(&parsed_hash) {
+            Ok(params) => {
+                params.m_cost() != self.params.m_cost() ||
+                    params.t_cost() != self.params.t_cost() ||
+                    params.p_cost() != self.params.p_cost() ||
+                    parsed_hash.hash.map(|output| output.len()) !=
+                        Some(OUTPUT_LENGTH)
+            },
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_needs_rehash() {
+        let password = Password::new("correct horse battery").unwrap();
+        let hasher = Argon2PasswordHasher::new(1024, 1, 1).unwrap();
+        let hash = hasher.hash(&password).await.unwrap();
+        assert!(!hasher.needs_rehash(&hash));
+
+        let stronger = Argon2PasswordHasher::new(2048, 2, 1).unwrap();
+        assert!(stronger.verify(&password, &hash).await.is_ok());
+        assert!(stronger.needs_rehash(&hash));
+        assert!(
+            !stronger.needs_rehash(&stronger.hash(&password).await.unwrap())
+        );
+
+        // Other algorithms are always replaced.
+        let argon2i = DomainPasswordHash::parse(
+            "$argon2i$v=19$m=1024,t=1,p=1$c29tZXNhbHQ$\
+             SqlVijFGiPG+935vDSGEsA2JmW4nEf4zsxDNdQi8Y4c",
+        )
+        .unwrap();
+        assert!(hasher.needs_rehash(&argon2i));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_hash() {
+        let password = Password::new("correct horse battery").unwrap();
+        let hasher = Argon2PasswordHasher::new(1024, 1, 1).unwrap();
+        let hash = DomainPasswordHash::parse(
+            pwhash::sha512_crypt::hash(password.as_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert!(hasher.supports(&hash));
+        assert!(hasher.verify(&password, &hash).await.is_ok());
+        assert!(hasher.needs_rehash(&hash));
+        assert!(
+            hasher
+                .verify(&Password::new("wrong horse battery").unwrap(), &hash)
+                .await
+                .is_err()
         );
 
-        let parsed_hash = PasswordHash::new(hash.as_str())
-            .map_err(|_| DomainError::InvalidCredentials)?;
+        let md5 =
+            DomainPasswordHash::parse("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0")
+                .unwrap();
+        assert!(!hasher.supports(&md5));
+    }
 
-        argon2
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .map_err(|_| DomainError::InvalidCredentials)?;
+    #[tokio::test]
+    async fn test_dummy_timing() {
+        const SAMPLES: usize = 21;
 
-        Ok(())
+        let hasher = Argon2PasswordHasher::new(4096, 1, 1).unwrap();
+        let hash = hasher
+            .hash(&Password::new("correct horse battery").unwrap())
+            .await
+            .unwrap();
+        let wrong = Password::new("wrong horse battery").unwrap();
+        assert!(hasher.verify_dummy(&wrong).await.is_ok());
+
+        // Interleave samples so that noise affects both alike, and compare
+        // medians, robust to outliers.
+        let (mut known, mut unknown) = (Vec::new(), Vec::new());
+        for _ in 0..SAMPLES {
+            let start = Instant::now();
+            assert!(hasher.verify(&wrong, &hash).await.is_err());
+            known.push(start.elapsed());
+
+            let start = Instant::now();
+            hasher.verify_dummy(&wrong).await.unwrap();
+            unknown.push(start.elapsed());
+        }
+        let median = |samples: &mut Vec<Duration>| {
+            samples.sort();
+            samples[SAMPLES / 2].as_secs_f64()
+        };
+
+        let ratio = median(&mut known) / median(&mut unknown);
+        assert!((0.75..=1.33).contains(&ratio), "ratio is {ratio}");
     }
 }
@@ -0,0 +1,144 @@
+//! HMAC-SHA256 blind index of email addresses.
+//!
+//! Indexes are written as `<secret ID>:<hex digest>`, so that rows indexed
+//! with a former secret are found and re-indexed. Indexes of the former
+//! format, a peppered SHA-256 digest, are plain hexadecimal.
+
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::{BlindIndex, Hasher};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::outbound::crypto::sha2::Sha256Hasher;
+
+/// Minimum length of a secret, in bytes.
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// Secret computing indexes.
+enum Version {
+    Hmac {
+        id: String,
+        secret: Zeroizing<Vec<u8>>,
+    },
+    /// Peppered SHA-256, used before indexes were keyed.
+    Legacy(Sha256Hasher),
+}
+
+impl Version {
+    fn index(&self, data: &[u8]) -> String {
+        match self {
+            Version::Hmac { id, secret } => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(data);
+                format!("{id}:{}", hex::encode(mac.finalize().into_bytes()))
+            },
+            Version::Legacy(hasher) => hasher.hash(data),
+        }
+    }
+}
+
+/// Blind index keyed with versioned secrets.
+pub struct HmacBlindIndex {
+    /// Versions, current first.
+    versions: Vec<Version>,
+}
+
+impl HmacBlindIndex {
+    /// Create a new [`HmacBlindIndex`] whose current secret is `secret`.
+    pub fn new(id: impl Into<String>, secret: &[u8]) -> Result<Self> {
+        Self { versions: vec![] }.with_key(id, secret)
+    }
+
+    /// Create a new [`HmacBlindIndex`] computing indexes of the former
+    /// format, peppered with `pepper`.
+    pub fn legacy(pepper: Vec<u8>) -> Self {
+        Self {
+            versions: vec![Version::Legacy(Sha256Hasher::new(pepper))],
+        }
+    }
+
+    /// Add a former secret, still used to look data up.
+    pub fn with_key(
+        mut self,
+        id: impl Into<String>,
+        secret: &[u8],
+    ) -> Result<Self> {
+        let id = id.into();
+        if id.is_empty() ||
+            id.contains(':') ||
+            secret.len() < MIN_SECRET_LENGTH
+        {
+            return Err(ApplicationError::Unknown);
+        }
+
+        self.versions.push(Version::Hmac {
+            id,
+            secret: Zeroizing::new(secret.to_vec()),
+        });
+        Ok(self)
+    }
+
+    /// Also look data up with indexes of the former format.
+    pub fn with_legacy(mut self, pepper: Vec<u8>) -> Self {
+        self.versions
+            .push(Version::Legacy(Sha256Hasher::new(pepper)));
+        self
+    }
+}
+
+impl BlindIndex for HmacBlindIndex {
+    fn index(&self, data: &[u8]) -> String {
+        self.versions[0].index(data)
+    }
+
+    fn candidates(&self, data: &[u8]) -> Vec<String> {
+        self.versions
+            .iter()
+            .map(|version| version.index(data))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"0123456789abcdef";
+
+    #[test]
+    fn test_index() {
+        let index = HmacBlindIndex::new("1", SECRET).unwrap();
+        let hash = index.index(b"alice@example.com");
+        assert!(hash.starts_with("1:"));
+        assert_eq!(hash, index.index(b"alice@example.com"));
+        assert_ne!(hash, index.index(b"bob@example.com"));
+        assert_ne!(
+            hash,
+            HmacBlindIndex::new("1", b"fedcba9876543210")
+                .unwrap()
+                .index(b"alice@example.com")
+        );
+
+        assert!(HmacBlindIndex::new("1", b"short").is_err());
+        assert!(HmacBlindIndex::new("a:b", SECRET).is_err());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let legacy = HmacBlindIndex::legacy(b"pepper".to_vec());
+        let old = HmacBlindIndex::new("1", SECRET).unwrap();
+        let new = HmacBlindIndex::new("2", b"fedcba9876543210")
+            .unwrap()
+            .with_key("1", SECRET)
+            .unwrap()
+            .with_legacy(b"pepper".to_vec());
+
+        let candidates = new.candidates(b"alice@example.com");
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0], new.index(b"alice@example.com"));
+        assert_eq!(candidates[1], old.index(b"alice@example.com"));
+        assert_eq!(candidates[2], legacy.index(b"alice@example.com"));
+    }
+}
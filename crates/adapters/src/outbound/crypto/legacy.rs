@@ -0,0 +1,127 @@
+//! Verification of password hashes imported from other systems.
+//!
+//! Supported formats are bcrypt (`$2a$`, `$2b$`, `$2y$`), SHA-crypt (`$5$`,
+//! `$6$`), and PBKDF2 (`$pbkdf2-sha256$`, `$pbkdf2-sha512$`) and scrypt
+//! (`$scrypt$`) PHC strings. Such hashes are replaced with Argon2id on the
+//! next login.
+
+use argon2::password_hash::{PasswordHash, PasswordVerifier};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+
+/// Identifier of the modular crypt or PHC format of `hash`.
+fn identifier(hash: &str) -> Option<&str> {
+    hash.strip_prefix('$')?.split('$').next()
+}
+
+/// Whether `hash` is in a supported format.
+pub(super) fn supports(hash: &str) -> bool {
+    matches!(
+        identifier(hash),
+        Some(
+            "2a" | "2b" |
+                "2y" |
+                "5" |
+                "6" |
+                "pbkdf2-sha256" |
+                "pbkdf2-sha512" |
+                "scrypt"
+        )
+    )
+}
+
+/// Verify `password` against `hash`, returning `false` for unsupported
+/// formats.
+pub(super) fn verify(password: &[u8], hash: &str) -> bool {
+    match identifier(hash) {
+        Some("2a" | "2b" | "2y") => pwhash::bcrypt::verify(password, hash),
+        Some("5") => pwhash::sha256_crypt::verify(password, hash),
+        Some("6") => pwhash::sha512_crypt::verify(password, hash),
+        Some("pbkdf2-sha256" | "pbkdf2-sha512") => {
+            verify_phc(&Pbkdf2, password, hash)
+        },
+        Some("scrypt") => verify_phc(&Scrypt, password, hash),
+        _ => false,
+    }
+}
+
+fn verify_phc(
+    verifier: &impl PasswordVerifier,
+    password: &[u8],
+    hash: &str,
+) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|hash| verifier.verify_password(password, &hash).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use argon2::password_hash::{PasswordHasher, SaltString};
+
+    use super::*;
+
+    const PASSWORD: &[u8] = b"correct horse battery";
+
+    #[test]
+    fn test_modular_crypt() {
+        let bcrypt = pwhash::bcrypt::hash_with(
+            pwhash::bcrypt::BcryptSetup {
+                cost: Some(4),
+                ..Default::default()
+            },
+            PASSWORD,
+        )
+        .unwrap();
+        for (password, hash) in [
+            (PASSWORD, bcrypt.as_str()),
+            (
+                b"test".as_slice(),
+                "$5$WH1ABM5sKhxbkgCK$sOnTVjQn1Y3EWibd8gWqqJqjH.KaFrxJE5rijqxcPp7",
+            ),
+            (PASSWORD, &pwhash::sha512_crypt::hash(PASSWORD).unwrap()),
+        ] {
+            assert!(supports(hash), "{hash}");
+            assert!(verify(password, hash), "{hash}");
+            assert!(!verify(b"wrong", hash), "{hash}");
+        }
+
+        // MD5-crypt is too weak to be imported.
+        let md5 = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        assert!(!supports(md5));
+        assert!(!verify(b"password", md5));
+    }
+
+    #[test]
+    fn test_phc() {
+        let salt = SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap();
+        let pbkdf2 = Pbkdf2
+            .hash_password_customized(
+                PASSWORD,
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params {
+                    rounds: 1_000,
+                    output_length: 32,
+                },
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        let scrypt = Scrypt
+            .hash_password_customized(
+                PASSWORD,
+                None,
+                None,
+                scrypt::Params::new(4, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+
+        for hash in [pbkdf2, scrypt] {
+            assert!(supports(&hash), "{hash}");
+            assert!(verify(PASSWORD, &hash), "{hash}");
+            assert!(!verify(b"wrong", &hash), "{hash}");
+        }
+    }
+}
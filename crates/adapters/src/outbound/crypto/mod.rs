@@ -2,7 +2,11 @@
 
 mod aes;
 mod argon2;
+mod hmac;
+mod legacy;
+mod pool;
 pub(crate) mod random;
+mod rsa;
 mod sha2;
 mod totp;
 
@@ -10,13 +14,17 @@ use std::sync::Arc;
 
 use application::error::Result;
 use application::ports::outbound::{
-    Clock, CryptoPort, Hasher, PasswordHasher, SecureRandom,
-    SymmetricEncryption, TotpGenerator,
+    BlindIndex, Clock, CryptoPort, Hasher, PasswordHasher, SecureRandom,
+    SignatureKeys, SymmetricEncryption, TotpGenerator,
 };
 
 use crate::outbound::crypto::aes::AesGcmEncryption;
+pub use crate::outbound::crypto::aes::Keyring;
 use crate::outbound::crypto::argon2::Argon2PasswordHasher;
+pub use crate::outbound::crypto::hmac::HmacBlindIndex;
+pub use crate::outbound::crypto::pool::HashingPool;
 use crate::outbound::crypto::random::OsRngRandom;
+use crate::outbound::crypto::rsa::RsaSignatureKeys;
 use crate::outbound::crypto::sha2::Sha256Hasher;
 use crate::outbound::crypto::totp::HmacTotpGenerator;
 
@@ -26,15 +34,18 @@ pub struct CryptoAdapter {
     totp_generator: HmacTotpGenerator,
     symmetric_encryption: AesGcmEncryption,
     hasher: Sha256Hasher,
+    blind_index: HmacBlindIndex,
     random: OsRngRandom,
+    signature_keys: RsaSignatureKeys,
 }
 
 impl CryptoAdapter {
     /// Create a new [`CryptoAdapter`].
     pub fn new(
         clock: Arc<dyn Clock>,
-        master_key: zeroize::Zeroizing<Vec<u8>>,
+        keyring: Keyring,
         salt: Vec<u8>,
+        blind_index: HmacBlindIndex,
         argon_memory_cost: u32,
         argon_iterations: u32,
         argon_parallelism: u32,
@@ -46,11 +57,20 @@ impl CryptoAdapter {
                 argon_parallelism,
             )?,
             totp_generator: HmacTotpGenerator::new(clock),
-            symmetric_encryption: AesGcmEncryption::new(master_key, &salt)?,
+            symmetric_encryption: AesGcmEncryption::new(keyring),
             hasher: Sha256Hasher::new(salt),
+            blind_index,
             random: OsRngRandom::new(),
+            signature_keys: RsaSignatureKeys::new(),
         })
     }
+
+    /// Hash passwords on `pool` instead of one sized from available
+    /// memory.
+    pub fn with_hashing_pool(mut self, pool: HashingPool) -> Self {
+        self.password_hasher = self.password_hasher.with_pool(pool);
+        self
+    }
 }
 
 impl CryptoPort for CryptoAdapter {
@@ -70,7 +90,291 @@ impl CryptoPort for CryptoAdapter {
         &self.hasher
     }
 
+    fn blind_index(&self) -> &dyn BlindIndex {
+        &self.blind_index
+    }
+
     fn secure_random(&self) -> &dyn SecureRandom {
         &self.random
     }
+
+    fn signature_keys(&self) -> &dyn SignatureKeys {
+        &self.signature_keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use application::dto::{
+        AccountDto, AccountSearchDto, AuthRequestDto, MediaKind, SessionDto,
+    };
+    use application::ports::inbound::Authenticate;
+    use application::ports::outbound::{
+        AccountRepository, RateLimitPolicy, RefreshTokenManager,
+        RefreshTokenRepository, Token, TokenSigner,
+    };
+    use application::usecases::AuthenticateUseCase;
+    use async_trait::async_trait;
+    use domain::auth::email::EmailHash;
+    use domain::auth::password::{Password, PasswordHash};
+    use domain::identity::email::EmailAddress;
+    use domain::identity::id::UserId;
+
+    use super::*;
+    use crate::outbound::clock::FixedClock;
+    use crate::outbound::rate_limit::memory::InMemoryRateLimiter;
+    use crate::outbound::telemetry::TracingTelemetry;
+
+    /// Single account, looked up by email.
+    struct OneAccount(AccountDto);
+
+    #[async_trait]
+    impl AccountRepository for OneAccount {
+        async fn find_by_id(
+            &self,
+            _id: &UserId,
+        ) -> Result<Option<AccountDto>> {
+            unimplemented!()
+        }
+
+        async fn find_by_email_hashes(
+            &self,
+            email_hashes: &[EmailHash],
+        ) -> Result<Option<AccountDto>> {
+            Ok(email_hashes
+                .contains(&self.0.email_hash)
+                .then(|| self.0.clone()))
+        }
+
+        async fn create(&self, _account: &AccountDto) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn update(&self, _account: &AccountDto) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn update_email_hash(
+            &self,
+            _id: &UserId,
+            _previous: &EmailHash,
+            _email_hash: &EmailHash,
+        ) -> Result<bool> {
+            unimplemented!()
+        }
+
+        async fn update_password_hash(
+            &self,
+            _id: &UserId,
+            _previous: &PasswordHash,
+            _password_hash: &PasswordHash,
+        ) -> Result<bool> {
+            unimplemented!()
+        }
+
+        async fn complete_password_reset(
+            &self,
+            _id: &UserId,
+            _previous: &PasswordHash,
+            _password_hash: &PasswordHash,
+        ) -> Result<bool> {
+            unimplemented!()
+        }
+
+        async fn replace_media(
+            &self,
+            _id: &UserId,
+            _kind: MediaKind,
+            _url: Option<&str>,
+        ) -> Result<Option<String>> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _id: &UserId) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn import(
+            &self,
+            _accounts: &[AccountDto],
+            _dry_run: bool,
+        ) -> Result<Vec<UserId>> {
+            unimplemented!()
+        }
+
+        async fn search(
+            &self,
+            _query: &AccountSearchDto,
+        ) -> Result<Vec<AccountDto>> {
+            unimplemented!()
+        }
+    }
+
+    /// Sessions and tokens, never reached by failed logins.
+    struct NoSessions;
+
+    #[async_trait]
+    impl RefreshTokenRepository for NoSessions {
+        async fn store(
+            &self,
+            _token: &str,
+            _session: &SessionDto,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn find_session(
+            &self,
+            _token: &str,
+        ) -> Result<Option<SessionDto>> {
+            unimplemented!()
+        }
+
+        async fn list_sessions(
+            &self,
+            _user_id: &UserId,
+        ) -> Result<Vec<SessionDto>> {
+            unimplemented!()
+        }
+
+        async fn revoke(&self, _token: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn revoke_session(
+            &self,
+            _user_id: &UserId,
+            _session_id: &str,
+        ) -> Result<bool> {
+            unimplemented!()
+        }
+
+        async fn revoke_other_sessions(
+            &self,
+            _user_id: &UserId,
+            _session_id: Option<&str>,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn revoke_all_for_user(&self, _user_id: &UserId) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    impl Token for NoSessions {
+        fn signer(&self) -> &dyn TokenSigner {
+            unimplemented!()
+        }
+
+        fn refresh_token(&self) -> &dyn RefreshTokenManager {
+            unimplemented!()
+        }
+    }
+
+    fn request(email: &str) -> AuthRequestDto {
+        AuthRequestDto {
+            email: Some(EmailAddress::parse(email).unwrap()),
+            user_id: None,
+            password: "wrong horse battery".into(),
+            totp_code: None,
+            new_password: None,
+            ip_address: None,
+            device: None,
+            user_agent: None,
+        }
+    }
+
+    /// Failed logins on known and unknown emails, with real Argon2
+    /// hashing, have the same latency distribution.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "statistical, run in release mode with --ignored"]
+    async fn test_login_enumeration_timing() {
+        const SAMPLES: usize = 201;
+        const SALT: &[u8] = b"260eb1a061cb61898f01fe7dd14bbe94";
+
+        let clock = Arc::new(FixedClock::new(1_000));
+        let crypto = CryptoAdapter::new(
+            clock.clone(),
+            Keyring::new("default", b"master key", SALT).unwrap(),
+            SALT.to_vec(),
+            HmacBlindIndex::new("1", SALT).unwrap(),
+            19_456,
+            2,
+            1,
+        )
+        .unwrap();
+        let alice = AccountDto {
+            id: UserId::parse("alice").unwrap(),
+            username: "alice".into(),
+            email_hash: EmailHash::new(
+                crypto.blind_index().index(b"alice@example.com"),
+            ),
+            email_cipher: String::new(),
+            password_hash: crypto
+                .password_hasher()
+                .hash(&Password::new("correct horse battery").unwrap())
+                .await
+                .unwrap(),
+            totp_secret: None,
+            locale: "en".into(),
+            summary: None,
+            avatar: None,
+            banner: None,
+            flags: Default::default(),
+            created_at: 0,
+            deleted_at: None,
+            public_keys: Vec::new(),
+            token_generation: 0,
+            suspension: None,
+            password_reset_required: false,
+        };
+        let usecase = AuthenticateUseCase::new(
+            Arc::new(OneAccount(alice)),
+            Arc::new(NoSessions),
+            None,
+            Arc::new(crypto),
+            Arc::new(NoSessions),
+            Arc::new(TracingTelemetry::new()),
+            clock.clone(),
+        )
+        .with_lockout(
+            Arc::new(InMemoryRateLimiter::new(clock)),
+            // Never reached: every sample takes the wrong password path.
+            vec![RateLimitPolicy::new(u32::MAX, 60)],
+        );
+
+        // Interleave samples so that noise affects both alike.
+        let (mut known, mut unknown) = (Vec::new(), Vec::new());
+        for _ in 0..SAMPLES {
+            let start = Instant::now();
+            assert!(
+                usecase.execute(request("alice@example.com")).await.is_err()
+            );
+            known.push(start.elapsed());
+
+            let start = Instant::now();
+            assert!(
+                usecase
+                    .execute(request("nobody@example.com"))
+                    .await
+                    .is_err()
+            );
+            unknown.push(start.elapsed());
+        }
+        known.sort();
+        unknown.sort();
+
+        // Compare the distributions at several quantiles, rather than only
+        // their medians.
+        let quantile = |samples: &[Duration], q: usize| {
+            samples[(SAMPLES - 1) * q / 100].as_secs_f64()
+        };
+        for q in [10, 25, 50, 75, 90] {
+            let ratio = quantile(&known, q) / quantile(&unknown, q);
+            assert!((0.9..=1.1).contains(&ratio), "p{q} ratio is {ratio}");
+        }
+    }
 }
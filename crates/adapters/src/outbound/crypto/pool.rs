@@ -0,0 +1,174 @@
+//! Bounded executor for CPU- and memory-heavy tasks, such as hashing.
+//!
+//! Tasks run on the blocking threads of Tokio, at most `concurrency` at
+//! once. Tasks beyond are queued, and rejected once `max_queue` wait.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use application::error::{ApplicationError, Result, ToInternal};
+use tokio::sync::Semaphore;
+
+/// Seconds a rejected client should wait before retrying.
+const RETRY_AFTER: u64 = 1;
+
+/// Bounded blocking executor.
+#[derive(Clone)]
+pub struct HashingPool {
+    permits: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+    max_queue: usize,
+    name: &'static str,
+}
+
+impl HashingPool {
+    /// Queued tasks per running task, by default.
+    pub const QUEUE_FACTOR: usize = 8;
+
+    /// Create a new [`HashingPool`] running `concurrency` tasks at once.
+    pub fn new(concurrency: usize, max_queue: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            queued: Arc::new(AtomicUsize::new(0)),
+            max_queue,
+            name: "password_hashing",
+        }
+    }
+
+    /// Prefix the metrics of the pool with `name`, `password_hashing` by
+    /// default.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Create a new [`HashingPool`] whose tasks, using `memory_cost` KiB
+    /// each, fit in available memory, with at most one task per CPU.
+    pub fn sized_for(memory_cost: u32) -> Self {
+        let concurrency = Self::concurrency_for(memory_cost);
+        Self::new(concurrency, concurrency * Self::QUEUE_FACTOR)
+    }
+
+    /// Tasks using `memory_cost` KiB each that fit in available memory, at
+    /// most one per CPU.
+    pub fn concurrency_for(memory_cost: u32) -> usize {
+        let cpus = std::thread::available_parallelism().map_or(1, usize::from);
+        available_memory()
+            .map_or(cpus, |memory| {
+                cpus.min((memory / u64::from(memory_cost.max(1))) as usize)
+            })
+            .max(1)
+    }
+
+    /// Run `task` once a slot is free.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApplicationError::Overloaded`] if too many tasks wait.
+    pub async fn run<T, F>(&self, task: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let queued = Queued::enter(&self.queued, self.max_queue, self.name)?;
+        let start = Instant::now();
+        let permit = self.permits.clone().acquire_owned().await.catch()?;
+        drop(queued);
+        metrics::histogram!(format!("{}_queue_seconds", self.name))
+            .record(start.elapsed().as_secs_f64());
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            task()
+        })
+        .await
+        .catch()
+    }
+}
+
+/// Place of a task in the queue, left once dropped, e.g. if the client
+/// went away.
+struct Queued<'a> {
+    queued: &'a AtomicUsize,
+    name: &'static str,
+}
+
+impl<'a> Queued<'a> {
+    fn enter(
+        queued: &'a AtomicUsize,
+        max_queue: usize,
+        name: &'static str,
+    ) -> Result<Self> {
+        let position = queued.fetch_add(1, Ordering::AcqRel);
+        let guard = Self { queued, name };
+        if position >= max_queue {
+            metrics::counter!(format!("{name}_rejected_total")).increment(1);
+            return Err(ApplicationError::Overloaded {
+                retry_after: RETRY_AFTER,
+            });
+        }
+
+        metrics::gauge!(format!("{name}_queued")).set((position + 1) as f64);
+        Ok(guard)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let remaining = self.queued.fetch_sub(1, Ordering::AcqRel) - 1;
+        metrics::gauge!(format!("{}_queued", self.name)).set(remaining as f64);
+    }
+}
+
+/// Memory available for new allocations, in KiB.
+fn available_memory() -> Option<u64> {
+    std::fs::read_to_string("/proc/meminfo")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saturation() {
+        let pool = HashingPool::new(1, 1);
+        let (release, wait) = mpsc::channel::<()>();
+
+        let running = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(move || wait.recv().unwrap()).await }
+        });
+        while pool.permits.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 2).await }
+        });
+        while pool.queued.load(Ordering::Acquire) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(matches!(
+            pool.run(|| 3).await,
+            Err(ApplicationError::Overloaded { retry_after: 1 })
+        ));
+
+        release.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        assert_eq!(queued.await.unwrap().unwrap(), 2);
+        assert_eq!(pool.queued.load(Ordering::Acquire), 0);
+        assert_eq!(pool.run(|| 4).await.unwrap(), 4);
+    }
+}
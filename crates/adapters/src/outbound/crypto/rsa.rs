@@ -0,0 +1,147 @@
+//! RSA key pairs used to sign HTTP requests of local actors.
+//!
+//! Generating a key pair takes up to hundreds of milliseconds, so keys are
+//! generated on a [`HashingPool`].
+
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::SignatureKeys;
+use async_trait::async_trait;
+use aws_lc_rs::encoding::AsDer;
+use aws_lc_rs::rand::SystemRandom;
+use aws_lc_rs::rsa::{KeyPair, KeySize};
+use aws_lc_rs::signature::{
+    KeyPair as _, RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_SHA256,
+    UnparsedPublicKey,
+};
+use spki::SubjectPublicKeyInfoOwned;
+use spki::der::pem::LineEnding;
+use spki::der::{Decode, DecodePem, EncodePem};
+
+use crate::outbound::crypto::HashingPool;
+
+/// `rsaEncryption` algorithm identifier.
+const RSA_ENCRYPTION: spki::ObjectIdentifier =
+    spki::ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+
+/// RSASSA-PKCS1-v1_5 with SHA-256, as expected by `rsa-sha256` peers.
+pub struct RsaSignatureKeys {
+    rng: SystemRandom,
+    pool: HashingPool,
+}
+
+impl RsaSignatureKeys {
+    /// Create a new [`RsaSignatureKeys`] generating one key pair per CPU at
+    /// once.
+    pub fn new() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(1, usize::from);
+        Self {
+            rng: SystemRandom::new(),
+            pool: HashingPool::new(cpus, cpus * HashingPool::QUEUE_FACTOR)
+                .with_name("key_generation"),
+        }
+    }
+}
+
+impl Default for RsaSignatureKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// SPKI PEM public key of `key_pair`.
+fn public_key_pem(key_pair: &KeyPair) -> Result<String> {
+    let public_key = key_pair
+        .public_key()
+        .as_der()
+        .map_err(|_| ApplicationError::Unknown)?;
+
+    SubjectPublicKeyInfoOwned::from_der(public_key.as_ref())
+        .and_then(|spki| spki.to_pem(LineEnding::LF))
+        .map_err(|err| ApplicationError::Internal(Box::new(err)))
+}
+
+/// Generate an RSA 2048-bit key pair.
+fn generate() -> Result<(String, Vec<u8>)> {
+    let key_pair = KeyPair::generate(KeySize::Rsa2048)
+        .map_err(|_| ApplicationError::Unknown)?;
+    let private_key =
+        AsDer::<aws_lc_rs::encoding::Pkcs8V1Der>::as_der(&key_pair)
+            .map_err(|_| ApplicationError::Unknown)?;
+
+    Ok((public_key_pem(&key_pair)?, private_key.as_ref().to_vec()))
+}
+
+#[async_trait]
+impl SignatureKeys for RsaSignatureKeys {
+    async fn generate(&self) -> Result<(String, Vec<u8>)> {
+        self.pool.run(generate).await?
+    }
+
+    fn public_key(&self, private_key: &[u8]) -> Result<String> {
+        let key_pair = KeyPair::from_pkcs8(private_key)
+            .map_err(|err| ApplicationError::Internal(Box::new(err)))?;
+
+        public_key_pem(&key_pair)
+    }
+
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+        let key_pair = KeyPair::from_pkcs8(private_key)
+            .map_err(|_| ApplicationError::Unknown)?;
+        let mut signature = vec![0; key_pair.public_modulus_len()];
+        key_pair
+            .sign(&RSA_PKCS1_SHA256, &self.rng, message, &mut signature)
+            .map_err(|_| ApplicationError::Unknown)?;
+
+        Ok(signature)
+    }
+
+    fn verify(
+        &self,
+        public_key_pem: &str,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        let spki = SubjectPublicKeyInfoOwned::from_pem(public_key_pem.trim())
+            .map_err(|_| ApplicationError::InvalidSignature)?;
+        if spki.algorithm.oid != RSA_ENCRYPTION {
+            return Err(ApplicationError::InvalidSignature);
+        }
+
+        Ok(UnparsedPublicKey::new(
+            &RSA_PKCS1_2048_8192_SHA256,
+            spki.subject_public_key.raw_bytes(),
+        )
+        .verify(message, signature)
+        .is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_and_verify() {
+        let keys = RsaSignatureKeys::new();
+        let (public_key, private_key) = keys.generate().await.unwrap();
+        assert!(public_key.starts_with("-----BEGIN PUBLIC KEY-----"));
+
+        let signature = keys.sign(&private_key, b"message").unwrap();
+        assert!(keys.verify(&public_key, b"message", &signature).unwrap());
+        assert!(!keys.verify(&public_key, b"tampered", &signature).unwrap());
+        assert_eq!(keys.public_key(&private_key).unwrap(), public_key);
+    }
+
+    #[test]
+    fn test_reject_non_rsa_key() {
+        const ED25519: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEAGb9ECWmEzf6FQbrBZ9w7lshQhqowtrbLDFw4rXAxZuE=
+-----END PUBLIC KEY-----";
+
+        assert!(
+            RsaSignatureKeys::new()
+                .verify(ED25519, b"message", &[0; 256])
+                .is_err()
+        );
+    }
+}
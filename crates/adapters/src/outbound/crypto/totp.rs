@@ -6,7 +6,7 @@ use application::error::{ApplicationError, Result, ToInternal};
 use application::ports::outbound::{Clock, TotpGenerator};
 use base32::decode;
 use domain::auth::factor::{TotpCode, TotpConfig, TotpSecret};
-use hmac::{Hmac, Mac};
+use hmac::{Hmac, KeyInit, Mac};
 use sha1::Sha1;
 use zeroize::Zeroizing;
 
@@ -0,0 +1,276 @@
+//! In-memory LRU token denylist.
+//!
+//! Denied `jti`s and user generations are cached in the process. A
+//! fallback store (usually PostgreSQL) is consulted on cache misses and
+//! receives every write, so that revocations reach every instance.
+//! `jti`s found not denied are cached too, for a while, so that every
+//! request does not query the fallback.
+//!
+//! Without fallback, entries evicted from a full cache are forgotten.
+
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::{Clock, TokenDenylist};
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+use lru::LruCache;
+
+/// Number of cached entries of each kind.
+const DEFAULT_CAPACITY: NonZeroUsize = NonZeroUsize::new(100_000).unwrap();
+/// Seconds a generation, or a `jti` not denied, read from the fallback
+/// is trusted.
+const DEFAULT_CACHE_TTL: u64 = 30;
+
+#[derive(Debug, Clone, Copy)]
+struct CachedGeneration {
+    generation: u64,
+    cached_at: u64,
+}
+
+/// Token denylist caching entries in memory.
+pub struct LruTokenDenylist {
+    clock: Arc<dyn Clock>,
+    fallback: Option<Arc<dyn TokenDenylist>>,
+    cache_ttl: u64,
+    /// `jti` to token expiration.
+    denied: Mutex<LruCache<String, u64>>,
+    /// `jti` found not denied to when it was read from the fallback.
+    allowed: Mutex<LruCache<String, u64>>,
+    generations: Mutex<LruCache<String, CachedGeneration>>,
+}
+
+impl LruTokenDenylist {
+    /// Create a new [`LruTokenDenylist`].
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            fallback: None,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            denied: Mutex::new(LruCache::new(DEFAULT_CAPACITY)),
+            allowed: Mutex::new(LruCache::new(DEFAULT_CAPACITY)),
+            generations: Mutex::new(LruCache::new(DEFAULT_CAPACITY)),
+        }
+    }
+
+    /// Set how many entries of each kind are cached.
+    pub fn with_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.denied = Mutex::new(LruCache::new(capacity));
+        self.allowed = Mutex::new(LruCache::new(capacity));
+        self.generations = Mutex::new(LruCache::new(capacity));
+        self
+    }
+
+    /// Store entries in `fallback` and read it on cache misses.
+    pub fn with_fallback(mut self, fallback: Arc<dyn TokenDenylist>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Set how many seconds a generation, or a `jti` not denied, read from
+    /// the fallback is trusted.
+    pub fn with_cache_ttl(mut self, ttl: u64) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    fn cache_denied(&self, jti: &str, expires_at: u64) -> Result<()> {
+        self.allowed
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .pop(jti);
+        self.denied
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .put(jti.to_string(), expires_at);
+        Ok(())
+    }
+
+    fn cache_allowed(&self, jti: &str) -> Result<()> {
+        self.allowed
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .put(jti.to_string(), self.clock.now());
+        Ok(())
+    }
+
+    /// Returns `true` if `jti` was found not denied less than `cache_ttl`
+    /// seconds ago.
+    fn is_allowed(&self, jti: &str, now: u64) -> Result<bool> {
+        let cached_at = self
+            .allowed
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .get(jti)
+            .copied();
+        Ok(
+            cached_at
+                .is_some_and(|cached_at| cached_at + self.cache_ttl > now),
+        )
+    }
+
+    fn cache_generation(
+        &self,
+        user_id: &UserId,
+        generation: u64,
+    ) -> Result<()> {
+        self.generations
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .put(
+                user_id.to_string(),
+                CachedGeneration {
+                    generation,
+                    cached_at: self.clock.now(),
+                },
+            );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TokenDenylist for LruTokenDenylist {
+    async fn deny(&self, jti: &str, expires_at: u64) -> Result<()> {
+        if let Some(fallback) = &self.fallback {
+            fallback.deny(jti, expires_at).await?;
+        }
+
+        self.cache_denied(jti, expires_at)
+    }
+
+    async fn is_denied(&self, jti: &str) -> Result<bool> {
+        let now = self.clock.now();
+        let cached = self
+            .denied
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .get(jti)
+            .copied();
+
+        match (cached, &self.fallback) {
+            (Some(expires_at), _) if expires_at > now => Ok(true),
+            (_, Some(_)) if self.is_allowed(jti, now)? => Ok(false),
+            (_, Some(fallback)) => {
+                let denied = fallback.is_denied(jti).await?;
+                if denied {
+                    // Expired tokens are rejected before reaching the
+                    // denylist, keep the entry until it gets evicted.
+                    self.cache_denied(jti, u64::MAX)?;
+                } else {
+                    self.cache_allowed(jti)?;
+                }
+                Ok(denied)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    async fn generation(&self, user_id: &UserId) -> Result<u64> {
+        let now = self.clock.now();
+        let cached = self
+            .generations
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .get(user_id.as_str())
+            .copied();
+
+        match (cached, &self.fallback) {
+            (Some(cached), Some(_))
+                if cached.cached_at + self.cache_ttl > now =>
+            {
+                Ok(cached.generation)
+            },
+            (_, Some(fallback)) => {
+                let generation = fallback.generation(user_id).await?;
+                self.cache_generation(user_id, generation)?;
+                Ok(generation)
+            },
+            (cached, None) => Ok(cached.map_or(0, |c| c.generation)),
+        }
+    }
+
+    async fn bump_generation(&self, user_id: &UserId) -> Result<u64> {
+        let generation = match &self.fallback {
+            Some(fallback) => fallback.bump_generation(user_id).await?,
+            None => self.generation(user_id).await? + 1,
+        };
+
+        self.cache_generation(user_id, generation)?;
+        Ok(generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbound::clock::FixedClock;
+
+    fn denylist(now: u64) -> LruTokenDenylist {
+        LruTokenDenylist::new(Arc::new(FixedClock::new(now)))
+    }
+
+    #[tokio::test]
+    async fn test_deny_until_expiration() {
+        assert!(!denylist(100).is_denied("jti").await.unwrap());
+
+        let list = denylist(100);
+        list.deny("jti", 200).await.unwrap();
+        assert!(list.is_denied("jti").await.unwrap());
+        assert!(!list.is_denied("other").await.unwrap());
+
+        let list = denylist(300);
+        list.deny("jti", 200).await.unwrap();
+        assert!(!list.is_denied("jti").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_bump_generation() {
+        let list = denylist(0);
+        let user_id = UserId::parse("admin").unwrap();
+
+        assert_eq!(list.generation(&user_id).await.unwrap(), 0);
+        assert_eq!(list.bump_generation(&user_id).await.unwrap(), 1);
+        assert_eq!(list.bump_generation(&user_id).await.unwrap(), 2);
+        assert_eq!(list.generation(&user_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_receives_writes() {
+        let fallback = Arc::new(denylist(0));
+        let list = denylist(0).with_fallback(fallback.clone());
+        let user_id = UserId::parse("admin").unwrap();
+
+        list.deny("jti", 900).await.unwrap();
+        list.bump_generation(&user_id).await.unwrap();
+
+        assert!(fallback.is_denied("jti").await.unwrap());
+        assert_eq!(fallback.generation(&user_id).await.unwrap(), 1);
+
+        // A fresh cache reads what another instance wrote.
+        let other = denylist(0).with_fallback(fallback);
+        assert!(other.is_denied("jti").await.unwrap());
+        assert_eq!(other.generation(&user_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_not_denied() {
+        let clock = Arc::new(FixedClock::new(0));
+        let fallback = Arc::new(denylist(0));
+        let list = LruTokenDenylist::new(clock.clone())
+            .with_cache_ttl(30)
+            .with_fallback(fallback.clone());
+
+        assert!(!list.is_denied("jti").await.unwrap());
+        // Denied by another instance, unnoticed until the entry expires.
+        fallback.deny("jti", 900).await.unwrap();
+        assert!(!list.is_denied("jti").await.unwrap());
+        clock.advance(30);
+        assert!(list.is_denied("jti").await.unwrap());
+
+        // Denied by this instance, noticed right away.
+        assert!(!list.is_denied("other").await.unwrap());
+        list.deny("other", 900).await.unwrap();
+        assert!(list.is_denied("other").await.unwrap());
+    }
+}
@@ -0,0 +1,42 @@
+//! IP geolocation using a MaxMind (GeoIP2 / GeoLite2) City database.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+use application::error::{Result, ToInternal};
+use application::ports::outbound::GeoLocator;
+use maxminddb::{Reader, geoip2};
+
+const LANGUAGE: &str = "en";
+
+/// GeoIP2 City database loaded in memory.
+pub struct MaxMindGeoLocator {
+    reader: Reader<Vec<u8>>,
+}
+
+impl MaxMindGeoLocator {
+    /// Load the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            reader: Reader::open_readfile(path).catch()?,
+        })
+    }
+}
+
+impl GeoLocator for MaxMindGeoLocator {
+    fn locate(&self, ip: IpAddr) -> Option<String> {
+        let record = self.reader.lookup::<geoip2::City>(ip).ok()?;
+
+        let name = |names: Option<BTreeMap<&str, &str>>| {
+            names.and_then(|names| names.get(LANGUAGE).map(|n| n.to_string()))
+        };
+        let city = record.city.and_then(|city| name(city.names));
+        let country = record.country.and_then(|country| name(country.names));
+
+        match (city, country) {
+            (Some(city), Some(country)) => Some(format!("{city}, {country}")),
+            (city, country) => city.or(country),
+        }
+    }
+}
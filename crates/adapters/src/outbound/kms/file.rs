@@ -0,0 +1,359 @@
+//! Keys held in a local file, encrypted with a passphrase.
+//!
+//! The file is JSON, with the key list encrypted with AES-256-GCM under a
+//! key derived from the passphrase with Argon2id:
+//! ```json
+//! {"version": 1, "salt": "...", "nonce": "...", "ciphertext": "..."}
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::KeyManagement;
+use async_trait::async_trait;
+use aws_lc_rs::encoding::AsDer;
+use aws_lc_rs::rand::SystemRandom;
+use aws_lc_rs::signature::{
+    ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, KeyPair as _,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use spki::SubjectPublicKeyInfoOwned;
+use spki::der::pem::LineEnding;
+use spki::der::{Decode, EncodePem};
+use zeroize::Zeroizing;
+
+const VERSION: u8 = 1;
+const KEY_LENGTH: usize = 32;
+const NONCE_SIZE: usize = 12;
+const SALT_SIZE: usize = 16;
+
+/// Kind of a key in the keystore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyKind {
+    /// AES-256-GCM key wrapping data keys.
+    Aes256Gcm,
+    /// ECDSA P-256 key pair signing tokens.
+    EcdsaP256,
+}
+
+enum StoredKey {
+    Aes256Gcm(Zeroizing<[u8; KEY_LENGTH]>),
+    EcdsaP256 {
+        pkcs8: Zeroizing<Vec<u8>>,
+        key_pair: EcdsaKeyPair,
+    },
+}
+
+impl StoredKey {
+    fn generate(kind: KeyKind) -> Result<Self> {
+        match kind {
+            KeyKind::Aes256Gcm => {
+                let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+                rand::rngs::OsRng.fill_bytes(&mut *key);
+                Ok(Self::Aes256Gcm(key))
+            },
+            KeyKind::EcdsaP256 => {
+                let pkcs8 = EcdsaKeyPair::generate_pkcs8(
+                    &ECDSA_P256_SHA256_FIXED_SIGNING,
+                    &SystemRandom::new(),
+                )
+                .map_err(|_| ApplicationError::Unknown)?;
+                Self::ecdsa_p256(pkcs8.as_ref().to_vec())
+            },
+        }
+    }
+
+    fn ecdsa_p256(pkcs8: Vec<u8>) -> Result<Self> {
+        let pkcs8 = Zeroizing::new(pkcs8);
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8)
+                .map_err(|_| ApplicationError::Unknown)?;
+
+        Ok(Self::EcdsaP256 { pkcs8, key_pair })
+    }
+
+    fn kind(&self) -> KeyKind {
+        match self {
+            Self::Aes256Gcm(_) => KeyKind::Aes256Gcm,
+            Self::EcdsaP256 { .. } => KeyKind::EcdsaP256,
+        }
+    }
+
+    fn material(&self) -> &[u8] {
+        match self {
+            Self::Aes256Gcm(key) => &**key,
+            Self::EcdsaP256 { pkcs8, .. } => pkcs8,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u8,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyEntry {
+    id: String,
+    kind: KeyKind,
+    /// Hex-encoded raw AES key or PKCS#8 DER key pair.
+    key: String,
+}
+
+/// Passphrase-derived key encrypting the keystore.
+fn derive_key(
+    passphrase: &[u8],
+    salt: &[u8],
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>> {
+    let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+    argon2::Argon2::default()
+        .hash_password_into(passphrase, salt, &mut *key)
+        .catch()?;
+
+    Ok(key)
+}
+
+/// Key management backed by an encrypted local file.
+#[derive(Default)]
+pub struct FileKeystore {
+    keys: HashMap<String, StoredKey>,
+}
+
+impl FileKeystore {
+    /// Create a new empty [`FileKeystore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read and decrypt the keystore at `path`.
+    pub fn open(path: impl AsRef<Path>, passphrase: &[u8]) -> Result<Self> {
+        let file: KeystoreFile =
+            serde_json::from_slice(&std::fs::read(path).catch()?).catch()?;
+        if file.version != VERSION {
+            return Err(ApplicationError::Unknown);
+        }
+
+        let salt = hex::decode(&file.salt).catch()?;
+        let nonce = hex::decode(&file.nonce).catch()?;
+        if nonce.len() != NONCE_SIZE {
+            return Err(ApplicationError::Unknown);
+        }
+        let key = derive_key(passphrase, &salt)?;
+        let entries = Zeroizing::new(
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key))
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &hex::decode(&file.ciphertext).catch()?,
+                        aad: &[VERSION],
+                    },
+                )
+                .map_err(|_| ApplicationError::Unknown)?,
+        );
+        let entries: Vec<KeyEntry> =
+            serde_json::from_slice(&entries).catch()?;
+
+        let mut keys = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let material = Zeroizing::new(hex::decode(&entry.key).catch()?);
+            let key = match entry.kind {
+                KeyKind::Aes256Gcm => StoredKey::Aes256Gcm(Zeroizing::new(
+                    material
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| ApplicationError::Unknown)?,
+                )),
+                KeyKind::EcdsaP256 => {
+                    StoredKey::ecdsa_p256(material.to_vec())?
+                },
+            };
+            keys.insert(entry.id, key);
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Encrypt the keystore with `passphrase` and write it to `path`.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        passphrase: &[u8],
+    ) -> Result<()> {
+        let entries = self
+            .keys
+            .iter()
+            .map(|(id, key)| KeyEntry {
+                id: id.clone(),
+                kind: key.kind(),
+                key: hex::encode(key.material()),
+            })
+            .collect::<Vec<_>>();
+        let entries = Zeroizing::new(serde_json::to_vec(&entries).catch()?);
+
+        let mut salt = [0u8; SALT_SIZE];
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let key = derive_key(passphrase, &salt)?;
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &entries,
+                    aad: &[VERSION],
+                },
+            )
+            .map_err(|_| ApplicationError::Unknown)?;
+
+        let file = KeystoreFile {
+            version: VERSION,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&file).catch()?).catch()
+    }
+
+    /// Generate the key `id` if the keystore does not hold it, and returns
+    /// whether it was generated.
+    pub fn ensure(&mut self, id: &str, kind: KeyKind) -> Result<bool> {
+        match self.keys.get(id) {
+            Some(key) if key.kind() == kind => Ok(false),
+            Some(_) => Err(ApplicationError::Unknown),
+            None => {
+                self.keys.insert(id.to_string(), StoredKey::generate(kind)?);
+                Ok(true)
+            },
+        }
+    }
+
+    fn aes(&self, key_id: &str) -> Result<Aes256Gcm> {
+        match self.keys.get(key_id) {
+            Some(StoredKey::Aes256Gcm(key)) => {
+                Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&**key)))
+            },
+            _ => Err(ApplicationError::Unknown),
+        }
+    }
+
+    fn key_pair(&self, key_id: &str) -> Result<&EcdsaKeyPair> {
+        match self.keys.get(key_id) {
+            Some(StoredKey::EcdsaP256 { key_pair, .. }) => Ok(key_pair),
+            _ => Err(ApplicationError::Unknown),
+        }
+    }
+}
+
+#[async_trait]
+impl KeyManagement for FileKeystore {
+    async fn wrap(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .aes(key_id)?
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: data_key,
+                    aad: key_id.as_bytes(),
+                },
+            )
+            .map_err(|_| ApplicationError::Unknown)?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    async fn unwrap(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> Result<Vec<u8>> {
+        if wrapped_key.len() < NONCE_SIZE {
+            return Err(ApplicationError::TooSmall {
+                expected: NONCE_SIZE,
+            });
+        }
+
+        let (nonce, ciphertext) = wrapped_key.split_at(NONCE_SIZE);
+        self.aes(key_id)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: key_id.as_bytes(),
+                },
+            )
+            .map_err(|_| ApplicationError::Unknown)
+    }
+
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>> {
+        self.key_pair(key_id)?
+            .sign(&SystemRandom::new(), message)
+            .map(|signature| signature.as_ref().to_vec())
+            .map_err(|_| ApplicationError::Unknown)
+    }
+
+    async fn public_key(&self, key_id: &str) -> Result<String> {
+        let public_key = self
+            .key_pair(key_id)?
+            .public_key()
+            .as_der()
+            .map_err(|_| ApplicationError::Unknown)?;
+
+        SubjectPublicKeyInfoOwned::from_der(public_key.as_ref())
+            .and_then(|spki| spki.to_pem(LineEnding::LF))
+            .map_err(|err| ApplicationError::Internal(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_lc_rs::signature::{ECDSA_P256_SHA256_FIXED, UnparsedPublicKey};
+    use spki::der::DecodePem;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keystore() {
+        let path = std::env::temp_dir()
+            .join(format!("autha-keystore-{}.json", std::process::id()));
+        let mut keystore = FileKeystore::new();
+        assert!(keystore.ensure("master", KeyKind::Aes256Gcm).unwrap());
+        assert!(keystore.ensure("jwt", KeyKind::EcdsaP256).unwrap());
+        assert!(!keystore.ensure("jwt", KeyKind::EcdsaP256).unwrap());
+        assert!(keystore.ensure("jwt", KeyKind::Aes256Gcm).is_err());
+        keystore.save(&path, b"passphrase").unwrap();
+
+        assert!(FileKeystore::open(&path, b"wrong").is_err());
+        let keystore = FileKeystore::open(&path, b"passphrase").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let wrapped = keystore.wrap("master", &[7; 32]).await.unwrap();
+        assert_eq!(
+            keystore.unwrap("master", &wrapped).await.unwrap(),
+            [7; 32]
+        );
+        assert!(keystore.unwrap("jwt", &wrapped).await.is_err());
+
+        let signature = keystore.sign("jwt", b"header.claims").await.unwrap();
+        assert_eq!(signature.len(), 64);
+        let public_key = SubjectPublicKeyInfoOwned::from_pem(
+            keystore.public_key("jwt").await.unwrap(),
+        )
+        .unwrap();
+        UnparsedPublicKey::new(
+            &ECDSA_P256_SHA256_FIXED,
+            public_key.subject_public_key.raw_bytes(),
+        )
+        .verify(b"header.claims", &signature)
+        .unwrap();
+    }
+}
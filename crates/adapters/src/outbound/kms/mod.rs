@@ -0,0 +1,9 @@
+//! Key management adapters: keys used without leaving their store.
+
+pub mod file;
+pub mod pkcs11;
+pub mod vault;
+
+pub use file::{FileKeystore, KeyKind};
+pub use pkcs11::Pkcs11KeyManagement;
+pub use vault::VaultTransit;
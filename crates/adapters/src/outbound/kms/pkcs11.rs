@@ -0,0 +1,335 @@
+//! Keys held by a PKCS#11 token, such as an HSM or SoftHSM.
+//!
+//! Keys are found by label: an AES secret key wraps data keys with
+//! `CKM_AES_GCM`, and an EC P-256 key pair signs with `CKM_ECDSA`.
+
+use std::sync::{Arc, Mutex};
+
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::KeyManagement;
+use async_trait::async_trait;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::error::{Error, RvError};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::mechanism::aead::GcmParams;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::{AuthPin, Ulong};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use spki::SubjectPublicKeyInfoOwned;
+use spki::der::pem::LineEnding;
+use spki::der::{Decode, EncodePem};
+use zeroize::Zeroizing;
+
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+/// Uncompressed P-256 point.
+const EC_POINT_SIZE: usize = 65;
+/// DER of a P-256 `SubjectPublicKeyInfo` up to its point.
+const P256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02,
+    0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03,
+    0x42, 0x00,
+];
+
+/// Log a failed PKCS#11 call.
+fn check<T>(result: std::result::Result<T, Error>) -> Result<T> {
+    result.map_err(|err| {
+        tracing::error!(%err, "PKCS#11 call failed");
+        ApplicationError::Unknown
+    })
+}
+
+/// Key management backed by a PKCS#11 token.
+///
+/// Calls to the module block, so they run on the blocking thread pool.
+pub struct Pkcs11KeyManagement {
+    /// Calls are serialized, as a session must not be used concurrently.
+    session: Arc<Mutex<Session>>,
+}
+
+impl Pkcs11KeyManagement {
+    /// Load the module at `path` and log into the token of `slot`.
+    pub fn new(path: &str, slot: u64, pin: &str) -> Result<Self> {
+        let pkcs11 = Pkcs11::new(path).map_err(|err| {
+            tracing::error!(%err, path, "cannot load PKCS#11 module");
+            ApplicationError::Unknown
+        })?;
+        match pkcs11.initialize(CInitializeArgs::OsThreads) {
+            Ok(()) |
+            Err(Error::Pkcs11(RvError::CryptokiAlreadyInitialized, _)) => {},
+            Err(err) => check(Err(err))?,
+        }
+
+        let session = check(
+            Slot::try_from(slot).and_then(|slot| pkcs11.open_rw_session(slot)),
+        )?;
+        match session
+            .login(UserType::User, Some(&AuthPin::new(pin.to_string())))
+        {
+            Ok(()) | Err(Error::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => {},
+            Err(err) => check(Err(err))?,
+        }
+
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+        })
+    }
+
+    /// Run `operation` with the session on a blocking thread.
+    async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Session) -> Result<T> + Send + 'static,
+    {
+        let session = self.session.clone();
+        tokio::task::spawn_blocking(move || {
+            let session =
+                session.lock().map_err(|_| ApplicationError::Unknown)?;
+            operation(&session)
+        })
+        .await
+        .catch()?
+    }
+}
+
+/// Find the object of `class` labeled `label`.
+fn find(
+    session: &Session,
+    class: ObjectClass,
+    label: &str,
+) -> Result<ObjectHandle> {
+    let objects = check(session.find_objects(&[
+        Attribute::Class(class),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ]))?;
+
+    match objects[..] {
+        [object] => Ok(object),
+        _ => Err(ApplicationError::Unknown),
+    }
+}
+
+/// Mechanism of AES-GCM with `nonce`, authenticating `key_id`.
+fn aes_gcm<'a>(
+    nonce: &'a mut [u8; NONCE_SIZE],
+    key_id: &'a str,
+) -> Result<Mechanism<'a>> {
+    let params = GcmParams::new(
+        nonce,
+        key_id.as_bytes(),
+        Ulong::from((TAG_SIZE * 8) as u64),
+    )
+    .map_err(|_| ApplicationError::Unknown)?;
+
+    Ok(Mechanism::AesGcm(params))
+}
+
+/// Build a SPKI PEM from a `CKA_EC_POINT`, which is an uncompressed point
+/// wrapped, or not for some modules, in a DER octet string.
+fn p256_public_key(ec_point: &[u8]) -> Result<String> {
+    let point = match ec_point {
+        [0x04, length, point @ ..] if *length as usize == EC_POINT_SIZE => {
+            point
+        },
+        point => point,
+    };
+    if point.len() != EC_POINT_SIZE || point[0] != 0x04 {
+        return Err(ApplicationError::Unknown);
+    }
+
+    let der = [P256_SPKI_PREFIX.as_slice(), point].concat();
+    SubjectPublicKeyInfoOwned::from_der(&der)
+        .and_then(|spki| spki.to_pem(LineEnding::LF))
+        .map_err(|err| ApplicationError::Internal(Box::new(err)))
+}
+
+#[async_trait]
+impl KeyManagement for Pkcs11KeyManagement {
+    async fn wrap(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>> {
+        let key_id = key_id.to_string();
+        let data_key = Zeroizing::new(data_key.to_vec());
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let nonce_bytes = nonce;
+
+        let ciphertext = self
+            .run(move |session| {
+                let key = find(session, ObjectClass::SECRET_KEY, &key_id)?;
+                check(session.encrypt(
+                    &aes_gcm(&mut nonce, &key_id)?,
+                    key,
+                    &data_key,
+                ))
+            })
+            .await?;
+
+        Ok([nonce_bytes.as_slice(), &ciphertext].concat())
+    }
+
+    async fn unwrap(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> Result<Vec<u8>> {
+        if wrapped_key.len() < NONCE_SIZE + TAG_SIZE {
+            return Err(ApplicationError::TooSmall {
+                expected: NONCE_SIZE + TAG_SIZE,
+            });
+        }
+
+        let (nonce, ciphertext) = wrapped_key.split_at(NONCE_SIZE);
+        let mut nonce: [u8; NONCE_SIZE] =
+            nonce.try_into().map_err(|_| ApplicationError::Unknown)?;
+        let key_id = key_id.to_string();
+        let ciphertext = ciphertext.to_vec();
+
+        self.run(move |session| {
+            let key = find(session, ObjectClass::SECRET_KEY, &key_id)?;
+            let plaintext = Zeroizing::new(check(session.decrypt(
+                &aes_gcm(&mut nonce, &key_id)?,
+                key,
+                &ciphertext,
+            ))?);
+
+            Ok(plaintext.to_vec())
+        })
+        .await
+    }
+
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>> {
+        let key_id = key_id.to_string();
+        let digest = Sha256::digest(message);
+
+        self.run(move |session| {
+            let key = find(session, ObjectClass::PRIVATE_KEY, &key_id)?;
+            check(session.sign(&Mechanism::Ecdsa, key, &digest))
+        })
+        .await
+    }
+
+    async fn public_key(&self, key_id: &str) -> Result<String> {
+        let key_id = key_id.to_string();
+
+        self.run(move |session| {
+            let key = find(session, ObjectClass::PUBLIC_KEY, &key_id)?;
+            let attributes =
+                check(session.get_attributes(key, &[AttributeType::EcPoint]))?;
+
+            match &attributes[..] {
+                [Attribute::EcPoint(ec_point)] => p256_public_key(ec_point),
+                _ => Err(ApplicationError::Unknown),
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_lc_rs::encoding::AsDer;
+    use aws_lc_rs::signature::{
+        ECDSA_P256_SHA256_FIXED, ECDSA_P256_SHA256_FIXED_SIGNING,
+        EcdsaKeyPair, KeyPair as _, UnparsedPublicKey,
+    };
+    use spki::der::DecodePem;
+
+    use super::*;
+
+    /// DER of the P-256 curve OID.
+    const P256_PARAMS: [u8; 10] =
+        [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+    #[test]
+    fn test_p256_public_key() {
+        let key_pair =
+            EcdsaKeyPair::generate(&ECDSA_P256_SHA256_FIXED_SIGNING).unwrap();
+        let point = key_pair.public_key().as_ref();
+        let expected = SubjectPublicKeyInfoOwned::from_der(
+            key_pair.public_key().as_der().unwrap().as_ref(),
+        )
+        .unwrap()
+        .to_pem(LineEnding::LF)
+        .unwrap();
+
+        assert_eq!(p256_public_key(point).unwrap(), expected);
+        let wrapped = [&[0x04, EC_POINT_SIZE as u8], point].concat();
+        assert_eq!(p256_public_key(&wrapped).unwrap(), expected);
+        assert!(p256_public_key(&point[1..]).is_err());
+    }
+
+    /// Runs against the first initialized token of the module at
+    /// `PKCS11_MODULE`, such as SoftHSM after
+    /// `softhsm2-util --init-token --free --label autha --pin 1234`, and is
+    /// skipped when unset.
+    #[tokio::test]
+    async fn test_softhsm() {
+        let Ok(module) = std::env::var("PKCS11_MODULE") else {
+            return;
+        };
+        let pin =
+            std::env::var("PKCS11_PIN").unwrap_or_else(|_| "1234".into());
+        let slot = match std::env::var("PKCS11_SLOT") {
+            Ok(slot) => slot.parse().unwrap(),
+            Err(_) => {
+                let pkcs11 = Pkcs11::new(&module).unwrap();
+                pkcs11.initialize(CInitializeArgs::OsThreads).unwrap();
+                pkcs11.get_slots_with_token().unwrap()[0].id()
+            },
+        };
+        let hsm = Pkcs11KeyManagement::new(&module, slot, &pin).unwrap();
+
+        // Session objects, destroyed with the session.
+        {
+            let session = hsm.session.lock().unwrap();
+            session
+                .generate_key(
+                    &Mechanism::AesKeyGen,
+                    &[
+                        Attribute::Token(false),
+                        Attribute::Label(b"master".to_vec()),
+                        Attribute::ValueLen(Ulong::from(32)),
+                        Attribute::Encrypt(true),
+                        Attribute::Decrypt(true),
+                    ],
+                )
+                .unwrap();
+            session
+                .generate_key_pair(
+                    &Mechanism::EccKeyPairGen,
+                    &[
+                        Attribute::Token(false),
+                        Attribute::Label(b"jwt".to_vec()),
+                        Attribute::EcParams(P256_PARAMS.to_vec()),
+                        Attribute::Verify(true),
+                    ],
+                    &[
+                        Attribute::Token(false),
+                        Attribute::Label(b"jwt".to_vec()),
+                        Attribute::Private(true),
+                        Attribute::Sensitive(true),
+                        Attribute::Sign(true),
+                    ],
+                )
+                .unwrap();
+        }
+
+        let wrapped = hsm.wrap("master", &[7; 32]).await.unwrap();
+        assert_eq!(hsm.unwrap("master", &wrapped).await.unwrap(), [7; 32]);
+        assert!(hsm.unwrap("jwt", &wrapped).await.is_err());
+
+        let signature = hsm.sign("jwt", b"header.claims").await.unwrap();
+        let public_key = SubjectPublicKeyInfoOwned::from_pem(
+            hsm.public_key("jwt").await.unwrap(),
+        )
+        .unwrap();
+        UnparsedPublicKey::new(
+            &ECDSA_P256_SHA256_FIXED,
+            public_key.subject_public_key.raw_bytes(),
+        )
+        .verify(b"header.claims", &signature)
+        .unwrap();
+    }
+}
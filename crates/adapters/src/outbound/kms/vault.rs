@@ -0,0 +1,271 @@
+//! Keys held by the transit secrets engine of HashiCorp Vault or OpenBao.
+//!
+//! Data keys are wrapped with `encrypt`, tokens signed with `sign` using
+//! the `jws` marshaling, and private keys never leave the server.
+
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::KeyManagement;
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use reqwest::{Client, Method};
+use serde_json::{Value, json};
+use zeroize::Zeroizing;
+
+const DEFAULT_MOUNT: &str = "transit";
+
+/// Key management backed by Vault transit.
+pub struct VaultTransit {
+    client: Client,
+    address: String,
+    mount: String,
+    token: Zeroizing<String>,
+}
+
+impl VaultTransit {
+    /// Create a new [`VaultTransit`] using the engine mounted at `transit`.
+    pub fn new(address: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            address: address.into().trim_end_matches('/').to_string(),
+            mount: DEFAULT_MOUNT.to_string(),
+            token: Zeroizing::new(token.into()),
+        }
+    }
+
+    /// Use the engine mounted at `mount`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Call `path` under the mount, and returns the `data` of the answer.
+    async fn call(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = format!("{}/v1/{}/{path}", self.address, self.mount);
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-vault-token", self.token.as_str());
+        if let Some(body) = body {
+            request = request
+                .header("content-type", "application/json")
+                .body(serde_json::to_vec(&body).catch()?);
+        }
+
+        let response = request.send().await.catch()?;
+        if !response.status().is_success() {
+            tracing::error!(status = %response.status(), path, "vault refused request");
+            return Err(ApplicationError::Unknown);
+        }
+
+        let mut answer: Value =
+            serde_json::from_slice(&response.bytes().await.catch()?)
+                .catch()?;
+        Ok(answer["data"].take())
+    }
+}
+
+/// Ensure `key_id` is a valid key name, as it is part of request paths.
+fn key_name(key_id: &str) -> Result<&str> {
+    let valid = !key_id.is_empty() &&
+        key_id.bytes().all(|b| {
+            b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.')
+        });
+
+    if valid {
+        Ok(key_id)
+    } else {
+        Err(ApplicationError::Unknown)
+    }
+}
+
+/// Strip the `vault:v<version>:` prefix of a ciphertext or signature.
+fn strip_prefix(value: &str) -> Result<&str> {
+    value
+        .strip_prefix("vault:v")
+        .and_then(|value| value.split_once(':'))
+        .map(|(_, value)| value)
+        .ok_or(ApplicationError::Unknown)
+}
+
+#[async_trait]
+impl KeyManagement for VaultTransit {
+    async fn wrap(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>> {
+        let data = self
+            .call(
+                Method::POST,
+                &format!("encrypt/{}", key_name(key_id)?),
+                Some(json!({ "plaintext": STANDARD.encode(data_key) })),
+            )
+            .await?;
+
+        data["ciphertext"]
+            .as_str()
+            .map(|ciphertext| ciphertext.as_bytes().to_vec())
+            .ok_or(ApplicationError::Unknown)
+    }
+
+    async fn unwrap(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> Result<Vec<u8>> {
+        let ciphertext = std::str::from_utf8(wrapped_key).catch()?;
+        let data = self
+            .call(
+                Method::POST,
+                &format!("decrypt/{}", key_name(key_id)?),
+                Some(json!({ "ciphertext": ciphertext })),
+            )
+            .await?;
+
+        let plaintext = Zeroizing::new(
+            data["plaintext"]
+                .as_str()
+                .ok_or(ApplicationError::Unknown)?
+                .to_string(),
+        );
+        STANDARD.decode(plaintext.as_bytes()).catch()
+    }
+
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>> {
+        let data = self
+            .call(
+                Method::POST,
+                &format!("sign/{}/sha2-256", key_name(key_id)?),
+                Some(json!({
+                    "input": STANDARD.encode(message),
+                    "marshaling_algorithm": "jws",
+                })),
+            )
+            .await?;
+
+        let signature = data["signature"]
+            .as_str()
+            .ok_or(ApplicationError::Unknown)?;
+        URL_SAFE_NO_PAD.decode(strip_prefix(signature)?).catch()
+    }
+
+    async fn public_key(&self, key_id: &str) -> Result<String> {
+        let data = self
+            .call(Method::GET, &format!("keys/{}", key_name(key_id)?), None)
+            .await?;
+
+        let version = data["latest_version"]
+            .as_u64()
+            .ok_or(ApplicationError::Unknown)?;
+        data["keys"][version.to_string()]["public_key"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or(ApplicationError::Unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+
+    use super::*;
+    use crate::outbound::kms::{FileKeystore, KeyKind};
+
+    const TOKEN: &str = "hvs.test";
+
+    /// Transit engine holding its keys in a keystore.
+    async fn transit(
+        State(keystore): State<Arc<FileKeystore>>,
+        Path((operation, key)): Path<(String, String)>,
+        headers: HeaderMap,
+        body: Option<Json<Value>>,
+    ) -> impl IntoResponse {
+        if headers
+            .get("x-vault-token")
+            .is_none_or(|token| token != TOKEN)
+        {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+
+        let body = body.map(|Json(body)| body).unwrap_or_default();
+        let input = |name: &str| {
+            STANDARD.decode(body[name].as_str().unwrap()).unwrap()
+        };
+        let data = match operation.as_str() {
+            "encrypt" => {
+                let wrapped =
+                    keystore.wrap(&key, &input("plaintext")).await.unwrap();
+                json!({ "ciphertext": format!("vault:v1:{}", STANDARD.encode(wrapped)) })
+            },
+            "decrypt" => {
+                let ciphertext = body["ciphertext"].as_str().unwrap();
+                let wrapped = STANDARD
+                    .decode(strip_prefix(ciphertext).unwrap())
+                    .unwrap();
+                match keystore.unwrap(&key, &wrapped).await {
+                    Ok(plaintext) => {
+                        json!({ "plaintext": STANDARD.encode(plaintext) })
+                    },
+                    Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+                }
+            },
+            "sign" => {
+                assert_eq!(body["marshaling_algorithm"], "jws");
+                let signature =
+                    keystore.sign(&key, &input("input")).await.unwrap();
+                json!({ "signature": format!("vault:v1:{}", URL_SAFE_NO_PAD.encode(signature)) })
+            },
+            "keys" => json!({
+                "latest_version": 1,
+                "keys": { "1": { "public_key": keystore.public_key(&key).await.unwrap() } },
+            }),
+            _ => return StatusCode::NOT_FOUND.into_response(),
+        };
+
+        Json(json!({ "data": data })).into_response()
+    }
+
+    #[tokio::test]
+    async fn test_transit() {
+        let mut keystore = FileKeystore::new();
+        keystore.ensure("autha", KeyKind::Aes256Gcm).unwrap();
+        keystore.ensure("jwt", KeyKind::EcdsaP256).unwrap();
+        let keystore = Arc::new(keystore);
+        let app = Router::new()
+            .route("/v1/transit/{operation}/{key}", get(transit).post(transit))
+            .route("/v1/transit/{operation}/{key}/sha2-256", post(transit))
+            .with_state(keystore.clone());
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let vault = VaultTransit::new(&address, TOKEN);
+        let wrapped = vault.wrap("autha", &[7; 32]).await.unwrap();
+        assert!(wrapped.starts_with(b"vault:v1:"));
+        assert_eq!(vault.unwrap("autha", &wrapped).await.unwrap(), [7; 32]);
+
+        let signature = vault.sign("jwt", b"header.claims").await.unwrap();
+        assert_eq!(signature.len(), 64);
+        assert_eq!(
+            vault.public_key("jwt").await.unwrap(),
+            keystore.public_key("jwt").await.unwrap()
+        );
+
+        assert!(vault.wrap("../sys", &[7; 32]).await.is_err());
+        assert!(
+            VaultTransit::new(&address, "wrong")
+                .wrap("autha", &[7; 32])
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Media storage on the local filesystem.
+//!
+//! Files are expected to be served as static files under `base_url`.
+
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::MediaStore;
+use async_trait::async_trait;
+
+use crate::outbound::media::is_valid_key;
+
+/// Media store writing files below a root directory.
+pub struct FilesystemMediaStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl FilesystemMediaStore {
+    /// Create a store writing into `root`, served at `base_url`.
+    pub fn new(root: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        if !is_valid_key(key) {
+            return Err(ApplicationError::Unknown);
+        }
+
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl MediaStore for FilesystemMediaStore {
+    async fn put(
+        &self,
+        key: &str,
+        _media_type: &str,
+        data: Vec<u8>,
+    ) -> Result<String> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.catch()?;
+        }
+
+        // Readers must never see a partially written file.
+        let partial = path.with_extension("part");
+        tokio::fs::write(&partial, data).await.catch()?;
+        tokio::fs::rename(&partial, &path).await.catch()?;
+
+        Ok(format!("{}/{}", self.base_url, key))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(ApplicationError::internal(err))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn key_of(&self, url: &str) -> Option<String> {
+        url.strip_prefix(&self.base_url)?
+            .strip_prefix('/')
+            .filter(|key| is_valid_key(key))
+            .map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_delete() {
+        let root = std::env::temp_dir()
+            .join(format!("autha-media-{}", std::process::id()));
+        let store =
+            FilesystemMediaStore::new(&root, "https://cdn.test/media/");
+
+        let url = store
+            .put("avatar/1/a.webp", "image/webp", b"RIFF".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(url, "https://cdn.test/media/avatar/1/a.webp");
+        assert_eq!(
+            tokio::fs::read(root.join("avatar/1/a.webp")).await.unwrap(),
+            b"RIFF"
+        );
+
+        let key = store.key_of(&url).unwrap();
+        store.delete(&key).await.unwrap();
+        store.delete(&key).await.unwrap();
+        assert!(!root.join("avatar/1/a.webp").exists());
+
+        assert!(
+            store
+                .key_of("https://cdn.test/media/../etc/passwd")
+                .is_none()
+        );
+        assert!(store.key_of("https://elsewhere.test/a.webp").is_none());
+        assert!(
+            store
+                .put("../a.webp", "image/webp", Vec::new())
+                .await
+                .is_err()
+        );
+
+        tokio::fs::remove_dir_all(root).await.unwrap();
+    }
+}
@@ -0,0 +1,23 @@
+//! Media adapters: image processing and public file storage.
+
+pub mod filesystem;
+pub mod processor;
+pub mod s3;
+
+pub use filesystem::FilesystemMediaStore;
+pub use processor::WebpImageProcessor;
+pub use s3::S3MediaStore;
+
+/// Returns `true` if `key` is a relative path without `.` or `..` segments.
+pub(crate) fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() &&
+        key.split('/').all(|segment| {
+            !segment.is_empty() &&
+                segment != "." &&
+                segment != ".." &&
+                segment.bytes().all(|b| {
+                    b.is_ascii_alphanumeric() ||
+                        matches!(b, b'-' | b'_' | b'.')
+                })
+        })
+}
@@ -0,0 +1,177 @@
+//! Image validation and re-encoding with the `image` crate.
+//!
+//! The format is sniffed from the content, never trusted from the client.
+//! Decoding then re-encoding to lossless WebP drops every metadata chunk,
+//! including EXIF, once its orientation has been applied.
+//!
+//! Decoding may use up to [`MAX_ALLOC`] bytes, so images are processed on a
+//! [`HashingPool`] sized for it: uploads beyond its queue are rejected with
+//! [`ApplicationError::Overloaded`].
+
+use std::io::Cursor;
+
+use application::dto::{MediaKind, ProcessedImageDto};
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::ImageProcessor;
+use async_trait::async_trait;
+use domain::error::DomainError;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+
+use crate::outbound::crypto::HashingPool;
+
+/// Formats accepted for upload.
+const FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+];
+/// Largest width or height accepted, to bound decoding cost.
+const MAX_DIMENSION: u32 = 8192;
+/// Memory allowed for decoding a single image.
+const MAX_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Image processor producing lossless WebP files.
+#[derive(Clone)]
+pub struct WebpImageProcessor {
+    pool: HashingPool,
+}
+
+impl WebpImageProcessor {
+    /// Create a new [`WebpImageProcessor`] decoding as many images at once
+    /// as fit in available memory.
+    pub fn new() -> Self {
+        Self {
+            pool: HashingPool::sized_for((MAX_ALLOC / 1024) as u32)
+                .with_name("image_processing"),
+        }
+    }
+
+    /// Decode `data`, then crop and resize it to `width` by `height`.
+    fn process_sync(
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<ProcessedImageDto> {
+        let format = image::guess_format(data)
+            .ok()
+            .filter(|format| FORMATS.contains(format))
+            .ok_or(ApplicationError::UnsupportedMediaType)?;
+
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_DIMENSION);
+        limits.max_image_height = Some(MAX_DIMENSION);
+        limits.max_alloc = Some(MAX_ALLOC);
+
+        let mut reader = ImageReader::with_format(Cursor::new(data), format);
+        reader.limits(limits);
+        let mut image = reader
+            .into_decoder()
+            .and_then(|mut decoder| {
+                let orientation = decoder.orientation()?;
+                let mut image = DynamicImage::from_decoder(decoder)?;
+                image.apply_orientation(orientation);
+                Ok(image)
+            })
+            .map_err(|err| DomainError::ValidationFailed {
+                field: "file".into(),
+                message: format!("Invalid image: {err}."),
+            })?;
+
+        image = image.resize_to_fill(width, height, FilterType::Lanczos3);
+        let image = image.to_rgba8();
+
+        let mut data = Vec::new();
+        WebPEncoder::new_lossless(&mut data)
+            .encode(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+            .catch()?;
+
+        Ok(ProcessedImageDto {
+            data,
+            media_type: ProcessedImageDto::WEBP,
+            width: image.width(),
+            height: image.height(),
+        })
+    }
+}
+
+impl Default for WebpImageProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ImageProcessor for WebpImageProcessor {
+    async fn process(
+        &self,
+        data: Vec<u8>,
+        kind: MediaKind,
+    ) -> Result<ProcessedImageDto> {
+        let (width, height) = kind.dimensions();
+
+        self.pool
+            .run(move || Self::process_sync(&data, width, height))
+            .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageBuffer, Rgb};
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = ImageBuffer::from_pixel(width, height, Rgb([200u8, 0, 0]));
+        let mut data = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[tokio::test]
+    async fn test_resize_to_webp() {
+        let image = WebpImageProcessor::new()
+            .process(png(900, 300), MediaKind::Avatar)
+            .await
+            .unwrap();
+        assert_eq!((image.width, image.height), (400, 400));
+        assert_eq!(
+            image::guess_format(&image.data).unwrap(),
+            ImageFormat::WebP
+        );
+
+        let decoded = image::load_from_memory(&image.data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (400, 400));
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_images() {
+        assert!(matches!(
+            WebpImageProcessor::new()
+                .process(b"<svg></svg>".to_vec(), MediaKind::Avatar)
+                .await,
+            Err(ApplicationError::UnsupportedMediaType)
+        ));
+
+        let mut truncated = png(64, 64);
+        truncated.truncate(48);
+        assert!(matches!(
+            WebpImageProcessor::new()
+                .process(truncated, MediaKind::Banner)
+                .await,
+            Err(ApplicationError::Domain(
+                DomainError::ValidationFailed { .. }
+            ))
+        ));
+    }
+}
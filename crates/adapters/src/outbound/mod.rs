@@ -1,8 +1,15 @@
 //! Outbound adapters.
 
+pub mod activitypub;
 pub mod clock;
 pub mod crypto;
+pub mod denylist;
+pub mod geo;
+pub mod kms;
 pub mod mail;
+pub mod media;
+pub mod password;
 pub mod persistence;
+pub mod rate_limit;
 pub mod telemetry;
 pub mod token;
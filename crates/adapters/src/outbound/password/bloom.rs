@@ -0,0 +1,158 @@
+//! Bloom filter of breached SHA-1 digests.
+//!
+//! The file is `AUTHABF1`, the number of hash functions as a big-endian
+//! `u32`, the number of bits as a big-endian `u64`, then the bits. Bit
+//! positions are derived from the digest itself, already uniform, by
+//! double hashing. Lookups read a few bytes of the file, which is never
+//! loaded in memory.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::PasswordViolation;
+
+use crate::outbound::password::BreachCorpus;
+
+const MAGIC: &[u8; 8] = b"AUTHABF1";
+const HEADER_LENGTH: u64 = 20;
+
+/// Bit positions of `sha1` among `bits`.
+fn positions(
+    sha1: &[u8; 20],
+    hashes: u32,
+    bits: u64,
+) -> impl Iterator<Item = u64> {
+    let h1 = u64::from_be_bytes(sha1[..8].try_into().unwrap_or_default());
+    let h2 =
+        u64::from_be_bytes(sha1[8..16].try_into().unwrap_or_default()) | 1;
+
+    (0..u64::from(hashes))
+        .map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bits)
+}
+
+/// Bloom filter read from a file.
+pub struct BloomFilter {
+    file: File,
+    hashes: u32,
+    bits: u64,
+}
+
+impl BloomFilter {
+    /// Open the filter written at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut file = File::open(path).catch()?;
+        let mut header = [0; HEADER_LENGTH as usize];
+        file.read_exact(&mut header).catch()?;
+
+        let hashes = u32::from_be_bytes(header[8..12].try_into().catch()?);
+        let bits = u64::from_be_bytes(header[12..20].try_into().catch()?);
+        let length = file.metadata().catch()?.len();
+        if &header[..8] != MAGIC ||
+            hashes == 0 ||
+            bits == 0 ||
+            length != HEADER_LENGTH + bits.div_ceil(8)
+        {
+            return Err(ApplicationError::Unknown);
+        }
+
+        Ok(Self { file, hashes, bits })
+    }
+}
+
+impl BreachCorpus for BloomFilter {
+    fn lookup(&self, sha1: &[u8; 20]) -> Result<Option<PasswordViolation>> {
+        for position in positions(sha1, self.hashes, self.bits) {
+            let mut byte = [0];
+            self.file
+                .read_exact_at(&mut byte, HEADER_LENGTH + position / 8)
+                .catch()?;
+            if byte[0] & (1 << (position % 8)) == 0 {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(PasswordViolation::Breached { count: None }))
+    }
+}
+
+/// Bloom filter built in memory.
+pub struct BloomFilterBuilder {
+    hashes: u32,
+    bits: u64,
+    data: Vec<u8>,
+}
+
+impl BloomFilterBuilder {
+    /// Create a new [`BloomFilterBuilder`] sized for `capacity` digests
+    /// with a false positive rate of `rate`.
+    pub fn new(capacity: u64, rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(capacity.max(1) as f64) * rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(8.0) as u64;
+        let hashes = ((bits as f64 / capacity.max(1) as f64) * ln2)
+            .round()
+            .max(1.0);
+
+        Self {
+            hashes: hashes as u32,
+            bits,
+            data: vec![0; bits.div_ceil(8) as usize],
+        }
+    }
+
+    /// Add `sha1` to the filter.
+    pub fn insert(&mut self, sha1: &[u8; 20]) {
+        for position in positions(sha1, self.hashes, self.bits) {
+            self.data[(position / 8) as usize] |= 1 << (position % 8);
+        }
+    }
+
+    /// Write the filter to `writer`.
+    pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.hashes.to_be_bytes())?;
+        writer.write_all(&self.bits.to_be_bytes())?;
+        writer.write_all(&self.data)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha1::{Digest, Sha1};
+
+    use super::*;
+
+    fn sha1(i: u32) -> [u8; 20] {
+        Sha1::digest(i.to_be_bytes()).into()
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut builder = BloomFilterBuilder::new(1_000, 0.01);
+        for i in 0..1_000 {
+            builder.insert(&sha1(i));
+        }
+
+        let path = std::env::temp_dir()
+            .join(format!("autha-bloom-{}.bin", std::process::id()));
+        builder.write_to(File::create(&path).unwrap()).unwrap();
+        let filter = BloomFilter::open(&path).unwrap();
+
+        for i in 0..1_000 {
+            assert!(filter.lookup(&sha1(i)).unwrap().is_some());
+        }
+        let false_positives = (1_000..11_000)
+            .filter(|i| filter.lookup(&sha1(*i)).unwrap().is_some())
+            .count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+
+        std::fs::write(&path, b"AUTHABF1").unwrap();
+        assert!(BloomFilter::open(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
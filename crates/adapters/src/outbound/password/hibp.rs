@@ -0,0 +1,96 @@
+//! Range files of Have I Been Pwned, as written by its downloader.
+//!
+//! A directory holds one file per 5 hexadecimal characters prefix of
+//! SHA-1 digests, named `<PREFIX>.txt` or `<PREFIX>`, whose lines are
+//! `<SUFFIX>:<COUNT>` with the 35 remaining characters of each digest.
+//! Only the prefix of a password is used to pick the file to read, as in
+//! the k-anonymity API.
+
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use application::error::{Result, ToInternal};
+use application::ports::outbound::PasswordViolation;
+
+use crate::outbound::password::BreachCorpus;
+
+/// Characters of a digest naming its range file.
+const PREFIX_LENGTH: usize = 5;
+
+/// Directory of range files.
+pub struct HibpRanges {
+    directory: PathBuf,
+}
+
+impl HibpRanges {
+    /// Create a new [`HibpRanges`] reading files in `directory`.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Read the range file of `prefix`, if any.
+    fn range(&self, prefix: &str) -> Result<Option<String>> {
+        for name in [format!("{prefix}.txt"), prefix.to_string()] {
+            match std::fs::read_to_string(self.directory.join(name)) {
+                Ok(range) => return Ok(Some(range)),
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).catch(),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl BreachCorpus for HibpRanges {
+    fn lookup(&self, sha1: &[u8; 20]) -> Result<Option<PasswordViolation>> {
+        let digest = hex::encode_upper(sha1);
+        let (prefix, suffix) = digest.split_at(PREFIX_LENGTH);
+        let Some(range) = self.range(prefix)? else {
+            return Ok(None);
+        };
+
+        Ok(range
+            .lines()
+            .filter_map(|line| line.trim_end().split_once(':'))
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+            .map(|(_, count)| PasswordViolation::Breached {
+                count: count.parse().ok(),
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha1::{Digest, Sha1};
+
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let directory = std::env::temp_dir()
+            .join(format!("autha-hibp-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        // SHA-1 of "password" is 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8.
+        std::fs::write(
+            directory.join("5BAA6.txt"),
+            "003D68EB55068C33ACE09247EE4C639306B:3\r\n\
+             1E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\r\n",
+        )
+        .unwrap();
+
+        let ranges = HibpRanges::new(&directory);
+        let sha1 = |password: &[u8]| Sha1::digest(password).into();
+        assert_eq!(
+            ranges.lookup(&sha1(b"password")).unwrap(),
+            Some(PasswordViolation::Breached {
+                count: Some(10_434_004)
+            })
+        );
+        assert_eq!(ranges.lookup(&sha1(b"passwore")).unwrap(), None);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
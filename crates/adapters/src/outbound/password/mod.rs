@@ -0,0 +1,163 @@
+//! Password policy checked without calling any remote service.
+//!
+//! Strength is estimated on the 0 to 4 scale of zxcvbn, and breached
+//! passwords are looked up by SHA-1 digest in a local corpus, either the
+//! range files of Have I Been Pwned or a Bloom filter built from them.
+
+pub mod bloom;
+pub mod hibp;
+
+use std::sync::Arc;
+
+use application::error::{Result, ToInternal};
+use application::ports::outbound::{PasswordPolicy, PasswordViolation};
+use async_trait::async_trait;
+pub use bloom::{BloomFilter, BloomFilterBuilder};
+use domain::auth::password::Password;
+pub use hibp::HibpRanges;
+use sha1::{Digest, Sha1};
+
+/// User inputs shorter than this are too common to be refused.
+const MIN_USER_INPUT_LENGTH: usize = 3;
+
+/// Set of breached passwords.
+pub trait BreachCorpus: Send + Sync {
+    /// Returns a [`PasswordViolation::Breached`] if the password whose
+    /// SHA-1 digest is `sha1` is in the corpus.
+    fn lookup(&self, sha1: &[u8; 20]) -> Result<Option<PasswordViolation>>;
+}
+
+/// Password policy using local data only.
+pub struct LocalPasswordPolicy {
+    min_score: u8,
+    corpus: Option<Arc<dyn BreachCorpus>>,
+}
+
+impl LocalPasswordPolicy {
+    /// Create a new [`LocalPasswordPolicy`] requiring a strength of at
+    /// least `min_score`, from 0, which accepts any password, to 4.
+    pub fn new(min_score: u8) -> Self {
+        Self {
+            min_score: min_score.min(4),
+            corpus: None,
+        }
+    }
+
+    /// Refuse passwords found in `corpus`.
+    pub fn with_corpus(mut self, corpus: Arc<dyn BreachCorpus>) -> Self {
+        self.corpus = Some(corpus);
+        self
+    }
+}
+
+/// Strength of `password`, from 0 to 4, as estimated by zxcvbn.
+///
+/// `user_inputs` are added to its dictionaries, so that a password built
+/// on them is guessed early.
+fn score(password: &str, user_inputs: &[&str]) -> u8 {
+    zxcvbn::zxcvbn(password, user_inputs).score().into()
+}
+
+/// Whether `password` contains any of `user_inputs`, ignoring case.
+fn contains_user_input(password: &str, user_inputs: &[&str]) -> bool {
+    let password = password.to_lowercase();
+    user_inputs
+        .iter()
+        .filter(|input| input.chars().count() >= MIN_USER_INPUT_LENGTH)
+        .any(|input| password.contains(&input.to_lowercase()))
+}
+
+#[async_trait]
+impl PasswordPolicy for LocalPasswordPolicy {
+    async fn check(
+        &self,
+        password: &Password,
+        user_inputs: &[&str],
+    ) -> Result<Vec<PasswordViolation>> {
+        let mut violations = Vec::new();
+
+        let score = score(password.as_str(), user_inputs);
+        if score < self.min_score {
+            violations.push(PasswordViolation::TooWeak {
+                score,
+                required: self.min_score,
+            });
+        }
+        if contains_user_input(password.as_str(), user_inputs) {
+            violations.push(PasswordViolation::ContainsUserInput);
+        }
+
+        if let Some(corpus) = &self.corpus {
+            let sha1: [u8; 20] = Sha1::digest(password.as_bytes()).into();
+            let corpus = Arc::clone(corpus);
+            let breached =
+                tokio::task::spawn_blocking(move || corpus.lookup(&sha1))
+                    .await
+                    .catch()??;
+            violations.extend(breached);
+        }
+
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Corpus;
+
+    impl BreachCorpus for Corpus {
+        fn lookup(
+            &self,
+            sha1: &[u8; 20],
+        ) -> Result<Option<PasswordViolation>> {
+            let breached: [u8; 20] =
+                Sha1::digest(b"correct horse battery staple").into();
+            Ok((*sha1 == breached)
+                .then_some(PasswordViolation::Breached { count: Some(3) }))
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("password", &[]), 0);
+        assert!(score("vorthanquil-8213", &[]) >= 3);
+        assert!(score("vorthanquil-8213", &["vorthanquil", "8213"]) < 3);
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let policy = LocalPasswordPolicy::new(3).with_corpus(Arc::new(Corpus));
+        let check = async |password: &str| {
+            policy
+                .check(&Password::new(password).unwrap(), &["alice", "al"])
+                .await
+                .unwrap()
+        };
+
+        assert!(check("Tr0ub4dor&3-Kettle!").await.is_empty());
+        assert!(matches!(
+            check("password").await[..],
+            [PasswordViolation::TooWeak { required: 3, .. }]
+        ));
+        assert_eq!(
+            check("ALICE-Tr0ub4dor&3!").await,
+            [PasswordViolation::ContainsUserInput]
+        );
+        assert!(
+            check("correct horse battery staple")
+                .await
+                .contains(&PasswordViolation::Breached { count: Some(3) })
+        );
+
+        // Level 0 accepts weak passwords.
+        assert!(
+            LocalPasswordPolicy::new(0)
+                .check(&Password::new("password").unwrap(), &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,15 +1,16 @@
 //! PostgreSQL implementation for account repository.
 
-use application::dto::AccountDto;
+use application::dto::{AccountDto, AccountSearchDto, MediaKind};
 use application::error::{ApplicationError, Result, ToInternal};
 use application::ports::outbound::AccountRepository;
 use async_trait::async_trait;
 use chrono::Utc;
 use domain::auth::email::EmailHash;
+use domain::auth::password::PasswordHash;
 use domain::error::DomainError;
 use domain::identity::id::UserId;
-use sqlx::PgPool;
 use sqlx::postgres::PgQueryResult;
+use sqlx::{PgExecutor, PgPool, Postgres};
 
 use super::models::UserRecord;
 
@@ -24,10 +25,16 @@ const USER_SELECT_BASE: &str = r#"
         u.locale,
         u.summary,
         u.avatar,
+        u.banner,
         u.flags,
         u.password,
         u.created_at,
         u.deleted_at,
+        u.token_generation,
+        u.suspended_at,
+        u.suspended_until,
+        u.suspension_reason,
+        u.password_reset_required,
         COALESCE(
             jsonb_agg(
                 jsonb_build_object(
@@ -55,11 +62,14 @@ impl PgAccountRepository {
     }
 
     /// Helper to execute the user query with a custom filter.
-    async fn find_one_by_filter(
+    async fn find_one_by_filter<T>(
         &self,
         filter_sql: &str,
-        bind_val: &str,
-    ) -> Result<Option<AccountDto>> {
+        bind_val: T,
+    ) -> Result<Option<AccountDto>>
+    where
+        T: for<'q> sqlx::Encode<'q, Postgres> + sqlx::Type<Postgres> + Send,
+    {
         let query_sql = format!(
             "{} WHERE {} AND u.deleted_at IS NULL GROUP BY u.id",
             USER_SELECT_BASE, filter_sql
@@ -84,69 +94,21 @@ impl AccountRepository for PgAccountRepository {
         self.find_one_by_filter("u.id = $1", id.as_str()).await
     }
 
-    async fn find_by_email_hash(
+    async fn find_by_email_hashes(
         &self,
-        email_hash: &EmailHash,
+        email_hashes: &[EmailHash],
     ) -> Result<Option<AccountDto>> {
-        self.find_one_by_filter("u.email_hash = $1", email_hash.as_str())
+        let email_hashes = email_hashes
+            .iter()
+            .map(|hash| hash.as_str())
+            .collect::<Vec<_>>();
+
+        self.find_one_by_filter("u.email_hash = ANY($1)", email_hashes)
             .await
     }
 
     async fn create(&self, account: &AccountDto) -> Result<()> {
-        let record = UserRecord::from(account);
-
-        let result = sqlx::query(
-            r#"
-            INSERT INTO users (
-                id, username, email_hash, email_cipher, totp_secret,
-                locale, summary, avatar, flags, password,
-                created_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            "#,
-        )
-        .bind(&record.id)
-        .bind(&record.username)
-        .bind(&record.email_hash)
-        .bind(&record.email_cipher)
-        .bind(&record.totp_secret)
-        .bind(&record.locale)
-        .bind(&record.summary)
-        .bind(&record.avatar)
-        .bind(record.flags)
-        .bind(&record.password)
-        .bind(record.created_at)
-        .execute(&self.pool)
-        .await;
-
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                if let Some(db_err) = e.as_database_error() &&
-                    db_err.code() == Some("23505".into())
-                {
-                    let constraint = db_err.constraint().unwrap_or("");
-
-                    if constraint.contains("pkey") || constraint.contains("id")
-                    {
-                        return Err(DomainError::ValidationFailed {
-                            field: "id".to_string(),
-                            message: "ID is already in use.".to_string(),
-                        }
-                        .into());
-                    }
-
-                    if constraint.contains("email") {
-                        return Err(DomainError::ValidationFailed {
-                            field: "email".to_string(),
-                            message: "Email is already in use.".to_string(),
-                        }
-                        .into());
-                    }
-                }
-                Err(ApplicationError::Internal(e.into()))
-            },
-        }
+        insert_user(&self.pool, account).await
     }
 
     async fn update(&self, account: &AccountDto) -> Result<()> {
@@ -164,7 +126,9 @@ impl AccountRepository for PgAccountRepository {
                 summary = $7,
                 avatar = $8,
                 flags = $9,
-                password = $10
+                password = $10,
+                password_reset_required = $11,
+                banner = $12
             WHERE id = $1 AND deleted_at IS NULL
             "#,
         )
@@ -178,6 +142,8 @@ impl AccountRepository for PgAccountRepository {
         .bind(&record.avatar)
         .bind(record.flags)
         .bind(&record.password)
+        .bind(record.password_reset_required)
+        .bind(&record.banner)
         .execute(&self.pool)
         .await
         .catch()?;
@@ -189,6 +155,108 @@ impl AccountRepository for PgAccountRepository {
         Ok(())
     }
 
+    async fn update_email_hash(
+        &self,
+        id: &UserId,
+        previous: &EmailHash,
+        email_hash: &EmailHash,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET email_hash = $3
+            WHERE id = $1 AND email_hash = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(previous.as_str())
+        .bind(email_hash.as_str())
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn update_password_hash(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET password = $3
+            WHERE id = $1 AND password = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(previous.as_str())
+        .bind(password_hash.as_str())
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn complete_password_reset(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET password = $3, password_reset_required = FALSE
+            WHERE id = $1
+              AND password = $2
+              AND password_reset_required
+              AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(previous.as_str())
+        .bind(password_hash.as_str())
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn replace_media(
+        &self,
+        id: &UserId,
+        kind: MediaKind,
+        url: Option<&str>,
+    ) -> Result<Option<String>> {
+        // Columns are named after media kinds.
+        let column = kind.as_str();
+        sqlx::query_scalar::<_, Option<String>>(&format!(
+            r#"
+            UPDATE users u
+            SET {column} = $2
+            FROM (
+                SELECT id, {column}
+                FROM users
+                WHERE id = $1 AND deleted_at IS NULL
+                FOR UPDATE
+            ) previous
+            WHERE u.id = previous.id
+            RETURNING previous.{column}
+            "#
+        ))
+        .bind(id.as_str())
+        .bind(url)
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?
+        .ok_or(ApplicationError::UserNotFound)
+    }
+
     async fn delete(&self, id: &UserId) -> Result<()> {
         let deletion_date = Utc::now() + chrono::Duration::days(30);
 
@@ -211,4 +279,173 @@ impl AccountRepository for PgAccountRepository {
 
         Ok(())
     }
+
+    async fn import(
+        &self,
+        accounts: &[AccountDto],
+        dry_run: bool,
+    ) -> Result<Vec<UserId>> {
+        let records =
+            accounts.iter().map(UserRecord::from).collect::<Vec<_>>();
+        let column = |field: fn(&UserRecord) -> &String| {
+            records.iter().map(field).collect::<Vec<_>>()
+        };
+
+        let mut transaction = self.pool.begin().await.catch()?;
+        let imported: Vec<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO users (
+                id, username, email_hash, email_cipher, locale, flags,
+                password, created_at
+            )
+            SELECT * FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::text[],
+                $6::int[], $7::text[], $8::timestamptz[]
+            )
+            ON CONFLICT DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(column(|record| &record.id))
+        .bind(column(|record| &record.username))
+        .bind(column(|record| &record.email_hash))
+        .bind(column(|record| &record.email_cipher))
+        .bind(column(|record| &record.locale))
+        .bind(
+            records
+                .iter()
+                .map(|record| record.flags)
+                .collect::<Vec<_>>(),
+        )
+        .bind(column(|record| &record.password))
+        .bind(
+            records
+                .iter()
+                .map(|record| record.created_at)
+                .collect::<Vec<_>>(),
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .catch()?;
+
+        if dry_run {
+            transaction.rollback().await.catch()?;
+        } else {
+            transaction.commit().await.catch()?;
+        }
+
+        imported
+            .into_iter()
+            .map(|id| UserId::parse(id).catch())
+            .collect()
+    }
+
+    async fn search(
+        &self,
+        query: &AccountSearchDto,
+    ) -> Result<Vec<AccountDto>> {
+        let query_sql = format!(
+            r#"{USER_SELECT_BASE}
+            WHERE ($1::text IS NULL OR u.id LIKE $1 ESCAPE '\')
+                AND (cardinality($2::text[]) = 0 OR u.email_hash = ANY($2))
+                AND ($3::text IS NULL OR u.id > $3)
+            GROUP BY u.id
+            ORDER BY u.id
+            LIMIT $4"#
+        );
+
+        sqlx::query_as::<_, UserRecord>(&query_sql)
+            .bind(query.id_prefix.as_deref().map(like_prefix))
+            .bind(
+                query
+                    .email_hashes
+                    .iter()
+                    .map(EmailHash::as_str)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(query.after.as_ref().map(UserId::as_str))
+            .bind(i64::from(query.limit))
+            .fetch_all(&self.pool)
+            .await
+            .catch()?
+            .into_iter()
+            .map(|record| record.try_into_dto().catch())
+            .collect()
+    }
+}
+
+/// Build a `LIKE` pattern matching values starting with `prefix`.
+fn like_prefix(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Insert a new user, mapping unique violations to validation errors.
+pub(super) async fn insert_user<'e, E>(
+    executor: E,
+    account: &AccountDto,
+) -> Result<()>
+where
+    E: PgExecutor<'e>,
+{
+    let record = UserRecord::from(account);
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO users (
+            id, username, email_hash, email_cipher, totp_secret,
+            locale, summary, avatar, flags, password,
+            created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#,
+    )
+    .bind(&record.id)
+    .bind(&record.username)
+    .bind(&record.email_hash)
+    .bind(&record.email_cipher)
+    .bind(&record.totp_secret)
+    .bind(&record.locale)
+    .bind(&record.summary)
+    .bind(&record.avatar)
+    .bind(record.flags)
+    .bind(&record.password)
+    .bind(record.created_at)
+    .execute(executor)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            if let Some(db_err) = e.as_database_error() &&
+                db_err.code() == Some("23505".into())
+            {
+                let constraint = db_err.constraint().unwrap_or("");
+
+                if constraint.contains("pkey") || constraint.contains("id") {
+                    return Err(DomainError::ValidationFailed {
+                        field: "id".to_string(),
+                        message: "ID is already in use.".to_string(),
+                    }
+                    .into());
+                }
+
+                if constraint.contains("email") {
+                    return Err(DomainError::ValidationFailed {
+                        field: "email".to_string(),
+                        message: "Email is already in use.".to_string(),
+                    }
+                    .into());
+                }
+            }
+            Err(ApplicationError::Internal(e.into()))
+        },
+    }
 }
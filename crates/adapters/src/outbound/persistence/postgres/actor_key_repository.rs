@@ -0,0 +1,85 @@
+//! PostgreSQL implementation of ActorKeyRepository.
+
+use application::dto::ActorKeyDto;
+use application::error::{Result, ToInternal};
+use application::ports::outbound::ActorKeyRepository;
+use async_trait::async_trait;
+use chrono::DateTime;
+use domain::identity::id::UserId;
+use sqlx::PgPool;
+
+use super::models::ActorKeyRecord;
+
+/// PostgreSQL actor key repository.
+pub struct PgActorKeyRepository {
+    pool: PgPool,
+}
+
+impl PgActorKeyRepository {
+    /// Create a new [`PgActorKeyRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ActorKeyRepository for PgActorKeyRepository {
+    async fn find(&self, user_id: &UserId) -> Result<Option<ActorKeyDto>> {
+        sqlx::query_as::<_, ActorKeyRecord>(
+            r#"
+            SELECT user_id, public_key_pem, private_key, created_at
+            FROM actor_keys
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?
+        .map(ActorKeyRecord::try_into_dto)
+        .transpose()
+    }
+
+    async fn create(&self, key: &ActorKeyDto) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO actor_keys (
+                user_id, public_key_pem, private_key, created_at
+            )
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(key.user_id.as_str())
+        .bind(&key.public_key_pem)
+        .bind(&key.private_key_cipher)
+        .bind(DateTime::from_timestamp(key.created_at as i64, 0))
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn list_missing(&self, limit: u32) -> Result<Vec<UserId>> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT u.id
+            FROM users u
+            WHERE u.deleted_at IS NULL
+              AND NOT EXISTS (
+                SELECT 1 FROM actor_keys k WHERE k.user_id = u.id
+              )
+            ORDER BY u.id
+            LIMIT $1
+            "#,
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .catch()?
+        .into_iter()
+        .map(|id| UserId::parse(id).catch())
+        .collect()
+    }
+}
@@ -0,0 +1,421 @@
+//! PostgreSQL implementation of AuditLog.
+//!
+//! Each row stores the hash of the previous one, so that altering or
+//! removing a row breaks the chain. Hashes are HMACs keyed with a secret
+//! kept outside the database: whoever can write to the table still cannot
+//! recompute the chain after altering a row.
+//!
+//! Appends are serialized with a single advisory lock, held for the
+//! transaction, so that each event links to the last one. Every login and
+//! failed login appends an event: across all instances, authentication
+//! throughput is bound by how fast PostgreSQL commits these transactions.
+
+use application::dto::SecurityEventDto;
+use application::error::{Result, ToInternal};
+use application::ports::outbound::AuditLog;
+use async_trait::async_trait;
+use chrono::DateTime;
+use domain::identity::id::UserId;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use zeroize::Zeroizing;
+
+use crate::outbound::persistence::postgres::models::{
+    ChainedEventRecord, SecurityEventRecord,
+};
+
+/// Advisory lock key held while appending an event.
+const APPEND_LOCK: i64 = 0x6175_6469_745f_6c6f;
+/// Previous hash of the first event.
+const GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+/// Events read at once while verifying the chain.
+const VERIFY_BATCH: i64 = 1_000;
+
+/// Columns of an event covered by its hash.
+#[derive(Debug, Clone, Copy)]
+pub struct HashedEvent<'a> {
+    pub user_id: Option<&'a str>,
+    pub kind: &'a str,
+    pub ip_address: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub details: Option<&'a str>,
+    /// Unix timestamp in seconds.
+    pub created_at: i64,
+}
+
+impl HashedEvent<'_> {
+    /// HMAC-SHA256 of `prev_hash` and every column, keyed with `key` and
+    /// hex-encoded.
+    ///
+    /// Fields are length-prefixed, in characters, so that no two events
+    /// serialize to the same input.
+    pub fn hash(&self, key: &[u8], prev_hash: &str) -> String {
+        let field = |value: Option<&str>| match value {
+            Some(value) => format!("{}:{value}", value.chars().count()),
+            None => "-".to_string(),
+        };
+        let input = [
+            prev_hash.to_string(),
+            field(self.user_id),
+            field(Some(self.kind)),
+            field(self.ip_address),
+            field(self.user_agent),
+            field(self.details),
+            self.created_at.to_string(),
+        ]
+        .join("|");
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(input.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl<'a> From<&'a ChainedEventRecord> for HashedEvent<'a> {
+    fn from(record: &'a ChainedEventRecord) -> Self {
+        Self {
+            user_id: record.user_id.as_deref(),
+            kind: &record.kind,
+            ip_address: record.ip_address.as_deref(),
+            user_agent: record.user_agent.as_deref(),
+            details: record.details.as_deref(),
+            created_at: record.created_at.timestamp(),
+        }
+    }
+}
+
+/// Walks the audit log in ID order, checking that each event links to the
+/// previous one and matches its hash.
+#[derive(Clone)]
+pub struct ChainVerifier {
+    key: Zeroizing<Vec<u8>>,
+    head: String,
+    count: u64,
+}
+
+impl std::fmt::Debug for ChainVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChainVerifier")
+            .field("head", &self.head)
+            .field("count", &self.count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChainVerifier {
+    /// Create a [`ChainVerifier`] checking hashes keyed with `key`.
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: Zeroizing::new(key.to_vec()),
+            head: GENESIS_HASH.to_string(),
+            count: 0,
+        }
+    }
+
+    /// Check the next event, returning `false` if the chain is broken.
+    pub fn push(&mut self, event: &ChainedEventRecord) -> bool {
+        if event.prev_hash != self.head ||
+            event.hash !=
+                HashedEvent::from(event)
+                    .hash(&self.key, &event.prev_hash)
+        {
+            return false;
+        }
+
+        self.head.clone_from(&event.hash);
+        self.count += 1;
+        true
+    }
+
+    /// Hash of the last verified event.
+    ///
+    /// Removed trailing events are only detected by comparing it with a
+    /// copy kept elsewhere.
+    pub fn head(&self) -> Option<&str> {
+        (self.count > 0).then_some(self.head.as_str())
+    }
+
+    /// Number of verified events.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Outcome of [`PgAuditLog::verify`].
+#[derive(Debug, Clone)]
+pub struct ChainReport {
+    /// Events verified before the chain broke, if it did.
+    pub verifier: ChainVerifier,
+    /// ID of the first event breaking the chain.
+    pub broken_at: Option<i64>,
+}
+
+/// PostgreSQL hash-chained audit log.
+pub struct PgAuditLog {
+    pool: PgPool,
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl PgAuditLog {
+    /// Create a new [`PgAuditLog`] hashing events with `key`.
+    pub fn new(pool: PgPool, key: &[u8]) -> Self {
+        Self {
+            pool,
+            key: Zeroizing::new(key.to_vec()),
+        }
+    }
+
+    /// Recompute the whole chain, from the first event.
+    pub async fn verify(&self) -> Result<ChainReport> {
+        let mut verifier = ChainVerifier::new(&self.key);
+        let mut after = 0;
+
+        loop {
+            let events = sqlx::query_as::<_, ChainedEventRecord>(
+                r#"
+                SELECT id, user_id, kind, ip_address, user_agent, details,
+                    created_at, prev_hash, hash
+                FROM security_events
+                WHERE id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(VERIFY_BATCH)
+            .fetch_all(&self.pool)
+            .await
+            .catch()?;
+
+            for event in &events {
+                if !verifier.push(event) {
+                    return Ok(ChainReport {
+                        verifier,
+                        broken_at: Some(event.id),
+                    });
+                }
+            }
+
+            match events.last() {
+                Some(last) => after = last.id,
+                None => {
+                    return Ok(ChainReport {
+                        verifier,
+                        broken_at: None,
+                    });
+                },
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AuditLog for PgAuditLog {
+    async fn append(&self, event: &SecurityEventDto) -> Result<()> {
+        let mut tx = self.pool.begin().await.catch()?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(APPEND_LOCK)
+            .execute(&mut *tx)
+            .await
+            .catch()?;
+
+        let prev_hash = sqlx::query_scalar::<_, String>(
+            "SELECT hash FROM security_events ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .catch()?
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+        let created_at = event.created_at as i64;
+        let hash = HashedEvent {
+            user_id: event.user_id.as_ref().map(UserId::as_str),
+            kind: event.kind.as_str(),
+            ip_address: event.ip_address.as_ref().map(|ip| ip.as_str()),
+            user_agent: event.user_agent.as_deref(),
+            details: event.details.as_deref(),
+            created_at,
+        }
+        .hash(&self.key, &prev_hash);
+
+        sqlx::query(
+            r#"
+            INSERT INTO security_events (
+                user_id, kind, ip_address, user_agent, details, created_at,
+                prev_hash, hash
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(event.user_id.as_ref().map(UserId::as_str))
+        .bind(event.kind.as_str())
+        .bind(event.ip_address.as_ref().map(|ip| ip.as_str()))
+        .bind(event.user_agent.as_deref())
+        .bind(event.details.as_deref())
+        .bind(DateTime::from_timestamp(created_at, 0))
+        .bind(&prev_hash)
+        .bind(&hash)
+        .execute(&mut *tx)
+        .await
+        .catch()?;
+
+        tx.commit().await.catch()?;
+
+        Ok(())
+    }
+
+    async fn list_for_user(
+        &self,
+        user_id: &UserId,
+        limit: u32,
+    ) -> Result<Vec<SecurityEventDto>> {
+        let records = sqlx::query_as::<_, SecurityEventRecord>(
+            r#"
+            SELECT user_id, kind, ip_address, user_agent, details, created_at
+            FROM security_events
+            WHERE user_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(user_id.as_str())
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .catch()?;
+
+        records
+            .into_iter()
+            .map(SecurityEventRecord::try_into_dto)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"audit log key";
+
+    /// Build a chain of `count` events, as appended.
+    fn chain(count: i64) -> Vec<ChainedEventRecord> {
+        let mut prev_hash = GENESIS_HASH.to_string();
+        (1..=count)
+            .map(|id| {
+                let mut event = ChainedEventRecord {
+                    id,
+                    user_id: Some("alice".into()),
+                    kind: "login".into(),
+                    ip_address: None,
+                    user_agent: Some("é|ua".into()),
+                    details: (id % 2 == 0).then(|| format!("event {id}")),
+                    created_at: DateTime::from_timestamp(1_000 + id, 0)
+                        .unwrap(),
+                    prev_hash: prev_hash.clone(),
+                    hash: String::new(),
+                };
+                event.hash = HashedEvent::from(&event).hash(KEY, &prev_hash);
+                prev_hash.clone_from(&event.hash);
+                event
+            })
+            .collect()
+    }
+
+    fn hmac_hex(input: String) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(KEY).unwrap();
+        mac.update(input.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// ID of the first event breaking the chain.
+    fn broken_at(events: &[ChainedEventRecord]) -> Option<i64> {
+        let mut verifier = ChainVerifier::new(KEY);
+        events
+            .iter()
+            .find(|event| !verifier.push(event))
+            .map(|event| event.id)
+    }
+
+    #[test]
+    fn test_hash() {
+        let event = HashedEvent {
+            user_id: Some("alice"),
+            kind: "login",
+            ip_address: None,
+            user_agent: Some("é"),
+            details: None,
+            created_at: 1_700_000_000,
+        };
+        let hash = event.hash(KEY, GENESIS_HASH);
+        assert_eq!(
+            hash,
+            hmac_hex(format!(
+                "{GENESIS_HASH}|5:alice|5:login|-|1:é|-|1700000000"
+            ))
+        );
+        // Without the key, the hash cannot be recomputed.
+        assert_ne!(event.hash(b"other key", GENESIS_HASH), hash);
+        // Moving a separator between fields changes the hash.
+        let shifted = HashedEvent {
+            user_id: Some("alice|5:login"),
+            kind: "",
+            ..event
+        };
+        assert_ne!(shifted.hash(KEY, GENESIS_HASH), hash);
+    }
+
+    #[test]
+    fn test_valid_chain() {
+        let events = chain(5);
+        let mut verifier = ChainVerifier::new(KEY);
+        assert_eq!(verifier.head(), None);
+
+        assert!(events.iter().all(|event| verifier.push(event)));
+        assert_eq!(verifier.count(), 5);
+        assert_eq!(verifier.head(), Some(events[4].hash.as_str()));
+    }
+
+    #[test]
+    fn test_tampered_event() {
+        let mut events = chain(5);
+        events[2].details = Some("tampered".into());
+        assert_eq!(broken_at(&events), Some(3));
+
+        // Rehashing the row still breaks the link with the next one.
+        events[2].hash =
+            HashedEvent::from(&events[2]).hash(KEY, &events[2].prev_hash);
+        assert_eq!(broken_at(&events), Some(4));
+
+        // Rehashing every following row needs the key.
+        let mut prev_hash = events[1].hash.clone();
+        for event in &mut events[2..] {
+            event.prev_hash.clone_from(&prev_hash);
+            event.hash =
+                HashedEvent::from(&*event).hash(b"other key", &prev_hash);
+            prev_hash.clone_from(&event.hash);
+        }
+        assert_eq!(broken_at(&events), Some(3));
+    }
+
+    #[test]
+    fn test_removed_event() {
+        let mut events = chain(5);
+        events.remove(2);
+        assert_eq!(broken_at(&events), Some(4));
+
+        let mut events = chain(5);
+        events.remove(0);
+        assert_eq!(broken_at(&events), Some(2));
+    }
+
+    #[test]
+    fn test_reordered_events() {
+        let mut events = chain(5);
+        events.swap(1, 3);
+        assert_eq!(broken_at(&events), Some(4));
+    }
+}
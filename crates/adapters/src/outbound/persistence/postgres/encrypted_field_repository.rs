@@ -0,0 +1,135 @@
+//! PostgreSQL implementation of EncryptedFieldRepository.
+
+use application::dto::EncryptedFieldsDto;
+use application::error::{Result, ToInternal};
+use application::ports::outbound::EncryptedFieldRepository;
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+use sqlx::PgPool;
+
+use super::models::EncryptedFieldsRecord;
+
+/// PostgreSQL access to the encrypted columns of users.
+pub struct PgEncryptedFieldRepository {
+    pool: PgPool,
+}
+
+impl PgEncryptedFieldRepository {
+    /// Create a new [`PgEncryptedFieldRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EncryptedFieldRepository for PgEncryptedFieldRepository {
+    async fn list(
+        &self,
+        after: Option<&UserId>,
+        limit: u32,
+    ) -> Result<Vec<EncryptedFieldsDto>> {
+        sqlx::query_as::<_, EncryptedFieldsRecord>(
+            r#"
+            SELECT u.id, u.email_cipher, u.totp_secret,
+                k.private_key AS actor_key,
+                ARRAY(
+                    SELECT ip FROM sessions
+                    WHERE user_id = u.id AND ip IS NOT NULL
+                    UNION
+                    SELECT ip FROM tokens
+                    WHERE user_id = u.id AND ip IS NOT NULL
+                ) AS session_ips
+            FROM users u
+            LEFT JOIN actor_keys k ON k.user_id = u.id
+            WHERE $1::TEXT IS NULL OR u.id > $1
+            ORDER BY u.id
+            LIMIT $2
+            "#,
+        )
+        .bind(after.map(UserId::as_str))
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .catch()?
+        .into_iter()
+        .map(EncryptedFieldsRecord::try_into_dto)
+        .collect()
+    }
+
+    async fn replace(
+        &self,
+        previous: &EncryptedFieldsDto,
+        current: &EncryptedFieldsDto,
+    ) -> Result<bool> {
+        let user_id = previous.user_id.as_str();
+        let mut replaced = 0;
+        let mut tx = self.pool.begin().await.catch()?;
+
+        if previous.email_cipher != current.email_cipher ||
+            previous.totp_secret != current.totp_secret
+        {
+            replaced += sqlx::query(
+                r#"
+                UPDATE users
+                SET email_cipher = $4, totp_secret = $5
+                WHERE id = $1
+                  AND email_cipher = $2
+                  AND totp_secret IS NOT DISTINCT FROM $3
+                "#,
+            )
+            .bind(user_id)
+            .bind(&previous.email_cipher)
+            .bind(&previous.totp_secret)
+            .bind(&current.email_cipher)
+            .bind(&current.totp_secret)
+            .execute(&mut *tx)
+            .await
+            .catch()?
+            .rows_affected();
+        }
+
+        if let (Some(previous), Some(current)) =
+            (&previous.actor_key, &current.actor_key) &&
+            previous != current
+        {
+            replaced += sqlx::query(
+                r#"
+                UPDATE actor_keys SET private_key = $3
+                WHERE user_id = $1 AND private_key = $2
+                "#,
+            )
+            .bind(user_id)
+            .bind(previous)
+            .bind(current)
+            .execute(&mut *tx)
+            .await
+            .catch()?
+            .rows_affected();
+        }
+
+        let session_ips = previous
+            .session_ips
+            .iter()
+            .zip(&current.session_ips)
+            .filter(|(previous, current)| previous != current);
+        for (previous, current) in session_ips {
+            for query in [
+                "UPDATE sessions SET ip = $3 WHERE user_id = $1 AND ip = $2",
+                "UPDATE tokens SET ip = $3 WHERE user_id = $1 AND ip = $2",
+            ] {
+                replaced += sqlx::query(query)
+                    .bind(user_id)
+                    .bind(previous)
+                    .bind(current)
+                    .execute(&mut *tx)
+                    .await
+                    .catch()?
+                    .rows_affected();
+            }
+        }
+
+        tx.commit().await.catch()?;
+
+        Ok(replaced > 0)
+    }
+}
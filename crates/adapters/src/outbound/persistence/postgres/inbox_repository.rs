@@ -0,0 +1,47 @@
+//! PostgreSQL implementation of InboxRepository.
+
+use application::dto::InboxActivityDto;
+use application::error::{Result, ToInternal};
+use application::ports::outbound::InboxRepository;
+use async_trait::async_trait;
+use chrono::DateTime;
+use sqlx::PgPool;
+use sqlx::types::Json;
+
+/// PostgreSQL inbox repository.
+pub struct PgInboxRepository {
+    pool: PgPool,
+}
+
+impl PgInboxRepository {
+    /// Create a new [`PgInboxRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl InboxRepository for PgInboxRepository {
+    async fn append(&self, activity: &InboxActivityDto) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO inbox_activities (
+                user_id, actor, kind, activity_id, activity, received_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, activity_id) DO NOTHING
+            "#,
+        )
+        .bind(activity.user_id.as_str())
+        .bind(&activity.actor)
+        .bind(&activity.kind)
+        .bind(&activity.activity_id)
+        .bind(Json(&activity.activity))
+        .bind(DateTime::from_timestamp(activity.received_at as i64, 0))
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
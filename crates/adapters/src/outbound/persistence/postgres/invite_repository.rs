@@ -0,0 +1,248 @@
+//! PostgreSQL implementation of InviteRepository.
+
+use application::dto::{AccountDto, InviteDto, InviteQuota};
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::InviteRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::identity::id::UserId;
+use sqlx::PgPool;
+
+use super::account_repository::insert_user;
+use super::models::InviteRecord;
+
+/// Base SQL for selecting an invitation code and who used it.
+const INVITE_SELECT_BASE: &str = r#"
+    SELECT
+        i.code,
+        i.created_by,
+        i.max_uses,
+        i.uses,
+        i.expires_at,
+        i.revoked_at,
+        i.created_at,
+        ARRAY(
+            SELECT u.user_id
+            FROM invite_uses u
+            WHERE u.code = i.code
+            ORDER BY u.used_at
+        ) AS used_by
+    FROM invite_codes i
+"#;
+
+/// Guard against cycles in the invitation graph.
+const MAX_CHAIN_DEPTH: i32 = 64;
+
+/// PostgreSQL invitation code repository.
+pub struct PgInviteRepository {
+    pool: PgPool,
+}
+
+impl PgInviteRepository {
+    /// Create a new [`PgInviteRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl InviteRepository for PgInviteRepository {
+    async fn find(&self, code: &str) -> Result<Option<InviteDto>> {
+        let query_sql = format!("{INVITE_SELECT_BASE} WHERE i.code = $1");
+
+        sqlx::query_as::<_, InviteRecord>(&query_sql)
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await
+            .catch()?
+            .map(InviteRecord::try_into_dto)
+            .transpose()
+    }
+
+    async fn create(
+        &self,
+        invite: &InviteDto,
+        quota: Option<&InviteQuota>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await.catch()?;
+
+        if let (Some(quota), Some(created_by)) = (quota, &invite.created_by) {
+            // Serialize invitations of the same creator until commit, so
+            // concurrent requests see each other.
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                .bind(format!("invite:{created_by}"))
+                .execute(&mut *tx)
+                .await
+                .catch()?;
+
+            let query_sql =
+                format!("{INVITE_SELECT_BASE} WHERE i.created_by = $1");
+            let invites = sqlx::query_as::<_, InviteRecord>(&query_sql)
+                .bind(created_by.as_str())
+                .fetch_all(&mut *tx)
+                .await
+                .catch()?
+                .into_iter()
+                .map(InviteRecord::try_into_dto)
+                .collect::<Result<Vec<_>>>()?;
+            quota.check(&invites, invite.created_at)?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO invite_codes (
+                code, created_by, max_uses, expires_at, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(&invite.code)
+        .bind(invite.created_by.as_ref().map(UserId::as_str))
+        .bind(invite.max_uses as i32)
+        .bind(
+            invite
+                .expires_at
+                .and_then(|d| DateTime::from_timestamp(d as i64, 0)),
+        )
+        .bind(
+            DateTime::from_timestamp(invite.created_at as i64, 0)
+                .unwrap_or_else(Utc::now),
+        )
+        .execute(&mut *tx)
+        .await
+        .catch()?;
+
+        tx.commit().await.catch()?;
+
+        Ok(())
+    }
+
+    async fn list_by_creator(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<InviteDto>> {
+        let query_sql = format!(
+            "{INVITE_SELECT_BASE} WHERE i.created_by = $1 \
+             ORDER BY i.created_at DESC"
+        );
+
+        sqlx::query_as::<_, InviteRecord>(&query_sql)
+            .bind(user_id.as_str())
+            .fetch_all(&self.pool)
+            .await
+            .catch()?
+            .into_iter()
+            .map(InviteRecord::try_into_dto)
+            .collect()
+    }
+
+    async fn list_recent(&self, limit: u32) -> Result<Vec<InviteDto>> {
+        let query_sql = format!(
+            "{INVITE_SELECT_BASE} ORDER BY i.created_at DESC LIMIT $1"
+        );
+
+        sqlx::query_as::<_, InviteRecord>(&query_sql)
+            .bind(i64::from(limit))
+            .fetch_all(&self.pool)
+            .await
+            .catch()?
+            .into_iter()
+            .map(InviteRecord::try_into_dto)
+            .collect()
+    }
+
+    async fn revoke(
+        &self,
+        code: &str,
+        user_id: Option<&UserId>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE invite_codes
+            SET revoked_at = NOW()
+            WHERE code = $1
+                AND ($2::text IS NULL OR created_by = $2)
+                AND revoked_at IS NULL
+            "#,
+        )
+        .bind(code)
+        .bind(user_id.map(UserId::as_str))
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn claim(&self, code: &str, account: &AccountDto) -> Result<()> {
+        let mut tx = self.pool.begin().await.catch()?;
+
+        // Lock the row so concurrent sign-ups cannot exceed `max_uses`.
+        let query_sql =
+            format!("{INVITE_SELECT_BASE} WHERE i.code = $1 FOR UPDATE");
+        sqlx::query_as::<_, InviteRecord>(&query_sql)
+            .bind(code)
+            .fetch_optional(&mut *tx)
+            .await
+            .catch()?
+            .ok_or(ApplicationError::InvalidInvite)?
+            .try_into_dto()?
+            .ensure_claimable(account.created_at)?;
+
+        insert_user(&mut *tx, account).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE invite_codes
+            SET uses = uses + 1, used_by = $2, used_at = NOW()
+            WHERE code = $1
+            "#,
+        )
+        .bind(code)
+        .bind(account.id.as_str())
+        .execute(&mut *tx)
+        .await
+        .catch()?;
+
+        sqlx::query("INSERT INTO invite_uses (code, user_id) VALUES ($1, $2)")
+            .bind(code)
+            .bind(account.id.as_str())
+            .execute(&mut *tx)
+            .await
+            .catch()?;
+
+        tx.commit().await.catch()?;
+
+        Ok(())
+    }
+
+    async fn inviter_chain(&self, user_id: &UserId) -> Result<Vec<UserId>> {
+        let records = sqlx::query_as::<_, (String,)>(
+            r#"
+            WITH RECURSIVE chain (user_id, depth) AS (
+                SELECT c.created_by, 1
+                FROM invite_uses u
+                JOIN invite_codes c ON c.code = u.code
+                WHERE u.user_id = $1 AND c.created_by IS NOT NULL
+                UNION
+                SELECT c.created_by, chain.depth + 1
+                FROM chain
+                JOIN invite_uses u ON u.user_id = chain.user_id
+                JOIN invite_codes c ON c.code = u.code
+                WHERE c.created_by IS NOT NULL AND chain.depth < $2
+            )
+            SELECT user_id FROM chain ORDER BY depth
+            "#,
+        )
+        .bind(user_id.as_str())
+        .bind(MAX_CHAIN_DEPTH)
+        .fetch_all(&self.pool)
+        .await
+        .catch()?;
+
+        records
+            .into_iter()
+            .map(|(id,)| UserId::parse(id).catch())
+            .collect()
+    }
+}
@@ -1,6 +1,16 @@
 //! PostgreSQL outbound persistence adapter.
 
 pub mod account_repository;
+pub mod actor_key_repository;
+pub mod audit_log;
+pub mod encrypted_field_repository;
+pub mod inbox_repository;
+pub mod invite_repository;
 pub mod models;
+pub mod moderation_repository;
+pub mod peer_repository;
 pub mod pool;
+pub mod rate_limiter;
+pub mod role_repository;
+pub mod token_denylist;
 pub mod token_repository;
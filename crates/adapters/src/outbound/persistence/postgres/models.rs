@@ -1,12 +1,18 @@
 //! Database models for PostgreSQL.
 
-use application::dto::{AccountDto, PublicKeyDto};
+use application::dto::{
+    AccountDto, ActorKeyDto, EncryptedFieldsDto, InviteDto,
+    ModerationActionDto, PeerDto, PublicKeyDto, SecurityEventDto, SessionDto,
+};
 use application::error::{Result, ToInternal};
 use chrono::{DateTime, NaiveDate, Utc};
 use domain::auth::email::EmailHash;
 use domain::auth::password::PasswordHash;
+use domain::identity::flags::UserFlags;
 use domain::identity::id::UserId;
-use domain::key::pem::PemFingerprint;
+use domain::identity::ip::EncryptedIp;
+use domain::identity::suspension::Suspension;
+use domain::key::pem::{PemFingerprint, PemPublicKey};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -21,12 +27,18 @@ pub struct UserRecord {
     pub locale: String,
     pub summary: Option<String>,
     pub avatar: Option<String>,
+    pub banner: Option<String>,
     pub flags: i32,
     pub password: String,
     pub created_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
     #[sqlx(json)]
     pub public_keys: Vec<PublicKeyRecord>,
+    pub token_generation: i64,
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub suspension_reason: Option<String>,
+    pub password_reset_required: bool,
 }
 
 /// Public key record embedded in UserRecord.
@@ -50,12 +62,119 @@ pub struct RefreshTokenRecord {
     pub revoked: bool,
 }
 
+/// Session record.
+#[derive(Debug, Clone, FromRow)]
+pub struct SessionRecord {
+    pub id: String,
+    pub user_id: String,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub amr: Vec<String>,
+    pub auth_time: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+impl SessionRecord {
+    /// Convert to [`SessionDto`].
+    pub fn try_into_dto(self) -> Result<SessionDto> {
+        // Sessions opened before `auth_time` was tracked started at login.
+        let authenticated_at = self.auth_time.unwrap_or(self.created_at);
+
+        Ok(SessionDto {
+            id: self.id,
+            user_id: UserId::parse(self.user_id).catch()?,
+            device: self.device,
+            ip_address: self.ip.map(EncryptedIp::new),
+            amr: self.amr,
+            authenticated_at: authenticated_at
+                .timestamp()
+                .try_into()
+                .unwrap_or(0),
+            created_at: self.created_at.timestamp().try_into().unwrap_or(0),
+            last_used_at: self
+                .last_used_at
+                .timestamp()
+                .try_into()
+                .unwrap_or(0),
+        })
+    }
+}
+
+impl From<&SessionDto> for SessionRecord {
+    fn from(dto: &SessionDto) -> Self {
+        Self {
+            id: dto.id.clone(),
+            user_id: dto.user_id.to_string(),
+            device: dto.device.clone(),
+            ip: dto.ip_address.as_ref().map(|ip| ip.to_string()),
+            amr: dto.amr.clone(),
+            auth_time: DateTime::from_timestamp(
+                dto.authenticated_at as i64,
+                0,
+            ),
+            created_at: DateTime::from_timestamp(dto.created_at as i64, 0)
+                .unwrap_or_else(Utc::now),
+            last_used_at: DateTime::from_timestamp(dto.last_used_at as i64, 0)
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Invitation code record.
+#[derive(Debug, Clone, FromRow)]
+pub struct InviteRecord {
+    pub code: String,
+    pub created_by: Option<String>,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub used_by: Vec<String>,
+}
+
+impl InviteRecord {
+    /// Convert to [`InviteDto`].
+    pub fn try_into_dto(self) -> Result<InviteDto> {
+        Ok(InviteDto {
+            code: self.code,
+            created_by: self
+                .created_by
+                .map(UserId::parse)
+                .transpose()
+                .catch()?,
+            max_uses: self.max_uses.try_into().unwrap_or(0),
+            uses: self.uses.try_into().unwrap_or(0),
+            expires_at: self
+                .expires_at
+                .and_then(|d| d.timestamp().try_into().ok()),
+            revoked_at: self
+                .revoked_at
+                .and_then(|d| d.timestamp().try_into().ok()),
+            created_at: self.created_at.timestamp().try_into().unwrap_or(0),
+            used_by: self
+                .used_by
+                .into_iter()
+                .map(UserId::parse)
+                .collect::<std::result::Result<_, _>>()
+                .catch()?,
+        })
+    }
+}
+
 impl From<&PublicKeyRecord> for PublicKeyDto {
     fn from(k: &PublicKeyRecord) -> Self {
+        let pem = PemPublicKey::parse(k.public_key_pem.clone()).ok();
         Self {
             id: PemFingerprint::new(&k.id),
             owner: k.owner.clone(),
             public_key_pem: k.public_key_pem.clone(),
+            algorithm: pem.as_ref().map(PemPublicKey::algorithm),
+            public_key_jwk: pem.as_ref().and_then(|pem| pem.to_jwk().ok()),
+            public_key_multibase: pem
+                .as_ref()
+                .and_then(|pem| pem.to_multibase().ok()),
             created_at: k.created_at.to_string(),
         }
     }
@@ -74,7 +193,8 @@ impl UserRecord {
             locale: self.locale,
             summary: self.summary,
             avatar: self.avatar,
-            flags: self.flags,
+            banner: self.banner,
+            flags: UserFlags::from_bits_retain(self.flags as u32),
             created_at: self.created_at.timestamp().try_into().unwrap_or(0),
             deleted_at: self
                 .deleted_at
@@ -84,6 +204,16 @@ impl UserRecord {
                 .iter()
                 .map(PublicKeyDto::from)
                 .collect(),
+            token_generation: self.token_generation.try_into().unwrap_or(0),
+            suspension: self.suspended_at.map(|since| {
+                Suspension::from_parts(
+                    self.suspension_reason.unwrap_or_default(),
+                    since.timestamp().try_into().unwrap_or(0),
+                    self.suspended_until
+                        .and_then(|d| d.timestamp().try_into().ok()),
+                )
+            }),
+            password_reset_required: self.password_reset_required,
         })
     }
 }
@@ -99,7 +229,8 @@ impl From<&AccountDto> for UserRecord {
             locale: dto.locale.clone(),
             summary: dto.summary.clone(),
             avatar: dto.avatar.clone(),
-            flags: dto.flags,
+            banner: dto.banner.clone(),
+            flags: dto.flags.bits() as i32,
             password: dto.password_hash.as_str().to_string(),
             created_at: DateTime::from_timestamp(dto.created_at as i64, 0)
                 .unwrap_or_else(Utc::now),
@@ -119,6 +250,155 @@ impl From<&AccountDto> for UserRecord {
                         .unwrap_or_else(|_| Utc::now().date_naive()),
                 })
                 .collect(),
+            token_generation: dto.token_generation as i64,
+            suspended_at: dto
+                .suspension
+                .as_ref()
+                .and_then(|s| DateTime::from_timestamp(s.since() as i64, 0)),
+            suspended_until: dto
+                .suspension
+                .as_ref()
+                .and_then(|s| s.until())
+                .and_then(|d| DateTime::from_timestamp(d as i64, 0)),
+            suspension_reason: dto
+                .suspension
+                .as_ref()
+                .map(|s| s.reason().to_string()),
+            password_reset_required: dto.password_reset_required,
         }
     }
 }
+
+/// Moderation action record.
+#[derive(Debug, Clone, FromRow)]
+pub struct ModerationActionRecord {
+    pub user_id: Option<String>,
+    pub moderator_id: String,
+    pub action: String,
+    pub reason: Option<String>,
+    pub target: Option<String>,
+    pub until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModerationActionRecord {
+    /// Convert to [`ModerationActionDto`].
+    pub fn try_into_dto(self) -> Result<ModerationActionDto> {
+        Ok(ModerationActionDto {
+            user_id: self.user_id.map(UserId::parse).transpose().catch()?,
+            moderator: UserId::parse(self.moderator_id).catch()?,
+            action: self.action.parse()?,
+            reason: self.reason,
+            target: self.target,
+            until: self.until.and_then(|d| d.timestamp().try_into().ok()),
+            created_at: self.created_at.timestamp().try_into().unwrap_or(0),
+        })
+    }
+}
+
+/// Security event record.
+#[derive(Debug, Clone, FromRow)]
+pub struct SecurityEventRecord {
+    pub user_id: Option<String>,
+    pub kind: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SecurityEventRecord {
+    /// Convert to [`SecurityEventDto`].
+    pub fn try_into_dto(self) -> Result<SecurityEventDto> {
+        Ok(SecurityEventDto {
+            user_id: self.user_id.map(UserId::parse).transpose().catch()?,
+            kind: self.kind.parse()?,
+            ip_address: self.ip_address.map(EncryptedIp::new),
+            user_agent: self.user_agent,
+            details: self.details,
+            created_at: self.created_at.timestamp().try_into().unwrap_or(0),
+        })
+    }
+}
+
+/// Security event record with its place in the hash chain.
+#[derive(Debug, Clone, FromRow)]
+pub struct ChainedEventRecord {
+    pub id: i64,
+    pub user_id: Option<String>,
+    pub kind: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// ActivityPub actor key record.
+#[derive(Debug, Clone, FromRow)]
+pub struct ActorKeyRecord {
+    pub user_id: String,
+    pub public_key_pem: String,
+    pub private_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ActorKeyRecord {
+    /// Convert to [`ActorKeyDto`].
+    pub fn try_into_dto(self) -> Result<ActorKeyDto> {
+        Ok(ActorKeyDto {
+            user_id: UserId::parse(self.user_id).catch()?,
+            public_key_pem: self.public_key_pem,
+            private_key_cipher: self.private_key,
+            created_at: self.created_at.timestamp().try_into().unwrap_or(0),
+        })
+    }
+}
+
+/// Remote instance record.
+#[derive(Debug, Clone, FromRow)]
+pub struct PeerRecord {
+    pub host: String,
+    pub status: Option<String>,
+    pub key_id: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PeerRecord {
+    /// Convert to [`PeerDto`].
+    pub fn try_into_dto(self) -> Result<PeerDto> {
+        Ok(PeerDto {
+            host: self.host,
+            status: self.status.map(|status| status.parse()).transpose()?,
+            key_id: self.key_id,
+            verified_at: self
+                .verified_at
+                .and_then(|d| d.timestamp().try_into().ok()),
+            updated_at: self.updated_at.timestamp().try_into().unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct EncryptedFieldsRecord {
+    pub id: String,
+    pub email_cipher: String,
+    pub totp_secret: Option<String>,
+    pub actor_key: Option<String>,
+    pub session_ips: Vec<String>,
+}
+
+impl EncryptedFieldsRecord {
+    /// Convert to [`EncryptedFieldsDto`].
+    pub fn try_into_dto(self) -> Result<EncryptedFieldsDto> {
+        Ok(EncryptedFieldsDto {
+            user_id: UserId::parse(self.id).catch()?,
+            email_cipher: self.email_cipher,
+            totp_secret: self.totp_secret,
+            actor_key: self.actor_key,
+            session_ips: self.session_ips,
+        })
+    }
+}
@@ -0,0 +1,200 @@
+//! PostgreSQL implementation of ModerationRepository.
+
+use application::dto::{ModerationAction, ModerationActionDto};
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::ModerationRepository;
+use async_trait::async_trait;
+use chrono::DateTime;
+use domain::identity::id::UserId;
+use domain::identity::suspension::Suspension;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+
+use super::models::ModerationActionRecord;
+
+/// PostgreSQL moderation repository.
+pub struct PgModerationRepository {
+    pool: PgPool,
+}
+
+impl PgModerationRepository {
+    /// Create a new [`PgModerationRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Record a moderation action within `tx`.
+async fn record_action(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: &UserId,
+    moderator: &UserId,
+    action: ModerationAction,
+    reason: &str,
+    until: Option<u64>,
+) -> Result<()> {
+    insert_action(
+        &mut **tx,
+        &ModerationActionDto {
+            user_id: Some(user_id.clone()),
+            moderator: moderator.clone(),
+            action,
+            reason: Some(reason.to_string()),
+            target: None,
+            until,
+            created_at: 0,
+        },
+    )
+    .await
+}
+
+/// Insert a moderation action, timestamped by the database.
+async fn insert_action<'e, E>(
+    executor: E,
+    action: &ModerationActionDto,
+) -> Result<()>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO moderation_actions (
+            user_id, moderator_id, action, reason, target, until
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(action.user_id.as_ref().map(UserId::as_str))
+    .bind(action.moderator.as_str())
+    .bind(action.action.as_str())
+    .bind(action.reason.as_deref())
+    .bind(action.target.as_deref())
+    .bind(
+        action
+            .until
+            .and_then(|d| DateTime::from_timestamp(d as i64, 0)),
+    )
+    .execute(executor)
+    .await
+    .catch()?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl ModerationRepository for PgModerationRepository {
+    async fn suspend(
+        &self,
+        user_id: &UserId,
+        suspension: &Suspension,
+        moderator: &UserId,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await.catch()?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                suspended_at = $2,
+                suspended_until = $3,
+                suspension_reason = $4
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id.as_str())
+        .bind(DateTime::from_timestamp(suspension.since() as i64, 0))
+        .bind(
+            suspension
+                .until()
+                .and_then(|d| DateTime::from_timestamp(d as i64, 0)),
+        )
+        .bind(suspension.reason())
+        .execute(&mut *tx)
+        .await
+        .catch()?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::UserNotFound);
+        }
+
+        record_action(
+            &mut tx,
+            user_id,
+            moderator,
+            ModerationAction::Suspend,
+            suspension.reason(),
+            suspension.until(),
+        )
+        .await?;
+
+        tx.commit().await.catch()
+    }
+
+    async fn unsuspend(
+        &self,
+        user_id: &UserId,
+        moderator: &UserId,
+        reason: &str,
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await.catch()?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                suspended_at = NULL,
+                suspended_until = NULL,
+                suspension_reason = NULL
+            WHERE id = $1 AND suspended_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id.as_str())
+        .execute(&mut *tx)
+        .await
+        .catch()?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        record_action(
+            &mut tx,
+            user_id,
+            moderator,
+            ModerationAction::Unsuspend,
+            reason,
+            None,
+        )
+        .await?;
+
+        tx.commit().await.catch()?;
+
+        Ok(true)
+    }
+
+    async fn record(&self, action: &ModerationActionDto) -> Result<()> {
+        insert_action(&self.pool, action).await
+    }
+
+    async fn list_actions(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<ModerationActionDto>> {
+        sqlx::query_as::<_, ModerationActionRecord>(
+            r#"
+            SELECT
+                user_id, moderator_id, action, reason, target, until,
+                created_at
+            FROM moderation_actions
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(user_id.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .catch()?
+        .into_iter()
+        .map(ModerationActionRecord::try_into_dto)
+        .collect()
+    }
+}
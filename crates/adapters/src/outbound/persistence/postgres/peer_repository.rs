@@ -0,0 +1,106 @@
+//! PostgreSQL implementation of PeerRepository.
+
+use application::dto::{PeerDto, PeerStatus};
+use application::error::{Result, ToInternal};
+use application::ports::outbound::PeerRepository;
+use async_trait::async_trait;
+use chrono::DateTime;
+use sqlx::PgPool;
+
+use super::models::PeerRecord;
+
+/// PostgreSQL trust store of remote instances.
+pub struct PgPeerRepository {
+    pool: PgPool,
+}
+
+impl PgPeerRepository {
+    /// Create a new [`PgPeerRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PeerRepository for PgPeerRepository {
+    async fn find(&self, host: &str) -> Result<Option<PeerDto>> {
+        sqlx::query_as::<_, PeerRecord>(
+            r#"
+            SELECT host, status, key_id, verified_at, updated_at
+            FROM federation_peers
+            WHERE host = $1
+            "#,
+        )
+        .bind(host)
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?
+        .map(PeerRecord::try_into_dto)
+        .transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<PeerDto>> {
+        sqlx::query_as::<_, PeerRecord>(
+            r#"
+            SELECT host, status, key_id, verified_at, updated_at
+            FROM federation_peers
+            ORDER BY host
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .catch()?
+        .into_iter()
+        .map(PeerRecord::try_into_dto)
+        .collect()
+    }
+
+    async fn set_status(
+        &self,
+        host: &str,
+        status: Option<PeerStatus>,
+        now: u64,
+    ) -> Result<PeerDto> {
+        sqlx::query_as::<_, PeerRecord>(
+            r#"
+            INSERT INTO federation_peers (host, status, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (host) DO UPDATE
+            SET status = EXCLUDED.status, updated_at = EXCLUDED.updated_at
+            RETURNING host, status, key_id, verified_at, updated_at
+            "#,
+        )
+        .bind(host)
+        .bind(status.map(|status| status.as_str()))
+        .bind(DateTime::from_timestamp(now as i64, 0))
+        .fetch_one(&self.pool)
+        .await
+        .catch()?
+        .try_into_dto()
+    }
+
+    async fn record_verified(
+        &self,
+        host: &str,
+        key_id: &str,
+        now: u64,
+    ) -> Result<()> {
+        let now = DateTime::from_timestamp(now as i64, 0);
+        sqlx::query(
+            r#"
+            INSERT INTO federation_peers (host, key_id, verified_at, updated_at)
+            VALUES ($1, $2, $3, $3)
+            ON CONFLICT (host) DO UPDATE
+            SET key_id = EXCLUDED.key_id, verified_at = EXCLUDED.verified_at
+            "#,
+        )
+        .bind(host)
+        .bind(key_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(())
+    }
+}
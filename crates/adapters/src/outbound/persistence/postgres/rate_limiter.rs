@@ -0,0 +1,159 @@
+//! PostgreSQL implementation of RateLimiter.
+
+use std::sync::Arc;
+
+use application::error::{Result, ToInternal};
+use application::ports::outbound::{
+    Clock, RateLimitDecision, RateLimitKey, RateLimitPolicy, RateLimiter,
+};
+use async_trait::async_trait;
+use rand::Rng;
+use sqlx::PgPool;
+
+use crate::outbound::rate_limit::{WindowCounts, decide, window_start};
+
+/// One in this many new windows also purges the stale windows of every key,
+/// so that keys hit once are not kept forever.
+const PURGE_SAMPLE: u32 = 100;
+
+/// PostgreSQL rate limiter, shared by every instance using the database.
+pub struct PgRateLimiter {
+    pool: PgPool,
+    clock: Arc<dyn Clock>,
+}
+
+impl PgRateLimiter {
+    /// Create a new [`PgRateLimiter`].
+    pub fn new(pool: PgPool, clock: Arc<dyn Clock>) -> Self {
+        Self { pool, clock }
+    }
+
+    async fn counts(
+        &self,
+        key: &str,
+        window: i64,
+        start: i64,
+    ) -> Result<WindowCounts> {
+        let record = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+            r#"
+            SELECT
+                MAX(hits) FILTER (WHERE window_start = $3) AS current,
+                MAX(hits) FILTER (WHERE window_start = $3 - $2) AS previous
+            FROM rate_limits
+            WHERE key = $1 AND window_size = $2
+            "#,
+        )
+        .bind(key)
+        .bind(window)
+        .bind(start)
+        .fetch_one(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(WindowCounts {
+            current: record.0.unwrap_or(0) as u64,
+            previous: record.1.unwrap_or(0) as u64,
+        })
+    }
+
+    /// Delete the windows of every key that no longer count at `now`.
+    pub async fn purge(&self, now: u64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM rate_limits
+            WHERE window_start < $1 - 2 * window_size
+            "#,
+        )
+        .bind(now as i64)
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl RateLimiter for PgRateLimiter {
+    async fn hit(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        let now = self.clock.now();
+        let window = policy.window.max(1);
+        let start = window_start(now, window);
+        let storage_key = key.as_storage_key();
+
+        let (current,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            INSERT INTO rate_limits (key, window_size, window_start, hits)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (key, window_size, window_start)
+            DO UPDATE SET hits = rate_limits.hits + 1
+            RETURNING hits
+            "#,
+        )
+        .bind(&storage_key)
+        .bind(window as i64)
+        .bind(start as i64)
+        .fetch_one(&self.pool)
+        .await
+        .catch()?;
+
+        if current == 1 {
+            // First hit of a new window: drop windows nobody looks at
+            // anymore.
+            sqlx::query(
+                r#"
+                DELETE FROM rate_limits
+                WHERE key = $1 AND window_size = $2 AND window_start < $3
+                "#,
+            )
+            .bind(&storage_key)
+            .bind(window as i64)
+            .bind(start as i64 - window as i64)
+            .execute(&self.pool)
+            .await
+            .catch()?;
+
+            if rand::thread_rng().gen_ratio(1, PURGE_SAMPLE) {
+                self.purge(now).await?;
+            }
+        }
+
+        let mut counts = self
+            .counts(&storage_key, window as i64, start as i64)
+            .await?;
+        counts.current = current as u64;
+
+        Ok(decide(counts, policy, now))
+    }
+
+    async fn peek(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        let now = self.clock.now();
+        let window = policy.window.max(1);
+        let start = window_start(now, window);
+
+        let mut counts = self
+            .counts(&key.as_storage_key(), window as i64, start as i64)
+            .await?;
+        counts.current += 1;
+
+        Ok(decide(counts, policy, now))
+    }
+
+    async fn reset(&self, key: &RateLimitKey) -> Result<()> {
+        sqlx::query("DELETE FROM rate_limits WHERE key = $1")
+            .bind(key.as_storage_key())
+            .execute(&self.pool)
+            .await
+            .catch()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! PostgreSQL implementation of RoleRepository.
+
+use application::error::{Result, ToInternal};
+use application::ports::outbound::RoleRepository;
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+use sqlx::PgPool;
+
+/// PostgreSQL staff role repository.
+pub struct PgRoleRepository {
+    pool: PgPool,
+}
+
+impl PgRoleRepository {
+    /// Create a new [`PgRoleRepository`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RoleRepository for PgRoleRepository {
+    async fn roles(&self, user_id: &UserId) -> Result<Vec<Role>> {
+        let records = sqlx::query_as::<_, (String,)>(
+            "SELECT role FROM user_roles WHERE user_id = $1 ORDER BY role",
+        )
+        .bind(user_id.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .catch()?;
+
+        // Ignore roles this version does not know about.
+        Ok(records
+            .into_iter()
+            .filter_map(|(role,)| role.parse().ok())
+            .collect())
+    }
+
+    async fn grant(
+        &self,
+        user_id: &UserId,
+        role: Role,
+        granted_by: &UserId,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO user_roles (user_id, role, granted_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, role) DO NOTHING
+            "#,
+        )
+        .bind(user_id.as_str())
+        .bind(role.as_str())
+        .bind(granted_by.as_str())
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke(&self, user_id: &UserId, role: Role) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM user_roles WHERE user_id = $1 AND role = $2",
+        )
+        .bind(user_id.as_str())
+        .bind(role.as_str())
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,94 @@
+//! PostgreSQL implementation of TokenDenylist.
+
+use application::error::{ApplicationError, Result, ToInternal};
+use application::ports::outbound::TokenDenylist;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::identity::id::UserId;
+use sqlx::PgPool;
+
+/// PostgreSQL token denylist, shared by every instance.
+///
+/// Generations are stored on the `users` table.
+pub struct PgTokenDenylist {
+    pool: PgPool,
+}
+
+impl PgTokenDenylist {
+    /// Create a new [`PgTokenDenylist`].
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TokenDenylist for PgTokenDenylist {
+    async fn deny(&self, jti: &str, expires_at: u64) -> Result<()> {
+        let expires_at = DateTime::from_timestamp(expires_at as i64, 0)
+            .unwrap_or_else(Utc::now);
+
+        sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, expires_at)
+            VALUES ($1, $2)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(jti)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .catch()?;
+
+        // Expired tokens are rejected anyway.
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await
+            .catch()?;
+
+        Ok(())
+    }
+
+    async fn is_denied(&self, jti: &str) -> Result<bool> {
+        let record = sqlx::query_as::<_, (i32,)>(
+            "SELECT 1 FROM revoked_tokens WHERE jti = $1",
+        )
+        .bind(jti)
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(record.is_some())
+    }
+
+    async fn generation(&self, user_id: &UserId) -> Result<u64> {
+        let (generation,) = sqlx::query_as::<_, (i64,)>(
+            "SELECT token_generation FROM users WHERE id = $1",
+        )
+        .bind(user_id.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?
+        .ok_or(ApplicationError::UserNotFound)?;
+
+        Ok(generation.try_into().unwrap_or(0))
+    }
+
+    async fn bump_generation(&self, user_id: &UserId) -> Result<u64> {
+        let (generation,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            UPDATE users
+            SET token_generation = token_generation + 1
+            WHERE id = $1
+            RETURNING token_generation
+            "#,
+        )
+        .bind(user_id.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .catch()?
+        .ok_or(ApplicationError::UserNotFound)?;
+
+        Ok(generation.try_into().unwrap_or(0))
+    }
+}
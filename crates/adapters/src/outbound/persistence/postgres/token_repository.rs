@@ -1,5 +1,6 @@
 //! PostgreSQL implementation of RefreshTokenRepository.
 
+use application::dto::SessionDto;
 use application::error::{ApplicationError, Result, ToInternal};
 use application::ports::outbound::RefreshTokenRepository;
 use async_trait::async_trait;
@@ -7,6 +8,15 @@ use chrono::{Duration, Utc};
 use domain::identity::id::UserId;
 use sqlx::PgPool;
 
+use super::models::SessionRecord;
+
+/// Base SQL for selecting a session.
+const SESSION_SELECT_BASE: &str = r#"
+    SELECT s.id, s.user_id, s.device, s.ip, s.amr, s.auth_time,
+           s.created_at, s.last_used_at
+    FROM sessions s
+"#;
+
 /// PostgreSQL refresh token repository.
 pub struct PgRefreshTokenRepository {
     pool: PgPool,
@@ -31,51 +41,97 @@ impl PgRefreshTokenRepository {
 
 #[async_trait]
 impl RefreshTokenRepository for PgRefreshTokenRepository {
-    async fn store(
-        &self,
-        token: &str,
-        user_id: &UserId,
-        ip_address: Option<&String>,
-    ) -> Result<()> {
+    async fn store(&self, token: &str, session: &SessionDto) -> Result<()> {
         let now = Utc::now();
         let expires_at = now + Duration::days(self.token_ttl_days);
+        let record = SessionRecord::from(session);
+
+        let mut tx = self.pool.begin().await.catch()?;
 
         sqlx::query(
             r#"
-            INSERT INTO tokens (token, user_id, ip, created_at, expires_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO sessions (
+                id, user_id, device, ip, amr, auth_time, created_at,
+                last_used_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id)
+            DO UPDATE SET ip = EXCLUDED.ip, last_used_at = EXCLUDED.last_used_at
             "#,
         )
-        .bind(token)
-        .bind(user_id.as_str())
-        .bind(ip_address)
-        .bind(now)
-        .bind(expires_at)
-        .execute(&self.pool)
+        .bind(&record.id)
+        .bind(&record.user_id)
+        .bind(&record.device)
+        .bind(&record.ip)
+        .bind(&record.amr)
+        .bind(record.auth_time)
+        .bind(record.created_at)
+        .bind(record.last_used_at)
+        .execute(&mut *tx)
         .await
         .catch()?;
 
-        Ok(())
-    }
-
-    async fn find_user_id(&self, token: &str) -> Result<Option<UserId>> {
-        let record = sqlx::query_as::<_, (String,)>(
+        sqlx::query(
             r#"
-            SELECT user_id
-            FROM tokens
-            WHERE token = $1
-              AND expires_at > NOW()
+            INSERT INTO tokens (
+                token, user_id, session_id, ip, created_at, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
         )
         .bind(token)
-        .fetch_optional(&self.pool)
+        .bind(&record.user_id)
+        .bind(&record.id)
+        .bind(&record.ip)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&mut *tx)
         .await
         .catch()?;
 
-        match record {
-            Some(id) => Ok(UserId::parse(id.0).ok()),
-            _ => Ok(None),
-        }
+        tx.commit().await.catch()?;
+
+        Ok(())
+    }
+
+    async fn find_session(&self, token: &str) -> Result<Option<SessionDto>> {
+        let query_sql = format!(
+            "{SESSION_SELECT_BASE} JOIN tokens t ON t.session_id = s.id \
+             WHERE t.token = $1 AND t.expires_at > NOW() \
+             AND s.revoked_at IS NULL"
+        );
+
+        sqlx::query_as::<_, SessionRecord>(&query_sql)
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .catch()?
+            .map(SessionRecord::try_into_dto)
+            .transpose()
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<SessionDto>> {
+        let query_sql = format!(
+            "{SESSION_SELECT_BASE} WHERE s.user_id = $1 \
+             AND s.revoked_at IS NULL \
+             AND EXISTS ( \
+                SELECT 1 FROM tokens t \
+                WHERE t.session_id = s.id AND t.expires_at > NOW() \
+             ) \
+             ORDER BY s.last_used_at DESC"
+        );
+
+        sqlx::query_as::<_, SessionRecord>(&query_sql)
+            .bind(user_id.as_str())
+            .fetch_all(&self.pool)
+            .await
+            .catch()?
+            .into_iter()
+            .map(SessionRecord::try_into_dto)
+            .collect()
     }
 
     async fn revoke(&self, token: &str) -> Result<()> {
@@ -99,20 +155,67 @@ impl RefreshTokenRepository for PgRefreshTokenRepository {
         Ok(())
     }
 
-    async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<()> {
+    async fn revoke_session(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+    ) -> Result<bool> {
+        let (revoked,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            WITH revoked AS (
+                UPDATE sessions
+                SET revoked_at = NOW()
+                WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+                RETURNING id
+            ), expired AS (
+                UPDATE tokens
+                SET expires_at = NOW()
+                WHERE session_id IN (SELECT id FROM revoked)
+                  AND expires_at > NOW()
+            )
+            SELECT COUNT(*) FROM revoked
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .catch()?;
+
+        Ok(revoked > 0)
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        user_id: &UserId,
+        keep: Option<&str>,
+    ) -> Result<()> {
         sqlx::query(
             r#"
+            WITH revoked AS (
+                UPDATE sessions
+                SET revoked_at = NOW()
+                WHERE user_id = $1
+                  AND revoked_at IS NULL
+                  AND id IS DISTINCT FROM $2
+                RETURNING id
+            )
             UPDATE tokens
-            SET expires_at = $1
-            WHERE user_id = $2 AND expires_at > NOW()
+            SET expires_at = NOW()
+            WHERE session_id IN (SELECT id FROM revoked)
+              AND expires_at > NOW()
             "#,
         )
-        .bind(Utc::now())
         .bind(user_id.as_str())
+        .bind(keep)
         .execute(&self.pool)
         .await
         .catch()?;
 
         Ok(())
     }
+
+    async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<()> {
+        self.revoke_other_sessions(user_id, None).await
+    }
 }
@@ -0,0 +1,251 @@
+//! In-memory sliding-window rate limiter.
+//!
+//! Counters live in the process, so each replica limits on its own. Use the
+//! PostgreSQL backend when running several instances.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use application::error::{ApplicationError, Result};
+use application::ports::outbound::{
+    Clock, RateLimitDecision, RateLimitKey, RateLimitPolicy, RateLimiter,
+};
+use async_trait::async_trait;
+
+use super::{WindowCounts, decide, window_start};
+
+/// Maximum number of tracked counters.
+const DEFAULT_CAPACITY: usize = 100_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    start: u64,
+    counts: WindowCounts,
+}
+
+impl Entry {
+    /// Slide the entry so that it describes the window starting at `start`.
+    fn roll(&mut self, start: u64, window: u64) {
+        if start == self.start {
+            return;
+        }
+
+        self.counts = if start == self.start + window {
+            WindowCounts {
+                previous: self.counts.current,
+                current: 0,
+            }
+        } else {
+            WindowCounts::default()
+        };
+        self.start = start;
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    entries: HashMap<(String, u64), Entry>,
+    /// Last time stale entries were purged.
+    swept_at: u64,
+}
+
+impl Counters {
+    /// Drop entries whose windows no longer count, at most once a second
+    /// so that a full map of live entries is not scanned on every hit.
+    fn sweep(&mut self, now: u64) {
+        if now <= self.swept_at {
+            return;
+        }
+
+        self.swept_at = now;
+        self.entries
+            .retain(|(_, window), entry| entry.start + window * 2 > now);
+    }
+}
+
+/// Process-local rate limiter.
+///
+/// At most `capacity` counters are kept: once full, hits of new keys are
+/// limited until stale counters can be purged.
+pub struct InMemoryRateLimiter {
+    clock: Arc<dyn Clock>,
+    capacity: usize,
+    counters: Mutex<Counters>,
+}
+
+impl InMemoryRateLimiter {
+    /// Create a new [`InMemoryRateLimiter`].
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            capacity: DEFAULT_CAPACITY,
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    /// Set how many counters may be kept.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    fn check(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+        record: bool,
+    ) -> Result<RateLimitDecision> {
+        let now = self.clock.now();
+        let window = policy.window.max(1);
+        let start = window_start(now, window);
+
+        let mut counters = self
+            .counters
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?;
+        let storage_key = (key.as_storage_key(), window);
+
+        let known = counters.entries.contains_key(&storage_key);
+        if !known && counters.entries.len() >= self.capacity {
+            counters.sweep(now);
+        }
+
+        let mut entry =
+            counters
+                .entries
+                .get(&storage_key)
+                .copied()
+                .unwrap_or(Entry {
+                    start,
+                    counts: WindowCounts::default(),
+                });
+        entry.roll(start, window);
+        entry.counts.current += 1;
+
+        if record {
+            if !known && counters.entries.len() >= self.capacity {
+                tracing::warn!(
+                    capacity = self.capacity,
+                    "rate limiter full, refusing new keys"
+                );
+                return Ok(RateLimitDecision::Limited {
+                    retry_after: window,
+                });
+            }
+            counters.entries.insert(storage_key, entry);
+        }
+
+        Ok(decide(entry.counts, policy, now))
+    }
+}
+
+#[async_trait]
+impl RateLimiter for InMemoryRateLimiter {
+    async fn hit(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        self.check(key, policy, true)
+    }
+
+    async fn peek(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        self.check(key, policy, false)
+    }
+
+    async fn reset(&self, key: &RateLimitKey) -> Result<()> {
+        let storage_key = key.as_storage_key();
+        self.counters
+            .lock()
+            .map_err(|_| ApplicationError::Unknown)?
+            .entries
+            .retain(|(k, _), _| *k != storage_key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbound::clock::FixedClock;
+
+    #[tokio::test]
+    async fn test_hit_until_limited() {
+        let limiter = InMemoryRateLimiter::new(Arc::new(FixedClock::new(0)));
+        let key = RateLimitKey::Ip("127.0.0.1".into());
+        let policy = RateLimitPolicy::new(3, 60);
+
+        for _ in 0..3 {
+            assert!(!limiter.hit(&key, &policy).await.unwrap().is_limited());
+        }
+        assert!(limiter.hit(&key, &policy).await.unwrap().is_limited());
+
+        let other = RateLimitKey::Ip("127.0.0.2".into());
+        assert!(!limiter.hit(&other, &policy).await.unwrap().is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_peek_does_not_record() {
+        let limiter = InMemoryRateLimiter::new(Arc::new(FixedClock::new(0)));
+        let key = RateLimitKey::Account("admin".into());
+        let policy = RateLimitPolicy::new(1, 60);
+
+        for _ in 0..3 {
+            assert!(!limiter.peek(&key, &policy).await.unwrap().is_limited());
+        }
+
+        limiter.hit(&key, &policy).await.unwrap();
+        assert!(limiter.peek(&key, &policy).await.unwrap().is_limited());
+
+        limiter.reset(&key).await.unwrap();
+        assert!(!limiter.peek(&key, &policy).await.unwrap().is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_capacity() {
+        let clock = Arc::new(FixedClock::new(0));
+        let limiter = InMemoryRateLimiter::new(clock.clone()).with_capacity(2);
+        let policy = RateLimitPolicy::new(3, 60);
+        let ip = |ip: &str| RateLimitKey::Ip(ip.into());
+
+        for key in [ip("10.0.0.1"), ip("10.0.0.2")] {
+            assert!(!limiter.hit(&key, &policy).await.unwrap().is_limited());
+        }
+        // Peeking does not store a counter.
+        assert!(
+            !limiter
+                .peek(&ip("10.0.0.3"), &policy)
+                .await
+                .unwrap()
+                .is_limited()
+        );
+        assert_eq!(
+            limiter.hit(&ip("10.0.0.3"), &policy).await.unwrap(),
+            RateLimitDecision::Limited { retry_after: 60 }
+        );
+        // Known keys are still counted.
+        assert!(
+            !limiter
+                .hit(&ip("10.0.0.1"), &policy)
+                .await
+                .unwrap()
+                .is_limited()
+        );
+        assert_eq!(limiter.counters.lock().unwrap().entries.len(), 2);
+
+        // Once their windows no longer count, stale counters make room.
+        clock.advance(120);
+        assert!(
+            !limiter
+                .hit(&ip("10.0.0.3"), &policy)
+                .await
+                .unwrap()
+                .is_limited()
+        );
+        assert_eq!(limiter.counters.lock().unwrap().entries.len(), 1);
+    }
+}
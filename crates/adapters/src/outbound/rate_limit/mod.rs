@@ -0,0 +1,140 @@
+//! Rate limiting adapters.
+//!
+//! Every backend implements the same sliding-window counter: hits are stored
+//! in fixed windows and the previous window is weighted by how much of it
+//! still overlaps the sliding one.
+
+pub mod memory;
+
+use application::ports::outbound::{RateLimitDecision, RateLimitPolicy};
+pub use memory::InMemoryRateLimiter;
+
+/// Hits of the current and previous fixed windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct WindowCounts {
+    pub previous: u64,
+    pub current: u64,
+}
+
+/// Start of the fixed window containing `now`.
+#[inline]
+pub(crate) fn window_start(now: u64, window: u64) -> u64 {
+    now - now % window.max(1)
+}
+
+/// Decide whether `counts` fit into `policy` at `now`.
+///
+/// `counts` must already include the hit being checked.
+pub(crate) fn decide(
+    counts: WindowCounts,
+    policy: &RateLimitPolicy,
+    now: u64,
+) -> RateLimitDecision {
+    let window = policy.window.max(1);
+    let elapsed = now - window_start(now, window);
+    let limit = policy.limit as f64;
+
+    let weight = (window - elapsed) as f64 / window as f64;
+    let estimate = counts.previous as f64 * weight + counts.current as f64;
+
+    if estimate <= limit {
+        return RateLimitDecision::Allowed {
+            remaining: (limit - estimate).floor() as u32,
+        };
+    }
+
+    RateLimitDecision::Limited {
+        retry_after: retry_after(counts, policy.limit, window, elapsed),
+    }
+}
+
+/// Seconds until one more hit fits into the sliding window.
+fn retry_after(
+    counts: WindowCounts,
+    limit: u32,
+    window: u64,
+    elapsed: u64,
+) -> u64 {
+    let target = limit.saturating_sub(1) as f64;
+    let w = window as f64;
+    let remaining = (window - elapsed) as f64;
+
+    let wait = if counts.current as f64 <= target && counts.previous > 0 {
+        // The previous window only has to decay a bit more.
+        let headroom = target - counts.current as f64;
+        remaining - headroom * w / counts.previous as f64
+    } else {
+        // Wait for the current window to become the previous one and decay.
+        let headroom = if counts.current == 0 {
+            0.0
+        } else {
+            target * w / counts.current as f64
+        };
+        remaining + (w - headroom)
+    };
+
+    (wait.ceil() as u64).clamp(1, window * 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: RateLimitPolicy = RateLimitPolicy::new(5, 60);
+
+    #[test]
+    fn test_window_start() {
+        assert_eq!(window_start(125, 60), 120);
+        assert_eq!(window_start(120, 60), 120);
+        assert_eq!(window_start(7, 0), 7);
+    }
+
+    #[test]
+    fn test_allows_within_limit() {
+        let counts = WindowCounts {
+            previous: 0,
+            current: 5,
+        };
+        assert_eq!(
+            decide(counts, &POLICY, 120),
+            RateLimitDecision::Allowed { remaining: 0 }
+        );
+    }
+
+    #[test]
+    fn test_limits_over_current_window() {
+        let counts = WindowCounts {
+            previous: 0,
+            current: 6,
+        };
+        let decision = decide(counts, &POLICY, 130);
+        assert!(decision.is_limited());
+
+        let RateLimitDecision::Limited { retry_after } = decision else {
+            unreachable!();
+        };
+        // 50 seconds until the window ends, then decay below 4/6.
+        assert_eq!(retry_after, 50 + 20);
+    }
+
+    #[test]
+    fn test_previous_window_is_weighted() {
+        let counts = WindowCounts {
+            previous: 10,
+            current: 1,
+        };
+
+        // Half of the previous window still overlaps: 10 * 0.5 + 1 = 6.
+        assert!(decide(counts, &POLICY, 150).is_limited());
+        // A sixth overlaps: 10 / 6 + 1 < 5.
+        assert!(!decide(counts, &POLICY, 170).is_limited());
+
+        let RateLimitDecision::Limited { retry_after } =
+            decide(counts, &POLICY, 150)
+        else {
+            unreachable!();
+        };
+        // 10 * (30 - t) / 60 + 1 <= 4 once t >= 12.
+        assert_eq!(retry_after, 12);
+    }
+}
@@ -1,9 +1,14 @@
 //! JWT signing and verification using ES256 (ECDSA P-256).
 
+use std::sync::Arc;
+
 use application::error::{ApplicationError, Result, ToInternal};
 use application::ports::outbound::{
-    SecureRandom, TokenClaims, TokenSigner as ImplTokenSigner,
+    KeyManagement, SecureRandom, TokenClaims, TokenSigner as ImplTokenSigner,
 };
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use domain::auth::proof::AuthenticationProof;
 use jsonwebtoken::{
     Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode,
@@ -15,16 +20,26 @@ use crate::outbound::crypto::random::OsRngRandom;
 const JTI_LENGTH: usize = 12;
 const DEFAULT_AUDIENCE: &str = "account.gravitalia.com";
 const ACCESS_TOKEN_EXPIRATION: u64 = 900; // 15 minutes.
+const ELEVATED_TOKEN_EXPIRATION: u64 = 300; // 5 minutes.
 pub static SCOPES: [&str; 3] =
     ["read:account", "write:account", "write:public_keys"];
 
+/// Key signing tokens.
+enum SigningKey {
+    Local(EncodingKey),
+    Managed {
+        key_management: Arc<dyn KeyManagement>,
+        key_id: String,
+    },
+}
+
 /// JWT signer.
 pub struct TokenSigner {
     algorithm: Algorithm,
     kid: String,
     issuer: String,
     audience: String,
-    encoding_key: EncodingKey,
+    signing_key: SigningKey,
     decoding_key: Option<DecodingKey>,
 }
 
@@ -50,16 +65,86 @@ impl TokenSigner {
             kid: kid.into(),
             issuer: issuer.into(),
             audience: DEFAULT_AUDIENCE.to_string(),
-            encoding_key,
+            signing_key: SigningKey::Local(encoding_key),
             decoding_key,
         })
     }
 
+    /// Create a new [`TokenSigner`] signing with the key `key_id` of a key
+    /// management system, which never exposes the private key.
+    pub async fn managed(
+        kid: impl Into<String>,
+        issuer: impl Into<String>,
+        key_management: Arc<dyn KeyManagement>,
+        key_id: impl Into<String>,
+    ) -> Result<Self> {
+        let key_id = key_id.into();
+        let public_key_pem = key_management.public_key(&key_id).await?;
+        let decoding_key =
+            DecodingKey::from_ec_pem(public_key_pem.as_bytes()).catch()?;
+
+        Ok(Self {
+            algorithm: Algorithm::ES256,
+            kid: kid.into(),
+            issuer: issuer.into(),
+            audience: DEFAULT_AUDIENCE.to_string(),
+            signing_key: SigningKey::Managed {
+                key_management,
+                key_id,
+            },
+            decoding_key: Some(decoding_key),
+        })
+    }
+
     /// Set `audience` field on JWT.
     pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
         self.audience = audience.into();
         self
     }
+
+    async fn sign(
+        &self,
+        proof: &AuthenticationProof<'_>,
+        lifetime: u64,
+    ) -> Result<String> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+
+        let claims = JwtClaims {
+            sub: proof.user_id().to_string(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            exp: proof.authenticated_at() + lifetime,
+            iat: proof.authenticated_at(),
+            jti: OsRngRandom::new().random_string(JTI_LENGTH)?,
+            scope: SCOPES.join(" "),
+            sid: proof.session_id().map(str::to_string),
+            generation: proof.token_generation(),
+            amr: proof.amr().into_iter().map(str::to_string).collect(),
+            auth_time: proof.auth_time(),
+        };
+
+        match &self.signing_key {
+            SigningKey::Local(encoding_key) => {
+                encode(&header, &claims, encoding_key).catch()
+            },
+            SigningKey::Managed {
+                key_management,
+                key_id,
+            } => {
+                let message = format!(
+                    "{}.{}",
+                    URL_SAFE_NO_PAD
+                        .encode(serde_json::to_vec(&header).catch()?),
+                    URL_SAFE_NO_PAD
+                        .encode(serde_json::to_vec(&claims).catch()?),
+                );
+                let signature =
+                    key_management.sign(key_id, message.as_bytes()).await?;
+                Ok(format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature)))
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,27 +156,30 @@ struct JwtClaims {
     iat: u64,
     jti: String,
     scope: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
+    #[serde(default, rename = "gen")]
+    generation: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    amr: Vec<String>,
+    #[serde(default)]
+    auth_time: u64,
 }
 
+#[async_trait]
 impl ImplTokenSigner for TokenSigner {
-    fn create_access_token(
+    async fn create_access_token(
         &self,
         proof: &AuthenticationProof,
     ) -> Result<String> {
-        let mut header = Header::new(self.algorithm);
-        header.kid = Some(self.kid.clone());
-
-        let claims = JwtClaims {
-            sub: proof.user_id().to_string(),
-            iss: self.issuer.clone(),
-            aud: self.audience.clone(),
-            exp: proof.authenticated_at() + ACCESS_TOKEN_EXPIRATION,
-            iat: proof.authenticated_at(),
-            jti: OsRngRandom::new().random_string(JTI_LENGTH)?,
-            scope: SCOPES.join(" "),
-        };
+        self.sign(proof, ACCESS_TOKEN_EXPIRATION).await
+    }
 
-        encode(&header, &claims, &self.encoding_key).catch()
+    async fn create_elevated_token(
+        &self,
+        proof: &AuthenticationProof,
+    ) -> Result<String> {
+        self.sign(proof, ELEVATED_TOKEN_EXPIRATION).await
     }
 
     fn verify_token(&self, token: &str) -> Result<TokenClaims> {
@@ -115,6 +203,10 @@ impl ImplTokenSigner for TokenSigner {
             iat: token_data.claims.iat,
             jti: token_data.claims.jti,
             scope: token_data.claims.scope,
+            sid: token_data.claims.sid,
+            generation: token_data.claims.generation,
+            amr: token_data.claims.amr,
+            auth_time: token_data.claims.auth_time,
         })
     }
 
@@ -122,3 +214,40 @@ impl ImplTokenSigner for TokenSigner {
         &self.kid
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use domain::auth::factor::{FactorMethod, FactorType, VerifiedFactor};
+    use domain::identity::id::UserId;
+
+    use super::*;
+    use crate::outbound::kms::{FileKeystore, KeyKind};
+
+    #[tokio::test]
+    async fn test_managed_signer() {
+        let mut keystore = FileKeystore::new();
+        keystore.ensure("jwt", KeyKind::EcdsaP256).unwrap();
+        let signer = TokenSigner::managed(
+            "1",
+            "https://auth",
+            Arc::new(keystore),
+            "jwt",
+        )
+        .await
+        .unwrap();
+
+        let user_id = UserId::parse("alice").unwrap();
+        let now = jsonwebtoken::get_current_timestamp();
+        let factor = VerifiedFactor::new(
+            FactorType::Knowledge,
+            FactorMethod::Password,
+            now,
+        );
+        let proof =
+            AuthenticationProof::new(&user_id, vec![factor], now).unwrap();
+        let token = signer.create_access_token(&proof).await.unwrap();
+        let claims = signer.verify_token(&token).unwrap();
+        assert_eq!(claims.sub, "alice");
+        assert_eq!(claims.iss, "https://auth");
+    }
+}
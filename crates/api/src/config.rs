@@ -3,9 +3,13 @@
 //! Reads `config.yaml` and maps it to the adapter/application types.
 
 use std::fs::File;
+use std::num::NonZeroUsize;
 use std::path::Path;
 
-use application::dto::StatusDto;
+use application::dto::{InviteQuota, PeerPolicy, StatusDto};
+use application::ports::outbound::RateLimitPolicy;
+use domain::key::algorithm::{KeyAlgorithm, KeyPolicy};
+use domain::key::public_key::KeyError;
 use serde::Deserialize;
 
 /// Top-level configuration matching `config.yaml`.
@@ -15,6 +19,8 @@ pub struct ServerConfig {
     pub url: String,
     pub support: Option<String>,
     pub favicon: Option<String>,
+    /// Background image of the instance.
+    pub background: Option<String>,
     pub terms_of_service: Option<String>,
     pub privacy_policy: Option<String>,
     #[serde(default)]
@@ -26,6 +32,34 @@ pub struct ServerConfig {
     pub token: TokenConfig,
     pub ldap: Option<LdapConfig>,
     pub mail: Option<MailConfig>,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Reverse proxies (CIDR or address) allowed to set `Forwarded` and
+    /// `X-Forwarded-For`.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+    #[serde(default)]
+    pub invites: InviteConfig,
+    #[serde(default)]
+    pub public_keys: PublicKeyConfig,
+    /// Path to a GeoIP2 or GeoLite2 City database.
+    pub geoip: Option<String>,
+    /// Path to a directory of Have I Been Pwned range files, or to a
+    /// Bloom filter built from them.
+    pub breached_passwords: Option<String>,
+    #[serde(default)]
+    pub denylist: DenylistConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub federation: FederationConfig,
+    #[serde(default)]
+    pub reencryption: ReencryptionConfig,
+    #[serde(default)]
+    pub email_index: EmailIndexConfig,
+    /// Holds the master key and the token signing key instead of the
+    /// environment and this file.
+    pub key_management: Option<KeyManagementConfig>,
 }
 
 impl From<ServerConfig> for StatusDto {
@@ -35,7 +69,7 @@ impl From<ServerConfig> for StatusDto {
             url: config.url,
             support: config.support,
             favicon: config.favicon,
-            background: None,
+            background: config.background,
             terms_of_service: config.terms_of_service,
             privacy_policy: config.privacy_policy,
             invite_only: config.invite_only,
@@ -66,7 +100,13 @@ pub struct Argon2Config {
     pub iterations: u32,
     pub parallelism: u32,
     pub hash_length: usize,
+    /// Minimum password strength, from 0 to 4. Defaults to 3.
     pub zxcvbn: Option<u8>,
+    /// Hashes computed at once, by default as many as fit in memory, up to
+    /// one per CPU.
+    pub concurrency: Option<usize>,
+    /// Hashes waiting for a slot before requests are rejected.
+    pub queue: Option<usize>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -80,7 +120,10 @@ pub struct TotpConfig {
 #[derive(Clone, Deserialize)]
 pub struct TokenConfig {
     pub key_id: String,
+    /// Unused when tokens are signed by `key_management`.
+    #[serde(default)]
     pub private_key_pem: String,
+    #[serde(default)]
     pub public_key_pem: String,
 }
 
@@ -107,6 +150,338 @@ pub struct MailConfig {
     pub tls: Option<bool>,
 }
 
+#[derive(Clone, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub backend: RateLimitBackend,
+    /// Requests allowed per client IP on `/login` and `/create`.
+    #[serde(default = "default_ip_limit")]
+    pub ip: LimitConfig,
+    /// Requests allowed for every client on `/login` and `/create`.
+    #[serde(default = "default_global_limit")]
+    pub global: LimitConfig,
+    /// Requests allowed per client IP on actors and their inboxes.
+    #[serde(default = "default_federation_limit")]
+    pub federation: LimitConfig,
+    /// Failed logins allowed per account, from shortest to longest lockout.
+    #[serde(default = "default_account_limits")]
+    pub account: Vec<LimitConfig>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            backend: RateLimitBackend::default(),
+            ip: default_ip_limit(),
+            global: default_global_limit(),
+            federation: default_federation_limit(),
+            account: default_account_limits(),
+        }
+    }
+}
+
+/// Cache in front of revoked access tokens.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct DenylistConfig {
+    /// Cached entries of each kind.
+    pub capacity: NonZeroUsize,
+    /// Seconds a user token generation, or a token found not revoked, is
+    /// cached.
+    pub cache_ttl: u64,
+}
+
+impl Default for DenylistConfig {
+    fn default() -> Self {
+        Self {
+            capacity: NonZeroUsize::new(100_000).unwrap_or(NonZeroUsize::MIN),
+            cache_ttl: 30,
+        }
+    }
+}
+
+/// Migration of encrypted data to the current master key.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ReencryptionConfig {
+    /// Re-encrypt data in the background at startup.
+    pub enabled: bool,
+    /// Users re-encrypted per batch.
+    pub batch_size: u32,
+    /// Milliseconds between two batches.
+    pub pause: u64,
+}
+
+impl Default for ReencryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            batch_size: 100,
+            pause: 100,
+        }
+    }
+}
+
+/// Blind index used to look accounts up by email.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct EmailIndexConfig {
+    /// Also look up emails indexed with `SALT`, before `EMAIL_INDEX_KEYS`
+    /// was set.
+    pub legacy: bool,
+}
+
+impl Default for EmailIndexConfig {
+    fn default() -> Self {
+        Self { legacy: true }
+    }
+}
+
+/// System holding the master key and the token signing key.
+#[derive(Clone, Deserialize)]
+pub struct KeyManagementConfig {
+    #[serde(flatten)]
+    pub backend: KeyManagementBackend,
+    /// ID of the key wrapping data keys.
+    #[serde(default = "default_encryption_key")]
+    pub encryption_key: String,
+    /// ID of the P-256 key signing tokens. When unset, tokens are signed
+    /// with `token.private_key_pem`.
+    pub signing_key: Option<String>,
+}
+
+fn default_encryption_key() -> String {
+    "autha".to_string()
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum KeyManagementBackend {
+    /// Keystore encrypted with the `KEYSTORE_PASSPHRASE` env var.
+    File { path: String },
+    /// Token of a PKCS#11 module, unlocked with the `PKCS11_PIN` env var.
+    Pkcs11 { module: String, slot: u64 },
+    /// Vault transit engine, reached with the `VAULT_TOKEN` env var.
+    Vault {
+        address: String,
+        mount: Option<String>,
+    },
+}
+
+/// Storage of avatars and banners.
+#[derive(Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum MediaConfig {
+    Filesystem {
+        /// Directory files are written to.
+        #[serde(default = "default_media_path")]
+        path: String,
+        /// URL files are served at. When unset, files are served by Autha
+        /// under `/media`.
+        url: Option<String>,
+    },
+    S3 {
+        /// Endpoint such as `https://s3.eu-west-3.amazonaws.com`.
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        /// URL the bucket is served at, e.g. through a CDN.
+        public_url: Option<String>,
+    },
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self::Filesystem {
+            path: default_media_path(),
+            url: None,
+        }
+    }
+}
+
+fn default_media_path() -> String {
+    "media".to_string()
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// Signatures of server-to-server messages.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct FederationConfig {
+    /// PKCS#8 PEM RSA key of the instance. When unset, a key is generated
+    /// at startup.
+    pub private_key_pem: Option<String>,
+    /// Sign requests with, and also accept, draft-cavage signatures.
+    pub compatibility: bool,
+    /// Instances allowed to federate when no decision was taken about them.
+    pub peers: PeerPolicyConfig,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            private_key_pem: None,
+            compatibility: true,
+            peers: PeerPolicyConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerPolicyConfig {
+    #[default]
+    Open,
+    AllowList,
+}
+
+impl From<PeerPolicyConfig> for PeerPolicy {
+    fn from(config: PeerPolicyConfig) -> Self {
+        match config {
+            PeerPolicyConfig::Open => PeerPolicy::Open,
+            PeerPolicyConfig::AllowList => PeerPolicy::AllowList,
+        }
+    }
+}
+
+/// Limits on invitations created by users.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct InviteConfig {
+    /// Usable invitations a user can hold at once. `0` disables creation.
+    pub quota: u32,
+    /// Seconds between two invitations of the same user.
+    pub cooldown: u64,
+    /// Maximum uses of a single invitation.
+    pub max_uses: u32,
+    /// Maximum lifetime of an invitation, in seconds.
+    pub max_ttl: u64,
+}
+
+impl Default for InviteConfig {
+    fn default() -> Self {
+        Self {
+            quota: 3,
+            cooldown: 86_400,
+            max_uses: 1,
+            max_ttl: 604_800,
+        }
+    }
+}
+
+impl From<InviteConfig> for InviteQuota {
+    fn from(config: InviteConfig) -> Self {
+        InviteQuota {
+            max_active: config.quota,
+            cooldown: config.cooldown,
+            max_uses: config.max_uses,
+            max_ttl: config.max_ttl,
+        }
+    }
+}
+
+/// Public keys users may register.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PublicKeyConfig {
+    /// Accepted algorithms: `Ed25519`, `P-256`, `P-384` and `RSA`.
+    pub algorithms: Vec<String>,
+    /// Minimum size of RSA keys, in bits.
+    pub min_rsa_bits: usize,
+}
+
+impl Default for PublicKeyConfig {
+    fn default() -> Self {
+        Self {
+            algorithms: KeyAlgorithm::ALL
+                .iter()
+                .map(|algorithm| algorithm.to_string())
+                .collect(),
+            min_rsa_bits: KeyPolicy::DEFAULT_MIN_RSA_BITS,
+        }
+    }
+}
+
+impl TryFrom<PublicKeyConfig> for KeyPolicy {
+    type Error = KeyError;
+
+    fn try_from(config: PublicKeyConfig) -> Result<Self, Self::Error> {
+        Ok(KeyPolicy::new(
+            config
+                .algorithms
+                .iter()
+                .map(|algorithm| algorithm.parse())
+                .collect::<Result<_, _>>()?,
+            config.min_rsa_bits,
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    #[default]
+    Memory,
+    Postgres,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct LimitConfig {
+    pub limit: u32,
+    /// Window length in seconds.
+    pub window: u64,
+}
+
+impl From<LimitConfig> for RateLimitPolicy {
+    fn from(config: LimitConfig) -> Self {
+        RateLimitPolicy::new(config.limit, config.window)
+    }
+}
+
+fn default_ip_limit() -> LimitConfig {
+    LimitConfig {
+        limit: 10,
+        window: 60,
+    }
+}
+
+fn default_global_limit() -> LimitConfig {
+    LimitConfig {
+        limit: 600,
+        window: 60,
+    }
+}
+
+fn default_federation_limit() -> LimitConfig {
+    LimitConfig {
+        limit: 300,
+        window: 60,
+    }
+}
+
+fn default_account_limits() -> Vec<LimitConfig> {
+    vec![
+        LimitConfig {
+            limit: 5,
+            window: 300,
+        },
+        LimitConfig {
+            limit: 10,
+            window: 3_600,
+        },
+        LimitConfig {
+            limit: 20,
+            window: 86_400,
+        },
+    ]
+}
+
 impl ServerConfig {
     /// Load configuration from a YAML file.
     pub fn load(
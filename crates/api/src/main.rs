@@ -11,25 +11,60 @@ mod telemetry;
 
 use std::env;
 use std::future::ready;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
+use adapters::inbound::http::authorization::RequireScopes;
+use adapters::inbound::http::client_ip::TrustedProxies;
+use adapters::inbound::http::rate_limit::RateLimitLayer;
+use adapters::inbound::http::signature::SignatureVerifier;
 use adapters::inbound::{http, ldap};
+use adapters::outbound::activitypub::{
+    HttpActorResolver, RequestSigner, SignatureStyle,
+};
+use adapters::outbound::denylist::LruTokenDenylist;
+use adapters::outbound::kms::{
+    FileKeystore, KeyKind, Pkcs11KeyManagement, VaultTransit,
+};
 use adapters::outbound::mail::RabbitMqMailer;
+use adapters::outbound::media::s3::S3Config;
+use adapters::outbound::media::{
+    FilesystemMediaStore, S3MediaStore, WebpImageProcessor,
+};
+use adapters::outbound::password::{
+    BloomFilter, BreachCorpus, HibpRanges, LocalPasswordPolicy,
+};
 use adapters::outbound::persistence::postgres;
-use adapters::outbound::{crypto, token};
-use application::ports::outbound::{LdapPort, Mailer};
-use axum::routing::{get, patch, post};
-use axum::{Router, middleware as axum_middleware};
-use config::ServerConfig;
+use adapters::outbound::rate_limit::InMemoryRateLimiter;
+use adapters::outbound::{crypto, geo, token};
+use application::dto::StatusDto;
+use application::ports::inbound::Reencrypt;
+use application::ports::outbound::{
+    CryptoPort, KeyManagement, LdapPort, Mailer, MediaStore, PasswordPolicy,
+    RateLimiter, TokenDenylist,
+};
+use axum::extract::DefaultBodyLimit;
+use axum::handler::Handler;
+use axum::routing::{delete, get, patch, post, put};
+use axum::{Extension, Router, middleware as axum_middleware};
+use config::{
+    Argon2Config, KeyManagementBackend, KeyManagementConfig, MediaConfig,
+    RateLimitBackend, ServerConfig,
+};
 use opentelemetry::trace::TracerProvider;
+use tower_http::services::ServeDir;
 use tower_http::timeout::RequestBodyTimeoutLayer;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::util::SubscriberInitExt;
 
-use crate::middleware::auth_middleware;
+use crate::middleware::{auth_middleware, sign_response, verify_signature};
+
+/// ID of the key set with `MASTER_KEY`.
+const LEGACY_KEY_ID: &str = "default";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -89,26 +124,90 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Arc::new(postgres::token_repository::PgRefreshTokenRepository::new(
             db_pool.clone(),
         ));
+    let invite_repo = Arc::new(
+        postgres::invite_repository::PgInviteRepository::new(db_pool.clone()),
+    );
+    let moderation_repo = Arc::new(
+        postgres::moderation_repository::PgModerationRepository::new(
+            db_pool.clone(),
+        ),
+    );
+    let role_repo = Arc::new(
+        postgres::role_repository::PgRoleRepository::new(db_pool.clone()),
+    );
+    let audit_log_key = zeroize::Zeroizing::new(
+        env::var("AUDIT_LOG_KEY").expect("AUDIT_LOG_KEY env var is required"),
+    );
+    let audit_log = Arc::new(postgres::audit_log::PgAuditLog::new(
+        db_pool.clone(),
+        audit_log_key.as_bytes(),
+    ));
+    let actor_key_repo =
+        Arc::new(postgres::actor_key_repository::PgActorKeyRepository::new(
+            db_pool.clone(),
+        ));
+    let inbox_repo = Arc::new(
+        postgres::inbox_repository::PgInboxRepository::new(db_pool.clone()),
+    );
+    let peer_repo = Arc::new(
+        postgres::peer_repository::PgPeerRepository::new(db_pool.clone()),
+    );
 
     let clock = Arc::new(adapters::outbound::clock::SystemClock);
 
-    let master_key = zeroize::Zeroizing::new(
-        env::var("MASTER_KEY")
-            .expect("MASTER_KEY env var is required")
-            .into_bytes(),
+    let rate_limiter: Arc<dyn RateLimiter> = match config.rate_limit.backend {
+        RateLimitBackend::Memory => {
+            Arc::new(InMemoryRateLimiter::new(clock.clone()))
+        },
+        RateLimitBackend::Postgres => {
+            Arc::new(postgres::rate_limiter::PgRateLimiter::new(
+                db_pool.clone(),
+                clock.clone(),
+            ))
+        },
+    };
+    let trusted_proxies = TrustedProxies::parse(&config.trusted_proxies)
+        .map_err(|network| format!("invalid trusted proxy: {network}"))?;
+    let token_denylist: Arc<dyn TokenDenylist> = Arc::new(
+        LruTokenDenylist::new(clock.clone())
+            .with_capacity(config.denylist.capacity)
+            .with_cache_ttl(config.denylist.cache_ttl)
+            .with_fallback(Arc::new(
+                postgres::token_denylist::PgTokenDenylist::new(
+                    db_pool.clone(),
+                ),
+            )),
     );
+    let ip_policy = config.rate_limit.ip.into();
+    let global_policy = config.rate_limit.global.into();
+    let federation_policy = config.rate_limit.federation.into();
+
     let salt = env::var("SALT")
         .expect("SALT env var is required")
         .into_bytes();
 
-    let crypto = Arc::new(crypto::CryptoAdapter::new(
-        clock.clone(),
-        master_key,
-        salt,
-        config.argon2.memory_cost,
-        config.argon2.iterations,
-        config.argon2.parallelism,
-    )?);
+    let key_management = config
+        .key_management
+        .as_ref()
+        .map(|cfg| key_management(cfg).map(|kms| (cfg, kms)))
+        .transpose()?;
+    let crypto = Arc::new(
+        crypto::CryptoAdapter::new(
+            clock.clone(),
+            keyring(
+                &salt,
+                key_management.as_ref().map(|(cfg, kms)| {
+                    (cfg.encryption_key.as_str(), kms.clone())
+                }),
+            )?,
+            salt.clone(),
+            blind_index(salt, config.email_index.legacy)?,
+            config.argon2.memory_cost,
+            config.argon2.iterations,
+            config.argon2.parallelism,
+        )?
+        .with_hashing_pool(hashing_pool(&config.argon2)),
+    );
     let mailer = if let Some(cfg) = &config.mail {
         Some(Arc::new(
             RabbitMqMailer::new(
@@ -122,12 +221,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         None
     };
-    let token_signer = token::jwt::TokenSigner::new(
-        &config.token.key_id,
-        &config.name,
-        &config.token.public_key_pem,
-        &config.token.private_key_pem,
-    )?;
+    let token_signer = match &key_management {
+        Some((cfg, kms)) if let Some(signing_key) = &cfg.signing_key => {
+            token::jwt::TokenSigner::managed(
+                &config.token.key_id,
+                &config.name,
+                kms.clone(),
+                signing_key,
+            )
+            .await?
+        },
+        _ => token::jwt::TokenSigner::new(
+            &config.token.key_id,
+            &config.name,
+            &config.token.public_key_pem,
+            &config.token.private_key_pem,
+        )?,
+    };
     let refresh_manager = token::SecureRefreshTokenManager::new();
     let token =
         Arc::new(token::TokenAdapter::new(token_signer, refresh_manager));
@@ -141,64 +251,505 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     let telemetry_adapter =
         Arc::new(adapters::outbound::telemetry::TracingTelemetry);
+    // Directory to serve when media are not served by another server.
+    let mut media_dir = None;
+    let media_store: Arc<dyn MediaStore> = match config.media.clone() {
+        MediaConfig::Filesystem { path, url } => {
+            let url = url.unwrap_or_else(|| {
+                media_dir = Some(path.clone());
+                format!("{}/media", config.url.trim_end_matches('/'))
+            });
+            Arc::new(FilesystemMediaStore::new(path, &url))
+        },
+        MediaConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key_id,
+            secret_access_key,
+            public_url,
+        } => Arc::new(S3MediaStore::new(S3Config {
+            endpoint: endpoint.parse()?,
+            bucket,
+            region,
+            access_key_id,
+            secret_access_key,
+            public_url,
+        })),
+    };
+
+    let password_policy = password_policy(&config)?;
+    let key_policy = config.public_keys.clone().try_into()?;
 
     let status_uc =
         application::usecases::StatusUseCase::new(config.clone().into());
-    let create_account_uc = application::usecases::CreateAccountUseCase::new(
+    let mut create_account_uc =
+        application::usecases::CreateAccountUseCase::new(
+            account_repo.clone(),
+            refresh_token_repo.clone(),
+            crypto.clone(),
+            mailer.clone(),
+            token.clone(),
+            telemetry_adapter.clone(),
+            clock.clone(),
+        )
+        .with_password_policy(password_policy.clone())
+        .with_actor_keys(actor_key_repo.clone());
+    if config.invite_only {
+        create_account_uc =
+            create_account_uc.with_invites(invite_repo.clone());
+    }
+    let manage_invites_uc = application::usecases::ManageInvitesUseCase::new(
+        invite_repo.clone(),
+        crypto.clone(),
+        clock.clone(),
+        config.invites.into(),
+    );
+    let mut manage_sessions_uc =
+        application::usecases::ManageSessionsUseCase::new(
+            refresh_token_repo.clone(),
+            crypto.clone(),
+            token.clone(),
+            token_denylist.clone(),
+            clock.clone(),
+        )
+        .with_audit_log(audit_log.clone());
+    let mut list_security_events_uc =
+        application::usecases::ListSecurityEventsUseCase::new(
+            audit_log.clone(),
+            crypto.clone(),
+        );
+    if let Some(path) = &config.geoip {
+        let geo_locator = Arc::new(geo::MaxMindGeoLocator::open(path)?);
+        manage_sessions_uc =
+            manage_sessions_uc.with_geo_locator(geo_locator.clone());
+        list_security_events_uc =
+            list_security_events_uc.with_geo_locator(geo_locator);
+    }
+    let refresh_token_uc = application::usecases::RefreshTokenUseCase::new(
         account_repo.clone(),
         refresh_token_repo.clone(),
         crypto.clone(),
-        mailer.clone(),
         token.clone(),
-        telemetry_adapter.clone(),
         clock.clone(),
     );
     let authenticate_uc = application::usecases::AuthenticateUseCase::new(
         account_repo.clone(),
-        refresh_token_repo,
+        refresh_token_repo.clone(),
         ldap_client,
         crypto.clone(),
         token.clone(),
-        telemetry_adapter,
-        clock,
+        telemetry_adapter.clone(),
+        clock.clone(),
+    )
+    .with_audit_log(audit_log.clone())
+    .with_password_policy(password_policy.clone())
+    .with_lockout(
+        rate_limiter.clone(),
+        config
+            .rate_limit
+            .account
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect(),
+    );
+    let federation = config.federation.clone();
+    let reencryption = config.reencryption;
+    let configuration: StatusDto = config.into();
+
+    let key_id = application::usecases::instance_key_id(&configuration.url);
+    let request_signer = match &federation.private_key_pem {
+        Some(pem) => RequestSigner::from_pem(key_id, pem, crypto.clone())?,
+        None => {
+            tracing::warn!(
+                "no federation key configured, generating one for this run"
+            );
+            let (_, private_key) = crypto.signature_keys().generate().await?;
+            RequestSigner::new(key_id, private_key.into(), crypto.clone())
+        },
+    };
+    let request_signer = Arc::new(if federation.compatibility {
+        request_signer.with_style(SignatureStyle::Cavage)
+    } else {
+        request_signer
+    });
+    let instance_key = request_signer.public_key_pem()?;
+
+    // Development instances federate with each other over plain HTTP, on
+    // local networks.
+    let actor_resolver =
+        HttpActorResolver::new()?.with_signer(request_signer.clone());
+    let actor_resolver =
+        Arc::new(if configuration.url.starts_with("http://") {
+            actor_resolver.allow_http()?.allow_private_addresses()?
+        } else {
+            actor_resolver
+        });
+    let peer_trust_uc =
+        Arc::new(application::usecases::PeerTrustUseCase::new(
+            peer_repo,
+            role_repo.clone(),
+            moderation_repo.clone(),
+            clock.clone(),
+            federation.peers.into(),
+        ));
+    let signature_verifier = SignatureVerifier::new(
+        actor_resolver,
+        crypto.clone(),
+        peer_trust_uc.clone(),
+        &configuration.url,
     );
+    let signature_verifier = Arc::new(if federation.compatibility {
+        signature_verifier.with_cavage()
+    } else {
+        signature_verifier
+    });
     let get_user_uc = application::usecases::GetUserUseCase::new(
         account_repo.clone(),
+        actor_key_repo.clone(),
         token.clone(),
-        config.into(),
+        clock.clone(),
+        configuration.clone(),
+    );
+    let federation_uc = application::usecases::FederationUseCase::new(
+        account_repo.clone(),
+        inbox_repo,
+        clock.clone(),
+        configuration,
+        instance_key,
+    );
+    let logout_uc = application::usecases::LogoutUseCase::new(
+        refresh_token_repo.clone(),
+        crypto.clone(),
+        token_denylist.clone(),
+        clock.clone(),
+    )
+    .with_audit_log(audit_log.clone());
+    let reauthenticate_uc = application::usecases::ReauthenticateUseCase::new(
+        account_repo.clone(),
+        refresh_token_repo.clone(),
+        crypto.clone(),
+        token.clone(),
+        clock.clone(),
+    )
+    .with_audit_log(audit_log.clone());
+    let delete_account_uc = application::usecases::DeleteAccountUseCase::new(
+        account_repo.clone(),
+        refresh_token_repo.clone(),
+        token_denylist.clone(),
+        clock.clone(),
+    )
+    .with_audit_log(audit_log.clone());
+    let moderate_account_uc =
+        application::usecases::ModerateAccountUseCase::new(
+            account_repo.clone(),
+            moderation_repo.clone(),
+            role_repo.clone(),
+            refresh_token_repo.clone(),
+            token_denylist.clone(),
+            clock.clone(),
+        )
+        .with_audit_log(audit_log.clone());
+    let administer_accounts_uc =
+        application::usecases::AdministerAccountsUseCase::new(
+            account_repo.clone(),
+            role_repo,
+            moderation_repo,
+            refresh_token_repo.clone(),
+            invite_repo,
+            token_denylist.clone(),
+            crypto.clone(),
+            clock.clone(),
+        )
+        .with_audit_log(audit_log.clone());
+    if reencryption.enabled {
+        let reencrypt_uc = application::usecases::ReencryptUseCase::new(
+            Arc::new(
+                postgres::encrypted_field_repository::PgEncryptedFieldRepository::new(
+                    db_pool.clone(),
+                ),
+            ),
+            crypto.clone(),
+        );
+        tokio::spawn(reencrypt(
+            reencrypt_uc,
+            reencryption.batch_size,
+            Duration::from_millis(reencryption.pause),
+        ));
+    }
+    let manage_media_uc = application::usecases::ManageMediaUseCase::new(
+        account_repo.clone(),
+        Arc::new(WebpImageProcessor::new()),
+        media_store,
+        crypto.clone(),
+        clock.clone(),
     );
     let update_user_uc = application::usecases::UpdateUserUseCase::new(
         account_repo,
-        crypto,
+        crypto.clone(),
         mailer,
-    );
+        refresh_token_repo,
+        token_denylist.clone(),
+        clock,
+    )
+    .with_audit_log(audit_log)
+    .with_password_policy(password_policy)
+    .with_key_policy(key_policy);
     let state = state::AppState {
         status: Arc::new(status_uc),
         create_account: Arc::new(create_account_uc),
         authenticate: Arc::new(authenticate_uc),
         get_user: Arc::new(get_user_uc),
         update_user: Arc::new(update_user_uc),
+        delete_account: Arc::new(delete_account_uc),
+        reauthenticate: Arc::new(reauthenticate_uc),
+        refresh_token: Arc::new(refresh_token_uc),
+        manage_invites: Arc::new(manage_invites_uc),
+        manage_sessions: Arc::new(manage_sessions_uc),
+        list_security_events: Arc::new(list_security_events_uc),
+        manage_media: Arc::new(manage_media_uc),
+        logout: Arc::new(logout_uc),
+        moderate_account: Arc::new(moderate_account_uc),
+        administer_accounts: Arc::new(administer_accounts_uc),
+        federation: Arc::new(federation_uc),
+        manage_peers: peer_trust_uc,
+        signature_verifier,
+        request_signer,
         token,
+        crypto,
+        token_denylist,
+    };
+
+    let auth_rate_limit = |scope: &str| {
+        RateLimitLayer::new(
+            rate_limiter.clone(),
+            telemetry_adapter.clone(),
+            scope,
+        )
+        .per_ip(ip_policy)
+        .global(global_policy)
     };
+    let create_rate_limit = auth_rate_limit("create");
+    let login_rate_limit = auth_rate_limit("login");
+    let refresh_rate_limit = auth_rate_limit("refresh");
+    let reauthenticate_rate_limit = auth_rate_limit("reauthenticate");
+    let media_rate_limit = auth_rate_limit("media");
+    let federation_rate_limit = RateLimitLayer::new(
+        rate_limiter.clone(),
+        telemetry_adapter.clone(),
+        "federation",
+    )
+    .per_ip(federation_policy);
+
+    let auth =
+        axum_middleware::from_fn_with_state(state.clone(), auth_middleware);
+    let read_account = || RequireScopes::new(["read:account"]);
+    let write_account = || RequireScopes::new(["write:account"]);
+
+    // Role checks happen in the application layer.
+    let admin = Router::new()
+        .route(
+            "/accounts",
+            get(http::admin::search_accounts_handler)
+                .route_layer(read_account()),
+        )
+        .route(
+            "/accounts/{id}",
+            get(http::admin::get_account_handler.layer(read_account()))
+                .delete(
+                    http::admin::delete_account_handler.layer(write_account()),
+                ),
+        )
+        .route(
+            "/accounts/{id}/password-reset",
+            post(http::admin::force_password_reset_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/accounts/{id}/totp",
+            delete(http::admin::reset_totp_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/accounts/{id}/sessions",
+            delete(http::admin::revoke_sessions_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/accounts/{id}/flags",
+            patch(http::admin::set_flags_handler).route_layer(write_account()),
+        )
+        .route(
+            "/accounts/{id}/suspension",
+            post(http::admin::suspend_handler)
+                .delete(http::admin::unsuspend_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/accounts/{id}/actions",
+            get(http::admin::list_actions_handler).route_layer(read_account()),
+        )
+        .route(
+            "/accounts/{id}/roles/{role}",
+            put(http::admin::grant_role_handler)
+                .delete(http::admin::revoke_role_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/invites",
+            get(http::admin::list_invites_handler.layer(read_account())).post(
+                http::admin::create_invite_handler.layer(write_account()),
+            ),
+        )
+        .route(
+            "/peers",
+            get(http::admin::list_peers_handler).route_layer(read_account()),
+        )
+        .route(
+            "/peers/{host}",
+            put(http::admin::set_peer_status_handler)
+                .delete(http::admin::reset_peer_status_handler)
+                .route_layer(write_account()),
+        )
+        .route(
+            "/invites/{code}",
+            delete(http::admin::revoke_invite_handler)
+                .route_layer(write_account()),
+        )
+        .route_layer(auth.clone());
+
+    // Server-to-server routes, whose requests and responses may be signed.
+    let federation = Router::new()
+        .route("/actor", get(http::activitypub::instance_actor_handler))
+        .route(
+            "/actor/outbox",
+            get(http::activitypub::instance_outbox_handler),
+        )
+        .route(
+            "/actor/inbox",
+            post(http::activitypub::instance_inbox_handler),
+        )
+        .route("/users/{id}", get(http::get_user::get_user_handler))
+        .route("/users/{id}/outbox", get(http::activitypub::outbox_handler))
+        .route(
+            "/users/{id}/followers",
+            get(http::activitypub::followers_handler),
+        )
+        .route(
+            "/users/{id}/following",
+            get(http::activitypub::following_handler),
+        )
+        .route("/users/{id}/inbox", post(http::activitypub::inbox_handler))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            verify_signature,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            sign_response,
+        ))
+        .layer(DefaultBodyLimit::max(http::activitypub::MAX_ACTIVITY_SIZE))
+        // Throttled before signatures, whose keys may be fetched remotely.
+        .layer(federation_rate_limit);
 
     let app = Router::new()
         .route("/metrics", get(move || ready(recorder_handle.render())))
         .route("/status.json", get(http::status::status_handler))
-        .route("/create", post(http::create::create_account_handler))
-        .route("/login", post(http::login::login_handler))
-        .route("/users/:id", get(http::get_user::get_user_handler))
+        .route(
+            "/create",
+            post(http::create::create_account_handler)
+                .route_layer(create_rate_limit),
+        )
+        .route(
+            "/login",
+            post(http::login::login_handler).route_layer(login_rate_limit),
+        )
+        .route(
+            "/refresh",
+            post(http::refresh::refresh_token_handler)
+                .route_layer(refresh_rate_limit),
+        )
+        .route(
+            "/reauthenticate",
+            post(http::reauthenticate::reauthenticate_handler)
+                .route_layer(auth.clone())
+                .route_layer(reauthenticate_rate_limit),
+        )
+        .route(
+            "/logout",
+            post(http::logout::logout_handler).route_layer(auth.clone()),
+        )
         .route(
             "/users/@me",
-            patch(http::update_user::handler).route_layer(
-                axum_middleware::from_fn_with_state(
-                    state.clone(),
-                    auth_middleware,
-                ),
-            ),
+            patch(http::update_user::handler)
+                .delete(http::delete_account::delete_account_handler)
+                .route_layer(write_account())
+                .route_layer(auth.clone()),
+        )
+        .route(
+            "/users/@me/invites",
+            get(http::invite::list_invites_handler.layer(read_account()))
+                .post(
+                    http::invite::create_invite_handler.layer(write_account()),
+                )
+                .route_layer(auth.clone()),
+        )
+        .route(
+            "/users/@me/invites/{code}",
+            delete(http::invite::revoke_invite_handler)
+                .route_layer(write_account())
+                .route_layer(auth.clone()),
+        )
+        .route(
+            "/users/@me/sessions",
+            get(http::session::list_sessions_handler.layer(read_account()))
+                .delete(
+                    http::session::revoke_other_sessions_handler
+                        .layer(write_account()),
+                )
+                .route_layer(auth.clone()),
+        )
+        .route(
+            "/users/@me/avatar",
+            put(http::media::upload_avatar_handler)
+                .delete(http::media::remove_avatar_handler)
+                .layer(DefaultBodyLimit::max(http::media::MAX_UPLOAD_SIZE))
+                .route_layer(write_account())
+                .route_layer(auth.clone())
+                .route_layer(media_rate_limit.clone()),
+        )
+        .route(
+            "/users/@me/banner",
+            put(http::media::upload_banner_handler)
+                .delete(http::media::remove_banner_handler)
+                .layer(DefaultBodyLimit::max(http::media::MAX_UPLOAD_SIZE))
+                .route_layer(write_account())
+                .route_layer(auth.clone())
+                .route_layer(media_rate_limit),
+        )
+        .route(
+            "/users/@me/security-events",
+            get(http::security_events::list_security_events_handler)
+                .route_layer(read_account())
+                .route_layer(auth.clone()),
         )
+        .route(
+            "/users/@me/sessions/{id}",
+            delete(http::session::revoke_session_handler)
+                .route_layer(write_account())
+                .route_layer(auth),
+        )
+        .nest("/admin", admin)
+        .merge(federation)
         .with_state(state)
         .route_layer(axum_middleware::from_fn(telemetry::track))
-        .layer(RequestBodyTimeoutLayer::new(Duration::from_secs(5)));
+        .layer(RequestBodyTimeoutLayer::new(Duration::from_secs(5)))
+        .layer(Extension(trusted_proxies));
+    let app = match media_dir {
+        Some(path) => app.nest_service("/media", ServeDir::new(path)),
+        None => app,
+    };
 
     match env::var("UNIX_SOCKET") {
         Ok(path) => listen_unix_socket(&path, app).await,
@@ -206,13 +757,211 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+/// Open the key management system of the configuration.
+fn key_management(
+    config: &KeyManagementConfig,
+) -> Result<Arc<dyn KeyManagement>, Box<dyn std::error::Error>> {
+    let secret = |name: &'static str| {
+        env::var(name)
+            .map(zeroize::Zeroizing::new)
+            .map_err(|_| format!("{name} env var is required"))
+    };
+
+    Ok(match &config.backend {
+        KeyManagementBackend::File { path } => {
+            let passphrase = secret("KEYSTORE_PASSPHRASE")?;
+            let mut keystore = if std::path::Path::new(path).exists() {
+                FileKeystore::open(path, passphrase.as_bytes())?
+            } else {
+                FileKeystore::new()
+            };
+
+            let mut generated =
+                keystore.ensure(&config.encryption_key, KeyKind::Aes256Gcm)?;
+            if let Some(signing_key) = &config.signing_key {
+                generated |=
+                    keystore.ensure(signing_key, KeyKind::EcdsaP256)?;
+            }
+            if generated {
+                keystore.save(path, passphrase.as_bytes())?;
+                tracing::warn!(path, "generated missing keys in keystore");
+            }
+
+            Arc::new(keystore)
+        },
+        KeyManagementBackend::Pkcs11 { module, slot } => Arc::new(
+            Pkcs11KeyManagement::new(module, *slot, &secret("PKCS11_PIN")?)?,
+        ),
+        KeyManagementBackend::Vault { address, mount } => {
+            let vault =
+                VaultTransit::new(address, secret("VAULT_TOKEN")?.as_str());
+            match mount {
+                Some(mount) => Arc::new(vault.with_mount(mount)),
+                None => Arc::new(vault),
+            }
+        },
+    })
+}
+
+/// Master keys from `MASTER_KEYS`, as `id=key` pairs separated by commas
+/// with the current key first, and from `MASTER_KEY`, which encrypted data
+/// before keys had IDs.
+///
+/// With a key management system, its key is the current one and the keys
+/// of the environment are only used to decrypt.
+fn keyring(
+    salt: &[u8],
+    managed: Option<(&str, Arc<dyn KeyManagement>)>,
+) -> Result<crypto::Keyring, Box<dyn std::error::Error>> {
+    let legacy = env::var("MASTER_KEY").ok().map(zeroize::Zeroizing::new);
+    let keys = env::var("MASTER_KEYS").map(zeroize::Zeroizing::new);
+    let mut keys = keys
+        .iter()
+        .flat_map(|keys| keys.split(','))
+        .map(|key| key.split_once('=').ok_or("invalid MASTER_KEYS"));
+
+    let mut keyring = match (managed, keys.next().transpose()?) {
+        (Some((id, kms)), first) => {
+            let keyring = crypto::Keyring::managed(id, kms)?;
+            match first {
+                Some((id, key)) => {
+                    keyring.with_key(id, key.as_bytes(), salt)?
+                },
+                None => keyring,
+            }
+        },
+        (None, Some((id, key))) => {
+            crypto::Keyring::new(id, key.as_bytes(), salt)?
+        },
+        (None, None) => crypto::Keyring::new(
+            LEGACY_KEY_ID,
+            legacy
+                .as_ref()
+                .ok_or("MASTER_KEY env var is required")?
+                .as_bytes(),
+            salt,
+        )?,
+    };
+    for key in keys {
+        let (id, key) = key?;
+        keyring = keyring.with_key(id, key.as_bytes(), salt)?;
+    }
+    if let Some(legacy) = legacy &&
+        keyring.primary() != LEGACY_KEY_ID
+    {
+        keyring = keyring.with_key(LEGACY_KEY_ID, legacy.as_bytes(), salt)?;
+    }
+
+    Ok(keyring.with_legacy(LEGACY_KEY_ID))
+}
+
+/// Password policy requiring the `argon2.zxcvbn` strength and refusing
+/// breached passwords, if a corpus is configured.
+fn password_policy(
+    config: &ServerConfig,
+) -> Result<Arc<dyn PasswordPolicy>, Box<dyn std::error::Error>> {
+    let mut policy =
+        LocalPasswordPolicy::new(config.argon2.zxcvbn.unwrap_or(3));
+
+    if let Some(path) = &config.breached_passwords {
+        let corpus: Arc<dyn BreachCorpus> = if Path::new(path).is_dir() {
+            Arc::new(HibpRanges::new(path))
+        } else {
+            Arc::new(BloomFilter::open(path)?)
+        };
+        policy = policy.with_corpus(corpus);
+    }
+
+    Ok(Arc::new(policy))
+}
+
+/// Pool computing password hashes, sized from available memory unless
+/// configured.
+fn hashing_pool(cfg: &Argon2Config) -> crypto::HashingPool {
+    let concurrency = cfg.concurrency.unwrap_or_else(|| {
+        crypto::HashingPool::concurrency_for(cfg.memory_cost)
+    });
+    let queue = cfg
+        .queue
+        .unwrap_or(concurrency * crypto::HashingPool::QUEUE_FACTOR);
+    tracing::info!(concurrency, queue, "password hashing pool");
+
+    crypto::HashingPool::new(concurrency, queue)
+}
+
+/// Email blind index keyed with `EMAIL_INDEX_KEYS`, as `id=secret` pairs
+/// separated by commas with the current secret first.
+///
+/// Without it, emails are indexed with `SALT` as before.
+fn blind_index(
+    salt: Vec<u8>,
+    legacy: bool,
+) -> Result<crypto::HmacBlindIndex, Box<dyn std::error::Error>> {
+    let Ok(keys) = env::var("EMAIL_INDEX_KEYS").map(zeroize::Zeroizing::new)
+    else {
+        tracing::warn!(
+            "EMAIL_INDEX_KEYS is unset, emails are indexed with SALT"
+        );
+        return Ok(crypto::HmacBlindIndex::legacy(salt));
+    };
+
+    let mut keys = keys
+        .split(',')
+        .map(|key| key.split_once('=').ok_or("invalid EMAIL_INDEX_KEYS"));
+    let (id, secret) = keys.next().ok_or("invalid EMAIL_INDEX_KEYS")??;
+    let mut blind_index = crypto::HmacBlindIndex::new(id, secret.as_bytes())?;
+    for key in keys {
+        let (id, secret) = key?;
+        blind_index = blind_index.with_key(id, secret.as_bytes())?;
+    }
+
+    Ok(if legacy {
+        blind_index.with_legacy(salt)
+    } else {
+        blind_index
+    })
+}
+
+/// Re-encrypt, batch by batch, data encrypted with former master keys.
+async fn reencrypt(
+    reencrypt_uc: application::usecases::ReencryptUseCase,
+    batch_size: u32,
+    pause: Duration,
+) {
+    let mut after = None;
+    let mut reencrypted = 0;
+    loop {
+        match reencrypt_uc.reencrypt(after.as_ref(), batch_size).await {
+            Ok(progress) => {
+                reencrypted += progress.reencrypted;
+                match progress.next {
+                    Some(next) => after = Some(next),
+                    None => break,
+                }
+            },
+            Err(err) => {
+                tracing::error!(%err, "re-encryption stopped");
+                return;
+            },
+        }
+
+        tokio::time::sleep(pause).await;
+    }
+
+    tracing::info!(reencrypted, "re-encryption finished");
+}
+
 /// Start a TCP listener.
 async fn listen_tcp(app: Router) -> Result<(), Box<dyn std::error::Error>> {
     let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let addr = format!("0.0.0.0:{}", port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("listening on {}", listener.local_addr()?);
-    Ok(axum::serve(listener, app).await?)
+    Ok(axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?)
 }
 
 /// Start a Unix socket listener.
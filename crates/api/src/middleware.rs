@@ -1,52 +1,76 @@
 //! API middlewares.
 
+use adapters::inbound::http::authentication::authenticate;
+use adapters::inbound::http::errors::HttpError;
+use axum::body::Body;
 use axum::extract::{Request, State};
-use axum::http::{StatusCode, header};
+use axum::http::StatusCode;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
-use domain::identity::id::UserId;
 
 use crate::state::AppState;
 
-const BEARER: &str = "Bearer ";
-
 /// Middleware to extract and verify the JWT authorization token.
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, Response> {
-    let auth_header = req
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|header| header.to_str().ok());
-
-    let token = match auth_header {
-        Some(header) if header.starts_with(BEARER) => {
-            header.replace(BEARER, "")
-        },
-        _ => {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                "Missing or invalid token",
-            )
-                .into_response());
-        },
-    };
+    let (user_id, claims) = authenticate(
+        state.token.signer(),
+        state.token_denylist.as_ref(),
+        req.headers(),
+    )
+    .await?;
 
-    let claims = state.token.signer().verify_token(&token).map_err(|_| {
-        (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response()
-    })?;
+    req.extensions_mut().insert(user_id);
+    req.extensions_mut().insert(claims);
 
-    let user_id = UserId::parse(&claims.sub).map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Invalid user ID in token",
-        )
-            .into_response()
-    })?;
+    Ok(next.run(req).await)
+}
 
-    req.extensions_mut().insert(user_id);
+/// Middleware verifying the signature of server-to-server requests.
+///
+/// Unsigned requests go through; handlers requiring a signature reject
+/// them.
+pub async fn verify_signature(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let req = state.signature_verifier.verify(req).await?;
 
     Ok(next.run(req).await)
 }
+
+/// Middleware signing responses for instances asking for it with
+/// `Accept-Signature`.
+pub async fn sign_response(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let requested = req.headers().contains_key("accept-signature");
+    let response = next.run(req).await;
+    if !requested {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!(%err, "failed to buffer response");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        },
+    };
+    if let Err(err) = state.request_signer.sign_response(
+        parts.status.as_u16(),
+        &mut parts.headers,
+        &body,
+    ) {
+        tracing::error!(%err, "failed to sign response");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
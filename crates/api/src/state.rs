@@ -2,10 +2,15 @@
 
 use std::sync::Arc;
 
+use adapters::inbound::http::signature::SignatureVerifier;
+use adapters::outbound::activitypub::RequestSigner;
 use application::ports::inbound::{
-    Authenticate, CreateAccount, GetUser, Status, UpdateUser,
+    AdministerAccounts, Authenticate, CreateAccount, DeleteAccount,
+    Federation, GetUser, ListSecurityEvents, Logout, ManageInvites,
+    ManageMedia, ManagePeers, ManageSessions, ModerateAccount, Reauthenticate,
+    RefreshAccessToken, Status, UpdateUser,
 };
-use application::ports::outbound::Token;
+use application::ports::outbound::{CryptoPort, Token, TokenDenylist};
 use axum::extract::FromRef;
 
 /// Shared state.
@@ -16,7 +21,23 @@ pub struct AppState {
     pub authenticate: Arc<dyn Authenticate>,
     pub get_user: Arc<dyn GetUser>,
     pub update_user: Arc<dyn UpdateUser>,
+    pub delete_account: Arc<dyn DeleteAccount>,
+    pub reauthenticate: Arc<dyn Reauthenticate>,
+    pub refresh_token: Arc<dyn RefreshAccessToken>,
+    pub manage_invites: Arc<dyn ManageInvites>,
+    pub manage_sessions: Arc<dyn ManageSessions>,
+    pub list_security_events: Arc<dyn ListSecurityEvents>,
+    pub manage_media: Arc<dyn ManageMedia>,
+    pub logout: Arc<dyn Logout>,
+    pub moderate_account: Arc<dyn ModerateAccount>,
+    pub administer_accounts: Arc<dyn AdministerAccounts>,
+    pub federation: Arc<dyn Federation>,
+    pub manage_peers: Arc<dyn ManagePeers>,
+    pub signature_verifier: Arc<SignatureVerifier>,
+    pub request_signer: Arc<RequestSigner>,
     pub token: Arc<dyn Token>,
+    pub crypto: Arc<dyn CryptoPort>,
+    pub token_denylist: Arc<dyn TokenDenylist>,
 }
 
 impl FromRef<AppState> for Arc<dyn Status> {
@@ -48,3 +69,81 @@ impl FromRef<AppState> for Arc<dyn UpdateUser> {
         Arc::clone(&state.update_user)
     }
 }
+
+impl FromRef<AppState> for Arc<dyn DeleteAccount> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.delete_account)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Reauthenticate> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.reauthenticate)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn RefreshAccessToken> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.refresh_token)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ManageInvites> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.manage_invites)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ManageSessions> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.manage_sessions)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ListSecurityEvents> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.list_security_events)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ManageMedia> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.manage_media)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Logout> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.logout)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ModerateAccount> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.moderate_account)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn AdministerAccounts> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.administer_accounts)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Federation> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.federation)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ManagePeers> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.manage_peers)
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn CryptoPort> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.crypto)
+    }
+}
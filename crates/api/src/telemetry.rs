@@ -54,6 +54,19 @@ pub fn setup_metrics_recorder() -> Result<PrometheusHandle, BuildError> {
         Unit::Bytes,
         "Total process memory in bytes."
     );
+    metrics::describe_histogram!(
+        "password_hashing_queue_seconds",
+        Unit::Seconds,
+        "Time password hashes waited for a free slot."
+    );
+    metrics::describe_gauge!(
+        "password_hashing_queued",
+        "Password hashes waiting for a free slot."
+    );
+    metrics::describe_counter!(
+        "password_hashing_rejected_total",
+        "Password hashes rejected because the queue was full."
+    );
 
     let mut system = System::new_with_specifics(RefreshKind::nothing());
     let pid = Pid::from_u32(std::process::id());
@@ -81,6 +94,10 @@ pub fn setup_metrics_recorder() -> Result<PrometheusHandle, BuildError> {
             Matcher::Full("http_requests_duration_seconds".to_string()),
             EXPONENTIAL_SECONDS,
         )?
+        .set_buckets_for_metric(
+            Matcher::Full("password_hashing_queue_seconds".to_string()),
+            EXPONENTIAL_SECONDS,
+        )?
         .install_recorder()
 }
 
@@ -6,12 +6,19 @@
 use domain::auth::email::EmailHash;
 use domain::auth::password::{Password, PasswordHash};
 use domain::identity::email::EmailAddress;
+use domain::identity::flags::UserFlags;
 use domain::identity::id::UserId;
 use domain::identity::ip::EncryptedIp;
+use domain::identity::role::Role;
+use domain::identity::suspension::Suspension;
+use domain::key::algorithm::KeyAlgorithm;
+use domain::key::format::Jwk;
 use domain::key::pem::PemFingerprint;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ApplicationError;
+
 /// Request DTO for authentication.
 pub struct AuthRequestDto {
     /// Email address (optional, mutually exclusive with user_id).
@@ -22,8 +29,73 @@ pub struct AuthRequestDto {
     pub password: String,
     /// TOTP code (optional).
     pub totp_code: Option<String>,
+    /// Replacement password, when a reset was forced on the account.
+    pub new_password: Option<String>,
     /// Client IP address.
     pub ip_address: Option<EncryptedIp>,
+    /// Device label derived from the user agent.
+    pub device: Option<String>,
+    /// Encrypted user agent, kept in the audit log.
+    pub user_agent: Option<String>,
+}
+
+/// Client making a request, as recorded in the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct ClientContextDto {
+    pub ip_address: Option<EncryptedIp>,
+    /// Encrypted user agent.
+    pub user_agent: Option<String>,
+}
+
+/// Request DTO for logout.
+pub struct LogoutRequestDto {
+    pub user_id: UserId,
+    /// `jti` of the access token used for the request.
+    pub jti: String,
+    /// Expiration of the access token used for the request.
+    pub expires_at: u64,
+    /// Session of the access token used for the request.
+    pub session_id: Option<String>,
+    /// Refresh token to revoke along with the access token.
+    pub refresh_token: Option<String>,
+    /// Revoke every session and token of the user.
+    pub everywhere: bool,
+    pub client: ClientContextDto,
+}
+
+/// Request DTO for reauthenticating before a sensitive operation.
+pub struct ReauthenticateRequestDto {
+    pub user_id: UserId,
+    /// Session of the access token used for the request.
+    pub session_id: Option<String>,
+    pub password: String,
+    /// TOTP code, required if the account has TOTP enabled.
+    pub totp_code: Option<String>,
+    /// Refresh token of the session, required without TOTP.
+    pub refresh_token: Option<String>,
+    pub client: ClientContextDto,
+}
+
+/// Response DTO for reauthentication.
+#[derive(Serialize)]
+pub struct ElevatedTokenDto {
+    /// Short-lived access token (JWT).
+    pub access_token: String,
+    /// Token type (e.g., "Bearer").
+    pub token_type: String,
+    /// Expiration time in seconds.
+    pub expires_in: u64,
+}
+
+/// Response DTO for an access token replacing the one of the request.
+#[derive(Serialize)]
+pub struct AccessTokenDto {
+    /// Access token (JWT).
+    pub access_token: String,
+    /// Token type (e.g., "Bearer").
+    pub token_type: String,
+    /// Expiration time in seconds.
+    pub expires_in: u64,
 }
 
 /// Response DTO for authentication.
@@ -54,6 +126,8 @@ pub struct CreateAccountRequestDto {
     pub invite_code: Option<String>,
     /// Client IP address.
     pub ip_address: Option<EncryptedIp>,
+    /// Device label derived from the user agent.
+    pub device: Option<String>,
 }
 
 /// Request DTO for token refresh.
@@ -65,6 +139,7 @@ pub struct RefreshTokenRequestDto {
 }
 
 /// DTO for account data (used between application and repository).
+#[derive(Clone)]
 pub struct AccountDto {
     pub id: UserId,
     pub username: String,
@@ -74,11 +149,416 @@ pub struct AccountDto {
     pub totp_secret: Option<String>,
     pub locale: String,
     pub summary: Option<String>,
+    /// URL of the profile picture.
     pub avatar: Option<String>,
-    pub flags: i32,
+    /// URL of the profile header image.
+    pub banner: Option<String>,
+    pub flags: UserFlags,
     pub created_at: u64,
     pub deleted_at: Option<u64>,
     pub public_keys: Vec<PublicKeyDto>,
+    /// Tokens issued with an older generation are revoked.
+    pub token_generation: u64,
+    /// Current or last suspension decided by a moderator.
+    pub suspension: Option<Suspension>,
+    /// The password must be changed on next login.
+    pub password_reset_required: bool,
+}
+
+impl AccountDto {
+    /// Returns `true` if the account may not be used at `now`.
+    pub fn is_suspended(&self, now: u64) -> bool {
+        self.suspension
+            .as_ref()
+            .is_some_and(|suspension| suspension.is_active(now))
+    }
+
+    /// Reject accounts that are deleted or suspended at `now`.
+    pub fn ensure_active(&self, now: u64) -> crate::error::Result<()> {
+        if let Some(date) = self.deleted_at {
+            return Err(ApplicationError::AccountDeleted { date });
+        }
+
+        match &self.suspension {
+            Some(suspension) if suspension.is_active(now) => {
+                Err(ApplicationError::AccountSuspended {
+                    until: suspension.until(),
+                })
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Request DTO for suspending an account.
+pub struct SuspendAccountDto {
+    pub user_id: UserId,
+    /// Moderator taking the action.
+    pub moderator: UserId,
+    pub reason: String,
+    /// End of the suspension, `None` until lifted.
+    pub until: Option<u64>,
+}
+
+/// Kind of moderation action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    Suspend,
+    Unsuspend,
+    SearchAccounts,
+    ViewAccount,
+    ForcePasswordReset,
+    ResetTotp,
+    RevokeSessions,
+    DeleteAccount,
+    GrantRole,
+    RevokeRole,
+    SetFlags,
+    ListInvites,
+    CreateInvite,
+    RevokeInvite,
+    ListPeers,
+    SetPeerStatus,
+}
+
+impl ModerationAction {
+    const ALL: [ModerationAction; 16] = [
+        ModerationAction::Suspend,
+        ModerationAction::Unsuspend,
+        ModerationAction::SearchAccounts,
+        ModerationAction::ViewAccount,
+        ModerationAction::ForcePasswordReset,
+        ModerationAction::ResetTotp,
+        ModerationAction::RevokeSessions,
+        ModerationAction::DeleteAccount,
+        ModerationAction::GrantRole,
+        ModerationAction::RevokeRole,
+        ModerationAction::SetFlags,
+        ModerationAction::ListInvites,
+        ModerationAction::CreateInvite,
+        ModerationAction::RevokeInvite,
+        ModerationAction::ListPeers,
+        ModerationAction::SetPeerStatus,
+    ];
+
+    /// Returns the stable name of the action.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::Suspend => "suspend",
+            ModerationAction::Unsuspend => "unsuspend",
+            ModerationAction::SearchAccounts => "search_accounts",
+            ModerationAction::ViewAccount => "view_account",
+            ModerationAction::ForcePasswordReset => "force_password_reset",
+            ModerationAction::ResetTotp => "reset_totp",
+            ModerationAction::RevokeSessions => "revoke_sessions",
+            ModerationAction::DeleteAccount => "delete_account",
+            ModerationAction::GrantRole => "grant_role",
+            ModerationAction::RevokeRole => "revoke_role",
+            ModerationAction::SetFlags => "set_flags",
+            ModerationAction::ListInvites => "list_invites",
+            ModerationAction::CreateInvite => "create_invite",
+            ModerationAction::RevokeInvite => "revoke_invite",
+            ModerationAction::ListPeers => "list_peers",
+            ModerationAction::SetPeerStatus => "set_peer_status",
+        }
+    }
+}
+
+impl std::str::FromStr for ModerationAction {
+    type Err = ApplicationError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or(ApplicationError::Unknown)
+    }
+}
+
+/// Action taken by a staff member, as kept in the moderation log.
+#[derive(Debug, Clone)]
+pub struct ModerationActionDto {
+    /// Account the action applies to, if any.
+    pub user_id: Option<UserId>,
+    /// Staff member taking the action.
+    pub moderator: UserId,
+    pub action: ModerationAction,
+    pub reason: Option<String>,
+    /// Other subject of the action, such as an invitation code, a role or
+    /// changed flags.
+    pub target: Option<String>,
+    /// End of the suspension, for `suspend` actions.
+    pub until: Option<u64>,
+    pub created_at: u64,
+}
+
+/// Query to search accounts from the administration.
+#[derive(Debug, Default)]
+pub struct AccountQueryDto {
+    /// Beginning of the user ID.
+    pub id: Option<String>,
+    /// Email address, hashed before searching.
+    pub email: Option<String>,
+    /// Hash of the email address.
+    pub email_hash: Option<String>,
+    /// Return accounts after this user ID.
+    pub after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Account filter used by the persistence layer.
+#[derive(Debug)]
+pub struct AccountSearchDto {
+    pub id_prefix: Option<String>,
+    /// Blind indexes of the email, any of which matches.
+    pub email_hashes: Vec<EmailHash>,
+    pub after: Option<UserId>,
+    pub limit: u32,
+}
+
+/// Account state as shown to staff.
+pub struct AdminAccountDto {
+    pub account: AccountDto,
+    pub roles: Vec<Role>,
+    /// Active sessions, most recently used first.
+    pub sessions: Vec<SessionDto>,
+    /// Users who, directly or not, invited this account, closest first.
+    pub invited_by: Vec<UserId>,
+}
+
+/// Kind of security event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityEventKind {
+    Login,
+    LoginFailed,
+    Reauthenticated,
+    Logout,
+    PasswordChanged,
+    EmailChanged,
+    TotpEnabled,
+    TotpDisabled,
+    KeysChanged,
+    SessionRevoked,
+    SessionsRevoked,
+    AccountDeleted,
+    /// Action taken by staff on the account.
+    AdminAction,
+}
+
+impl SecurityEventKind {
+    const ALL: [SecurityEventKind; 13] = [
+        SecurityEventKind::Login,
+        SecurityEventKind::LoginFailed,
+        SecurityEventKind::Reauthenticated,
+        SecurityEventKind::Logout,
+        SecurityEventKind::PasswordChanged,
+        SecurityEventKind::EmailChanged,
+        SecurityEventKind::TotpEnabled,
+        SecurityEventKind::TotpDisabled,
+        SecurityEventKind::KeysChanged,
+        SecurityEventKind::SessionRevoked,
+        SecurityEventKind::SessionsRevoked,
+        SecurityEventKind::AccountDeleted,
+        SecurityEventKind::AdminAction,
+    ];
+
+    /// Returns the stable name of the event.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEventKind::Login => "login",
+            SecurityEventKind::LoginFailed => "login_failed",
+            SecurityEventKind::Reauthenticated => "reauthenticated",
+            SecurityEventKind::Logout => "logout",
+            SecurityEventKind::PasswordChanged => "password_changed",
+            SecurityEventKind::EmailChanged => "email_changed",
+            SecurityEventKind::TotpEnabled => "totp_enabled",
+            SecurityEventKind::TotpDisabled => "totp_disabled",
+            SecurityEventKind::KeysChanged => "keys_changed",
+            SecurityEventKind::SessionRevoked => "session_revoked",
+            SecurityEventKind::SessionsRevoked => "sessions_revoked",
+            SecurityEventKind::AccountDeleted => "account_deleted",
+            SecurityEventKind::AdminAction => "admin_action",
+        }
+    }
+}
+
+impl std::str::FromStr for SecurityEventKind {
+    type Err = ApplicationError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or(ApplicationError::Unknown)
+    }
+}
+
+/// Security event, as kept in the audit log.
+#[derive(Debug, Clone)]
+pub struct SecurityEventDto {
+    /// Account concerned by the event, if any.
+    pub user_id: Option<UserId>,
+    pub kind: SecurityEventKind,
+    pub ip_address: Option<EncryptedIp>,
+    /// Encrypted user agent.
+    pub user_agent: Option<String>,
+    /// Free-form context, such as a failure reason or a session ID.
+    pub details: Option<String>,
+    pub created_at: u64,
+}
+
+impl SecurityEventDto {
+    /// Create an event of `user_id` triggered by `client` at `now`.
+    pub fn new(
+        user_id: &UserId,
+        kind: SecurityEventKind,
+        client: &ClientContextDto,
+        now: u64,
+    ) -> Self {
+        Self {
+            user_id: Some(user_id.clone()),
+            kind,
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            details: None,
+            created_at: now,
+        }
+    }
+
+    /// Attach context to the event.
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Security event as shown to the user concerned.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityEventInfoDto {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub details: Option<String>,
+    /// Coarse location of the client IP address.
+    pub location: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: u64,
+}
+
+/// DTO for invitation codes.
+#[derive(Debug, Clone)]
+pub struct InviteDto {
+    pub code: String,
+    /// User who created the code, `None` for administrators.
+    pub created_by: Option<UserId>,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    pub created_at: u64,
+    /// Users who joined with this code, oldest first.
+    pub used_by: Vec<UserId>,
+}
+
+impl InviteDto {
+    /// Check that the code can still be used at `now`.
+    pub fn ensure_claimable(&self, now: u64) -> crate::error::Result<()> {
+        if self.revoked_at.is_some() ||
+            self.expires_at.is_some_and(|expires_at| expires_at <= now)
+        {
+            return Err(ApplicationError::InvalidInvite);
+        }
+
+        if self.uses >= self.max_uses {
+            return Err(ApplicationError::InviteExhausted);
+        }
+
+        Ok(())
+    }
+}
+
+/// Limits applied to invitations created by users.
+#[derive(Debug, Clone, Copy)]
+pub struct InviteQuota {
+    /// Maximum number of usable invitations per user.
+    pub max_active: u32,
+    /// Seconds between two invitations of the same user.
+    pub cooldown: u64,
+    /// Maximum uses of a single invitation.
+    pub max_uses: u32,
+    /// Maximum lifetime of an invitation, in seconds.
+    pub max_ttl: u64,
+}
+
+impl InviteQuota {
+    /// Check that a user holding `invites` may create another one at `now`.
+    pub fn check(
+        &self,
+        invites: &[InviteDto],
+        now: u64,
+    ) -> crate::error::Result<()> {
+        if let Some(latest) = invites.iter().map(|i| i.created_at).max() {
+            let next = latest.saturating_add(self.cooldown);
+            if next > now {
+                return Err(ApplicationError::RateLimited {
+                    retry_after: next - now,
+                });
+            }
+        }
+
+        let active = invites
+            .iter()
+            .filter(|invite| invite.ensure_claimable(now).is_ok())
+            .count();
+        if active >= self.max_active as usize {
+            return Err(ApplicationError::InviteQuotaExceeded {
+                limit: self.max_active,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// DTO for a session, i.e. a chain of rotated refresh tokens.
+#[derive(Debug, Clone)]
+pub struct SessionDto {
+    /// Stable identifier, kept across refresh token rotations.
+    pub id: String,
+    pub user_id: UserId,
+    /// Device label derived from the user agent.
+    pub device: Option<String>,
+    /// Last known client IP address.
+    pub ip_address: Option<EncryptedIp>,
+    /// Authentication method references (RFC 8176) used at login.
+    pub amr: Vec<String>,
+    /// When the user last proved their identity for this session.
+    pub authenticated_at: u64,
+    pub created_at: u64,
+    pub last_used_at: u64,
+}
+
+/// Session as shown to its owner.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfoDto {
+    pub id: String,
+    pub device: Option<String>,
+    /// Coarse location of the last known IP address.
+    pub location: Option<String>,
+    pub created_at: u64,
+    pub last_used_at: u64,
+    /// Whether the request was made from this session.
+    pub current: bool,
+}
+
+/// Request DTO for invitation creation.
+#[derive(Debug, Default)]
+pub struct CreateInviteDto {
+    /// Accounts allowed to join with the code.
+    pub max_uses: Option<u32>,
+    /// Seconds before the code expires.
+    pub expires_in: Option<u64>,
 }
 
 /// DTO for public key data.
@@ -87,6 +567,13 @@ pub struct PublicKeyDto {
     pub id: PemFingerprint,
     pub owner: String,
     pub public_key_pem: String,
+    /// `None` for keys registered before algorithms were checked, whose
+    /// algorithm is no longer supported.
+    pub algorithm: Option<KeyAlgorithm>,
+    /// Same key as a JSON Web Key.
+    pub public_key_jwk: Option<Jwk>,
+    /// Same key as the `publicKeyMultibase` of a Multikey.
+    pub public_key_multibase: Option<String>,
     /// `yyyy-mm-dd` date.
     pub created_at: String,
 }
@@ -96,10 +583,16 @@ impl Serialize for PublicKeyDto {
     where
         S: serde::Serializer,
     {
-        let mut s = serializer.serialize_struct("PublicKey", 4)?;
+        let mut s = serializer.serialize_struct("PublicKey", 7)?;
         s.serialize_field("id", &self.id.as_str())?;
         s.serialize_field("owner", &self.owner)?;
         s.serialize_field("public_key_pem", &self.public_key_pem)?;
+        s.serialize_field(
+            "algorithm",
+            &self.algorithm.as_ref().map(KeyAlgorithm::as_str),
+        )?;
+        s.serialize_field("public_key_jwk", &self.public_key_jwk)?;
+        s.serialize_field("public_key_multibase", &self.public_key_multibase)?;
         s.serialize_field("created_at", &self.created_at)?;
         s.end()
     }
@@ -118,18 +611,275 @@ pub struct StatusDto {
     pub version: String,
 }
 
+/// Key pair an actor signs HTTP requests with.
+#[derive(Clone)]
+pub struct ActorKeyDto {
+    pub user_id: UserId,
+    pub public_key_pem: String,
+    /// Encrypted PKCS#8 DER private key.
+    pub private_key_cipher: String,
+    pub created_at: u64,
+}
+
+/// `publicKey` object of a local or remote actor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorPublicKeyDto {
+    /// Key ID, usually the actor URL with a `#main-key` fragment.
+    pub id: String,
+    /// URL of the actor owning the key.
+    pub owner: String,
+    pub public_key_pem: String,
+}
+
+/// Collections exposed by an actor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionKind {
+    Outbox,
+    Followers,
+    Following,
+}
+
+impl CollectionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollectionKind::Outbox => "outbox",
+            CollectionKind::Followers => "followers",
+            CollectionKind::Following => "following",
+        }
+    }
+}
+
+/// ActivityStreams `OrderedCollection`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollectionDto {
+    #[serde(rename = "@context")]
+    pub context: &'static str,
+    pub id: String,
+    pub r#type: &'static str,
+    pub total_items: u64,
+    pub ordered_items: Vec<serde_json::Value>,
+}
+
+impl OrderedCollectionDto {
+    /// Create an empty collection.
+    pub fn empty(id: String) -> Self {
+        Self {
+            context: "https://www.w3.org/ns/activitystreams",
+            id,
+            r#type: "OrderedCollection",
+            total_items: 0,
+            ordered_items: Vec::new(),
+        }
+    }
+}
+
+/// Actor representing the instance itself, whose key signs
+/// server-to-server requests.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceActorDto {
+    #[serde(rename = "@context")]
+    pub context: Vec<&'static str>,
+    pub id: String,
+    pub r#type: &'static str,
+    pub preferred_username: String,
+    pub name: String,
+    pub inbox: String,
+    pub outbox: String,
+    pub public_key: ActorPublicKeyDto,
+}
+
+/// Decision of an administrator about a remote instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerStatus {
+    Allowed,
+    Denied,
+}
+
+impl PeerStatus {
+    /// Returns the stable name of the status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerStatus::Allowed => "allowed",
+            PeerStatus::Denied => "denied",
+        }
+    }
+}
+
+impl std::str::FromStr for PeerStatus {
+    type Err = ApplicationError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "allowed" => Ok(PeerStatus::Allowed),
+            "denied" => Ok(PeerStatus::Denied),
+            _ => Err(ApplicationError::Unknown),
+        }
+    }
+}
+
+/// Which instances may federate when no decision was taken about them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PeerPolicy {
+    /// Any instance which is not denied.
+    #[default]
+    Open,
+    /// Only allowed instances.
+    AllowList,
+}
+
+/// Remote instance known to the trust store.
+#[derive(Debug, Clone)]
+pub struct PeerDto {
+    /// Host, with its port if not the default one.
+    pub host: String,
+    pub status: Option<PeerStatus>,
+    /// Last key a request of the instance was verified with.
+    pub key_id: Option<String>,
+    pub verified_at: Option<u64>,
+    pub updated_at: u64,
+}
+
+/// Encrypted columns of a user.
+#[derive(Debug, Clone)]
+pub struct EncryptedFieldsDto {
+    pub user_id: UserId,
+    pub email_cipher: String,
+    pub totp_secret: Option<String>,
+    /// Encrypted private key of the actor.
+    pub actor_key: Option<String>,
+    /// Distinct encrypted IP addresses of the sessions and refresh tokens.
+    pub session_ips: Vec<String>,
+}
+
+/// Progress of a re-encryption pass.
+#[derive(Debug, Clone, Default)]
+pub struct ReencryptionDto {
+    pub scanned: usize,
+    pub reencrypted: usize,
+    /// User to resume after, or `None` once every user was scanned.
+    pub next: Option<UserId>,
+}
+
+/// User exported from another system.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportedAccountDto {
+    pub id: String,
+    pub email: String,
+    /// Hash in PHC or modular crypt format, e.g. bcrypt.
+    pub password_hash: String,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Outcome of the import of a batch of users.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReportDto {
+    pub imported: Vec<UserId>,
+    /// Users whose ID or email is already taken.
+    pub skipped: Vec<UserId>,
+    /// Rejected users, by position in the batch, with the reason.
+    pub invalid: Vec<(usize, String)>,
+}
+
+/// Activity delivered to the inbox of a local actor.
+#[derive(Debug, Clone)]
+pub struct InboxActivityDto {
+    /// Recipient of the activity.
+    pub user_id: UserId,
+    /// Remote actor whose key signed the delivery.
+    pub actor: String,
+    pub kind: String,
+    /// `id` of the activity, used to ignore duplicate deliveries.
+    pub activity_id: Option<String>,
+    pub activity: serde_json::Value,
+    pub received_at: u64,
+}
+
+/// ActivityStreams `Image` object.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageDto {
+    pub r#type: &'static str,
+    pub media_type: &'static str,
+    pub url: String,
+}
+
+impl ImageDto {
+    /// Image published at `url` by the media store.
+    pub fn webp(url: impl Into<String>) -> Self {
+        Self {
+            r#type: "Image",
+            media_type: ProcessedImageDto::WEBP,
+            url: url.into(),
+        }
+    }
+}
+
+/// Image attached to a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    /// Profile picture, exposed as the actor `icon`.
+    Avatar,
+    /// Profile header, exposed as the actor `image`.
+    Banner,
+}
+
+impl MediaKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaKind::Avatar => "avatar",
+            MediaKind::Banner => "banner",
+        }
+    }
+
+    /// Width and height images are cropped and resized to.
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            MediaKind::Avatar => (400, 400),
+            MediaKind::Banner => (1500, 500),
+        }
+    }
+}
+
+/// Uploaded image once validated and re-encoded.
+#[derive(Debug, Clone)]
+pub struct ProcessedImageDto {
+    pub data: Vec<u8>,
+    pub media_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ProcessedImageDto {
+    /// Media type of every image re-encoded for publication.
+    pub const WEBP: &'static str = "image/webp";
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserResponseDto {
     #[serde(rename = "@context")]
     pub context: Vec<String>,
     pub r#type: String,
+    /// Type of the actor replaced by a `Tombstone`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub former_type: Option<String>,
     pub id: String,
     pub preferred_username: String,
     pub name: Option<String>,
-    pub icon: Vec<String>,
+    /// Key the actor signs HTTP requests with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<ActorPublicKeyDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<ImageDto>,
+    /// Profile header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageDto>,
     pub summary: Option<String>,
-    pub flags: i32,
+    pub flags: UserFlags,
     pub public_keys: Vec<PublicKeyDto>,
     pub published: String,
     pub inbox: String,
@@ -158,4 +908,68 @@ pub struct UpdateUserDto {
     pub email: Option<String>,
     pub password: Option<String>,
     pub new_password: Option<String>,
+    /// Remove the TOTP secret of the account.
+    #[serde(default)]
+    pub disable_totp: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invite() -> InviteDto {
+        InviteDto {
+            code: "code".into(),
+            created_by: None,
+            max_uses: 2,
+            uses: 1,
+            expires_at: Some(100),
+            revoked_at: None,
+            created_at: 0,
+            used_by: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_ensure_claimable() {
+        assert!(invite().ensure_claimable(99).is_ok());
+        assert!(
+            InviteDto {
+                expires_at: None,
+                ..invite()
+            }
+            .ensure_claimable(u64::MAX)
+            .is_ok()
+        );
+
+        assert!(matches!(
+            invite().ensure_claimable(100),
+            Err(ApplicationError::InvalidInvite)
+        ));
+        assert!(matches!(
+            InviteDto {
+                revoked_at: Some(50),
+                ..invite()
+            }
+            .ensure_claimable(60),
+            Err(ApplicationError::InvalidInvite)
+        ));
+        assert!(matches!(
+            InviteDto {
+                uses: 2,
+                ..invite()
+            }
+            .ensure_claimable(60),
+            Err(ApplicationError::InviteExhausted)
+        ));
+        // An expired code is invalid even once exhausted.
+        assert!(matches!(
+            InviteDto {
+                uses: 2,
+                ..invite()
+            }
+            .ensure_claimable(100),
+            Err(ApplicationError::InvalidInvite)
+        ));
+    }
 }
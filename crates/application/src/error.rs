@@ -2,6 +2,8 @@
 
 use domain::error::DomainError;
 
+use crate::ports::outbound::PasswordViolation;
+
 pub type Result<T> = std::result::Result<T, ApplicationError>;
 
 /// Errors that can occur in the application layer.
@@ -14,6 +16,61 @@ pub enum ApplicationError {
     UserNotFound,
     #[error("user is deleted since {date}")]
     AccountDeleted { date: u64 },
+    #[error("account is suspended")]
+    AccountSuspended { until: Option<u64> },
+    #[error("account is not suspended")]
+    AccountNotSuspended,
+    #[error("password must be changed before logging in")]
+    PasswordResetRequired,
+    #[error("account cannot sign in with a password")]
+    PasswordLoginDisabled,
+    #[error("account is locked")]
+    AccountLocked,
+    #[error("{field} is refused by the password policy")]
+    PasswordRejected {
+        field: String,
+        violations: Vec<PasswordViolation>,
+    },
+
+    #[error("missing role to perform this action")]
+    Forbidden,
+
+    #[error("HTTP signature is missing or invalid")]
+    InvalidSignature,
+
+    #[error("remote instance is not trusted")]
+    PeerNotTrusted,
+
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("token lacks required scopes: {}", scopes.join(" "))]
+    InsufficientScope { scopes: Vec<String> },
+    #[error("multi-factor authentication is required")]
+    MfaRequired,
+    #[error("a recent reauthentication is required")]
+    ReauthenticationRequired { max_age: u64 },
+
+    #[error("an invitation code is required")]
+    InviteRequired,
+    #[error("invitation code is invalid or expired")]
+    InvalidInvite,
+    #[error("invitation not found")]
+    InviteNotFound,
+    #[error("invitation code has no uses left")]
+    InviteExhausted,
+    #[error("no more than {limit} active invitations are allowed")]
+    InviteQuotaExceeded { limit: u32 },
+
+    #[error("too many requests, retry after {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+    #[error("server is overloaded, retry after {retry_after} seconds")]
+    Overloaded { retry_after: u64 },
+
+    #[error("image format is not supported")]
+    UnsupportedMediaType,
+    #[error("upload exceeds {limit} bytes")]
+    PayloadTooLarge { limit: usize },
 
     #[error("argument requires {expected} minimum length")]
     TooSmall { expected: usize },
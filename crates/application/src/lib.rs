@@ -3,4 +3,6 @@
 pub mod dto;
 pub mod error;
 pub mod ports;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod usecases;
@@ -0,0 +1,108 @@
+//! Account administration use case port.
+
+use async_trait::async_trait;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+
+use crate::dto::{
+    AccountDto, AccountQueryDto, AdminAccountDto, CreateInviteDto, InviteDto,
+};
+use crate::error::Result;
+
+/// Inbound port for staff managing accounts and invitations.
+///
+/// `actor` is the staff member making the call. Searching and viewing
+/// accounts or revoking their sessions requires the moderator role, every
+/// other operation the administrator role. Each call is recorded in the
+/// moderation log.
+#[async_trait]
+pub trait AdministerAccounts: Send + Sync {
+    /// Search accounts by ID prefix or email.
+    async fn search_accounts(
+        &self,
+        actor: &UserId,
+        query: AccountQueryDto,
+    ) -> Result<Vec<AccountDto>>;
+
+    /// Show the state of an account.
+    async fn account(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+    ) -> Result<AdminAccountDto>;
+
+    /// Require the user to choose a new password on next login.
+    async fn force_password_reset(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()>;
+
+    /// Remove the TOTP secret of an account.
+    async fn reset_totp(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()>;
+
+    /// End every session of an account.
+    async fn revoke_sessions(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()>;
+
+    /// Soft delete an account and end its sessions.
+    async fn delete_account(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()>;
+
+    /// Grant a staff role to a user.
+    async fn grant_role(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        role: Role,
+    ) -> Result<()>;
+
+    /// Revoke a staff role from a user.
+    async fn revoke_role(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        role: Role,
+    ) -> Result<()>;
+
+    /// Set then clear flags on an account, and returns the new flags.
+    async fn set_flags(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        set: UserFlags,
+        clear: UserFlags,
+    ) -> Result<UserFlags>;
+
+    /// List the most recent invitations of every user.
+    async fn list_invites(
+        &self,
+        actor: &UserId,
+        limit: Option<u32>,
+    ) -> Result<Vec<InviteDto>>;
+
+    /// Create an invitation owned by no user and exempt from quotas.
+    async fn create_invite(
+        &self,
+        actor: &UserId,
+        request: CreateInviteDto,
+    ) -> Result<InviteDto>;
+
+    /// Revoke any invitation.
+    async fn revoke_invite(&self, actor: &UserId, code: &str) -> Result<()>;
+}
@@ -0,0 +1,22 @@
+//! Account deletion use case port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::ClientContextDto;
+use crate::error::Result;
+use crate::ports::outbound::TokenClaims;
+
+/// Inbound port for users deleting their own account.
+#[async_trait]
+pub trait DeleteAccount: Send + Sync {
+    /// Schedule the deletion of the account and end all of its sessions.
+    ///
+    /// `claims` must prove a recent reauthentication.
+    async fn execute(
+        &self,
+        user_id: &UserId,
+        claims: &TokenClaims,
+        client: &ClientContextDto,
+    ) -> Result<()>;
+}
@@ -0,0 +1,76 @@
+//! Inbound port for ActivityPub federation.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{
+    CollectionKind, InstanceActorDto, OrderedCollectionDto, PeerDto,
+    PeerStatus,
+};
+use crate::error::Result;
+
+/// Use case interface for remote servers interacting with local actors.
+#[async_trait]
+pub trait Federation: Send + Sync {
+    /// Returns the actor of the instance.
+    fn instance_actor(&self) -> InstanceActorDto;
+
+    /// Returns the outbox of the instance actor, which is always empty.
+    fn instance_outbox(&self) -> OrderedCollectionDto;
+
+    /// Returns a collection of an actor.
+    async fn collection(
+        &self,
+        user_id: UserId,
+        kind: CollectionKind,
+    ) -> Result<OrderedCollectionDto>;
+
+    /// Accept an activity delivered to the inbox of `user_id`.
+    ///
+    /// `sender` is the owner of the key which signed the delivery, and must
+    /// be the actor of the activity.
+    async fn receive(
+        &self,
+        user_id: UserId,
+        sender: &str,
+        activity: serde_json::Value,
+    ) -> Result<()>;
+}
+
+/// Use case interface generating the key pairs of actors created before
+/// keys were generated with accounts, e.g. imported ones.
+#[async_trait]
+pub trait GenerateActorKeys: Send + Sync {
+    /// Generate the key pairs of up to `limit` users without one, and
+    /// returns how many were generated.
+    async fn generate_missing(&self, limit: u32) -> Result<usize>;
+}
+
+/// Use case interface deciding which remote instances may federate.
+#[async_trait]
+pub trait PeerTrust: Send + Sync {
+    /// Ensure requests signed by a key of `host` may be accepted.
+    async fn authorize(&self, host: &str) -> Result<()>;
+
+    /// Record that a request of `host` was verified with `key_id`.
+    async fn verified(&self, host: &str, key_id: &str) -> Result<()>;
+}
+
+/// Inbound port for staff managing the trust store.
+///
+/// Listing requires the moderator role, changing a decision the
+/// administrator role. Each call is recorded in the moderation log.
+#[async_trait]
+pub trait ManagePeers: Send + Sync {
+    /// List every known remote instance.
+    async fn list_peers(&self, actor: &UserId) -> Result<Vec<PeerDto>>;
+
+    /// Allow or deny an instance, or clear the decision with `None`.
+    async fn set_peer_status(
+        &self,
+        actor: &UserId,
+        host: &str,
+        status: Option<PeerStatus>,
+        reason: Option<String>,
+    ) -> Result<PeerDto>;
+}
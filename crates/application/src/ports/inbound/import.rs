@@ -0,0 +1,18 @@
+//! Inbound port for importing users from another system.
+
+use async_trait::async_trait;
+
+use crate::dto::{ImportReportDto, ImportedAccountDto};
+use crate::error::Result;
+
+/// Use case interface for importing users along with their password hash.
+#[async_trait]
+pub trait ImportAccounts: Send + Sync {
+    /// Import a batch of users. Nothing is kept on `dry_run`, but the
+    /// report is the same.
+    async fn import(
+        &self,
+        accounts: Vec<ImportedAccountDto>,
+        dry_run: bool,
+    ) -> Result<ImportReportDto>;
+}
@@ -0,0 +1,24 @@
+//! Inbound port for invitation management.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{CreateInviteDto, InviteDto};
+use crate::error::Result;
+
+/// Use case interface for users managing their own invitations.
+#[async_trait]
+pub trait ManageInvites: Send + Sync {
+    /// Create an invitation owned by `user_id`.
+    async fn create(
+        &self,
+        user_id: &UserId,
+        request: CreateInviteDto,
+    ) -> Result<InviteDto>;
+
+    /// List invitations owned by `user_id`.
+    async fn list(&self, user_id: &UserId) -> Result<Vec<InviteDto>>;
+
+    /// Revoke an invitation owned by `user_id`.
+    async fn revoke(&self, user_id: &UserId, code: &str) -> Result<()>;
+}
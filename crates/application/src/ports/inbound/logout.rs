@@ -0,0 +1,13 @@
+//! Logout use case port.
+
+use async_trait::async_trait;
+
+use crate::dto::LogoutRequestDto;
+use crate::error::Result;
+
+/// Inbound port for ending sessions.
+#[async_trait]
+pub trait Logout: Send + Sync {
+    /// Revoke the tokens described by `request`.
+    async fn execute(&self, request: LogoutRequestDto) -> Result<()>;
+}
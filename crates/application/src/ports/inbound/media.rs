@@ -0,0 +1,22 @@
+//! Inbound port for profile media.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{ImageDto, MediaKind};
+use crate::error::Result;
+
+/// Use case interface for users changing their avatar and banner.
+#[async_trait]
+pub trait ManageMedia: Send + Sync {
+    /// Replace the image of `kind` with an uploaded file.
+    async fn upload(
+        &self,
+        user_id: &UserId,
+        kind: MediaKind,
+        data: Vec<u8>,
+    ) -> Result<ImageDto>;
+
+    /// Remove the image of `kind`.
+    async fn remove(&self, user_id: &UserId, kind: MediaKind) -> Result<()>;
+}
@@ -1,15 +1,39 @@
 //! These traits define what the application can do.
 
+pub mod admin;
 pub mod auth;
 pub mod create_account;
+pub mod delete_account;
+pub mod federation;
 pub mod get_user;
+pub mod import;
+pub mod invite;
+pub mod logout;
+pub mod media;
+pub mod moderation;
+pub mod reauthenticate;
+pub mod reencrypt;
 pub mod refresh_token;
+pub mod security_events;
+pub mod session;
 pub mod status;
 mod update_user;
 
+pub use admin::*;
 pub use auth::*;
 pub use create_account::*;
+pub use delete_account::*;
+pub use federation::*;
 pub use get_user::*;
+pub use import::*;
+pub use invite::*;
+pub use logout::*;
+pub use media::*;
+pub use moderation::*;
+pub use reauthenticate::*;
+pub use reencrypt::*;
 pub use refresh_token::*;
+pub use security_events::*;
+pub use session::*;
 pub use status::*;
 pub use update_user::*;
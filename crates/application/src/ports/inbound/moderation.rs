@@ -0,0 +1,31 @@
+//! Account moderation use case port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{ModerationActionDto, SuspendAccountDto};
+use crate::error::Result;
+
+/// Inbound port for moderators restricting accounts.
+///
+/// Every operation requires the moderator role.
+#[async_trait]
+pub trait ModerateAccount: Send + Sync {
+    /// Suspend an account and end all of its sessions.
+    async fn suspend(&self, request: SuspendAccountDto) -> Result<()>;
+
+    /// Lift the suspension of an account.
+    async fn unsuspend(
+        &self,
+        user_id: &UserId,
+        moderator: &UserId,
+        reason: String,
+    ) -> Result<()>;
+
+    /// List moderation actions taken on an account, newest first.
+    async fn history(
+        &self,
+        moderator: &UserId,
+        user_id: &UserId,
+    ) -> Result<Vec<ModerationActionDto>>;
+}
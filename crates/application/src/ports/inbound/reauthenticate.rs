@@ -0,0 +1,16 @@
+//! Reauthentication use case port.
+
+use async_trait::async_trait;
+
+use crate::dto::{ElevatedTokenDto, ReauthenticateRequestDto};
+use crate::error::Result;
+
+/// Inbound port for proving identity again before sensitive operations.
+#[async_trait]
+pub trait Reauthenticate: Send + Sync {
+    /// Verify the factors in `request` and issue an elevated token.
+    async fn execute(
+        &self,
+        request: ReauthenticateRequestDto,
+    ) -> Result<ElevatedTokenDto>;
+}
@@ -0,0 +1,19 @@
+//! Inbound port for migrating data to the current master key.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::ReencryptionDto;
+use crate::error::Result;
+
+/// Use case interface for re-encrypting data after a master key rotation.
+#[async_trait]
+pub trait Reencrypt: Send + Sync {
+    /// Re-encrypt the data of up to `limit` users after `after` which was
+    /// encrypted with a former key.
+    async fn reencrypt(
+        &self,
+        after: Option<&UserId>,
+        limit: u32,
+    ) -> Result<ReencryptionDto>;
+}
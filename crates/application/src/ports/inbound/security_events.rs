@@ -0,0 +1,17 @@
+//! Inbound port for reading security events.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::SecurityEventInfoDto;
+use crate::error::Result;
+
+/// Use case interface for users reviewing their security history.
+#[async_trait]
+pub trait ListSecurityEvents: Send + Sync {
+    /// List the most recent security events of `user_id`, newest first.
+    async fn list(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<SecurityEventInfoDto>>;
+}
@@ -0,0 +1,40 @@
+//! Inbound port for session management.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{AccessTokenDto, ClientContextDto, SessionInfoDto};
+use crate::error::Result;
+use crate::ports::outbound::TokenClaims;
+
+/// Use case interface for users managing their sessions.
+#[async_trait]
+pub trait ManageSessions: Send + Sync {
+    /// List active sessions, flagging `current` as such.
+    async fn list(
+        &self,
+        user_id: &UserId,
+        current: Option<&str>,
+    ) -> Result<Vec<SessionInfoDto>>;
+
+    /// Revoke a single session and its access tokens on behalf of
+    /// `client`.
+    async fn revoke(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+        client: &ClientContextDto,
+    ) -> Result<()>;
+
+    /// Revoke every session except the one of `claims` on behalf of
+    /// `client`.
+    ///
+    /// Every access token is revoked too, so the one of the request is
+    /// replaced by the returned token.
+    async fn revoke_others(
+        &self,
+        user_id: &UserId,
+        claims: &TokenClaims,
+        client: &ClientContextDto,
+    ) -> Result<AccessTokenDto>;
+}
@@ -3,17 +3,23 @@
 use async_trait::async_trait;
 use domain::identity::id::UserId;
 
-use crate::dto::UpdateUserDto;
+use crate::dto::{ClientContextDto, UpdateUserDto};
 use crate::error::Result;
+use crate::ports::outbound::TokenClaims;
 
 /// Use case interface for updating user information.
 #[async_trait]
 pub trait UpdateUser: Send + Sync {
     /// Updates the user with the given ID using the provided payload.
     /// Returns the list of updated public key IDs.
+    ///
+    /// Sensitive changes require `claims` to prove a recent
+    /// reauthentication. Changes are audited as made by `client`.
     async fn update(
         &self,
         user_id: &UserId,
+        claims: &TokenClaims,
         payload: UpdateUserDto,
+        client: &ClientContextDto,
     ) -> Result<Vec<String>>;
 }
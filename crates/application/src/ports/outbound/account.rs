@@ -2,9 +2,12 @@
 
 use async_trait::async_trait;
 use domain::auth::email::EmailHash;
+use domain::auth::password::PasswordHash;
 use domain::identity::id::UserId;
 
-use crate::dto::AccountDto;
+use crate::dto::{
+    AccountDto, AccountSearchDto, EncryptedFieldsDto, MediaKind, SessionDto,
+};
 use crate::error::Result;
 
 /// Port for account/user persistence operations.
@@ -13,10 +16,10 @@ pub trait AccountRepository: Send + Sync {
     /// Find an account by user ID.
     async fn find_by_id(&self, id: &UserId) -> Result<Option<AccountDto>>;
 
-    /// Find an account by email hash.
-    async fn find_by_email_hash(
+    /// Find an account by any of the blind indexes of its email.
+    async fn find_by_email_hashes(
         &self,
-        email_hash: &EmailHash,
+        email_hashes: &[EmailHash],
     ) -> Result<Option<AccountDto>>;
 
     /// Create a new account.
@@ -25,27 +28,115 @@ pub trait AccountRepository: Send + Sync {
     /// Update an existing account.
     async fn update(&self, account: &AccountDto) -> Result<()>;
 
+    /// Replace the email blind index of an account, unless it changed since
+    /// `previous` was read. Returns whether it was replaced.
+    async fn update_email_hash(
+        &self,
+        id: &UserId,
+        previous: &EmailHash,
+        email_hash: &EmailHash,
+    ) -> Result<bool>;
+
+    /// Replace the password hash of an account, unless it changed since
+    /// `previous` was read. Returns whether it was replaced.
+    async fn update_password_hash(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool>;
+
+    /// Replace the password hash of an account required to reset it and
+    /// clear that requirement, unless the hash changed since `previous` was
+    /// read. Returns whether it was replaced.
+    async fn complete_password_reset(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool>;
+
+    /// Set the URL of the `kind` media of an account, and returns the URL
+    /// it replaced.
+    async fn replace_media(
+        &self,
+        id: &UserId,
+        kind: MediaKind,
+        url: Option<&str>,
+    ) -> Result<Option<String>>;
+
     /// Soft delete an account.
     async fn delete(&self, id: &UserId) -> Result<()>;
+
+    /// Insert accounts at once, skipping those whose ID or email is taken,
+    /// and returns the IDs of inserted ones. Nothing is kept on `dry_run`.
+    async fn import(
+        &self,
+        accounts: &[AccountDto],
+        dry_run: bool,
+    ) -> Result<Vec<UserId>>;
+
+    /// Search accounts, including deleted ones, ordered by ID.
+    async fn search(
+        &self,
+        query: &AccountSearchDto,
+    ) -> Result<Vec<AccountDto>>;
+}
+
+/// Port for rewriting encrypted columns under another key.
+#[async_trait]
+pub trait EncryptedFieldRepository: Send + Sync {
+    /// List up to `limit` users after `after`, ordered by ID.
+    async fn list(
+        &self,
+        after: Option<&UserId>,
+        limit: u32,
+    ) -> Result<Vec<EncryptedFieldsDto>>;
+
+    /// Replace the columns of a user, each unless it changed since
+    /// `previous` was read. `session_ips` are replaced pairwise. Returns
+    /// whether any was replaced.
+    async fn replace(
+        &self,
+        previous: &EncryptedFieldsDto,
+        current: &EncryptedFieldsDto,
+    ) -> Result<bool>;
 }
 
 /// Port for refresh token persistence.
+///
+/// Each refresh token belongs to a session which outlives token rotation.
 #[async_trait]
 pub trait RefreshTokenRepository: Send + Sync {
-    /// Store a new refresh token.
-    async fn store(
-        &self,
-        token: &str,
-        user_id: &UserId,
-        ip_address: Option<&String>,
-    ) -> Result<()>;
+    /// Store a new refresh token, creating or touching its session.
+    async fn store(&self, token: &str, session: &SessionDto) -> Result<()>;
+
+    /// Find the active session associated with a refresh token.
+    async fn find_session(&self, token: &str) -> Result<Option<SessionDto>>;
 
-    /// Find the user ID associated with a refresh token.
-    async fn find_user_id(&self, token: &str) -> Result<Option<UserId>>;
+    /// List active sessions of a user, most recently used first.
+    async fn list_sessions(&self, user_id: &UserId)
+    -> Result<Vec<SessionDto>>;
 
     /// Revoke a refresh token.
     async fn revoke(&self, token: &str) -> Result<()>;
 
+    /// Revoke a session and its refresh tokens.
+    ///
+    /// Returns `false` if the user has no such active session.
+    async fn revoke_session(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+    ) -> Result<bool>;
+
+    /// Revoke every session of a user, except `keep` if set.
+    async fn revoke_other_sessions(
+        &self,
+        user_id: &UserId,
+        keep: Option<&str>,
+    ) -> Result<()>;
+
     /// Revoke all refresh tokens for a user.
     async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<()>;
 }
@@ -0,0 +1,62 @@
+//! Interfaces for ActivityPub federation.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{
+    ActorKeyDto, ActorPublicKeyDto, InboxActivityDto, PeerDto, PeerStatus,
+};
+use crate::error::Result;
+
+/// Port for the signing keys of local actors.
+#[async_trait]
+pub trait ActorKeyRepository: Send + Sync {
+    /// Find the key pair of a user.
+    async fn find(&self, user_id: &UserId) -> Result<Option<ActorKeyDto>>;
+
+    /// Store a key pair, and returns `false` if the user already has one.
+    async fn create(&self, key: &ActorKeyDto) -> Result<bool>;
+
+    /// List up to `limit` active users without a key pair.
+    async fn list_missing(&self, limit: u32) -> Result<Vec<UserId>>;
+}
+
+/// Port for activities received by local actors.
+#[async_trait]
+pub trait InboxRepository: Send + Sync {
+    /// Store an activity, and returns `false` if it was already received.
+    async fn append(&self, activity: &InboxActivityDto) -> Result<bool>;
+}
+
+/// Port for the trust store of remote instances.
+#[async_trait]
+pub trait PeerRepository: Send + Sync {
+    /// Find a remote instance by host.
+    async fn find(&self, host: &str) -> Result<Option<PeerDto>>;
+
+    /// List every known remote instance.
+    async fn list(&self) -> Result<Vec<PeerDto>>;
+
+    /// Allow or deny an instance, or clear the decision with `None`.
+    async fn set_status(
+        &self,
+        host: &str,
+        status: Option<PeerStatus>,
+        now: u64,
+    ) -> Result<PeerDto>;
+
+    /// Record a request of `host` verified with `key_id`.
+    async fn record_verified(
+        &self,
+        host: &str,
+        key_id: &str,
+        now: u64,
+    ) -> Result<()>;
+}
+
+/// Port fetching the keys of remote actors.
+#[async_trait]
+pub trait ActorResolver: Send + Sync {
+    /// Fetch the public key identified by `key_id`.
+    async fn public_key(&self, key_id: &str) -> Result<ActorPublicKeyDto>;
+}
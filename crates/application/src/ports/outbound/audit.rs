@@ -0,0 +1,23 @@
+//! Security audit log port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::SecurityEventDto;
+use crate::error::Result;
+
+/// Port for the append-only log of security events.
+///
+/// Implementations must make tampering with past events detectable.
+#[async_trait]
+pub trait AuditLog: Send + Sync {
+    /// Append an event to the log.
+    async fn append(&self, event: &SecurityEventDto) -> Result<()>;
+
+    /// List the most recent events of a user, newest first.
+    async fn list_for_user(
+        &self,
+        user_id: &UserId,
+        limit: u32,
+    ) -> Result<Vec<SecurityEventDto>>;
+}
@@ -1,17 +1,37 @@
 //! Interfaces for cryptographic operations.
 
+use async_trait::async_trait;
 use domain::auth::factor::{TotpCode, TotpConfig, TotpSecret};
 use domain::auth::password::{Password, PasswordHash};
 
 use crate::error::Result;
 
 /// Port for password hashing operations.
+///
+/// Hashing is slow on purpose, so it runs away from the async runtime.
+#[async_trait]
 pub trait PasswordHasher: Send + Sync {
     /// Hash a password using a secure algorithm.
-    fn hash(&self, password: &Password) -> Result<PasswordHash>;
+    async fn hash(&self, password: &Password) -> Result<PasswordHash>;
 
     /// Verify a password against a stored hash.
-    fn verify(&self, password: &Password, hash: &PasswordHash) -> Result<()>;
+    async fn verify(
+        &self,
+        password: &Password,
+        hash: &PasswordHash,
+    ) -> Result<()>;
+
+    /// Verify a password against a hash of no account, taking as long as
+    /// [`verify`](Self::verify), so that unknown accounts cannot be told
+    /// apart by latency.
+    async fn verify_dummy(&self, password: &Password) -> Result<()>;
+
+    /// Whether `hash` is in a format which can be verified.
+    fn supports(&self, hash: &PasswordHash) -> bool;
+
+    /// Whether `hash` was computed with another algorithm or parameters
+    /// than the current ones, and should be replaced once verified.
+    fn needs_rehash(&self, hash: &PasswordHash) -> bool;
 }
 
 /// Port for TOTP (Time-based One-Time Password) operations.
@@ -50,18 +70,26 @@ pub trait TotpGenerator: Send + Sync {
 }
 
 /// Port for symmetric encryption operations.
+///
+/// Data keys may be wrapped by a [`KeyManagement`] system, so calls may wait
+/// on it.
+#[async_trait]
 pub trait SymmetricEncryption: Send + Sync {
     /// Encrypt plaintext data.
-    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+    async fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
 
     /// Decrypt ciphertext data.
-    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
 
     /// Encrypt and encode as hex string.
-    fn encrypt_to_hex(&self, plaintext: &[u8]) -> Result<String>;
+    async fn encrypt_to_hex(&self, plaintext: &[u8]) -> Result<String>;
 
     /// Decrypt from hex-encoded ciphertext.
-    fn decrypt_from_hex(&self, hex_ciphertext: &str) -> Result<Vec<u8>>;
+    async fn decrypt_from_hex(&self, hex_ciphertext: &str) -> Result<Vec<u8>>;
+
+    /// Whether a hex-encoded ciphertext was encrypted with another key than
+    /// the current one.
+    fn needs_reencryption(&self, hex_ciphertext: &str) -> bool;
 }
 
 /// Port for hashing operations (non-password, e.g., email hashing).
@@ -70,6 +98,15 @@ pub trait Hasher: Send + Sync {
     fn hash(&self, data: &[u8]) -> String;
 }
 
+/// Port for keyed blind indexes, looking up encrypted data.
+pub trait BlindIndex: Send + Sync {
+    /// Index `data` with the current secret.
+    fn index(&self, data: &[u8]) -> String;
+
+    /// Indexes of `data` with every accepted secret, current first.
+    fn candidates(&self, data: &[u8]) -> Vec<String>;
+}
+
 /// Port for secure random generation.
 pub trait SecureRandom: Send + Sync {
     /// Generate random bytes.
@@ -82,11 +119,62 @@ pub trait SecureRandom: Send + Sync {
     fn random_hex(&self, length: usize) -> Result<String>;
 }
 
+/// Port for keys held by a key management system, whose private material
+/// never leaves it.
+///
+/// Calls may wait on a remote service or a hardware module, and must not
+/// block the async runtime while doing so.
+#[async_trait]
+pub trait KeyManagement: Send + Sync {
+    /// Wrap a data key with the symmetric key `key_id`.
+    async fn wrap(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>>;
+
+    /// Unwrap a data key wrapped with the symmetric key `key_id`.
+    async fn unwrap(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> Result<Vec<u8>>;
+
+    /// Sign `message` with the ECDSA P-256 key `key_id`, using SHA-256, and
+    /// returns the fixed-size `r || s` signature.
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>>;
+
+    /// Returns the SPKI PEM public key of the key pair `key_id`.
+    async fn public_key(&self, key_id: &str) -> Result<String>;
+}
+
+/// Port for the key pairs actors sign HTTP requests with.
+///
+/// Generation is slow, so it runs away from the async runtime.
+#[async_trait]
+pub trait SignatureKeys: Send + Sync {
+    /// Generate a key pair, and returns the public key as SPKI PEM and the
+    /// private key as PKCS#8 DER.
+    async fn generate(&self) -> Result<(String, Vec<u8>)>;
+
+    /// Sign `message` with a PKCS#8 DER private key.
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>>;
+
+    /// Returns the SPKI PEM public key of a PKCS#8 DER private key.
+    fn public_key(&self, private_key: &[u8]) -> Result<String>;
+
+    /// Verify the `signature` of `message` against a PEM public key.
+    fn verify(
+        &self,
+        public_key_pem: &str,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool>;
+}
+
 /// Aggregated crypto port combining all cryptographic operations.
 pub trait CryptoPort: Send + Sync {
     fn password_hasher(&self) -> &dyn PasswordHasher;
     fn totp_generator(&self) -> &dyn TotpGenerator;
     fn symmetric_encryption(&self) -> &dyn SymmetricEncryption;
     fn hasher(&self) -> &dyn Hasher;
+    fn blind_index(&self) -> &dyn BlindIndex;
     fn secure_random(&self) -> &dyn SecureRandom;
+    fn signature_keys(&self) -> &dyn SignatureKeys;
 }
@@ -0,0 +1,43 @@
+//! Interface for access token revocation.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::error::Result;
+
+/// Port for revoked access tokens.
+///
+/// Tokens are revoked one by one through their `jti`, by session through
+/// their `sid`, or in bulk by bumping the token generation of their owner.
+#[async_trait]
+pub trait TokenDenylist: Send + Sync {
+    /// Deny `jti` until the token expires at `expires_at`.
+    async fn deny(&self, jti: &str, expires_at: u64) -> Result<()>;
+
+    /// Returns `true` if `jti` was denied.
+    async fn is_denied(&self, jti: &str) -> Result<bool>;
+
+    /// Deny every token of `session_id` until the last one expires at
+    /// `expires_at`.
+    async fn deny_session(
+        &self,
+        session_id: &str,
+        expires_at: u64,
+    ) -> Result<()> {
+        self.deny(&format!("session:{session_id}"), expires_at)
+            .await
+    }
+
+    /// Returns `true` if `session_id` was denied.
+    async fn is_session_denied(&self, session_id: &str) -> Result<bool> {
+        self.is_denied(&format!("session:{session_id}")).await
+    }
+
+    /// Current token generation of a user.
+    async fn generation(&self, user_id: &UserId) -> Result<u64>;
+
+    /// Invalidate every token issued to a user so far.
+    ///
+    /// Returns the new generation.
+    async fn bump_generation(&self, user_id: &UserId) -> Result<u64>;
+}
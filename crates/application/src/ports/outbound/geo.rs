@@ -0,0 +1,9 @@
+//! Interface for IP geolocation.
+
+use std::net::IpAddr;
+
+/// Port resolving IP addresses to coarse locations.
+pub trait GeoLocator: Send + Sync {
+    /// Returns a human-readable location such as `Paris, France`.
+    fn locate(&self, ip: IpAddr) -> Option<String>;
+}
@@ -0,0 +1,51 @@
+//! Invitation code repository port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{AccountDto, InviteDto, InviteQuota};
+use crate::error::Result;
+
+/// Port for invitation code persistence.
+#[async_trait]
+pub trait InviteRepository: Send + Sync {
+    /// Find an invitation by its code.
+    async fn find(&self, code: &str) -> Result<Option<InviteDto>>;
+
+    /// Store a new invitation.
+    ///
+    /// With a `quota`, the invitations of its creator are checked against
+    /// it in the same transaction, so concurrent requests cannot exceed it.
+    async fn create(
+        &self,
+        invite: &InviteDto,
+        quota: Option<&InviteQuota>,
+    ) -> Result<()>;
+
+    /// List invitations created by a user, newest first.
+    async fn list_by_creator(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<InviteDto>>;
+
+    /// List the most recent invitations of every user.
+    async fn list_recent(&self, limit: u32) -> Result<Vec<InviteDto>>;
+
+    /// Revoke an invitation created by `user_id`, or by anyone if `None`.
+    ///
+    /// Returns `false` if no such invitation is active.
+    async fn revoke(
+        &self,
+        code: &str,
+        user_id: Option<&UserId>,
+    ) -> Result<bool>;
+
+    /// Claim one use of `code` and create `account` in the same
+    /// transaction.
+    ///
+    /// Nothing is written if the code cannot be claimed anymore.
+    async fn claim(&self, code: &str, account: &AccountDto) -> Result<()>;
+
+    /// Users who, directly or not, invited `user_id`, closest first.
+    async fn inviter_chain(&self, user_id: &UserId) -> Result<Vec<UserId>>;
+}
@@ -0,0 +1,43 @@
+//! Interfaces for user media.
+
+use async_trait::async_trait;
+
+use crate::dto::{MediaKind, ProcessedImageDto};
+use crate::error::Result;
+
+/// Port storing public media files.
+#[async_trait]
+pub trait MediaStore: Send + Sync {
+    /// Store `data` under `key`, and returns its public URL.
+    async fn put(
+        &self,
+        key: &str,
+        media_type: &str,
+        data: Vec<u8>,
+    ) -> Result<String>;
+
+    /// Remove the file stored under `key`, if any.
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Returns the key of a URL returned by [`MediaStore::put`], or `None`
+    /// if the URL does not belong to this store.
+    fn key_of(&self, url: &str) -> Option<String>;
+}
+
+/// Port validating and re-encoding uploaded images.
+#[async_trait]
+pub trait ImageProcessor: Send + Sync {
+    /// Check the actual format of `data`, then strip its metadata, crop and
+    /// resize it to the dimensions of `kind`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::ApplicationError::UnsupportedMediaType`] if
+    /// `data` is not a supported image, or a validation error if it cannot
+    /// be decoded.
+    async fn process(
+        &self,
+        data: Vec<u8>,
+        kind: MediaKind,
+    ) -> Result<ProcessedImageDto>;
+}
@@ -1,19 +1,39 @@
 //! These traits define what the application needs from the outside world.
 
 pub mod account;
+pub mod activitypub;
+pub mod audit;
 pub mod clock;
 pub mod crypto;
+pub mod denylist;
+pub mod geo;
+pub mod invite;
 pub mod key;
 pub mod ldap;
 pub mod mailer;
+pub mod media;
+pub mod moderation;
+pub mod password_policy;
+pub mod rate_limit;
+pub mod role;
 pub mod telemetry;
 pub mod token;
 
 pub use account::*;
+pub use activitypub::*;
+pub use audit::*;
 pub use clock::*;
 pub use crypto::*;
+pub use denylist::*;
+pub use geo::*;
+pub use invite::*;
 pub use key::*;
 pub use ldap::*;
 pub use mailer::*;
+pub use media::*;
+pub use moderation::*;
+pub use password_policy::*;
+pub use rate_limit::*;
+pub use role::*;
 pub use telemetry::*;
 pub use token::*;
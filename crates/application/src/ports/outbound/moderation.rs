@@ -0,0 +1,41 @@
+//! Moderation repository port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+use domain::identity::suspension::Suspension;
+
+use crate::dto::ModerationActionDto;
+use crate::error::Result;
+
+/// Port for account moderation persistence.
+///
+/// Every change is recorded along with the moderator who made it.
+#[async_trait]
+pub trait ModerationRepository: Send + Sync {
+    /// Suspend an account, replacing any previous suspension.
+    async fn suspend(
+        &self,
+        user_id: &UserId,
+        suspension: &Suspension,
+        moderator: &UserId,
+    ) -> Result<()>;
+
+    /// Lift the suspension of an account.
+    ///
+    /// Returns `false` if the account is not suspended.
+    async fn unsuspend(
+        &self,
+        user_id: &UserId,
+        moderator: &UserId,
+        reason: &str,
+    ) -> Result<bool>;
+
+    /// Record an action taken by a staff member.
+    async fn record(&self, action: &ModerationActionDto) -> Result<()>;
+
+    /// List moderation actions taken on an account, newest first.
+    async fn list_actions(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<ModerationActionDto>>;
+}
@@ -0,0 +1,29 @@
+//! Interface for password acceptance rules.
+
+use async_trait::async_trait;
+use domain::auth::password::Password;
+
+use crate::error::Result;
+
+/// Reason a password is refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordViolation {
+    /// Strength estimate, from 0 to 4, below the required one.
+    TooWeak { score: u8, required: u8 },
+    /// Contains the ID, username or another input of the user.
+    ContainsUserInput,
+    /// Found in known data breaches, `count` times if known.
+    Breached { count: Option<u64> },
+}
+
+/// Port deciding which passwords users may choose.
+#[async_trait]
+pub trait PasswordPolicy: Send + Sync {
+    /// Returns every rule `password` breaks, given `user_inputs` such as
+    /// the ID and username it must not contain.
+    async fn check(
+        &self,
+        password: &Password,
+        user_inputs: &[&str],
+    ) -> Result<Vec<PasswordViolation>>;
+}
@@ -0,0 +1,81 @@
+//! Interface for request throttling.
+
+use async_trait::async_trait;
+
+use crate::error::Result;
+
+/// What a rate limit is counted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    /// Client IP address.
+    Ip(String),
+    /// Account identifier (email hash or user ID).
+    Account(String),
+    /// Whole instance, scoped by a route or operation name.
+    Global(String),
+}
+
+impl RateLimitKey {
+    /// Returns a stable string usable as a storage key.
+    pub fn as_storage_key(&self) -> String {
+        match self {
+            Self::Ip(ip) => format!("ip:{ip}"),
+            Self::Account(id) => format!("account:{id}"),
+            Self::Global(scope) => format!("global:{scope}"),
+        }
+    }
+}
+
+/// Maximum number of hits allowed during a sliding window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    /// Allowed hits per window.
+    pub limit: u32,
+    /// Window length in seconds.
+    pub window: u64,
+}
+
+impl RateLimitPolicy {
+    /// Create a new [`RateLimitPolicy`].
+    pub const fn new(limit: u32, window: u64) -> Self {
+        Self { limit, window }
+    }
+}
+
+/// Outcome of a rate limit check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// Request may proceed.
+    Allowed { remaining: u32 },
+    /// Request must be rejected for `retry_after` seconds.
+    Limited { retry_after: u64 },
+}
+
+impl RateLimitDecision {
+    /// Returns `true` if the request must be rejected.
+    #[inline]
+    pub fn is_limited(&self) -> bool {
+        matches!(self, Self::Limited { .. })
+    }
+}
+
+/// Port for sliding-window rate limiting.
+#[async_trait]
+pub trait RateLimiter: Send + Sync {
+    /// Record a hit for `key` and check it against `policy`.
+    async fn hit(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision>;
+
+    /// Check `key` against `policy` without recording a hit.
+    async fn peek(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision>;
+
+    /// Forget every hit recorded for `key`.
+    async fn reset(&self, key: &RateLimitKey) -> Result<()>;
+}
@@ -0,0 +1,29 @@
+//! Staff role repository port.
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+
+use crate::error::Result;
+
+/// Port for staff role persistence.
+#[async_trait]
+pub trait RoleRepository: Send + Sync {
+    /// List the roles granted to a user.
+    async fn roles(&self, user_id: &UserId) -> Result<Vec<Role>>;
+
+    /// Grant `role` to a user.
+    ///
+    /// Returns `false` if the user already had it.
+    async fn grant(
+        &self,
+        user_id: &UserId,
+        role: Role,
+        granted_by: &UserId,
+    ) -> Result<bool>;
+
+    /// Revoke `role` from a user.
+    ///
+    /// Returns `false` if the user did not have it.
+    async fn revoke(&self, user_id: &UserId, role: Role) -> Result<bool>;
+}
@@ -1,5 +1,6 @@
 //! Interface for JWT/token operations.
 
+use async_trait::async_trait;
 use domain::auth::proof::AuthenticationProof;
 
 use crate::error::Result;
@@ -21,12 +22,42 @@ pub struct TokenClaims {
     pub jti: String,
     /// Scopes/permissions.
     pub scope: String,
+    /// Session ID, if issued for a session.
+    pub sid: Option<String>,
+    /// Token generation of the user when issued.
+    pub generation: u64,
+    /// Authentication method references (RFC 8176).
+    pub amr: Vec<String>,
+    /// Time when the user authenticated (Unix timestamp).
+    pub auth_time: u64,
+}
+
+impl TokenClaims {
+    /// Returns `true` if the token was granted `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope.split_whitespace().any(|s| s == scope)
+    }
+
+    /// Returns `true` if the user authenticated using method `amr`.
+    pub fn has_amr(&self, amr: &str) -> bool {
+        self.amr.iter().any(|a| a == amr)
+    }
 }
 
 /// Port for token signing and verification.
+///
+/// Tokens may be signed by a key management system, so creating one may
+/// wait on it.
+#[async_trait]
 pub trait TokenSigner: Send + Sync {
     /// Create a signed access token for an authenticated user.
-    fn create_access_token(
+    async fn create_access_token(
+        &self,
+        proof: &AuthenticationProof,
+    ) -> Result<String>;
+
+    /// Create a short-lived access token proving a recent reauthentication.
+    async fn create_elevated_token(
         &self,
         proof: &AuthenticationProof,
     ) -> Result<String>;
@@ -0,0 +1,886 @@
+//! In-memory ports to run use cases in tests.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use domain::auth::email::EmailHash;
+use domain::auth::factor::{TotpCode, TotpConfig, TotpSecret};
+use domain::auth::password::{Password, PasswordHash};
+use domain::auth::proof::AuthenticationProof;
+use domain::identity::id::UserId;
+
+use crate::dto::{
+    AccountDto, AccountSearchDto, ActorKeyDto, InviteDto, InviteQuota,
+    MediaKind, SessionDto,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::outbound::*;
+
+/// Time spent by [`TestCrypto`] to hash or verify a password.
+pub const HASH_DELAY: Duration = Duration::from_millis(50);
+
+/// Clock stopped at a given time.
+pub struct TestClock(AtomicU64);
+
+impl TestClock {
+    pub fn new(now: u64) -> Arc<Self> {
+        Arc::new(Self(AtomicU64::new(now)))
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.0.fetch_add(seconds, Ordering::Relaxed);
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn now_millis(&self) -> u128 {
+        u128::from(self.now()) * 1000
+    }
+}
+
+/// Reversible, hence insecure, cryptography.
+///
+/// Password hashing sleeps for [`HASH_DELAY`], so that a paused Tokio clock
+/// tells how long a use case spent hashing.
+#[derive(Default)]
+pub struct TestCrypto {
+    /// Passwords hashed or verified, dummy ones included.
+    pub hashes: AtomicUsize,
+    random: AtomicU64,
+}
+
+impl TestCrypto {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Hash `password` without sleeping, e.g. to seed an account.
+    pub fn password_hash(password: &str) -> PasswordHash {
+        PasswordHash::parse(format!("$test$salt${}", hex(password.as_bytes())))
+            .unwrap()
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[async_trait]
+impl PasswordHasher for TestCrypto {
+    async fn hash(&self, password: &Password) -> Result<PasswordHash> {
+        self.hashes.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(HASH_DELAY).await;
+        Ok(Self::password_hash(password.as_str()))
+    }
+
+    async fn verify(
+        &self,
+        password: &Password,
+        hash: &PasswordHash,
+    ) -> Result<()> {
+        let expected = PasswordHasher::hash(self, password).await?;
+        if &expected == hash {
+            Ok(())
+        } else {
+            Err(domain::error::DomainError::InvalidCredentials.into())
+        }
+    }
+
+    async fn verify_dummy(&self, password: &Password) -> Result<()> {
+        PasswordHasher::hash(self, password).await.map(|_| ())
+    }
+
+    fn supports(&self, hash: &PasswordHash) -> bool {
+        hash.as_str().starts_with("$test$")
+    }
+
+    fn needs_rehash(&self, _hash: &PasswordHash) -> bool {
+        false
+    }
+}
+
+impl TotpGenerator for TestCrypto {
+    fn generate(
+        &self,
+        _secret: &TotpSecret,
+        _config: &TotpConfig,
+    ) -> Result<TotpCode> {
+        Ok(TotpCode::six_digits("123456")?)
+    }
+
+    fn generate_at(
+        &self,
+        secret: &TotpSecret,
+        config: &TotpConfig,
+        _timestamp: u64,
+    ) -> Result<TotpCode> {
+        TotpGenerator::generate(self, secret, config)
+    }
+
+    fn verify(
+        &self,
+        code: &TotpCode,
+        _secret: &TotpSecret,
+        _config: &TotpConfig,
+    ) -> Result<bool> {
+        Ok(code.value() == "123456")
+    }
+
+    fn verify_with_window(
+        &self,
+        code: &TotpCode,
+        secret: &TotpSecret,
+        config: &TotpConfig,
+        _window: u8,
+    ) -> Result<bool> {
+        TotpGenerator::verify(self, code, secret, config)
+    }
+}
+
+#[async_trait]
+impl SymmetricEncryption for TestCrypto {
+    async fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        Ok(plaintext.to_vec())
+    }
+
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        Ok(ciphertext.to_vec())
+    }
+
+    async fn encrypt_to_hex(&self, plaintext: &[u8]) -> Result<String> {
+        Ok(hex(plaintext))
+    }
+
+    async fn decrypt_from_hex(&self, hex_ciphertext: &str) -> Result<Vec<u8>> {
+        (0..hex_ciphertext.len())
+            .step_by(2)
+            .map(|i| {
+                hex_ciphertext
+                    .get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                    .ok_or(ApplicationError::Unknown)
+            })
+            .collect()
+    }
+
+    fn needs_reencryption(&self, _hex_ciphertext: &str) -> bool {
+        false
+    }
+}
+
+impl Hasher for TestCrypto {
+    fn hash(&self, data: &[u8]) -> String {
+        format!("hash:{}", hex(data))
+    }
+}
+
+impl BlindIndex for TestCrypto {
+    fn index(&self, data: &[u8]) -> String {
+        format!("index:{}", hex(data))
+    }
+
+    fn candidates(&self, data: &[u8]) -> Vec<String> {
+        vec![self.index(data)]
+    }
+}
+
+impl SecureRandom for TestCrypto {
+    fn random_bytes(&self, length: usize) -> Result<Vec<u8>> {
+        let seed = self.random.fetch_add(1, Ordering::Relaxed);
+        Ok(seed
+            .to_be_bytes()
+            .into_iter()
+            .cycle()
+            .take(length)
+            .collect())
+    }
+
+    fn random_string(&self, length: usize) -> Result<String> {
+        self.random_hex(length)
+    }
+
+    fn random_hex(&self, length: usize) -> Result<String> {
+        let mut random = hex(&self.random_bytes(length)?);
+        random.truncate(length);
+        Ok(random)
+    }
+}
+
+#[async_trait]
+impl SignatureKeys for TestCrypto {
+    async fn generate(&self) -> Result<(String, Vec<u8>)> {
+        let private_key = self.random_bytes(32)?;
+        Ok((format!("test-{}", hex(&private_key)), private_key))
+    }
+
+    fn sign(&self, _private_key: &[u8], _message: &[u8]) -> Result<Vec<u8>> {
+        unimplemented!("actor signatures are not used in tests")
+    }
+
+    fn public_key(&self, _private_key: &[u8]) -> Result<String> {
+        unimplemented!("actor signatures are not used in tests")
+    }
+
+    fn verify(
+        &self,
+        _public_key_pem: &str,
+        _message: &[u8],
+        _signature: &[u8],
+    ) -> Result<bool> {
+        unimplemented!("actor signatures are not used in tests")
+    }
+}
+
+impl CryptoPort for TestCrypto {
+    fn password_hasher(&self) -> &dyn PasswordHasher {
+        self
+    }
+
+    fn totp_generator(&self) -> &dyn TotpGenerator {
+        self
+    }
+
+    fn symmetric_encryption(&self) -> &dyn SymmetricEncryption {
+        self
+    }
+
+    fn hasher(&self) -> &dyn Hasher {
+        self
+    }
+
+    fn blind_index(&self) -> &dyn BlindIndex {
+        self
+    }
+
+    fn secure_random(&self) -> &dyn SecureRandom {
+        self
+    }
+
+    fn signature_keys(&self) -> &dyn SignatureKeys {
+        self
+    }
+}
+
+/// Build an active account of `id` with `email` and `password`.
+pub fn account(
+    crypto: &TestCrypto,
+    id: &str,
+    email: &str,
+    password: &str,
+) -> AccountDto {
+    AccountDto {
+        id: UserId::parse(id).unwrap(),
+        username: id.to_string(),
+        email_hash: EmailHash::new(crypto.index(email.as_bytes())),
+        email_cipher: hex(email.as_bytes()),
+        password_hash: TestCrypto::password_hash(password),
+        totp_secret: None,
+        locale: "en".to_string(),
+        summary: None,
+        avatar: None,
+        banner: None,
+        flags: Default::default(),
+        created_at: 0,
+        deleted_at: None,
+        public_keys: Vec::new(),
+        token_generation: 0,
+        suspension: None,
+        password_reset_required: false,
+    }
+}
+
+/// Claims of a token of `user_id` authenticated at `auth_time` with `amr`.
+pub fn claims(user_id: &UserId, amr: &[&str], auth_time: u64) -> TokenClaims {
+    TokenClaims {
+        sub: user_id.to_string(),
+        iss: "test".to_string(),
+        aud: "test".to_string(),
+        exp: auth_time + 300,
+        iat: auth_time,
+        jti: "jti".to_string(),
+        scope: String::new(),
+        sid: Some("session".to_string()),
+        generation: 0,
+        amr: amr.iter().map(|amr| amr.to_string()).collect(),
+        auth_time,
+    }
+}
+
+/// Accounts kept in memory.
+#[derive(Default)]
+pub struct MemoryAccounts(pub Mutex<Vec<AccountDto>>);
+
+impl MemoryAccounts {
+    pub fn new(accounts: impl IntoIterator<Item = AccountDto>) -> Arc<Self> {
+        Arc::new(Self(Mutex::new(accounts.into_iter().collect())))
+    }
+}
+
+#[async_trait]
+impl AccountRepository for MemoryAccounts {
+    async fn find_by_id(&self, id: &UserId) -> Result<Option<AccountDto>> {
+        let accounts = self.0.lock().unwrap();
+        Ok(accounts.iter().find(|account| &account.id == id).cloned())
+    }
+
+    async fn find_by_email_hashes(
+        &self,
+        email_hashes: &[EmailHash],
+    ) -> Result<Option<AccountDto>> {
+        let accounts = self.0.lock().unwrap();
+        Ok(accounts
+            .iter()
+            .find(|account| email_hashes.contains(&account.email_hash))
+            .cloned())
+    }
+
+    async fn create(&self, account: &AccountDto) -> Result<()> {
+        self.0.lock().unwrap().push(account.clone());
+        Ok(())
+    }
+
+    async fn update(&self, account: &AccountDto) -> Result<()> {
+        let mut accounts = self.0.lock().unwrap();
+        if let Some(stored) = accounts.iter_mut().find(|a| a.id == account.id)
+        {
+            *stored = account.clone();
+        }
+        Ok(())
+    }
+
+    async fn update_email_hash(
+        &self,
+        id: &UserId,
+        previous: &EmailHash,
+        email_hash: &EmailHash,
+    ) -> Result<bool> {
+        let mut accounts = self.0.lock().unwrap();
+        let Some(account) = accounts
+            .iter_mut()
+            .find(|a| &a.id == id && &a.email_hash == previous)
+        else {
+            return Ok(false);
+        };
+        account.email_hash = email_hash.clone();
+        Ok(true)
+    }
+
+    async fn update_password_hash(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool> {
+        let mut accounts = self.0.lock().unwrap();
+        let Some(account) = accounts
+            .iter_mut()
+            .find(|a| &a.id == id && &a.password_hash == previous)
+        else {
+            return Ok(false);
+        };
+        account.password_hash = password_hash.clone();
+        Ok(true)
+    }
+
+    async fn complete_password_reset(
+        &self,
+        id: &UserId,
+        previous: &PasswordHash,
+        password_hash: &PasswordHash,
+    ) -> Result<bool> {
+        let mut accounts = self.0.lock().unwrap();
+        let Some(account) = accounts.iter_mut().find(|a| {
+            &a.id == id &&
+                &a.password_hash == previous &&
+                a.password_reset_required
+        }) else {
+            return Ok(false);
+        };
+        account.password_hash = password_hash.clone();
+        account.password_reset_required = false;
+        Ok(true)
+    }
+
+    async fn replace_media(
+        &self,
+        id: &UserId,
+        kind: MediaKind,
+        url: Option<&str>,
+    ) -> Result<Option<String>> {
+        let mut accounts = self.0.lock().unwrap();
+        let account = accounts
+            .iter_mut()
+            .find(|a| &a.id == id)
+            .ok_or(ApplicationError::UserNotFound)?;
+        let field = match kind {
+            MediaKind::Avatar => &mut account.avatar,
+            MediaKind::Banner => &mut account.banner,
+        };
+        Ok(std::mem::replace(field, url.map(str::to_string)))
+    }
+
+    async fn delete(&self, id: &UserId) -> Result<()> {
+        let mut accounts = self.0.lock().unwrap();
+        if let Some(account) = accounts.iter_mut().find(|a| &a.id == id) {
+            account.deleted_at = Some(0);
+        }
+        Ok(())
+    }
+
+    async fn import(
+        &self,
+        _accounts: &[AccountDto],
+        _dry_run: bool,
+    ) -> Result<Vec<UserId>> {
+        unimplemented!("imports are not used in tests")
+    }
+
+    async fn search(
+        &self,
+        _query: &AccountSearchDto,
+    ) -> Result<Vec<AccountDto>> {
+        unimplemented!("searches are not used in tests")
+    }
+}
+
+/// Sessions and their refresh tokens kept in memory.
+#[derive(Default)]
+pub struct MemorySessions(pub Mutex<HashMap<String, SessionDto>>);
+
+impl MemorySessions {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for MemorySessions {
+    async fn store(&self, token: &str, session: &SessionDto) -> Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(token.to_string(), session.clone());
+        Ok(())
+    }
+
+    async fn find_session(&self, token: &str) -> Result<Option<SessionDto>> {
+        Ok(self.0.lock().unwrap().get(token).cloned())
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<SessionDto>> {
+        let sessions = self.0.lock().unwrap();
+        Ok(sessions
+            .values()
+            .filter(|session| &session.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn revoke(&self, token: &str) -> Result<()> {
+        self.0.lock().unwrap().remove(token);
+        Ok(())
+    }
+
+    async fn revoke_session(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+    ) -> Result<bool> {
+        let mut sessions = self.0.lock().unwrap();
+        let count = sessions.len();
+        sessions.retain(|_, session| {
+            &session.user_id != user_id || session.id != session_id
+        });
+        Ok(sessions.len() < count)
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        user_id: &UserId,
+        keep: Option<&str>,
+    ) -> Result<()> {
+        self.0.lock().unwrap().retain(|_, session| {
+            &session.user_id != user_id || Some(session.id.as_str()) == keep
+        });
+        Ok(())
+    }
+
+    async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<()> {
+        self.revoke_other_sessions(user_id, None).await
+    }
+}
+
+/// Invitations kept in memory.
+#[derive(Default)]
+pub struct MemoryInvites(pub Mutex<Vec<InviteDto>>);
+
+impl MemoryInvites {
+    pub fn new(invites: impl IntoIterator<Item = InviteDto>) -> Arc<Self> {
+        Arc::new(Self(Mutex::new(invites.into_iter().collect())))
+    }
+}
+
+#[async_trait]
+impl InviteRepository for MemoryInvites {
+    async fn find(&self, code: &str) -> Result<Option<InviteDto>> {
+        let invites = self.0.lock().unwrap();
+        Ok(invites.iter().find(|invite| invite.code == code).cloned())
+    }
+
+    async fn create(
+        &self,
+        invite: &InviteDto,
+        quota: Option<&InviteQuota>,
+    ) -> Result<()> {
+        let mut invites = self.0.lock().unwrap();
+        if let Some(quota) = quota {
+            let created = invites
+                .iter()
+                .filter(|i| i.created_by == invite.created_by)
+                .cloned()
+                .collect::<Vec<_>>();
+            quota.check(&created, invite.created_at)?;
+        }
+        invites.push(invite.clone());
+        Ok(())
+    }
+
+    async fn list_by_creator(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<InviteDto>> {
+        let invites = self.0.lock().unwrap();
+        let mut invites = invites
+            .iter()
+            .filter(|invite| invite.created_by.as_ref() == Some(user_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        invites.sort_by_key(|invite| std::cmp::Reverse(invite.created_at));
+        Ok(invites)
+    }
+
+    async fn list_recent(&self, limit: u32) -> Result<Vec<InviteDto>> {
+        let mut invites = self.0.lock().unwrap().clone();
+        invites.sort_by_key(|invite| std::cmp::Reverse(invite.created_at));
+        invites.truncate(limit as usize);
+        Ok(invites)
+    }
+
+    async fn revoke(
+        &self,
+        code: &str,
+        user_id: Option<&UserId>,
+    ) -> Result<bool> {
+        let mut invites = self.0.lock().unwrap();
+        let invite = invites.iter_mut().find(|invite| {
+            invite.code == code &&
+                invite.revoked_at.is_none() &&
+                user_id
+                    .is_none_or(|id| invite.created_by.as_ref() == Some(id))
+        });
+
+        Ok(invite.map(|invite| invite.revoked_at = Some(0)).is_some())
+    }
+
+    async fn claim(&self, code: &str, account: &AccountDto) -> Result<()> {
+        let mut invites = self.0.lock().unwrap();
+        let invite = invites
+            .iter_mut()
+            .find(|invite| invite.code == code)
+            .ok_or(ApplicationError::InvalidInvite)?;
+        invite.ensure_claimable(account.created_at)?;
+        invite.uses += 1;
+        invite.used_by.push(account.id.clone());
+        Ok(())
+    }
+
+    async fn inviter_chain(&self, user_id: &UserId) -> Result<Vec<UserId>> {
+        let invites = self.0.lock().unwrap();
+        let mut chain = Vec::new();
+        let mut current = user_id.clone();
+        while let Some(inviter) = invites
+            .iter()
+            .find(|invite| invite.used_by.contains(&current))
+            .and_then(|invite| invite.created_by.clone())
+        {
+            current = inviter.clone();
+            chain.push(inviter);
+        }
+        Ok(chain)
+    }
+}
+
+/// Actor keys kept in memory, for the accounts of a [`MemoryAccounts`].
+pub struct MemoryActorKeys {
+    pub keys: Mutex<Vec<ActorKeyDto>>,
+    accounts: Arc<MemoryAccounts>,
+}
+
+impl MemoryActorKeys {
+    pub fn new(accounts: Arc<MemoryAccounts>) -> Arc<Self> {
+        Arc::new(Self {
+            keys: Mutex::new(Vec::new()),
+            accounts,
+        })
+    }
+}
+
+#[async_trait]
+impl ActorKeyRepository for MemoryActorKeys {
+    async fn find(&self, user_id: &UserId) -> Result<Option<ActorKeyDto>> {
+        let keys = self.keys.lock().unwrap();
+        Ok(keys.iter().find(|key| &key.user_id == user_id).cloned())
+    }
+
+    async fn create(&self, key: &ActorKeyDto) -> Result<bool> {
+        let mut keys = self.keys.lock().unwrap();
+        if keys.iter().any(|k| k.user_id == key.user_id) {
+            return Ok(false);
+        }
+        keys.push(key.clone());
+        Ok(true)
+    }
+
+    async fn list_missing(&self, limit: u32) -> Result<Vec<UserId>> {
+        let keys = self.keys.lock().unwrap();
+        let mut missing = self
+            .accounts
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|account| account.deleted_at.is_none())
+            .filter(|account| keys.iter().all(|key| key.user_id != account.id))
+            .map(|account| account.id.clone())
+            .collect::<Vec<_>>();
+        missing.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        missing.truncate(limit as usize);
+        Ok(missing)
+    }
+}
+
+/// Denylist kept in memory, ignoring expiration.
+#[derive(Default)]
+pub struct MemoryDenylist {
+    pub denied: Mutex<Vec<String>>,
+    pub generations: Mutex<HashMap<String, u64>>,
+}
+
+impl MemoryDenylist {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+}
+
+#[async_trait]
+impl TokenDenylist for MemoryDenylist {
+    async fn deny(&self, jti: &str, _expires_at: u64) -> Result<()> {
+        self.denied.lock().unwrap().push(jti.to_string());
+        Ok(())
+    }
+
+    async fn is_denied(&self, jti: &str) -> Result<bool> {
+        Ok(self
+            .denied
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|denied| denied == jti))
+    }
+
+    async fn generation(&self, user_id: &UserId) -> Result<u64> {
+        let generations = self.generations.lock().unwrap();
+        Ok(generations
+            .get(user_id.as_str())
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn bump_generation(&self, user_id: &UserId) -> Result<u64> {
+        let mut generations = self.generations.lock().unwrap();
+        let generation = generations.entry(user_id.to_string()).or_default();
+        *generation += 1;
+        Ok(*generation)
+    }
+}
+
+/// Rate limiter counting hits in memory, ignoring windows.
+///
+/// A key is limited for a whole window once its hits exceed the limit.
+#[derive(Default)]
+pub struct MemoryRateLimiter(pub Mutex<HashMap<String, u32>>);
+
+impl MemoryRateLimiter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn decide(hits: u32, policy: &RateLimitPolicy) -> RateLimitDecision {
+        match policy.limit.checked_sub(hits) {
+            Some(remaining) => RateLimitDecision::Allowed { remaining },
+            None => RateLimitDecision::Limited {
+                retry_after: policy.window,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimiter for MemoryRateLimiter {
+    async fn hit(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        let mut hits = self.0.lock().unwrap();
+        let hits = hits.entry(key.as_storage_key()).or_default();
+        *hits += 1;
+        Ok(Self::decide(*hits, policy))
+    }
+
+    async fn peek(
+        &self,
+        key: &RateLimitKey,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision> {
+        let hits = self.0.lock().unwrap();
+        let hits = hits.get(&key.as_storage_key()).copied().unwrap_or(0);
+        Ok(Self::decide(hits + 1, policy))
+    }
+
+    async fn reset(&self, key: &RateLimitKey) -> Result<()> {
+        self.0.lock().unwrap().remove(&key.as_storage_key());
+        Ok(())
+    }
+}
+
+/// Unsigned tokens, as `|`-separated claims.
+pub struct TestToken;
+
+impl TestToken {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+
+    fn encode(proof: &AuthenticationProof, lifetime: u64) -> String {
+        [
+            proof.user_id().to_string(),
+            proof.session_id().unwrap_or_default().to_string(),
+            proof.token_generation().to_string(),
+            proof.amr().join(","),
+            proof.auth_time().to_string(),
+            (proof.authenticated_at() + lifetime).to_string(),
+        ]
+        .join("|")
+    }
+}
+
+#[async_trait]
+impl TokenSigner for TestToken {
+    async fn create_access_token(
+        &self,
+        proof: &AuthenticationProof,
+    ) -> Result<String> {
+        Ok(Self::encode(proof, 900))
+    }
+
+    async fn create_elevated_token(
+        &self,
+        proof: &AuthenticationProof,
+    ) -> Result<String> {
+        Ok(Self::encode(proof, 300))
+    }
+
+    fn verify_token(&self, token: &str) -> Result<TokenClaims> {
+        let parts = token.split('|').collect::<Vec<_>>();
+        let [sub, sid, generation, amr, auth_time, exp] = parts[..] else {
+            return Err(ApplicationError::Unknown);
+        };
+        let number = |value: &str| {
+            value.parse::<u64>().map_err(|_| ApplicationError::Unknown)
+        };
+
+        Ok(TokenClaims {
+            sub: sub.to_string(),
+            iss: "test".to_string(),
+            aud: "test".to_string(),
+            exp: number(exp)?,
+            iat: number(auth_time)?,
+            jti: token.to_string(),
+            scope: String::new(),
+            sid: (!sid.is_empty()).then(|| sid.to_string()),
+            generation: number(generation)?,
+            amr: amr
+                .split(',')
+                .filter(|amr| !amr.is_empty())
+                .map(str::to_string)
+                .collect(),
+            auth_time: number(auth_time)?,
+        })
+    }
+
+    fn key_id(&self) -> &str {
+        "test"
+    }
+}
+
+impl RefreshTokenManager for TestToken {
+    fn generate(&self) -> Result<String> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        Ok(format!(
+            "refresh{}",
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    fn expiration_seconds(&self) -> u64 {
+        86_400
+    }
+}
+
+impl Token for TestToken {
+    fn signer(&self) -> &dyn TokenSigner {
+        self
+    }
+
+    fn refresh_token(&self) -> &dyn RefreshTokenManager {
+        self
+    }
+}
+
+/// Telemetry discarding everything.
+pub struct NoTelemetry;
+
+impl NoTelemetry {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl TelemetryPort for NoTelemetry {
+    fn record_auth_success(&self, _user_id: &str, _method: &str) {}
+
+    fn record_auth_failure(&self, _reason: &str) {}
+
+    fn record_account_created(&self, _user_id: &str) {}
+
+    fn increment_counter(&self, _name: &str, _labels: &[(&str, &str)]) {}
+
+    fn record_histogram(
+        &self,
+        _name: &str,
+        _value: f64,
+        _labels: &[(&str, &str)],
+    ) {
+    }
+}
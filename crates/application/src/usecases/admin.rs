@@ -0,0 +1,484 @@
+//! Account administration use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::auth::email::EmailHash;
+use domain::error::DomainError;
+use domain::identity::email::EmailAddress;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+use domain::identity::suspension::Suspension;
+
+use crate::dto::{
+    AccountDto, AccountQueryDto, AccountSearchDto, AdminAccountDto,
+    ClientContextDto, CreateInviteDto, InviteDto, ModerationAction,
+    ModerationActionDto, SecurityEventDto, SecurityEventKind,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::AdministerAccounts;
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, Clock, CryptoPort, InviteRepository,
+    ModerationRepository, RefreshTokenRepository, RoleRepository,
+    TokenDenylist,
+};
+use crate::usecases::invite::CODE_LENGTH;
+use crate::usecases::{audit, email_hashes, ensure_outranks, ensure_role};
+
+/// Number of results returned when the caller sets no limit.
+const DEFAULT_LIMIT: u32 = 50;
+/// Maximum number of results returned at once.
+const MAX_LIMIT: u32 = 100;
+
+/// Account administration use case service.
+pub struct AdministerAccountsUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    role_repo: Arc<dyn RoleRepository>,
+    moderation_repo: Arc<dyn ModerationRepository>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    invite_repo: Arc<dyn InviteRepository>,
+    denylist: Arc<dyn TokenDenylist>,
+    crypto: Arc<dyn CryptoPort>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl AdministerAccountsUseCase {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        role_repo: Arc<dyn RoleRepository>,
+        moderation_repo: Arc<dyn ModerationRepository>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        invite_repo: Arc<dyn InviteRepository>,
+        denylist: Arc<dyn TokenDenylist>,
+        crypto: Arc<dyn CryptoPort>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            role_repo,
+            moderation_repo,
+            refresh_token_repo,
+            invite_repo,
+            denylist,
+            crypto,
+            clock,
+            audit_log: None,
+        }
+    }
+
+    /// Record actions on accounts in the audit log of their owner.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Ensure `actor` holds `required` and outranks `user_id`, and returns
+    /// the account of `user_id`.
+    async fn target(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        required: Role,
+    ) -> Result<AccountDto> {
+        let roles =
+            ensure_role(self.role_repo.as_ref(), actor, required).await?;
+        ensure_outranks(&roles, &self.role_repo.roles(user_id).await?)?;
+
+        self.account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)
+    }
+
+    /// Add an entry to the moderation log.
+    async fn record(
+        &self,
+        actor: &UserId,
+        action: ModerationAction,
+        user_id: Option<&UserId>,
+        reason: Option<String>,
+        target: Option<String>,
+    ) -> Result<()> {
+        let now = self.clock.now();
+        self.moderation_repo
+            .record(&ModerationActionDto {
+                user_id: user_id.cloned(),
+                moderator: actor.clone(),
+                action,
+                reason,
+                target,
+                until: None,
+                created_at: now,
+            })
+            .await?;
+
+        match user_id {
+            Some(user_id) => {
+                // Staff addresses are not disclosed to the account owner.
+                let event = SecurityEventDto::new(
+                    user_id,
+                    SecurityEventKind::AdminAction,
+                    &ClientContextDto::default(),
+                    now,
+                )
+                .with_details(action.as_str());
+                audit(self.audit_log.as_deref(), event).await
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+/// Clamp a requested number of results.
+fn limit(limit: Option<u32>) -> Result<u32> {
+    match limit.unwrap_or(DEFAULT_LIMIT) {
+        limit @ 1..=MAX_LIMIT => Ok(limit),
+        _ => Err(DomainError::ValidationFailed {
+            field: "limit".into(),
+            message: format!("Must be between 1 and {MAX_LIMIT}."),
+        }
+        .into()),
+    }
+}
+
+/// Validate an optional reason given by a staff member.
+fn reason(reason: Option<String>) -> Result<Option<String>> {
+    Ok(reason.map(Suspension::normalize_reason).transpose()?)
+}
+
+#[async_trait]
+impl AdministerAccounts for AdministerAccountsUseCase {
+    async fn search_accounts(
+        &self,
+        actor: &UserId,
+        query: AccountQueryDto,
+    ) -> Result<Vec<AccountDto>> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Moderator).await?;
+
+        let email_hashes = match (query.email, query.email_hash) {
+            (Some(email), _) => {
+                let email = EmailAddress::parse(&email)?;
+                email_hashes(self.crypto.as_ref(), email.as_bytes())
+            },
+            (None, hash) => hash.map(EmailHash::new).into_iter().collect(),
+        };
+        let search = AccountSearchDto {
+            id_prefix: query.id.filter(|id| !id.is_empty()),
+            email_hashes,
+            after: query.after.map(UserId::parse).transpose()?,
+            limit: limit(query.limit)?,
+        };
+
+        let accounts = self.account_repo.search(&search).await?;
+        self.record(
+            actor,
+            ModerationAction::SearchAccounts,
+            None,
+            None,
+            search.id_prefix,
+        )
+        .await?;
+
+        Ok(accounts)
+    }
+
+    async fn account(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+    ) -> Result<AdminAccountDto> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Moderator).await?;
+
+        let account = self
+            .account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?;
+        let roles = self.role_repo.roles(user_id).await?;
+        let sessions = self.refresh_token_repo.list_sessions(user_id).await?;
+        let invited_by = self.invite_repo.inviter_chain(user_id).await?;
+
+        self.record(
+            actor,
+            ModerationAction::ViewAccount,
+            Some(user_id),
+            None,
+            None,
+        )
+        .await?;
+
+        Ok(AdminAccountDto {
+            account,
+            roles,
+            sessions,
+            invited_by,
+        })
+    }
+
+    async fn force_password_reset(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let reason = self::reason(reason)?;
+        let mut account = self.target(actor, user_id, Role::Admin).await?;
+
+        account.password_reset_required = true;
+        self.account_repo.update(&account).await?;
+        // Existing sessions could otherwise keep using the old password.
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+        self.denylist.bump_generation(user_id).await?;
+
+        self.record(
+            actor,
+            ModerationAction::ForcePasswordReset,
+            Some(user_id),
+            reason,
+            None,
+        )
+        .await
+    }
+
+    async fn reset_totp(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let reason = self::reason(reason)?;
+        let mut account = self.target(actor, user_id, Role::Admin).await?;
+
+        if account.totp_secret.take().is_some() {
+            self.account_repo.update(&account).await?;
+        }
+
+        self.record(
+            actor,
+            ModerationAction::ResetTotp,
+            Some(user_id),
+            reason,
+            None,
+        )
+        .await
+    }
+
+    async fn revoke_sessions(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let reason = self::reason(reason)?;
+        self.target(actor, user_id, Role::Moderator).await?;
+
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+        self.denylist.bump_generation(user_id).await?;
+
+        self.record(
+            actor,
+            ModerationAction::RevokeSessions,
+            Some(user_id),
+            reason,
+            None,
+        )
+        .await
+    }
+
+    async fn delete_account(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let reason = self::reason(reason)?;
+        let account = self.target(actor, user_id, Role::Admin).await?;
+        if let Some(date) = account.deleted_at {
+            return Err(ApplicationError::AccountDeleted { date });
+        }
+
+        self.account_repo.delete(user_id).await?;
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+        self.denylist.bump_generation(user_id).await?;
+
+        self.record(
+            actor,
+            ModerationAction::DeleteAccount,
+            Some(user_id),
+            reason,
+            None,
+        )
+        .await
+    }
+
+    async fn grant_role(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        role: Role,
+    ) -> Result<()> {
+        if actor == user_id {
+            return Err(ApplicationError::Forbidden);
+        }
+        self.target(actor, user_id, Role::Admin).await?;
+
+        if self.role_repo.grant(user_id, role, actor).await? {
+            self.record(
+                actor,
+                ModerationAction::GrantRole,
+                Some(user_id),
+                None,
+                Some(role.to_string()),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_role(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        role: Role,
+    ) -> Result<()> {
+        if actor == user_id {
+            return Err(ApplicationError::Forbidden);
+        }
+        self.target(actor, user_id, Role::Admin).await?;
+
+        if self.role_repo.revoke(user_id, role).await? {
+            self.record(
+                actor,
+                ModerationAction::RevokeRole,
+                Some(user_id),
+                None,
+                Some(role.to_string()),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn set_flags(
+        &self,
+        actor: &UserId,
+        user_id: &UserId,
+        set: UserFlags,
+        clear: UserFlags,
+    ) -> Result<UserFlags> {
+        let mut account = self.target(actor, user_id, Role::Admin).await?;
+
+        let flags = (account.flags | set) - clear;
+        if flags != account.flags {
+            let added = flags - account.flags;
+            let removed = account.flags - flags;
+            account.flags = flags;
+            self.account_repo.update(&account).await?;
+
+            let changes = added
+                .names()
+                .into_iter()
+                .map(|name| format!("+{name}"))
+                .chain(
+                    removed.names().into_iter().map(|name| format!("-{name}")),
+                )
+                .collect::<Vec<_>>()
+                .join(",");
+            self.record(
+                actor,
+                ModerationAction::SetFlags,
+                Some(user_id),
+                None,
+                Some(changes),
+            )
+            .await?;
+        }
+
+        Ok(flags)
+    }
+
+    async fn list_invites(
+        &self,
+        actor: &UserId,
+        limit: Option<u32>,
+    ) -> Result<Vec<InviteDto>> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Admin).await?;
+
+        let invites =
+            self.invite_repo.list_recent(self::limit(limit)?).await?;
+        self.record(actor, ModerationAction::ListInvites, None, None, None)
+            .await?;
+
+        Ok(invites)
+    }
+
+    async fn create_invite(
+        &self,
+        actor: &UserId,
+        request: CreateInviteDto,
+    ) -> Result<InviteDto> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Admin).await?;
+
+        let max_uses = request.max_uses.unwrap_or(1);
+        if max_uses == 0 {
+            return Err(DomainError::ValidationFailed {
+                field: "maxUses".to_string(),
+                message: "Must be at least 1.".to_string(),
+            }
+            .into());
+        }
+        if request.expires_in == Some(0) {
+            return Err(DomainError::ValidationFailed {
+                field: "expiresIn".to_string(),
+                message: "Must be at least 1 second.".to_string(),
+            }
+            .into());
+        }
+
+        let now = self.clock.now();
+        let invite = InviteDto {
+            code: self.crypto.secure_random().random_string(CODE_LENGTH)?,
+            created_by: None,
+            max_uses,
+            uses: 0,
+            expires_at: request.expires_in.map(|ttl| now + ttl),
+            revoked_at: None,
+            created_at: now,
+            used_by: Vec::new(),
+        };
+        self.invite_repo.create(&invite, None).await?;
+
+        self.record(
+            actor,
+            ModerationAction::CreateInvite,
+            None,
+            None,
+            Some(invite.code.clone()),
+        )
+        .await?;
+
+        Ok(invite)
+    }
+
+    async fn revoke_invite(&self, actor: &UserId, code: &str) -> Result<()> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Admin).await?;
+
+        if !self.invite_repo.revoke(code, None).await? {
+            return Err(ApplicationError::InviteNotFound);
+        }
+
+        self.record(
+            actor,
+            ModerationAction::RevokeInvite,
+            None,
+            None,
+            Some(code.to_string()),
+        )
+        .await
+    }
+}
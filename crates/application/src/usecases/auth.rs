@@ -11,15 +11,24 @@ use domain::auth::invariants::validate_totp_requirement;
 use domain::auth::password::Password;
 use domain::auth::proof::AuthenticationProofBuilder;
 use domain::error::DomainError;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
 
-use crate::dto::{AuthRequestDto, AuthResponseDto};
+use crate::dto::{
+    AuthRequestDto, AuthResponseDto, ClientContextDto, SecurityEventDto,
+    SecurityEventKind, SessionDto,
+};
 use crate::error::{ApplicationError, Result};
 use crate::ports::inbound::Authenticate;
 use crate::ports::outbound::{
-    AccountRepository, Clock, CryptoPort, LdapPort, RefreshTokenRepository,
-    TelemetryPort, Token,
+    AccountRepository, AuditLog, Clock, CryptoPort, LdapPort, PasswordPolicy,
+    RateLimitDecision, RateLimitKey, RateLimitPolicy, RateLimiter,
+    RefreshTokenRepository, TelemetryPort, Token,
+};
+use crate::usecases::{
+    EXPIRES_IN, SESSION_ID_BYTES, TOKEN_TYPE, audit, email_hashes,
+    ensure_password_allowed,
 };
-use crate::usecases::{EXPIRES_IN, TOKEN_TYPE};
 
 /// Authentication use case service.
 pub struct AuthenticateUseCase {
@@ -30,6 +39,10 @@ pub struct AuthenticateUseCase {
     token: Arc<dyn Token>,
     telemetry: Arc<dyn TelemetryPort>,
     clock: Arc<dyn Clock>,
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
+    lockout_policies: Vec<RateLimitPolicy>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+    password_policy: Option<Arc<dyn PasswordPolicy>>,
 }
 
 impl AuthenticateUseCase {
@@ -50,8 +63,124 @@ impl AuthenticateUseCase {
             token,
             telemetry,
             clock,
+            rate_limiter: None,
+            lockout_policies: Vec::new(),
+            audit_log: None,
+            password_policy: None,
         }
     }
+
+    /// Record logins and failed attempts in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Refuse passwords breaking `password_policy` when a reset is
+    /// forced.
+    pub fn with_password_policy(
+        mut self,
+        password_policy: Arc<dyn PasswordPolicy>,
+    ) -> Self {
+        self.password_policy = Some(password_policy);
+        self
+    }
+
+    /// Record a failed attempt on an existing account.
+    async fn audit_failure(
+        &self,
+        user_id: &UserId,
+        client: &ClientContextDto,
+        reason: &'static str,
+    ) -> Result<()> {
+        self.telemetry.record_auth_failure(reason);
+
+        let event = SecurityEventDto::new(
+            user_id,
+            SecurityEventKind::LoginFailed,
+            client,
+            self.clock.now(),
+        )
+        .with_details(reason);
+        audit(self.audit_log.as_deref(), event).await
+    }
+
+    /// Lock identifiers out after repeated failed attempts, whether an
+    /// account uses them or not.
+    ///
+    /// Each policy is a tier: the first one exceeded rejects the attempt
+    /// until its window slides, so longer windows lock for longer.
+    pub fn with_lockout(
+        mut self,
+        rate_limiter: Arc<dyn RateLimiter>,
+        policies: Vec<RateLimitPolicy>,
+    ) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self.lockout_policies = policies;
+        self
+    }
+
+    /// Key counting failed attempts on `identifier`, whether an account
+    /// uses it or not, so that lockouts do not tell accounts apart.
+    fn lockout_key(&self, identifier: &[u8]) -> RateLimitKey {
+        RateLimitKey::Account(self.crypto.blind_index().index(identifier))
+    }
+
+    /// Reject the attempt if the identifier is currently locked out.
+    ///
+    /// The password is verified against a dummy hash first, so that the
+    /// rejection takes as long as a wrong password.
+    async fn ensure_not_locked(
+        &self,
+        key: &RateLimitKey,
+        password: &Password,
+    ) -> Result<()> {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+
+        let mut retry_after = 0;
+        for policy in &self.lockout_policies {
+            if let RateLimitDecision::Limited { retry_after: secs } =
+                rate_limiter.peek(key, policy).await?
+            {
+                retry_after = retry_after.max(secs);
+            }
+        }
+
+        if retry_after > 0 {
+            self.crypto.password_hasher().verify_dummy(password).await?;
+            self.telemetry.record_auth_failure("account_locked");
+            return Err(ApplicationError::RateLimited { retry_after });
+        }
+
+        Ok(())
+    }
+
+    /// Count a failed attempt against every lockout tier.
+    async fn record_failure(&self, key: &RateLimitKey) -> Result<()> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            for policy in &self.lockout_policies {
+                rate_limiter.hit(key, policy).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject an attempt on no account once a password was verified, so
+    /// that it looks like a wrong password.
+    async fn reject_unknown(
+        &self,
+        password: &Password,
+        lockout_key: &RateLimitKey,
+        reason: &'static str,
+    ) -> Result<AuthResponseDto> {
+        self.crypto.password_hasher().verify_dummy(password).await?;
+        self.telemetry.record_auth_failure(reason);
+        self.record_failure(lockout_key).await?;
+        Err(DomainError::InvalidCredentials.into())
+    }
 }
 
 #[async_trait]
@@ -61,26 +190,14 @@ impl Authenticate for AuthenticateUseCase {
         request: AuthRequestDto,
     ) -> Result<AuthResponseDto> {
         let password = Password::new(&request.password)?;
+        let client = ClientContextDto {
+            ip_address: request.ip_address.clone(),
+            user_agent: request.user_agent.clone(),
+        };
 
-        let account = match (&request.email, request.user_id) {
-            (Some(email), None) => {
-                let email_hash = self.crypto.hasher().hash(email.as_bytes());
-                self.account_repo
-                    .find_by_email_hash(&EmailHash::new(email_hash))
-                    .await?
-                    .ok_or(ApplicationError::UserNotFound)?
-            },
-            (None, Some(user_id)) => {
-                if let Some(ldap) = &self.ldap {
-                    ldap.authenticate(&user_id, &request.password)
-                        .await
-                        .map_err(|_| ApplicationError::UserNotFound)?;
-                    // self.account_repo.create().await?;
-                    return Err(ApplicationError::UserNotFound);
-                } else {
-                    return Err(ApplicationError::UserNotFound);
-                }
-            },
+        let identifier = match (&request.email, &request.user_id) {
+            (Some(email), None) => email.as_bytes(),
+            (None, Some(user_id)) => user_id.as_bytes(),
             (Some(_), Some(_)) => {
                 self.telemetry.record_auth_failure("ambiguous_identifier");
                 return Err(DomainError::ValidationFailed {
@@ -98,17 +215,70 @@ impl Authenticate for AuthenticateUseCase {
                 .into());
             },
         };
+        let lockout_key = self.lockout_key(identifier);
+        self.ensure_not_locked(&lockout_key, &password).await?;
+
+        let account = match (&request.email, &request.user_id) {
+            (Some(email), _) => {
+                self.account_repo
+                    .find_by_email_hashes(&email_hashes(
+                        self.crypto.as_ref(),
+                        email.as_bytes(),
+                    ))
+                    .await?
+            },
+            (None, Some(user_id)) => {
+                if let Some(ldap) = &self.ldap {
+                    ldap.authenticate(user_id, &request.password)
+                        .await
+                        .map_err(|_| DomainError::InvalidCredentials)?;
+                    // self.account_repo.create().await?;
+                }
+                None
+            },
+            (None, None) => None,
+        };
+        let Some(mut account) = account else {
+            return self
+                .reject_unknown(&password, &lockout_key, "user_not_found")
+                .await;
+        };
 
+        if let Err(err) = self
+            .crypto
+            .password_hasher()
+            .verify(&password, &account.password_hash)
+            .await
+        {
+            self.audit_failure(&account.id, &client, "invalid_password")
+                .await?;
+            self.record_failure(&lockout_key).await?;
+            return Err(err);
+        }
+
+        // Only tell a deleted account apart once its password is known.
         if let Some(date) = account.deleted_at {
             self.telemetry.record_auth_failure("account_deleted");
             return Err(ApplicationError::AccountDeleted { date });
         }
 
-        self.crypto
-            .password_hasher()
-            .verify(&password, &account.password_hash)?;
-
         let now = self.clock.now();
+        if let Some(suspension) = &account.suspension &&
+            suspension.is_active(now)
+        {
+            self.audit_failure(&account.id, &client, "account_suspended")
+                .await?;
+            return Err(ApplicationError::AccountSuspended {
+                until: suspension.until(),
+            });
+        }
+
+        if account.flags.contains(UserFlags::BOT) {
+            self.audit_failure(&account.id, &client, "bot_account")
+                .await?;
+            return Err(ApplicationError::PasswordLoginDisabled);
+        }
+
         let mut verified_factors = vec![VerifiedFactor::new(
             FactorType::Knowledge,
             FactorMethod::Password,
@@ -126,7 +296,8 @@ impl Authenticate for AuthenticateUseCase {
             let secret_bytes = self
                 .crypto
                 .symmetric_encryption()
-                .decrypt_from_hex(encrypted_secret)?;
+                .decrypt_from_hex(encrypted_secret)
+                .await?;
             let secret_str = String::from_utf8(secret_bytes)
                 .map_err(|_| DomainError::InvalidTotpSecret)?;
 
@@ -138,7 +309,9 @@ impl Authenticate for AuthenticateUseCase {
                 &secret,
                 &TotpConfig::default(),
             )? {
-                self.telemetry.record_auth_failure("invalid_totp");
+                self.audit_failure(&account.id, &client, "invalid_totp")
+                    .await?;
+                self.record_failure(&lockout_key).await?;
                 return Err(DomainError::InvalidTotpCode.into());
             }
 
@@ -149,26 +322,149 @@ impl Authenticate for AuthenticateUseCase {
             ));
         }
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.reset(&lockout_key).await?;
+        }
+
+        // Re-index the email if it was indexed with a former secret, unless
+        // it changed since the account was read.
+        if let Some(email) = &request.email {
+            let email_hash = EmailHash::new(
+                self.crypto.blind_index().index(email.as_bytes()),
+            );
+            if account.email_hash != email_hash &&
+                self.account_repo
+                    .update_email_hash(
+                        &account.id,
+                        &account.email_hash,
+                        &email_hash,
+                    )
+                    .await?
+            {
+                account.email_hash = email_hash;
+            }
+        }
+        // Re-hash the password if hashed with former parameters, unless it
+        // changed since the account was read.
+        if !account.password_reset_required &&
+            self.crypto
+                .password_hasher()
+                .needs_rehash(&account.password_hash)
+        {
+            let password_hash =
+                self.crypto.password_hasher().hash(&password).await?;
+            if self
+                .account_repo
+                .update_password_hash(
+                    &account.id,
+                    &account.password_hash,
+                    &password_hash,
+                )
+                .await?
+            {
+                account.password_hash = password_hash;
+            }
+        }
+
+        if account.password_reset_required {
+            let new_password = match &request.new_password {
+                Some(new_password) if *new_password == request.password => {
+                    return Err(DomainError::ValidationFailed {
+                        field: "newPassword".into(),
+                        message: "must differ from the current password"
+                            .into(),
+                    }
+                    .into());
+                },
+                Some(new_password) => {
+                    let new_password = Password::new(new_password)?;
+                    ensure_password_allowed(
+                        self.password_policy.as_deref(),
+                        "newPassword",
+                        &new_password,
+                        &[account.id.as_str(), &account.username],
+                    )
+                    .await?;
+                    new_password
+                },
+                None => {
+                    self.telemetry
+                        .record_auth_failure("password_reset_required");
+                    return Err(ApplicationError::PasswordResetRequired);
+                },
+            };
+
+            let password_hash =
+                self.crypto.password_hasher().hash(&new_password).await?;
+            // The password was changed meanwhile, e.g. from another device.
+            if !self
+                .account_repo
+                .complete_password_reset(
+                    &account.id,
+                    &account.password_hash,
+                    &password_hash,
+                )
+                .await?
+            {
+                return Err(DomainError::InvalidCredentials.into());
+            }
+            account.password_hash = password_hash;
+            account.password_reset_required = false;
+
+            let event = SecurityEventDto::new(
+                &account.id,
+                SecurityEventKind::PasswordChanged,
+                &client,
+                now,
+            )
+            .with_details("forced_reset");
+            audit(self.audit_log.as_deref(), event).await?;
+        }
+
+        let session_id =
+            self.crypto.secure_random().random_hex(SESSION_ID_BYTES)?;
         let proof = AuthenticationProofBuilder::default()
             .user_id(&account.id)
+            .token_generation(account.token_generation)
+            .session_id(&session_id)
             .authenticated_at(now)
             .add_factors(verified_factors)
             .build()?;
 
-        let access_token = self.token.signer().create_access_token(&proof)?;
+        let session = SessionDto {
+            id: session_id.clone(),
+            user_id: account.id.clone(),
+            device: request.device,
+            ip_address: request.ip_address,
+            amr: proof.amr().into_iter().map(str::to_string).collect(),
+            authenticated_at: proof.auth_time(),
+            created_at: now,
+            last_used_at: now,
+        };
+
+        let access_token =
+            self.token.signer().create_access_token(&proof).await?;
         let refresh_token = self.token.refresh_token().generate()?;
 
         self.refresh_token_repo
             .store(
                 &self.crypto.hasher().hash(refresh_token.as_bytes()),
-                &account.id,
-                request.ip_address.as_deref(),
+                &session,
             )
             .await?;
 
         self.telemetry
             .record_auth_success(account.id.as_str(), "password");
 
+        let event = SecurityEventDto::new(
+            &account.id,
+            SecurityEventKind::Login,
+            &client,
+            now,
+        )
+        .with_details(session.amr.join(" "));
+        audit(self.audit_log.as_deref(), event).await?;
+
         Ok(AuthResponseDto {
             access_token,
             refresh_token,
@@ -177,3 +473,119 @@ impl Authenticate for AuthenticateUseCase {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use domain::identity::email::EmailAddress;
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::testing::{
+        HASH_DELAY, MemoryAccounts, MemoryRateLimiter, MemorySessions,
+        NoTelemetry, TestClock, TestCrypto, TestToken, account,
+    };
+
+    const PASSWORD: &str = "correct horse battery";
+
+    fn request(email: &str, password: &str) -> AuthRequestDto {
+        AuthRequestDto {
+            email: Some(EmailAddress::parse(email).unwrap()),
+            user_id: None,
+            password: password.into(),
+            totp_code: None,
+            new_password: None,
+            ip_address: None,
+            device: None,
+            user_agent: None,
+        }
+    }
+
+    /// Failed logins take one password verification, and are locked out
+    /// alike, whether an account uses the email or not.
+    #[tokio::test(start_paused = true)]
+    async fn test_lockout_enumeration() {
+        let crypto = TestCrypto::new();
+        let alice = account(&crypto, "alice", "alice@example.com", PASSWORD);
+        let usecase = AuthenticateUseCase::new(
+            MemoryAccounts::new([alice]),
+            MemorySessions::new(),
+            None,
+            crypto.clone(),
+            TestToken::new(),
+            NoTelemetry::new(),
+            TestClock::new(1_000),
+        )
+        .with_lockout(
+            MemoryRateLimiter::new(),
+            vec![RateLimitPolicy::new(3, 60)],
+        );
+
+        for email in ["alice@example.com", "nobody@example.com"] {
+            let mut errors = Vec::new();
+            for _ in 0..4 {
+                let hashes = crypto.hashes.load(Ordering::Relaxed);
+                let start = Instant::now();
+                errors.push(
+                    usecase.execute(request(email, "wrong password")).await,
+                );
+                assert_eq!(start.elapsed(), HASH_DELAY, "{email}");
+                assert_eq!(
+                    crypto.hashes.load(Ordering::Relaxed),
+                    hashes + 1,
+                    "{email}"
+                );
+            }
+
+            for err in &errors[..3] {
+                assert!(matches!(
+                    err,
+                    Err(ApplicationError::Domain(
+                        DomainError::InvalidCredentials
+                    ))
+                ));
+            }
+            assert!(matches!(
+                errors[3],
+                Err(ApplicationError::RateLimited { retry_after: 60 })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forced_reset() {
+        const NEW_PASSWORD: &str = "another horse battery";
+
+        let crypto = TestCrypto::new();
+        let mut alice =
+            account(&crypto, "alice", "alice@example.com", PASSWORD);
+        alice.password_reset_required = true;
+        let accounts = MemoryAccounts::new([alice]);
+        let usecase = AuthenticateUseCase::new(
+            accounts.clone(),
+            MemorySessions::new(),
+            None,
+            crypto,
+            TestToken::new(),
+            NoTelemetry::new(),
+            TestClock::new(1_000),
+        );
+
+        let err = usecase
+            .execute(request("alice@example.com", PASSWORD))
+            .await;
+        assert!(matches!(err, Err(ApplicationError::PasswordResetRequired)));
+
+        let mut request = request("alice@example.com", PASSWORD);
+        request.new_password = Some(NEW_PASSWORD.into());
+        usecase.execute(request).await.unwrap();
+
+        let alice = accounts.0.lock().unwrap()[0].clone();
+        assert!(!alice.password_reset_required);
+        assert_eq!(
+            alice.password_hash,
+            TestCrypto::password_hash(NEW_PASSWORD)
+        );
+    }
+}
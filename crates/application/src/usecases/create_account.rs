@@ -3,19 +3,26 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use domain::auth::email::EmailHash;
 use domain::auth::factor::{FactorMethod, FactorType, VerifiedFactor};
 use domain::auth::proof::AuthenticationProofBuilder;
 use domain::identity::account::DEFAULT_LOCALE;
+use domain::identity::flags::UserFlags;
 
-use crate::dto::{AccountDto, AuthResponseDto, CreateAccountRequestDto};
-use crate::error::Result;
+use crate::dto::{
+    AccountDto, AuthResponseDto, CreateAccountRequestDto, SessionDto,
+};
+use crate::error::{ApplicationError, Result};
 use crate::ports::inbound::CreateAccount;
 use crate::ports::outbound::{
-    AccountRepository, Clock, CryptoPort, Mailer, RefreshTokenRepository,
+    AccountRepository, ActorKeyRepository, Clock, CryptoPort,
+    InviteRepository, Mailer, PasswordPolicy, RefreshTokenRepository,
     TelemetryPort, Token,
 };
-use crate::usecases::{EXPIRES_IN, TOKEN_TYPE};
+use crate::usecases::federation::generate_actor_key;
+use crate::usecases::{
+    EXPIRES_IN, SESSION_ID_BYTES, TOKEN_TYPE, email_hashes,
+    ensure_email_available, ensure_password_allowed,
+};
 
 /// Account creation use case service.
 pub struct CreateAccountUseCase {
@@ -26,6 +33,9 @@ pub struct CreateAccountUseCase {
     token: Arc<dyn Token>,
     telemetry: Arc<dyn TelemetryPort>,
     clock: Arc<dyn Clock>,
+    invite_repo: Option<Arc<dyn InviteRepository>>,
+    password_policy: Option<Arc<dyn PasswordPolicy>>,
+    actor_key_repo: Option<Arc<dyn ActorKeyRepository>>,
 }
 
 impl CreateAccountUseCase {
@@ -46,8 +56,39 @@ impl CreateAccountUseCase {
             token,
             telemetry,
             clock,
+            invite_repo: None,
+            password_policy: None,
+            actor_key_repo: None,
         }
     }
+
+    /// Generate the key pair signing the requests of the actor of each new
+    /// account.
+    pub fn with_actor_keys(
+        mut self,
+        actor_key_repo: Arc<dyn ActorKeyRepository>,
+    ) -> Self {
+        self.actor_key_repo = Some(actor_key_repo);
+        self
+    }
+
+    /// Require a valid invitation code to create an account.
+    pub fn with_invites(
+        mut self,
+        invite_repo: Arc<dyn InviteRepository>,
+    ) -> Self {
+        self.invite_repo = Some(invite_repo);
+        self
+    }
+
+    /// Refuse passwords breaking `password_policy`.
+    pub fn with_password_policy(
+        mut self,
+        password_policy: Arc<dyn PasswordPolicy>,
+    ) -> Self {
+        self.password_policy = Some(password_policy);
+        self
+    }
 }
 
 #[async_trait]
@@ -56,21 +97,68 @@ impl CreateAccount for CreateAccountUseCase {
         &self,
         request: CreateAccountRequestDto,
     ) -> Result<AuthResponseDto> {
-        let password_hash =
-            self.crypto.password_hasher().hash(&request.password)?;
+        let now = self.clock.now();
+
+        // Reject bad codes before spending time on password hashing.
+        let invite = match &self.invite_repo {
+            Some(invite_repo) => {
+                let code = request
+                    .invite_code
+                    .as_deref()
+                    .ok_or(ApplicationError::InviteRequired)?;
+                invite_repo
+                    .find(code)
+                    .await?
+                    .ok_or(ApplicationError::InvalidInvite)?
+                    .ensure_claimable(now)?;
+
+                Some((invite_repo, code))
+            },
+            None => None,
+        };
+
+        ensure_password_allowed(
+            self.password_policy.as_deref(),
+            "password",
+            &request.password,
+            &[request.user_id.as_str()],
+        )
+        .await?;
+
+        let password_hash = self
+            .crypto
+            .password_hasher()
+            .hash(&request.password)
+            .await?;
 
         let email_bytes = request.email.as_bytes();
-        let email_hash =
-            EmailHash::new(self.crypto.hasher().hash(email_bytes));
+        let mut email_hashes = email_hashes(self.crypto.as_ref(), email_bytes);
+        ensure_email_available(self.account_repo.as_ref(), &email_hashes)
+            .await?;
+        let email_hash = email_hashes.swap_remove(0);
         let email_cipher = self
             .crypto
             .symmetric_encryption()
-            .encrypt_to_hex(email_bytes)?;
+            .encrypt_to_hex(email_bytes)
+            .await?;
+
+        // Generated first, so that an overloaded server creates nothing.
+        let actor_key = match &self.actor_key_repo {
+            Some(actor_key_repo) => Some((
+                actor_key_repo,
+                generate_actor_key(
+                    self.crypto.as_ref(),
+                    &request.user_id,
+                    now,
+                )
+                .await?,
+            )),
+            None => None,
+        };
 
         let locale =
             request.locale.unwrap_or_else(|| DEFAULT_LOCALE.to_string());
 
-        let now = self.clock.now();
         let account = AccountDto {
             username: request.user_id.to_string(),
             id: request.user_id,
@@ -81,13 +169,25 @@ impl CreateAccount for CreateAccountUseCase {
             locale: locale.clone(),
             summary: None,
             avatar: None,
-            flags: 0,
+            banner: None,
+            flags: UserFlags::empty(),
             created_at: now,
             deleted_at: None,
             public_keys: Vec::new(),
+            token_generation: 0,
+            suspension: None,
+            password_reset_required: false,
         };
 
-        self.account_repo.create(&account).await?;
+        match invite {
+            Some((invite_repo, code)) => {
+                invite_repo.claim(code, &account).await?
+            },
+            None => self.account_repo.create(&account).await?,
+        }
+        if let Some((actor_key_repo, key)) = actor_key {
+            actor_key_repo.create(&key).await?;
+        }
 
         if let Some(ref mailer) = self.mailer {
             // Later, we should handle error with retries and DLQ.
@@ -102,20 +202,34 @@ impl CreateAccount for CreateAccountUseCase {
             now,
         );
 
+        let session_id =
+            self.crypto.secure_random().random_hex(SESSION_ID_BYTES)?;
         let proof = AuthenticationProofBuilder::default()
             .user_id(&account.id)
+            .session_id(&session_id)
             .authenticated_at(now)
             .add_factor(verified_factor)
             .build()?;
 
-        let access_token = self.token.signer().create_access_token(&proof)?;
+        let session = SessionDto {
+            id: session_id.clone(),
+            user_id: account.id.clone(),
+            device: request.device,
+            ip_address: request.ip_address,
+            amr: proof.amr().into_iter().map(str::to_string).collect(),
+            authenticated_at: proof.auth_time(),
+            created_at: now,
+            last_used_at: now,
+        };
+
+        let access_token =
+            self.token.signer().create_access_token(&proof).await?;
         let refresh_token = self.token.refresh_token().generate()?;
 
         self.refresh_token_repo
             .store(
                 &self.crypto.hasher().hash(refresh_token.as_bytes()),
-                &account.id,
-                request.ip_address.as_deref(),
+                &session,
             )
             .await?;
 
@@ -129,3 +243,134 @@ impl CreateAccount for CreateAccountUseCase {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use domain::auth::password::Password;
+    use domain::identity::email::EmailAddress;
+    use domain::identity::id::UserId;
+
+    use super::*;
+    use crate::dto::InviteDto;
+    use crate::ports::inbound::GenerateActorKeys;
+    use crate::testing::{
+        MemoryAccounts, MemoryActorKeys, MemoryInvites, MemorySessions,
+        NoTelemetry, TestClock, TestCrypto, TestToken, account,
+    };
+    use crate::usecases::GenerateActorKeysUseCase;
+
+    const NOW: u64 = 1_000;
+
+    fn invite(code: &str) -> InviteDto {
+        InviteDto {
+            code: code.into(),
+            created_by: None,
+            max_uses: 1,
+            uses: 0,
+            expires_at: Some(NOW + 60),
+            revoked_at: None,
+            created_at: 0,
+            used_by: Vec::new(),
+        }
+    }
+
+    fn request(invite_code: Option<&str>) -> CreateAccountRequestDto {
+        CreateAccountRequestDto {
+            user_id: UserId::parse("alice").unwrap(),
+            email: EmailAddress::parse("alice@example.com").unwrap(),
+            password: Password::new("correct horse battery").unwrap(),
+            locale: None,
+            invite_code: invite_code.map(str::to_string),
+            ip_address: None,
+            device: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invites() {
+        let crypto = TestCrypto::new();
+        let invites = MemoryInvites::new([
+            invite("valid"),
+            InviteDto {
+                expires_at: Some(NOW),
+                ..invite("expired")
+            },
+            InviteDto {
+                uses: 1,
+                ..invite("exhausted")
+            },
+        ]);
+        let usecase = CreateAccountUseCase::new(
+            MemoryAccounts::new([]),
+            MemorySessions::new(),
+            crypto.clone(),
+            None,
+            TestToken::new(),
+            NoTelemetry::new(),
+            TestClock::new(NOW),
+        )
+        .with_invites(invites.clone());
+
+        let result = usecase.execute(request(None)).await;
+        assert!(matches!(result, Err(ApplicationError::InviteRequired)));
+        for (code, expected) in [
+            ("unknown", "invitation code is invalid or expired"),
+            ("expired", "invitation code is invalid or expired"),
+            ("exhausted", "invitation code has no uses left"),
+        ] {
+            let err = usecase.execute(request(Some(code))).await.err();
+            assert_eq!(err.unwrap().to_string(), expected, "{code}");
+        }
+        // Bad codes are refused before hashing the password.
+        assert_eq!(
+            crypto.hashes.load(std::sync::atomic::Ordering::Relaxed),
+            0
+        );
+
+        usecase.execute(request(Some("valid"))).await.unwrap();
+        let claimed = invites.find("valid").await.unwrap().unwrap();
+        assert_eq!(claimed.uses, 1);
+        assert_eq!(claimed.used_by, [UserId::parse("alice").unwrap()]);
+
+        let result = usecase.execute(request(Some("valid"))).await;
+        assert!(matches!(result, Err(ApplicationError::InviteExhausted)));
+    }
+
+    #[tokio::test]
+    async fn test_actor_keys() {
+        let crypto = TestCrypto::new();
+        let accounts = MemoryAccounts::new([account(
+            &crypto,
+            "bob",
+            "bob@example.com",
+            "correct horse battery",
+        )]);
+        let actor_keys = MemoryActorKeys::new(accounts.clone());
+        let usecase = CreateAccountUseCase::new(
+            accounts.clone(),
+            MemorySessions::new(),
+            crypto.clone(),
+            None,
+            TestToken::new(),
+            NoTelemetry::new(),
+            TestClock::new(NOW),
+        )
+        .with_actor_keys(actor_keys.clone());
+
+        usecase.execute(request(None)).await.unwrap();
+        let alice = UserId::parse("alice").unwrap();
+        let key = actor_keys.find(&alice).await.unwrap().unwrap();
+        assert_eq!(key.created_at, NOW);
+
+        // Accounts created before get theirs from the backfill.
+        let backfill = GenerateActorKeysUseCase::new(
+            actor_keys.clone(),
+            crypto,
+            TestClock::new(NOW),
+        );
+        assert_eq!(backfill.generate_missing(10).await.unwrap(), 1);
+        assert_eq!(backfill.generate_missing(10).await.unwrap(), 0);
+        let bob = UserId::parse("bob").unwrap();
+        assert!(actor_keys.find(&bob).await.unwrap().is_some());
+    }
+}
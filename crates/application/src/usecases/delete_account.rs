@@ -0,0 +1,164 @@
+//! Account deletion use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+
+use crate::dto::{ClientContextDto, SecurityEventDto, SecurityEventKind};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::DeleteAccount;
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, Clock, RefreshTokenRepository, TokenClaims,
+    TokenDenylist,
+};
+use crate::usecases::{audit, ensure_sensitive_operation};
+
+/// Account deletion use case service.
+pub struct DeleteAccountUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    denylist: Arc<dyn TokenDenylist>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl DeleteAccountUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        denylist: Arc<dyn TokenDenylist>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            refresh_token_repo,
+            denylist,
+            clock,
+            audit_log: None,
+        }
+    }
+
+    /// Record deletions in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+}
+
+#[async_trait]
+impl DeleteAccount for DeleteAccountUseCase {
+    async fn execute(
+        &self,
+        user_id: &UserId,
+        claims: &TokenClaims,
+        client: &ClientContextDto,
+    ) -> Result<()> {
+        let now = self.clock.now();
+        let account = self
+            .account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?;
+        ensure_sensitive_operation(
+            user_id,
+            claims,
+            now,
+            account.totp_secret.is_some(),
+        )?;
+        if account.flags.contains(UserFlags::LOCKED) {
+            return Err(ApplicationError::AccountLocked);
+        }
+
+        self.account_repo.delete(user_id).await?;
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+        self.denylist.bump_generation(user_id).await?;
+
+        let event = SecurityEventDto::new(
+            user_id,
+            SecurityEventKind::AccountDeleted,
+            client,
+            now,
+        );
+        audit(self.audit_log.as_deref(), event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        MemoryAccounts, MemoryDenylist, MemorySessions, TestClock, TestCrypto,
+        account, claims,
+    };
+    use crate::usecases::SENSITIVE_MAX_AGE;
+
+    const NOW: u64 = 10_000;
+
+    #[tokio::test]
+    async fn test_reauthentication_required() {
+        let crypto = TestCrypto::new();
+        let accounts = MemoryAccounts::new([account(
+            &crypto,
+            "alice",
+            "alice@example.com",
+            "correct horse battery",
+        )]);
+        let usecase = DeleteAccountUseCase::new(
+            accounts.clone(),
+            MemorySessions::new(),
+            MemoryDenylist::new(),
+            TestClock::new(NOW),
+        );
+        let alice = UserId::parse("alice").unwrap();
+        let client = ClientContextDto::default();
+
+        for claims in [
+            claims(&alice, &["pwd", "otp"], NOW - SENSITIVE_MAX_AGE - 1),
+            claims(&alice, &["pwd"], NOW),
+        ] {
+            let err = usecase.execute(&alice, &claims, &client).await;
+            assert!(matches!(
+                err,
+                Err(ApplicationError::ReauthenticationRequired { .. })
+            ));
+        }
+        let account = accounts.find_by_id(&alice).await.unwrap().unwrap();
+        assert!(account.deleted_at.is_none());
+
+        let elevated = claims(&alice, &["pwd", "swk"], NOW);
+        usecase.execute(&alice, &elevated, &client).await.unwrap();
+        let account = accounts.find_by_id(&alice).await.unwrap().unwrap();
+        assert!(account.deleted_at.is_some());
+    }
+
+    /// The refresh token of the session is no possession factor, so it does
+    /// not stand in for an enrolled TOTP.
+    #[tokio::test]
+    async fn test_second_factor_required() {
+        let crypto = TestCrypto::new();
+        let mut alice =
+            account(&crypto, "alice", "alice@example.com", "correct horse");
+        alice.totp_secret = Some("secret".into());
+        let accounts = MemoryAccounts::new([alice]);
+        let usecase = DeleteAccountUseCase::new(
+            accounts.clone(),
+            MemorySessions::new(),
+            MemoryDenylist::new(),
+            TestClock::new(NOW),
+        );
+        let alice = UserId::parse("alice").unwrap();
+        let client = ClientContextDto::default();
+
+        let session = claims(&alice, &["pwd", "swk"], NOW);
+        let err = usecase.execute(&alice, &session, &client).await;
+        assert!(matches!(
+            err,
+            Err(ApplicationError::ReauthenticationRequired { .. })
+        ));
+
+        let elevated = claims(&alice, &["pwd", "otp"], NOW);
+        usecase.execute(&alice, &elevated, &client).await.unwrap();
+    }
+}
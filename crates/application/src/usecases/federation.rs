@@ -0,0 +1,243 @@
+//! ActivityPub federation use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+use serde_json::Value;
+
+use crate::dto::{
+    ActorKeyDto, ActorPublicKeyDto, CollectionKind, InboxActivityDto,
+    InstanceActorDto, OrderedCollectionDto, StatusDto,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::{Federation, GenerateActorKeys};
+use crate::ports::outbound::{
+    AccountRepository, ActorKeyRepository, Clock, CryptoPort, InboxRepository,
+};
+
+const ACTIVITY_STREAMS: &str = "https://www.w3.org/ns/activitystreams";
+
+/// URL of the actor representing the instance at `base_url`.
+pub fn instance_actor_url(base_url: &str) -> String {
+    format!("{}/actor", base_url.trim_end_matches('/'))
+}
+
+/// ID of the key signing requests of the instance at `base_url`.
+pub fn instance_key_id(base_url: &str) -> String {
+    format!("{}#main-key", instance_actor_url(base_url))
+}
+
+/// URL of the actor of `user_id` on the instance at `base_url`.
+pub(crate) fn actor_url(base_url: &str, user_id: &UserId) -> String {
+    format!("{}/users/{}", base_url.trim_end_matches('/'), user_id)
+}
+
+/// Generate a signing key for `user_id`, with its private key encrypted.
+pub(crate) async fn generate_actor_key(
+    crypto: &dyn CryptoPort,
+    user_id: &UserId,
+    now: u64,
+) -> Result<ActorKeyDto> {
+    let (public_key_pem, private_key) =
+        crypto.signature_keys().generate().await?;
+
+    Ok(ActorKeyDto {
+        user_id: user_id.clone(),
+        public_key_pem,
+        private_key_cipher: crypto
+            .symmetric_encryption()
+            .encrypt_to_hex(&private_key)
+            .await?,
+        created_at: now,
+    })
+}
+
+/// `publicKey` object of the actor at `actor_url`.
+pub(crate) fn public_key(
+    actor_url: &str,
+    key: ActorKeyDto,
+) -> ActorPublicKeyDto {
+    ActorPublicKeyDto {
+        id: format!("{actor_url}#main-key"),
+        owner: actor_url.to_string(),
+        public_key_pem: key.public_key_pem,
+    }
+}
+
+/// Actor key generation use case service.
+pub struct GenerateActorKeysUseCase {
+    actor_key_repo: Arc<dyn ActorKeyRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    clock: Arc<dyn Clock>,
+}
+
+impl GenerateActorKeysUseCase {
+    pub fn new(
+        actor_key_repo: Arc<dyn ActorKeyRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            actor_key_repo,
+            crypto,
+            clock,
+        }
+    }
+}
+
+#[async_trait]
+impl GenerateActorKeys for GenerateActorKeysUseCase {
+    async fn generate_missing(&self, limit: u32) -> Result<usize> {
+        let mut generated = 0;
+        for user_id in self.actor_key_repo.list_missing(limit).await? {
+            let key = generate_actor_key(
+                self.crypto.as_ref(),
+                &user_id,
+                self.clock.now(),
+            )
+            .await?;
+            // Users created meanwhile already have one.
+            if self.actor_key_repo.create(&key).await? {
+                generated += 1;
+            }
+        }
+
+        Ok(generated)
+    }
+}
+
+/// ActivityPub federation use case service.
+pub struct FederationUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    inbox_repo: Arc<dyn InboxRepository>,
+    clock: Arc<dyn Clock>,
+    configuration: StatusDto,
+    /// SPKI PEM of the instance key.
+    instance_key: String,
+}
+
+impl FederationUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        inbox_repo: Arc<dyn InboxRepository>,
+        clock: Arc<dyn Clock>,
+        configuration: StatusDto,
+        instance_key: String,
+    ) -> Self {
+        Self {
+            account_repo,
+            inbox_repo,
+            clock,
+            configuration,
+            instance_key,
+        }
+    }
+
+    /// Ensure `user_id` is an active local actor.
+    async fn ensure_actor(&self, user_id: &UserId) -> Result<()> {
+        self.account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?
+            .ensure_active(self.clock.now())
+    }
+}
+
+/// Returns the `id` of an object, which may be embedded or referenced.
+fn object_id(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(id) => Some(id),
+        Value::Object(object) => object.get("id").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Federation for FederationUseCase {
+    fn instance_actor(&self) -> InstanceActorDto {
+        let url = instance_actor_url(&self.configuration.url);
+        let host = self
+            .configuration
+            .url
+            .split_once("://")
+            .map_or(self.configuration.url.as_str(), |(_, rest)| rest)
+            .trim_end_matches('/');
+
+        InstanceActorDto {
+            context: vec![ACTIVITY_STREAMS, "https://w3id.org/security/v1"],
+            id: url.clone(),
+            r#type: "Application",
+            preferred_username: host.to_string(),
+            name: self.configuration.name.clone(),
+            inbox: format!("{url}/inbox"),
+            outbox: format!("{url}/outbox"),
+            public_key: ActorPublicKeyDto {
+                id: instance_key_id(&self.configuration.url),
+                owner: url,
+                public_key_pem: self.instance_key.clone(),
+            },
+        }
+    }
+
+    fn instance_outbox(&self) -> OrderedCollectionDto {
+        OrderedCollectionDto::empty(format!(
+            "{}/outbox",
+            instance_actor_url(&self.configuration.url)
+        ))
+    }
+
+    async fn collection(
+        &self,
+        user_id: UserId,
+        kind: CollectionKind,
+    ) -> Result<OrderedCollectionDto> {
+        self.ensure_actor(&user_id).await?;
+
+        // Nothing is published nor followed yet.
+        Ok(OrderedCollectionDto::empty(format!(
+            "{}/{}",
+            actor_url(&self.configuration.url, &user_id),
+            kind.as_str()
+        )))
+    }
+
+    async fn receive(
+        &self,
+        user_id: UserId,
+        sender: &str,
+        activity: Value,
+    ) -> Result<()> {
+        self.ensure_actor(&user_id).await?;
+
+        let kind = activity
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| DomainError::ValidationFailed {
+                field: "type".into(),
+                message: "Missing activity type.".into(),
+            })?
+            .to_string();
+        // Forwarded activities would need their own signature.
+        if activity.get("actor").and_then(object_id) != Some(sender) {
+            return Err(ApplicationError::InvalidSignature);
+        }
+
+        self.inbox_repo
+            .append(&InboxActivityDto {
+                user_id,
+                actor: sender.to_string(),
+                kind,
+                activity_id: activity
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                activity,
+                received_at: self.clock.now(),
+            })
+            .await?;
+
+        Ok(())
+    }
+}
@@ -2,30 +2,38 @@
 
 use std::sync::Arc;
 
+use domain::identity::flags::UserFlags;
 use domain::identity::id::UserId;
 
-use crate::dto::{StatusDto, UserResponseDto};
+use crate::dto::{ImageDto, StatusDto, UserResponseDto};
 use crate::error::{ApplicationError, Result};
 use crate::ports::inbound::GetUser;
-use crate::ports::outbound::Token;
 use crate::ports::outbound::account::AccountRepository;
+use crate::ports::outbound::{ActorKeyRepository, Clock, Token};
+use crate::usecases::federation::{actor_url, public_key};
 
 /// Get user use case service.
 pub struct GetUserUseCase {
     account_repo: Arc<dyn AccountRepository>,
+    actor_key_repo: Arc<dyn ActorKeyRepository>,
     _token: Arc<dyn Token>,
+    clock: Arc<dyn Clock>,
     configuration: StatusDto,
 }
 
 impl GetUserUseCase {
     pub fn new(
         account_repo: Arc<dyn AccountRepository>,
+        actor_key_repo: Arc<dyn ActorKeyRepository>,
         _token: Arc<dyn Token>,
+        clock: Arc<dyn Clock>,
         configuration: StatusDto,
     ) -> Self {
         Self {
             account_repo,
+            actor_key_repo,
             _token,
+            clock,
             configuration,
         }
     }
@@ -51,37 +59,68 @@ impl GetUser for GetUserUseCase {
             chrono::DateTime::from_timestamp(account.created_at as i64, 0)
                 .map(|dt| dt.to_rfc3339())
                 .unwrap_or_default();
-        let url = url::Url::parse(&self.configuration.url)
-            .map_err(|err| ApplicationError::Internal(Box::new(err)))?;
-        let user_url = format!(
-            "{}://{}/users/{}",
-            url.scheme(),
-            url.host().map(|u| u.to_string()).unwrap_or_default(),
-            account.username
-        );
+        // Actors are addressed by ID as usernames may change.
+        let user_url = actor_url(&self.configuration.url, &account.id);
+
+        let context = vec![
+            "https://www.w3.org/ns/activitystreams".to_string(),
+            "https://w3id.org/security/v1".to_string(),
+        ];
+
+        // Suspended actors keep their identity but expose no content.
+        if account.is_suspended(self.clock.now()) {
+            return Ok(UserResponseDto {
+                context,
+                r#type: "Tombstone".to_string(),
+                former_type: Some(actor_type(account.flags).to_string()),
+                id: user_url.clone(),
+                preferred_username: account.username,
+                name: None,
+                summary: None,
+                flags: UserFlags::empty(),
+                public_keys: Vec::new(),
+                public_key: None,
+                inbox: format!("{}/inbox", user_url),
+                outbox: format!("{}/outbox", user_url),
+                followers: format!("{}/followers", user_url),
+                following: format!("{}/following", user_url),
+                published: published_date,
+                icon: None,
+                image: None,
+            });
+        }
+
+        // Keys are generated with accounts, never here: fetching actors
+        // must stay cheap.
+        let key = self.actor_key_repo.find(&account.id).await?;
 
         Ok(UserResponseDto {
-            context: vec![
-                "https://www.w3.org/ns/activitystreams".to_string(),
-                "https://w3id.org/security/v1".to_string(),
-            ],
-            r#type: "Person".to_string(),
+            context,
+            r#type: actor_type(account.flags).to_string(),
+            former_type: None,
             id: user_url.clone(),
             preferred_username: account.username.clone(),
             name: Some(account.username),
             summary: account.summary,
             flags: account.flags,
             public_keys: account.public_keys,
+            public_key: key.map(|key| public_key(&user_url, key)),
             inbox: format!("{}/inbox", user_url),
             outbox: format!("{}/outbox", user_url),
             followers: format!("{}/followers", user_url),
             following: format!("{}/following", user_url),
             published: published_date,
-            icon: if let Some(avatar) = account.avatar {
-                vec![avatar]
-            } else {
-                Vec::default()
-            },
+            icon: account.avatar.map(ImageDto::webp),
+            image: account.banner.map(ImageDto::webp),
         })
     }
 }
+
+/// ActivityPub type of an actor with `flags`.
+fn actor_type(flags: UserFlags) -> &'static str {
+    if flags.contains(UserFlags::BOT) {
+        "Service"
+    } else {
+        "Person"
+    }
+}
@@ -0,0 +1,127 @@
+//! Import use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::auth::email::EmailHash;
+use domain::auth::password::PasswordHash;
+use domain::error::DomainError;
+use domain::identity::account::DEFAULT_LOCALE;
+use domain::identity::email::EmailAddress;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+
+use crate::dto::{AccountDto, ImportReportDto, ImportedAccountDto};
+use crate::error::Result;
+use crate::ports::inbound::ImportAccounts;
+use crate::ports::outbound::{AccountRepository, Clock, CryptoPort};
+
+/// Import use case service.
+///
+/// Password hashes are kept as is, and replaced with Argon2id on the first
+/// login of each user.
+pub struct ImportAccountsUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ImportAccountsUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            crypto,
+            clock,
+        }
+    }
+
+    /// Validate an imported user, and encrypt and index its email.
+    async fn prepare(
+        &self,
+        account: ImportedAccountDto,
+        now: u64,
+    ) -> Result<AccountDto> {
+        let id = UserId::parse(&account.id)?;
+        let email = EmailAddress::parse(&account.email)?;
+        let password_hash = PasswordHash::parse(account.password_hash)
+            .ok()
+            .filter(|hash| self.crypto.password_hasher().supports(hash))
+            .ok_or_else(|| DomainError::ValidationFailed {
+                field: "password_hash".into(),
+                message: "unsupported hash format".into(),
+            })?;
+        let locale = match account.locale {
+            Some(locale)
+                if locale.len() == 2 &&
+                    locale.bytes().all(|b| b.is_ascii_lowercase()) =>
+            {
+                locale
+            },
+            Some(_) => {
+                return Err(DomainError::ValidationFailed {
+                    field: "locale".into(),
+                    message: "must be an ISO 639-1 code".into(),
+                }
+                .into());
+            },
+            None => DEFAULT_LOCALE.to_string(),
+        };
+
+        Ok(AccountDto {
+            username: id.to_string(),
+            id,
+            email_hash: EmailHash::new(
+                self.crypto.blind_index().index(email.as_bytes()),
+            ),
+            email_cipher: self
+                .crypto
+                .symmetric_encryption()
+                .encrypt_to_hex(email.as_bytes())
+                .await?,
+            password_hash,
+            totp_secret: None,
+            locale,
+            summary: None,
+            avatar: None,
+            banner: None,
+            flags: UserFlags::empty(),
+            created_at: now,
+            deleted_at: None,
+            public_keys: Vec::new(),
+            token_generation: 0,
+            suspension: None,
+            password_reset_required: false,
+        })
+    }
+}
+
+#[async_trait]
+impl ImportAccounts for ImportAccountsUseCase {
+    async fn import(
+        &self,
+        accounts: Vec<ImportedAccountDto>,
+        dry_run: bool,
+    ) -> Result<ImportReportDto> {
+        let now = self.clock.now();
+        let mut report = ImportReportDto::default();
+        let mut valid = Vec::with_capacity(accounts.len());
+        for (position, account) in accounts.into_iter().enumerate() {
+            match self.prepare(account, now).await {
+                Ok(account) => valid.push(account),
+                Err(err) => report.invalid.push((position, err.to_string())),
+            }
+        }
+
+        let imported = self.account_repo.import(&valid, dry_run).await?;
+        (report.imported, report.skipped) = valid
+            .into_iter()
+            .map(|account| account.id)
+            .partition(|id| imported.contains(id));
+
+        Ok(report)
+    }
+}
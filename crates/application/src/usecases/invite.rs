@@ -0,0 +1,213 @@
+//! Invitation management use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+
+use crate::dto::{CreateInviteDto, InviteDto, InviteQuota};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::ManageInvites;
+use crate::ports::outbound::{Clock, CryptoPort, InviteRepository};
+
+/// Length of generated invitation codes.
+pub(crate) const CODE_LENGTH: usize = 12;
+
+/// Invitation management use case service.
+pub struct ManageInvitesUseCase {
+    invite_repo: Arc<dyn InviteRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    clock: Arc<dyn Clock>,
+    quota: InviteQuota,
+}
+
+impl ManageInvitesUseCase {
+    pub fn new(
+        invite_repo: Arc<dyn InviteRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        clock: Arc<dyn Clock>,
+        quota: InviteQuota,
+    ) -> Self {
+        Self {
+            invite_repo,
+            crypto,
+            clock,
+            quota,
+        }
+    }
+}
+
+#[async_trait]
+impl ManageInvites for ManageInvitesUseCase {
+    async fn create(
+        &self,
+        user_id: &UserId,
+        request: CreateInviteDto,
+    ) -> Result<InviteDto> {
+        let max_uses = request.max_uses.unwrap_or(1);
+        if max_uses == 0 || max_uses > self.quota.max_uses {
+            return Err(DomainError::ValidationFailed {
+                field: "maxUses".to_string(),
+                message: format!(
+                    "Must be between 1 and {}.",
+                    self.quota.max_uses
+                ),
+            }
+            .into());
+        }
+
+        let ttl = request.expires_in.unwrap_or(self.quota.max_ttl);
+        if ttl == 0 || ttl > self.quota.max_ttl {
+            return Err(DomainError::ValidationFailed {
+                field: "expiresIn".to_string(),
+                message: format!(
+                    "Must be between 1 and {} seconds.",
+                    self.quota.max_ttl
+                ),
+            }
+            .into());
+        }
+
+        let now = self.clock.now();
+        let invite = InviteDto {
+            code: self.crypto.secure_random().random_string(CODE_LENGTH)?,
+            created_by: Some(user_id.clone()),
+            max_uses,
+            uses: 0,
+            expires_at: Some(now + ttl),
+            revoked_at: None,
+            created_at: now,
+            used_by: Vec::new(),
+        };
+        self.invite_repo.create(&invite, Some(&self.quota)).await?;
+
+        Ok(invite)
+    }
+
+    async fn list(&self, user_id: &UserId) -> Result<Vec<InviteDto>> {
+        self.invite_repo.list_by_creator(user_id).await
+    }
+
+    async fn revoke(&self, user_id: &UserId, code: &str) -> Result<()> {
+        if !self.invite_repo.revoke(code, Some(user_id)).await? {
+            return Err(ApplicationError::InviteNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MemoryInvites, TestClock, TestCrypto};
+
+    const QUOTA: InviteQuota = InviteQuota {
+        max_active: 2,
+        cooldown: 60,
+        max_uses: 5,
+        max_ttl: 3_600,
+    };
+
+    fn usecase() -> (ManageInvitesUseCase, Arc<TestClock>) {
+        let clock = TestClock::new(1_000);
+        let usecase = ManageInvitesUseCase::new(
+            MemoryInvites::new([]),
+            TestCrypto::new(),
+            clock.clone(),
+            QUOTA,
+        );
+        (usecase, clock)
+    }
+
+    #[tokio::test]
+    async fn test_quota() {
+        let (usecase, clock) = usecase();
+        let alice = UserId::parse("alice").unwrap();
+        let bob = UserId::parse("bob").unwrap();
+
+        usecase.create(&alice, Default::default()).await.unwrap();
+        let err = usecase.create(&alice, Default::default()).await;
+        assert!(matches!(
+            err,
+            Err(ApplicationError::RateLimited { retry_after: 60 })
+        ));
+        // The cooldown is per user.
+        usecase.create(&bob, Default::default()).await.unwrap();
+
+        clock.advance(60);
+        usecase.create(&alice, Default::default()).await.unwrap();
+        clock.advance(60);
+        let err = usecase.create(&alice, Default::default()).await;
+        assert!(matches!(
+            err,
+            Err(ApplicationError::InviteQuotaExceeded { limit: 2 })
+        ));
+
+        // Revoked and expired invitations are no longer counted.
+        let code = usecase.list(&alice).await.unwrap()[0].code.clone();
+        usecase.revoke(&alice, &code).await.unwrap();
+        usecase.create(&alice, Default::default()).await.unwrap();
+        clock.advance(QUOTA.max_ttl);
+        usecase.create(&alice, Default::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_limits() {
+        let (usecase, _) = usecase();
+        let alice = UserId::parse("alice").unwrap();
+
+        for request in [
+            CreateInviteDto {
+                max_uses: Some(0),
+                expires_in: None,
+            },
+            CreateInviteDto {
+                max_uses: Some(QUOTA.max_uses + 1),
+                expires_in: None,
+            },
+            CreateInviteDto {
+                max_uses: None,
+                expires_in: Some(QUOTA.max_ttl + 1),
+            },
+        ] {
+            let err = usecase.create(&alice, request).await;
+            assert!(matches!(err, Err(ApplicationError::Domain(_))));
+        }
+
+        let invite = usecase
+            .create(
+                &alice,
+                CreateInviteDto {
+                    max_uses: Some(QUOTA.max_uses),
+                    expires_in: Some(60),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(invite.max_uses, QUOTA.max_uses);
+        assert_eq!(invite.expires_at, Some(1_060));
+    }
+
+    #[tokio::test]
+    async fn test_revoke() {
+        let (usecase, _) = usecase();
+        let alice = UserId::parse("alice").unwrap();
+        let bob = UserId::parse("bob").unwrap();
+        let code = usecase
+            .create(&alice, Default::default())
+            .await
+            .unwrap()
+            .code;
+
+        for (user, code) in [(&bob, code.as_str()), (&alice, "unknown")] {
+            let err = usecase.revoke(user, code).await;
+            assert!(matches!(err, Err(ApplicationError::InviteNotFound)));
+        }
+
+        usecase.revoke(&alice, &code).await.unwrap();
+        let err = usecase.revoke(&alice, &code).await;
+        assert!(matches!(err, Err(ApplicationError::InviteNotFound)));
+    }
+}
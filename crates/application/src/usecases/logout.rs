@@ -0,0 +1,96 @@
+//! Logout use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::dto::{LogoutRequestDto, SecurityEventDto, SecurityEventKind};
+use crate::error::Result;
+use crate::ports::inbound::Logout;
+use crate::ports::outbound::{
+    AuditLog, Clock, CryptoPort, RefreshTokenRepository, TokenDenylist,
+};
+use crate::usecases::audit;
+
+/// Logout use case service.
+pub struct LogoutUseCase {
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    denylist: Arc<dyn TokenDenylist>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl LogoutUseCase {
+    pub fn new(
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        denylist: Arc<dyn TokenDenylist>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            refresh_token_repo,
+            crypto,
+            denylist,
+            clock,
+            audit_log: None,
+        }
+    }
+
+    /// Record logouts in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    async fn audit(&self, request: &LogoutRequestDto) -> Result<()> {
+        let mut event = SecurityEventDto::new(
+            &request.user_id,
+            SecurityEventKind::Logout,
+            &request.client,
+            self.clock.now(),
+        );
+        if request.everywhere {
+            event = event.with_details("everywhere");
+        }
+        audit(self.audit_log.as_deref(), event).await
+    }
+}
+
+#[async_trait]
+impl Logout for LogoutUseCase {
+    async fn execute(&self, request: LogoutRequestDto) -> Result<()> {
+        self.denylist.deny(&request.jti, request.expires_at).await?;
+
+        if request.everywhere {
+            self.refresh_token_repo
+                .revoke_all_for_user(&request.user_id)
+                .await?;
+            self.denylist.bump_generation(&request.user_id).await?;
+            return self.audit(&request).await;
+        }
+
+        if let Some(refresh_token) = &request.refresh_token {
+            let refresh_token =
+                self.crypto.hasher().hash(refresh_token.as_bytes());
+
+            // Never let a user end a session owned by someone else.
+            if let Some(session) =
+                self.refresh_token_repo.find_session(&refresh_token).await? &&
+                session.user_id == request.user_id
+            {
+                self.refresh_token_repo
+                    .revoke_session(&request.user_id, &session.id)
+                    .await?;
+            }
+        }
+
+        if let Some(session_id) = &request.session_id {
+            self.refresh_token_repo
+                .revoke_session(&request.user_id, session_id)
+                .await?;
+        }
+
+        self.audit(&request).await
+    }
+}
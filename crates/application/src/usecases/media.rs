@@ -0,0 +1,125 @@
+//! Profile media use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::identity::flags::UserFlags;
+use domain::identity::id::UserId;
+
+use crate::dto::{AccountDto, ImageDto, MediaKind};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::ManageMedia;
+use crate::ports::outbound::{
+    AccountRepository, Clock, CryptoPort, ImageProcessor, MediaStore,
+};
+
+/// Length of the random part of media file names.
+const NAME_LENGTH: usize = 16;
+
+/// Profile media use case service.
+pub struct ManageMediaUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    image_processor: Arc<dyn ImageProcessor>,
+    media_store: Arc<dyn MediaStore>,
+    crypto: Arc<dyn CryptoPort>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ManageMediaUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        image_processor: Arc<dyn ImageProcessor>,
+        media_store: Arc<dyn MediaStore>,
+        crypto: Arc<dyn CryptoPort>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            image_processor,
+            media_store,
+            crypto,
+            clock,
+        }
+    }
+
+    /// Returns the account of `user_id` if its profile may be changed.
+    async fn account(&self, user_id: &UserId) -> Result<AccountDto> {
+        let account = self
+            .account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?;
+        account.ensure_active(self.clock.now())?;
+        if account.flags.contains(UserFlags::LOCKED) {
+            return Err(ApplicationError::AccountLocked);
+        }
+
+        Ok(account)
+    }
+
+    /// Delete a file previously returned by the media store.
+    async fn delete(&self, url: Option<String>) {
+        if let Some(key) = url.and_then(|url| self.media_store.key_of(&url)) {
+            // An orphaned file is harmless, the account is already updated.
+            let _ = self.media_store.delete(&key).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ManageMedia for ManageMediaUseCase {
+    async fn upload(
+        &self,
+        user_id: &UserId,
+        kind: MediaKind,
+        data: Vec<u8>,
+    ) -> Result<ImageDto> {
+        self.account(user_id).await?;
+        let image = self.image_processor.process(data, kind).await?;
+
+        // A new name for each upload keeps cached copies from going stale.
+        let key = format!(
+            "{}/{}/{}.webp",
+            kind.as_str(),
+            user_id,
+            self.crypto.secure_random().random_string(NAME_LENGTH)?
+        );
+        let url = self
+            .media_store
+            .put(&key, image.media_type, image.data)
+            .await?;
+
+        // Only the URL is written: the account may have changed during
+        // processing.
+        let previous = match self
+            .account_repo
+            .replace_media(user_id, kind, Some(&url))
+            .await
+        {
+            Ok(previous) => previous,
+            Err(err) => {
+                let _ = self.media_store.delete(&key).await;
+                return Err(err);
+            },
+        };
+        self.delete(previous).await;
+
+        Ok(ImageDto::webp(url))
+    }
+
+    async fn remove(&self, user_id: &UserId, kind: MediaKind) -> Result<()> {
+        let account = self.account(user_id).await?;
+
+        let current = match kind {
+            MediaKind::Avatar => account.avatar,
+            MediaKind::Banner => account.banner,
+        };
+        if current.is_some() {
+            let previous =
+                self.account_repo.replace_media(user_id, kind, None).await?;
+            self.delete(previous).await;
+        }
+
+        Ok(())
+    }
+}
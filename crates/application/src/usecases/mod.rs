@@ -1,18 +1,192 @@
 //! Application services implementing business logic.
 
+use domain::auth::email::EmailHash;
+use domain::auth::factor::VerifiedFactor;
+use domain::auth::invariants::{
+    validate_sensitive_operation, validate_session_reauthentication,
+};
+use domain::auth::password::Password;
+use domain::auth::proof::AuthenticationProof;
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+
+use crate::dto::SecurityEventDto;
+use crate::error::{ApplicationError, Result};
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, CryptoPort, PasswordPolicy, RoleRepository,
+    TokenClaims,
+};
+
 pub const TOKEN_TYPE: &str = "Bearer";
 const EXPIRES_IN: u64 = 900; // 15 minutes.
+/// Random bytes in a session ID.
+const SESSION_ID_BYTES: usize = 16;
+/// Maximum age, in seconds, of the authentication allowing a sensitive
+/// operation.
+const SENSITIVE_MAX_AGE: u64 = 300; // 5 minutes.
 
+pub mod admin;
 pub mod auth;
 pub mod create_account;
+pub mod delete_account;
+pub mod federation;
 pub mod get_user;
+pub mod import;
+pub mod invite;
+pub mod logout;
+pub mod media;
+pub mod moderation;
+pub mod peer;
+pub mod reauthenticate;
+pub mod reencrypt;
 pub mod refresh_token;
+pub mod security_events;
+pub mod session;
 pub mod status;
 pub mod update_user;
 
+pub use admin::*;
 pub use auth::*;
 pub use create_account::*;
+pub use delete_account::*;
+pub use federation::*;
 pub use get_user::*;
+pub use import::*;
+pub use invite::*;
+pub use logout::*;
+pub use media::*;
+pub use moderation::*;
+pub use peer::*;
+pub use reauthenticate::*;
+pub use reencrypt::*;
 pub use refresh_token::*;
+pub use security_events::*;
+pub use session::*;
 pub use status::*;
 pub use update_user::*;
+
+/// Rebuild the proof behind `claims` and ensure it allows a sensitive
+/// operation, i.e. is recent and contains a possession factor.
+///
+/// Accounts without `second_factor` cannot prove possession, so a recent
+/// reauthentication bound to the session is enough for them.
+fn ensure_sensitive_operation(
+    user_id: &UserId,
+    claims: &TokenClaims,
+    now: u64,
+    second_factor: bool,
+) -> Result<()> {
+    let factors = claims
+        .amr
+        .iter()
+        .filter_map(|amr| VerifiedFactor::from_amr(amr, claims.auth_time))
+        .collect();
+
+    AuthenticationProof::new(user_id, factors, claims.auth_time)
+        .and_then(|proof| {
+            if second_factor {
+                validate_sensitive_operation(&proof, now, SENSITIVE_MAX_AGE)
+            } else {
+                validate_session_reauthentication(
+                    &proof,
+                    now,
+                    SENSITIVE_MAX_AGE,
+                )
+            }
+        })
+        .map_err(|_| ApplicationError::ReauthenticationRequired {
+            max_age: SENSITIVE_MAX_AGE,
+        })
+}
+
+/// Ensure `actor` holds `required` or a role including it, and returns the
+/// roles of `actor`.
+async fn ensure_role(
+    role_repo: &dyn RoleRepository,
+    actor: &UserId,
+    required: Role,
+) -> Result<Vec<Role>> {
+    let roles = role_repo.roles(actor).await?;
+
+    if roles.iter().any(|role| role.includes(required)) {
+        Ok(roles)
+    } else {
+        Err(ApplicationError::Forbidden)
+    }
+}
+
+/// Ensure staff with `actor_roles` may act on a user with `target_roles`,
+/// i.e. that they hold a strictly higher role.
+fn ensure_outranks(actor_roles: &[Role], target_roles: &[Role]) -> Result<()> {
+    match target_roles.iter().max() {
+        Some(target) if actor_roles.iter().max() <= Some(target) => {
+            Err(ApplicationError::Forbidden)
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Blind indexes of `email` with every accepted secret, current first.
+fn email_hashes(crypto: &dyn CryptoPort, email: &[u8]) -> Vec<EmailHash> {
+    crypto
+        .blind_index()
+        .candidates(email)
+        .into_iter()
+        .map(EmailHash::new)
+        .collect()
+}
+
+/// Ensure no account uses an email indexed with a former secret, as
+/// these escape the unique constraint on the current index.
+async fn ensure_email_available(
+    account_repo: &dyn AccountRepository,
+    email_hashes: &[EmailHash],
+) -> Result<()> {
+    let former = email_hashes.get(1..).unwrap_or_default();
+    if !former.is_empty() &&
+        account_repo.find_by_email_hashes(former).await?.is_some()
+    {
+        return Err(DomainError::ValidationFailed {
+            field: "email".to_string(),
+            message: "Email is already in use.".to_string(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Ensure `password`, sent as `field`, follows the password policy, if
+/// one is configured.
+async fn ensure_password_allowed(
+    password_policy: Option<&dyn PasswordPolicy>,
+    field: &str,
+    password: &Password,
+    user_inputs: &[&str],
+) -> Result<()> {
+    let Some(password_policy) = password_policy else {
+        return Ok(());
+    };
+
+    let violations = password_policy.check(password, user_inputs).await?;
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ApplicationError::PasswordRejected {
+            field: field.to_string(),
+            violations,
+        })
+    }
+}
+
+/// Append `event` to the audit log, if one is configured.
+async fn audit(
+    audit_log: Option<&dyn AuditLog>,
+    event: SecurityEventDto,
+) -> Result<()> {
+    match audit_log {
+        Some(audit_log) => audit_log.append(&event).await,
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,165 @@
+//! Account moderation use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+use domain::identity::suspension::Suspension;
+
+use crate::dto::{
+    AccountDto, ClientContextDto, ModerationAction, ModerationActionDto,
+    SecurityEventDto, SecurityEventKind, SuspendAccountDto,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::ModerateAccount;
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, Clock, ModerationRepository,
+    RefreshTokenRepository, RoleRepository, TokenDenylist,
+};
+use crate::usecases::{audit, ensure_outranks, ensure_role};
+
+/// Account moderation use case service.
+pub struct ModerateAccountUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    moderation_repo: Arc<dyn ModerationRepository>,
+    role_repo: Arc<dyn RoleRepository>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    denylist: Arc<dyn TokenDenylist>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl ModerateAccountUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        moderation_repo: Arc<dyn ModerationRepository>,
+        role_repo: Arc<dyn RoleRepository>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        denylist: Arc<dyn TokenDenylist>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            moderation_repo,
+            role_repo,
+            refresh_token_repo,
+            denylist,
+            clock,
+            audit_log: None,
+        }
+    }
+
+    /// Record suspensions in the audit log of the account owner.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    async fn audit(
+        &self,
+        user_id: &UserId,
+        action: ModerationAction,
+        now: u64,
+    ) -> Result<()> {
+        let event = SecurityEventDto::new(
+            user_id,
+            SecurityEventKind::AdminAction,
+            &ClientContextDto::default(),
+            now,
+        )
+        .with_details(action.as_str());
+        audit(self.audit_log.as_deref(), event).await
+    }
+
+    /// Ensure `moderator` may moderate `user_id`, and returns the account.
+    async fn find_account(
+        &self,
+        moderator: &UserId,
+        user_id: &UserId,
+    ) -> Result<AccountDto> {
+        let roles =
+            ensure_role(self.role_repo.as_ref(), moderator, Role::Moderator)
+                .await?;
+        ensure_outranks(&roles, &self.role_repo.roles(user_id).await?)?;
+
+        let account = self
+            .account_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?;
+
+        if let Some(date) = account.deleted_at {
+            return Err(ApplicationError::AccountDeleted { date });
+        }
+
+        Ok(account)
+    }
+}
+
+#[async_trait]
+impl ModerateAccount for ModerateAccountUseCase {
+    async fn suspend(&self, request: SuspendAccountDto) -> Result<()> {
+        if request.user_id == request.moderator {
+            return Err(DomainError::ValidationFailed {
+                field: "id".into(),
+                message: "moderators cannot suspend themselves".into(),
+            }
+            .into());
+        }
+
+        self.find_account(&request.moderator, &request.user_id)
+            .await?;
+
+        let now = self.clock.now();
+        let suspension = Suspension::new(request.reason, now, request.until)?;
+        self.moderation_repo
+            .suspend(&request.user_id, &suspension, &request.moderator)
+            .await?;
+
+        // Tokens issued before the suspension must stop working at once.
+        self.refresh_token_repo
+            .revoke_all_for_user(&request.user_id)
+            .await?;
+        self.denylist.bump_generation(&request.user_id).await?;
+
+        self.audit(&request.user_id, ModerationAction::Suspend, now)
+            .await
+    }
+
+    async fn unsuspend(
+        &self,
+        user_id: &UserId,
+        moderator: &UserId,
+        reason: String,
+    ) -> Result<()> {
+        let account = self.find_account(moderator, user_id).await?;
+        let now = self.clock.now();
+        if !account.is_suspended(now) {
+            return Err(ApplicationError::AccountNotSuspended);
+        }
+
+        let reason = Suspension::normalize_reason(reason)?;
+        if !self
+            .moderation_repo
+            .unsuspend(user_id, moderator, &reason)
+            .await?
+        {
+            return Err(ApplicationError::AccountNotSuspended);
+        }
+
+        self.audit(user_id, ModerationAction::Unsuspend, now).await
+    }
+
+    async fn history(
+        &self,
+        moderator: &UserId,
+        user_id: &UserId,
+    ) -> Result<Vec<ModerationActionDto>> {
+        ensure_role(self.role_repo.as_ref(), moderator, Role::Moderator)
+            .await?;
+
+        self.moderation_repo.list_actions(user_id).await
+    }
+}
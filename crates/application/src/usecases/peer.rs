@@ -0,0 +1,146 @@
+//! Trust store of remote instances use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::error::DomainError;
+use domain::identity::id::UserId;
+use domain::identity::role::Role;
+
+use crate::dto::{
+    ModerationAction, ModerationActionDto, PeerDto, PeerPolicy, PeerStatus,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::{ManagePeers, PeerTrust};
+use crate::ports::outbound::{
+    Clock, ModerationRepository, PeerRepository, RoleRepository,
+};
+use crate::usecases::ensure_role;
+
+/// Longest host name allowed by DNS, plus a port.
+const MAX_HOST_LENGTH: usize = 253 + 6;
+
+/// Trust store of remote instances use case service.
+pub struct PeerTrustUseCase {
+    peer_repo: Arc<dyn PeerRepository>,
+    role_repo: Arc<dyn RoleRepository>,
+    moderation_repo: Arc<dyn ModerationRepository>,
+    clock: Arc<dyn Clock>,
+    policy: PeerPolicy,
+}
+
+impl PeerTrustUseCase {
+    pub fn new(
+        peer_repo: Arc<dyn PeerRepository>,
+        role_repo: Arc<dyn RoleRepository>,
+        moderation_repo: Arc<dyn ModerationRepository>,
+        clock: Arc<dyn Clock>,
+        policy: PeerPolicy,
+    ) -> Self {
+        Self {
+            peer_repo,
+            role_repo,
+            moderation_repo,
+            clock,
+            policy,
+        }
+    }
+}
+
+/// Normalize a `host[:port]` authority.
+fn normalize_host(host: &str) -> Result<String> {
+    let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid = !host.is_empty() &&
+        host.len() <= MAX_HOST_LENGTH &&
+        host.bytes().all(|b| {
+            b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b':')
+        });
+
+    if valid {
+        Ok(host)
+    } else {
+        Err(DomainError::ValidationFailed {
+            field: "host".into(),
+            message: "Invalid host name.".into(),
+        }
+        .into())
+    }
+}
+
+#[async_trait]
+impl PeerTrust for PeerTrustUseCase {
+    async fn authorize(&self, host: &str) -> Result<()> {
+        let host = normalize_host(host)
+            .map_err(|_| ApplicationError::PeerNotTrusted)?;
+        let status = self
+            .peer_repo
+            .find(&host)
+            .await?
+            .and_then(|peer| peer.status);
+
+        match (status, self.policy) {
+            (Some(PeerStatus::Allowed), _) | (None, PeerPolicy::Open) => {
+                Ok(())
+            },
+            _ => Err(ApplicationError::PeerNotTrusted),
+        }
+    }
+
+    async fn verified(&self, host: &str, key_id: &str) -> Result<()> {
+        self.peer_repo
+            .record_verified(&normalize_host(host)?, key_id, self.clock.now())
+            .await
+    }
+}
+
+#[async_trait]
+impl ManagePeers for PeerTrustUseCase {
+    async fn list_peers(&self, actor: &UserId) -> Result<Vec<PeerDto>> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Moderator).await?;
+
+        let peers = self.peer_repo.list().await?;
+        self.moderation_repo
+            .record(&ModerationActionDto {
+                user_id: None,
+                moderator: actor.clone(),
+                action: ModerationAction::ListPeers,
+                reason: None,
+                target: None,
+                until: None,
+                created_at: self.clock.now(),
+            })
+            .await?;
+
+        Ok(peers)
+    }
+
+    async fn set_peer_status(
+        &self,
+        actor: &UserId,
+        host: &str,
+        status: Option<PeerStatus>,
+        reason: Option<String>,
+    ) -> Result<PeerDto> {
+        ensure_role(self.role_repo.as_ref(), actor, Role::Admin).await?;
+
+        let host = normalize_host(host)?;
+        let now = self.clock.now();
+        let peer = self.peer_repo.set_status(&host, status, now).await?;
+        self.moderation_repo
+            .record(&ModerationActionDto {
+                user_id: None,
+                moderator: actor.clone(),
+                action: ModerationAction::SetPeerStatus,
+                reason,
+                target: Some(format!(
+                    "{host}={}",
+                    status.map_or("none", |status| status.as_str())
+                )),
+                until: None,
+                created_at: now,
+            })
+            .await?;
+
+        Ok(peer)
+    }
+}
@@ -0,0 +1,336 @@
+//! Reauthentication use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::auth::factor::{
+    FactorMethod, FactorType, TotpCode, TotpConfig, TotpSecret, VerifiedFactor,
+};
+use domain::auth::invariants::validate_totp_requirement;
+use domain::auth::password::Password;
+use domain::auth::proof::AuthenticationProofBuilder;
+use domain::error::DomainError;
+
+use crate::dto::{
+    ElevatedTokenDto, ReauthenticateRequestDto, SecurityEventDto,
+    SecurityEventKind,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::Reauthenticate;
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, Clock, CryptoPort, RefreshTokenRepository,
+    Token,
+};
+use crate::usecases::{SENSITIVE_MAX_AGE, TOKEN_TYPE, audit};
+
+/// Reauthentication use case service.
+///
+/// The elevated token requires the password and the TOTP code when the
+/// account has one. Otherwise the refresh token of the current session
+/// (`swk`) binds the token to the session: it proves the device holds the
+/// session, not a second factor of the user, so it is neither a possession
+/// factor nor counted towards `mfa`.
+pub struct ReauthenticateUseCase {
+    account_repo: Arc<dyn AccountRepository>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    token: Arc<dyn Token>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl ReauthenticateUseCase {
+    pub fn new(
+        account_repo: Arc<dyn AccountRepository>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        token: Arc<dyn Token>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            account_repo,
+            refresh_token_repo,
+            crypto,
+            token,
+            clock,
+            audit_log: None,
+        }
+    }
+
+    /// Record reauthentications in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Verify the TOTP code against the encrypted secret of the account.
+    async fn verify_totp(
+        &self,
+        encrypted_secret: &str,
+        code: &str,
+    ) -> Result<()> {
+        let secret_bytes = self
+            .crypto
+            .symmetric_encryption()
+            .decrypt_from_hex(encrypted_secret)
+            .await?;
+        let secret_str = String::from_utf8(secret_bytes)
+            .map_err(|_| DomainError::InvalidTotpSecret)?;
+
+        let secret = TotpSecret::new(secret_str)?;
+        let totp_code = TotpCode::six_digits(code)?;
+
+        if !self.crypto.totp_generator().verify(
+            &totp_code,
+            &secret,
+            &TotpConfig::default(),
+        )? {
+            return Err(DomainError::InvalidTotpCode.into());
+        }
+
+        Ok(())
+    }
+
+    /// Ensure `refresh_token` belongs to the session of the request.
+    async fn verify_refresh_token(
+        &self,
+        request: &ReauthenticateRequestDto,
+        refresh_token: &str,
+    ) -> Result<()> {
+        let refresh_token =
+            self.crypto.hasher().hash(refresh_token.as_bytes());
+        let session = self
+            .refresh_token_repo
+            .find_session(&refresh_token)
+            .await?
+            .ok_or(DomainError::InvalidCredentials)?;
+
+        if session.user_id != request.user_id ||
+            request.session_id.as_deref() != Some(session.id.as_str())
+        {
+            return Err(DomainError::InvalidCredentials.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Reauthenticate for ReauthenticateUseCase {
+    async fn execute(
+        &self,
+        request: ReauthenticateRequestDto,
+    ) -> Result<ElevatedTokenDto> {
+        let account = self
+            .account_repo
+            .find_by_id(&request.user_id)
+            .await?
+            .ok_or(ApplicationError::UserNotFound)?;
+
+        account.ensure_active(self.clock.now())?;
+
+        let password = Password::new(&request.password)?;
+        self.crypto
+            .password_hasher()
+            .verify(&password, &account.password_hash)
+            .await?;
+
+        let now = self.clock.now();
+        let mut verified_factors = vec![VerifiedFactor::new(
+            FactorType::Knowledge,
+            FactorMethod::Password,
+            now,
+        )];
+
+        validate_totp_requirement(
+            account.totp_secret.is_some(),
+            request.totp_code.is_some(),
+        )?;
+
+        // Accounts without a second factor bind the reauthentication to
+        // their session through its refresh token instead. Whoever holds
+        // both the password and that token passes, so it never replaces an
+        // enrolled TOTP.
+        let factor = match (&account.totp_secret, &request.totp_code) {
+            (Some(encrypted_secret), Some(code)) => {
+                self.verify_totp(encrypted_secret, code).await?;
+                VerifiedFactor::new(
+                    FactorType::Possession,
+                    FactorMethod::Totp,
+                    now,
+                )
+            },
+            _ => {
+                let refresh_token = request.refresh_token.as_deref().ok_or(
+                    DomainError::ValidationFailed {
+                        field: "refreshToken".into(),
+                        message: "refresh token is required".into(),
+                    },
+                )?;
+                self.verify_refresh_token(&request, refresh_token).await?;
+                VerifiedFactor::new(
+                    FactorType::Session,
+                    FactorMethod::RefreshToken,
+                    now,
+                )
+            },
+        };
+        verified_factors.push(factor);
+
+        let mut builder = AuthenticationProofBuilder::default()
+            .user_id(&account.id)
+            .token_generation(account.token_generation)
+            .authenticated_at(now)
+            .add_factors(verified_factors);
+        if let Some(session_id) = &request.session_id {
+            builder = builder.session_id(session_id);
+        }
+        let proof = builder.build()?;
+
+        let event = SecurityEventDto::new(
+            &account.id,
+            SecurityEventKind::Reauthenticated,
+            &request.client,
+            now,
+        )
+        .with_details(proof.amr().join(" "));
+        audit(self.audit_log.as_deref(), event).await?;
+
+        Ok(ElevatedTokenDto {
+            access_token: self
+                .token
+                .signer()
+                .create_elevated_token(&proof)
+                .await?,
+            token_type: TOKEN_TYPE.to_string(),
+            expires_in: SENSITIVE_MAX_AGE,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::identity::id::UserId;
+
+    use super::*;
+    use crate::dto::SessionDto;
+    use crate::ports::outbound::{Hasher, SymmetricEncryption, TokenSigner};
+    use crate::testing::{
+        MemoryAccounts, MemorySessions, TestClock, TestCrypto, TestToken,
+        account,
+    };
+
+    const PASSWORD: &str = "correct horse battery";
+    const REFRESH_TOKEN: &str = "refresh";
+
+    async fn usecase(totp: bool) -> ReauthenticateUseCase {
+        let crypto = TestCrypto::new();
+        let mut alice =
+            account(&crypto, "alice", "alice@example.com", PASSWORD);
+        if totp {
+            alice.totp_secret = Some(
+                crypto
+                    .encrypt_to_hex(b"JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let sessions = MemorySessions::new();
+        for (id, user_id) in [("current", "alice"), ("other", "bob")] {
+            let session = SessionDto {
+                id: id.into(),
+                user_id: UserId::parse(user_id).unwrap(),
+                device: None,
+                ip_address: None,
+                amr: vec!["pwd".into()],
+                authenticated_at: 0,
+                created_at: 0,
+                last_used_at: 0,
+            };
+            let token = format!("{REFRESH_TOKEN}-{id}");
+            sessions
+                .store(&crypto.hash(token.as_bytes()), &session)
+                .await
+                .unwrap();
+        }
+
+        ReauthenticateUseCase::new(
+            MemoryAccounts::new([alice]),
+            sessions,
+            crypto,
+            TestToken::new(),
+            TestClock::new(1_000),
+        )
+    }
+
+    fn request(
+        password: &str,
+        totp_code: Option<&str>,
+        refresh_token: Option<&str>,
+    ) -> ReauthenticateRequestDto {
+        ReauthenticateRequestDto {
+            user_id: UserId::parse("alice").unwrap(),
+            session_id: Some("current".into()),
+            password: password.into(),
+            totp_code: totp_code.map(str::to_string),
+            refresh_token: refresh_token
+                .map(|session| format!("{REFRESH_TOKEN}-{session}")),
+            client: Default::default(),
+        }
+    }
+
+    /// Without TOTP, the refresh token of the session binds the token to the
+    /// session, which is not counted towards `mfa`.
+    #[tokio::test]
+    async fn test_refresh_token_downgrade() {
+        let usecase = usecase(false).await;
+
+        let token = usecase
+            .execute(request(PASSWORD, None, Some("current")))
+            .await
+            .unwrap();
+        let claims = TestToken.verify_token(&token.access_token).unwrap();
+        assert_eq!(claims.amr, ["pwd", "swk"]);
+        assert_eq!(claims.auth_time, 1_000);
+        assert_eq!(token.expires_in, SENSITIVE_MAX_AGE);
+
+        for request in [
+            request(PASSWORD, None, None),
+            // The refresh token of another session, or user, is refused.
+            request(PASSWORD, None, Some("other")),
+            request(PASSWORD, None, Some("unknown")),
+            request("wrong password", None, Some("current")),
+        ] {
+            assert!(usecase.execute(request).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_totp_required() {
+        let usecase = usecase(true).await;
+
+        // A refresh token does not replace an enrolled second factor.
+        let err = usecase
+            .execute(request(PASSWORD, None, Some("current")))
+            .await;
+        assert!(matches!(
+            err,
+            Err(ApplicationError::Domain(DomainError::TotpRequired))
+        ));
+        let err = usecase
+            .execute(request(PASSWORD, Some("000000"), Some("current")))
+            .await;
+        assert!(matches!(
+            err,
+            Err(ApplicationError::Domain(DomainError::InvalidTotpCode))
+        ));
+
+        let token = usecase
+            .execute(request(PASSWORD, Some("123456"), None))
+            .await
+            .unwrap();
+        let claims = TestToken.verify_token(&token.access_token).unwrap();
+        assert_eq!(claims.amr, ["pwd", "otp", "mfa"]);
+    }
+}
@@ -0,0 +1,94 @@
+//! Re-encryption use case implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::ReencryptionDto;
+use crate::error::Result;
+use crate::ports::inbound::Reencrypt;
+use crate::ports::outbound::{
+    CryptoPort, EncryptedFieldRepository, SymmetricEncryption,
+};
+
+/// Re-encryption use case service.
+///
+/// Rows are rewritten one by one, only if they did not change meanwhile, so
+/// it runs alongside regular traffic.
+pub struct ReencryptUseCase {
+    encrypted_field_repo: Arc<dyn EncryptedFieldRepository>,
+    crypto: Arc<dyn CryptoPort>,
+}
+
+impl ReencryptUseCase {
+    pub fn new(
+        encrypted_field_repo: Arc<dyn EncryptedFieldRepository>,
+        crypto: Arc<dyn CryptoPort>,
+    ) -> Self {
+        Self {
+            encrypted_field_repo,
+            crypto,
+        }
+    }
+}
+
+/// Encrypt `cipher` with the current key, if it was encrypted with another.
+/// Returns `None` otherwise.
+async fn rotate(
+    encryption: &dyn SymmetricEncryption,
+    cipher: &str,
+) -> Result<Option<String>> {
+    if !encryption.needs_reencryption(cipher) {
+        return Ok(None);
+    }
+
+    let plaintext = encryption.decrypt_from_hex(cipher).await?;
+    encryption.encrypt_to_hex(&plaintext).await.map(Some)
+}
+
+#[async_trait]
+impl Reencrypt for ReencryptUseCase {
+    async fn reencrypt(
+        &self,
+        after: Option<&UserId>,
+        limit: u32,
+    ) -> Result<ReencryptionDto> {
+        let encryption = self.crypto.symmetric_encryption();
+        let users = self.encrypted_field_repo.list(after, limit).await?;
+
+        let mut progress = ReencryptionDto {
+            scanned: users.len(),
+            next: (users.len() == limit as usize)
+                .then(|| users.last().map(|user| user.user_id.clone()))
+                .flatten(),
+            ..Default::default()
+        };
+        for user in users {
+            let mut rotated = false;
+            let mut current = user.clone();
+            for cipher in std::iter::once(&mut current.email_cipher)
+                .chain(current.totp_secret.iter_mut())
+                .chain(current.actor_key.iter_mut())
+                .chain(current.session_ips.iter_mut())
+            {
+                if let Some(rotated_cipher) =
+                    rotate(encryption, cipher).await?
+                {
+                    *cipher = rotated_cipher;
+                    rotated = true;
+                }
+            }
+            if !rotated {
+                continue;
+            }
+
+            // Rows updated meanwhile already use the current key.
+            if self.encrypted_field_repo.replace(&user, &current).await? {
+                progress.reencrypted += 1;
+            }
+        }
+
+        Ok(progress)
+    }
+}
@@ -7,12 +7,11 @@ use domain::auth::factor::{FactorMethod, FactorType, VerifiedFactor};
 use domain::auth::proof::AuthenticationProofBuilder;
 use domain::error::DomainError;
 
-use crate::dto::{AuthResponseDto, RefreshTokenRequestDto};
+use crate::dto::{AuthResponseDto, RefreshTokenRequestDto, SessionDto};
 use crate::error::{ApplicationError, Result};
 use crate::ports::inbound::RefreshAccessToken;
 use crate::ports::outbound::{
-    AccountRepository, Clock, CryptoPort, RefreshTokenManager,
-    RefreshTokenRepository, TokenSigner,
+    AccountRepository, Clock, CryptoPort, RefreshTokenRepository, Token,
 };
 use crate::usecases::{EXPIRES_IN, TOKEN_TYPE};
 
@@ -21,8 +20,7 @@ pub struct RefreshTokenUseCase {
     account_repo: Arc<dyn AccountRepository>,
     refresh_token_repo: Arc<dyn RefreshTokenRepository>,
     crypto: Arc<dyn CryptoPort>,
-    token_signer: Arc<dyn TokenSigner>,
-    refresh_token_manager: Arc<dyn RefreshTokenManager>,
+    token: Arc<dyn Token>,
     clock: Arc<dyn Clock>,
 }
 
@@ -31,16 +29,14 @@ impl RefreshTokenUseCase {
         account_repo: Arc<dyn AccountRepository>,
         refresh_token_repo: Arc<dyn RefreshTokenRepository>,
         crypto: Arc<dyn CryptoPort>,
-        token_signer: Arc<dyn TokenSigner>,
-        refresh_token_manager: Arc<dyn RefreshTokenManager>,
+        token: Arc<dyn Token>,
         clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             account_repo,
             refresh_token_repo,
             crypto,
-            token_signer,
-            refresh_token_manager,
+            token,
             clock,
         }
     }
@@ -54,46 +50,62 @@ impl RefreshAccessToken for RefreshTokenUseCase {
     ) -> Result<AuthResponseDto> {
         let refresh_token =
             self.crypto.hasher().hash(request.refresh_token.as_bytes());
-        let user_id = self
+        let session = self
             .refresh_token_repo
-            .find_user_id(&refresh_token)
+            .find_session(&refresh_token)
             .await?
             .ok_or(DomainError::TokenNotFound)?;
 
         // Verify user still exists and is not deleted.
         let account = self
             .account_repo
-            .find_by_id(&user_id)
+            .find_by_id(&session.user_id)
             .await?
             .ok_or(ApplicationError::UserNotFound)?;
 
-        if let Some(date) = account.deleted_at {
-            return Err(ApplicationError::AccountDeleted { date });
-        }
+        account.ensure_active(self.clock.now())?;
 
         self.refresh_token_repo.revoke(&refresh_token).await?;
 
         let now = self.clock.now();
-        let verified_factor = VerifiedFactor::new(
-            FactorType::Possession,
-            FactorMethod::Password,
-            now,
-        );
+
+        // Access tokens keep the factors verified when the session began.
+        let mut verified_factors: Vec<VerifiedFactor> = session
+            .amr
+            .iter()
+            .filter_map(|amr| {
+                VerifiedFactor::from_amr(amr, session.authenticated_at)
+            })
+            .collect();
+        if verified_factors.is_empty() {
+            verified_factors.push(VerifiedFactor::new(
+                FactorType::Knowledge,
+                FactorMethod::Password,
+                session.authenticated_at,
+            ));
+        }
 
         let proof = AuthenticationProofBuilder::default()
             .user_id(&account.id)
+            .token_generation(account.token_generation)
             .authenticated_at(now)
-            .add_factor(verified_factor)
+            .add_factors(verified_factors)
+            .session_id(&session.id)
             .build()?;
 
-        let access_token = self.token_signer.create_access_token(&proof)?;
-        let new_refresh_token = self.refresh_token_manager.generate()?;
+        let access_token =
+            self.token.signer().create_access_token(&proof).await?;
+        let new_refresh_token = self.token.refresh_token().generate()?;
 
+        let session = SessionDto {
+            ip_address: request.ip_address.or(session.ip_address.clone()),
+            last_used_at: now,
+            ..session.clone()
+        };
         self.refresh_token_repo
             .store(
                 &self.crypto.hasher().hash(new_refresh_token.as_bytes()),
-                &account.id,
-                request.ip_address.as_deref(),
+                &session,
             )
             .await?;
 
@@ -0,0 +1,93 @@
+//! Security event listing use case implementation.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::identity::id::UserId;
+
+use crate::dto::{SecurityEventDto, SecurityEventInfoDto};
+use crate::error::Result;
+use crate::ports::inbound::ListSecurityEvents;
+use crate::ports::outbound::{AuditLog, CryptoPort, GeoLocator};
+
+/// Number of events shown to users.
+const MAX_EVENTS: u32 = 100;
+
+/// Security event listing use case service.
+pub struct ListSecurityEventsUseCase {
+    audit_log: Arc<dyn AuditLog>,
+    crypto: Arc<dyn CryptoPort>,
+    geo_locator: Option<Arc<dyn GeoLocator>>,
+}
+
+impl ListSecurityEventsUseCase {
+    pub fn new(
+        audit_log: Arc<dyn AuditLog>,
+        crypto: Arc<dyn CryptoPort>,
+    ) -> Self {
+        Self {
+            audit_log,
+            crypto,
+            geo_locator: None,
+        }
+    }
+
+    /// Show a coarse location for each event.
+    pub fn with_geo_locator(
+        mut self,
+        geo_locator: Arc<dyn GeoLocator>,
+    ) -> Self {
+        self.geo_locator = Some(geo_locator);
+        self
+    }
+
+    async fn decrypt(&self, cipher: &str) -> Option<String> {
+        let plaintext = self
+            .crypto
+            .symmetric_encryption()
+            .decrypt_from_hex(cipher)
+            .await
+            .ok()?;
+
+        String::from_utf8(plaintext).ok()
+    }
+
+    async fn locate(&self, event: &SecurityEventDto) -> Option<String> {
+        let geo_locator = self.geo_locator.as_ref()?;
+        let ip: IpAddr = self
+            .decrypt(event.ip_address.as_deref()?)
+            .await?
+            .parse()
+            .ok()?;
+
+        geo_locator.locate(ip)
+    }
+}
+
+#[async_trait]
+impl ListSecurityEvents for ListSecurityEventsUseCase {
+    async fn list(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<SecurityEventInfoDto>> {
+        let events = self.audit_log.list_for_user(user_id, MAX_EVENTS).await?;
+
+        let mut infos = Vec::with_capacity(events.len());
+        for event in &events {
+            let user_agent = match event.user_agent.as_deref() {
+                Some(cipher) => self.decrypt(cipher).await,
+                None => None,
+            };
+            infos.push(SecurityEventInfoDto {
+                kind: event.kind.as_str(),
+                details: event.details.clone(),
+                location: self.locate(event).await,
+                user_agent,
+                created_at: event.created_at,
+            });
+        }
+
+        Ok(infos)
+    }
+}
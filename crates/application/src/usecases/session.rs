@@ -0,0 +1,280 @@
+//! Session management use case implementation.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::auth::factor::VerifiedFactor;
+use domain::auth::proof::AuthenticationProofBuilder;
+use domain::identity::id::UserId;
+
+use crate::dto::{
+    AccessTokenDto, ClientContextDto, SecurityEventDto, SecurityEventKind,
+    SessionDto, SessionInfoDto,
+};
+use crate::error::{ApplicationError, Result};
+use crate::ports::inbound::ManageSessions;
+use crate::ports::outbound::{
+    AuditLog, Clock, CryptoPort, GeoLocator, RefreshTokenRepository, Token,
+    TokenClaims, TokenDenylist,
+};
+use crate::usecases::{EXPIRES_IN, TOKEN_TYPE, audit};
+
+/// Session management use case service.
+pub struct ManageSessionsUseCase {
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    crypto: Arc<dyn CryptoPort>,
+    token: Arc<dyn Token>,
+    denylist: Arc<dyn TokenDenylist>,
+    clock: Arc<dyn Clock>,
+    geo_locator: Option<Arc<dyn GeoLocator>>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+}
+
+impl ManageSessionsUseCase {
+    pub fn new(
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        crypto: Arc<dyn CryptoPort>,
+        token: Arc<dyn Token>,
+        denylist: Arc<dyn TokenDenylist>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            refresh_token_repo,
+            crypto,
+            token,
+            denylist,
+            clock,
+            geo_locator: None,
+            audit_log: None,
+        }
+    }
+
+    /// Show a coarse location for each session.
+    pub fn with_geo_locator(
+        mut self,
+        geo_locator: Arc<dyn GeoLocator>,
+    ) -> Self {
+        self.geo_locator = Some(geo_locator);
+        self
+    }
+
+    /// Record revocations in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    async fn locate(&self, session: &SessionDto) -> Option<String> {
+        let geo_locator = self.geo_locator.as_ref()?;
+        let ip = self
+            .crypto
+            .symmetric_encryption()
+            .decrypt_from_hex(session.ip_address.as_deref()?)
+            .await
+            .ok()?;
+        let ip: IpAddr = std::str::from_utf8(&ip).ok()?.parse().ok()?;
+
+        geo_locator.locate(ip)
+    }
+}
+
+#[async_trait]
+impl ManageSessions for ManageSessionsUseCase {
+    async fn list(
+        &self,
+        user_id: &UserId,
+        current: Option<&str>,
+    ) -> Result<Vec<SessionInfoDto>> {
+        let sessions = self.refresh_token_repo.list_sessions(user_id).await?;
+
+        let mut infos = Vec::with_capacity(sessions.len());
+        for session in &sessions {
+            infos.push(SessionInfoDto {
+                id: session.id.clone(),
+                device: session.device.clone(),
+                location: self.locate(session).await,
+                created_at: session.created_at,
+                last_used_at: session.last_used_at,
+                current: current == Some(session.id.as_str()),
+            });
+        }
+
+        Ok(infos)
+    }
+
+    async fn revoke(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+        client: &ClientContextDto,
+    ) -> Result<()> {
+        if !self
+            .refresh_token_repo
+            .revoke_session(user_id, session_id)
+            .await?
+        {
+            return Err(ApplicationError::SessionNotFound);
+        }
+
+        // No token is issued for the session anymore, so the last one
+        // expires at most `EXPIRES_IN` from now.
+        let now = self.clock.now();
+        self.denylist
+            .deny_session(session_id, now + EXPIRES_IN)
+            .await?;
+
+        let event = SecurityEventDto::new(
+            user_id,
+            SecurityEventKind::SessionRevoked,
+            client,
+            now,
+        )
+        .with_details(session_id);
+        audit(self.audit_log.as_deref(), event).await
+    }
+
+    async fn revoke_others(
+        &self,
+        user_id: &UserId,
+        claims: &TokenClaims,
+        client: &ClientContextDto,
+    ) -> Result<AccessTokenDto> {
+        self.refresh_token_repo
+            .revoke_other_sessions(user_id, claims.sid.as_deref())
+            .await?;
+        // Revoke access tokens of every session at once, then replace the
+        // one of the caller.
+        let generation = self.denylist.bump_generation(user_id).await?;
+
+        let now = self.clock.now();
+        let factors = claims
+            .amr
+            .iter()
+            .filter_map(|amr| VerifiedFactor::from_amr(amr, claims.auth_time))
+            .collect();
+        let mut proof = AuthenticationProofBuilder::default()
+            .user_id(user_id)
+            .token_generation(generation)
+            .authenticated_at(now)
+            .add_factors(factors);
+        if let Some(session_id) = &claims.sid {
+            proof = proof.session_id(session_id);
+        }
+        let access_token = self
+            .token
+            .signer()
+            .create_access_token(&proof.build()?)
+            .await?;
+
+        let event = SecurityEventDto::new(
+            user_id,
+            SecurityEventKind::SessionsRevoked,
+            client,
+            now,
+        );
+        audit(self.audit_log.as_deref(), event).await?;
+
+        Ok(AccessTokenDto {
+            access_token,
+            token_type: TOKEN_TYPE.to_string(),
+            expires_in: EXPIRES_IN,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::outbound::TokenSigner;
+    use crate::testing::{
+        MemoryDenylist, MemorySessions, TestClock, TestCrypto, TestToken,
+    };
+
+    fn session(id: &str, user_id: &UserId) -> SessionDto {
+        SessionDto {
+            id: id.into(),
+            user_id: user_id.clone(),
+            device: None,
+            ip_address: None,
+            amr: vec!["pwd".into()],
+            authenticated_at: 0,
+            created_at: 0,
+            last_used_at: 0,
+        }
+    }
+
+    async fn claims(
+        token: &TestToken,
+        user_id: &UserId,
+        sid: &str,
+    ) -> TokenClaims {
+        let proof = AuthenticationProofBuilder::default()
+            .user_id(user_id)
+            .session_id(sid)
+            .authenticated_at(1_000)
+            .add_factors(vec![VerifiedFactor::from_amr("pwd", 900).unwrap()])
+            .build()
+            .unwrap();
+        let access_token = token.create_access_token(&proof).await.unwrap();
+        token.verify_token(&access_token).unwrap()
+    }
+
+    async fn usecase() -> (ManageSessionsUseCase, Arc<MemoryDenylist>, UserId)
+    {
+        let user_id = UserId::parse("alice").unwrap();
+        let sessions = MemorySessions::new();
+        for id in ["current", "other"] {
+            sessions.store(id, &session(id, &user_id)).await.unwrap();
+        }
+        let denylist = MemoryDenylist::new();
+        let usecase = ManageSessionsUseCase::new(
+            sessions,
+            TestCrypto::new(),
+            TestToken::new(),
+            denylist.clone(),
+            TestClock::new(1_000),
+        );
+        (usecase, denylist, user_id)
+    }
+
+    #[tokio::test]
+    async fn test_revoke() {
+        let (usecase, denylist, user_id) = usecase().await;
+        let client = ClientContextDto::default();
+
+        usecase.revoke(&user_id, "other", &client).await.unwrap();
+        assert!(denylist.is_session_denied("other").await.unwrap());
+        assert!(!denylist.is_session_denied("current").await.unwrap());
+
+        let err = usecase.revoke(&user_id, "other", &client).await;
+        assert!(matches!(err, Err(ApplicationError::SessionNotFound)));
+        let bob = UserId::parse("bob").unwrap();
+        let err = usecase.revoke(&bob, "current", &client).await;
+        assert!(matches!(err, Err(ApplicationError::SessionNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_others() {
+        let (usecase, denylist, user_id) = usecase().await;
+        let claims = claims(&TestToken, &user_id, "current").await;
+
+        let token = usecase
+            .revoke_others(&user_id, &claims, &Default::default())
+            .await
+            .unwrap();
+
+        let sessions = usecase.list(&user_id, Some("current")).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].current);
+
+        // The token of the request is revoked and replaced.
+        let generation = denylist.generation(&user_id).await.unwrap();
+        assert!(claims.generation < generation);
+        let renewed = TestToken.verify_token(&token.access_token).unwrap();
+        assert_eq!(renewed.generation, generation);
+        assert_eq!(renewed.sid.as_deref(), Some("current"));
+        assert_eq!(renewed.amr, claims.amr);
+        assert_eq!(renewed.auth_time, claims.auth_time);
+    }
+}
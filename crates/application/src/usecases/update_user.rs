@@ -7,19 +7,37 @@ use domain::auth::factor::{TotpCode, TotpConfig, TotpSecret};
 use domain::auth::password::Password;
 use domain::error::DomainError;
 use domain::identity::email::EmailAddress;
+use domain::identity::flags::UserFlags;
 use domain::identity::id::UserId;
+use domain::key::algorithm::KeyPolicy;
 use domain::key::pem::PemPublicKey;
 
-use crate::dto::{PublicKeyDto, TypedKeyDto, UpdateUserDto};
+use crate::dto::{
+    ClientContextDto, PublicKeyDto, SecurityEventDto, SecurityEventKind,
+    TypedKeyDto, UpdateUserDto,
+};
 use crate::error::{ApplicationError, Result};
 use crate::ports::inbound::UpdateUser;
-use crate::ports::outbound::{AccountRepository, CryptoPort, Mailer};
+use crate::ports::outbound::{
+    AccountRepository, AuditLog, Clock, CryptoPort, Mailer, PasswordPolicy,
+    RefreshTokenRepository, TokenClaims, TokenDenylist,
+};
+use crate::usecases::{
+    audit, email_hashes, ensure_email_available, ensure_password_allowed,
+    ensure_sensitive_operation,
+};
 
 /// Use case for updating user profile.
 pub struct UpdateUserUseCase {
     account_repo: Arc<dyn AccountRepository>,
     crypto: Arc<dyn CryptoPort>,
     mailer: Option<Arc<dyn Mailer>>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    denylist: Arc<dyn TokenDenylist>,
+    clock: Arc<dyn Clock>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+    password_policy: Option<Arc<dyn PasswordPolicy>>,
+    key_policy: KeyPolicy,
 }
 
 impl UpdateUserUseCase {
@@ -27,13 +45,66 @@ impl UpdateUserUseCase {
         account_repo: Arc<dyn AccountRepository>,
         crypto: Arc<dyn CryptoPort>,
         mailer: Option<Arc<dyn Mailer>>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        denylist: Arc<dyn TokenDenylist>,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             account_repo,
             crypto,
             mailer,
+            refresh_token_repo,
+            denylist,
+            clock,
+            audit_log: None,
+            password_policy: None,
+            key_policy: KeyPolicy::default(),
         }
     }
+
+    /// Record credential and key changes in an audit log.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Accept only public keys allowed by `key_policy`, instead of
+    /// [`KeyPolicy::default`].
+    pub fn with_key_policy(mut self, key_policy: KeyPolicy) -> Self {
+        self.key_policy = key_policy;
+        self
+    }
+
+    /// Parse `key`, in any supported format, and ensure it is allowed by
+    /// the key policy.
+    fn public_key(&self, owner: &UserId, key: String) -> Result<PublicKeyDto> {
+        let pem = PemPublicKey::parse(key.clone())?;
+        self.key_policy.check(pem.algorithm(), pem.bits())?;
+
+        Ok(PublicKeyDto {
+            id: pem.fingerprint()?,
+            owner: owner.to_string(),
+            public_key_pem: pem.as_str().to_string(),
+            algorithm: Some(pem.algorithm()),
+            public_key_jwk: pem.to_jwk().ok(),
+            public_key_multibase: pem.to_multibase().ok(),
+            created_at: chrono::DateTime::from_timestamp(
+                self.clock.now() as i64,
+                0,
+            )
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        })
+    }
+
+    /// Refuse new passwords breaking `password_policy`.
+    pub fn with_password_policy(
+        mut self,
+        password_policy: Arc<dyn PasswordPolicy>,
+    ) -> Self {
+        self.password_policy = Some(password_policy);
+        self
+    }
 }
 
 #[async_trait]
@@ -41,15 +112,34 @@ impl UpdateUser for UpdateUserUseCase {
     async fn update(
         &self,
         user_id: &UserId,
+        claims: &TokenClaims,
         payload: UpdateUserDto,
+        client: &ClientContextDto,
     ) -> Result<Vec<String>> {
+        let sensitive = payload.email.is_some() ||
+            payload.new_password.is_some() ||
+            payload.public_keys.is_some() ||
+            payload.disable_totp;
         let mut user = self
             .account_repo
             .find_by_id(user_id)
             .await?
             .ok_or(ApplicationError::UserNotFound)?;
+        if sensitive {
+            ensure_sensitive_operation(
+                user_id,
+                claims,
+                self.clock.now(),
+                user.totp_secret.is_some(),
+            )?;
+        }
+        if user.flags.contains(UserFlags::LOCKED) {
+            return Err(ApplicationError::AccountLocked);
+        }
 
         let mut updated_keys = Vec::new();
+        let mut password_changed = false;
+        let mut events = Vec::new();
 
         if let Some(username) = payload.username {
             user.username = username;
@@ -65,7 +155,8 @@ impl UpdateUser for UpdateUserUseCase {
             let pwd = Password::new(password_str)?;
             self.crypto
                 .password_hasher()
-                .verify(&pwd, &user.password_hash)?;
+                .verify(&pwd, &user.password_hash)
+                .await?;
 
             let secret = TotpSecret::new(secret_str)?;
             let code = TotpCode::six_digits(code_str)?;
@@ -78,8 +169,10 @@ impl UpdateUser for UpdateUserUseCase {
                 let encrypted_secret = self
                     .crypto
                     .symmetric_encryption()
-                    .encrypt_to_hex(secret.as_str().as_bytes())?;
+                    .encrypt_to_hex(secret.as_str().as_bytes())
+                    .await?;
                 user.totp_secret = Some(encrypted_secret);
+                events.push(SecurityEventKind::TotpEnabled);
             } else {
                 return Err(DomainError::InvalidTotpCode.into());
             }
@@ -90,6 +183,19 @@ impl UpdateUser for UpdateUserUseCase {
                 message: "Missing required fields for TOTP update".into(),
             }
             .into());
+        } else if payload.disable_totp {
+            if user.flags.contains(UserFlags::MFA_ENFORCED) {
+                return Err(DomainError::ValidationFailed {
+                    field: "disableTotp".into(),
+                    message: "Two-factor authentication is enforced on this \
+                              account."
+                        .into(),
+                }
+                .into());
+            }
+            if user.totp_secret.take().is_some() {
+                events.push(SecurityEventKind::TotpDisabled);
+            }
         }
 
         if let (Some(new_email), Some(password_str)) =
@@ -98,17 +204,23 @@ impl UpdateUser for UpdateUserUseCase {
             let pwd = Password::new(password_str)?;
             self.crypto
                 .password_hasher()
-                .verify(&pwd, &user.password_hash)?;
+                .verify(&pwd, &user.password_hash)
+                .await?;
 
             let email = EmailAddress::parse(new_email)?;
-            let email_hash = self.crypto.hasher().hash(email.as_bytes());
+            let mut email_hashes =
+                email_hashes(self.crypto.as_ref(), email.as_bytes());
+            ensure_email_available(self.account_repo.as_ref(), &email_hashes)
+                .await?;
             let email_cipher = self
                 .crypto
                 .symmetric_encryption()
-                .encrypt_to_hex(email.as_bytes())?;
+                .encrypt_to_hex(email.as_bytes())
+                .await?;
 
-            user.email_hash = domain::auth::email::EmailHash::new(email_hash);
+            user.email_hash = email_hashes.swap_remove(0);
             user.email_cipher = email_cipher;
+            events.push(SecurityEventKind::EmailChanged);
 
             if let Some(mailer) = &self.mailer {
                 mailer
@@ -127,19 +239,31 @@ impl UpdateUser for UpdateUserUseCase {
             let pwd = Password::new(current_password_str)?;
             self.crypto
                 .password_hasher()
-                .verify(&pwd, &user.password_hash)?;
+                .verify(&pwd, &user.password_hash)
+                .await?;
 
             let new_password = Password::new(new_password_str)?;
+            ensure_password_allowed(
+                self.password_policy.as_deref(),
+                "newPassword",
+                &new_password,
+                &[user.id.as_str(), &user.username],
+            )
+            .await?;
             let new_password_hash =
-                self.crypto.password_hasher().hash(&new_password)?;
+                self.crypto.password_hasher().hash(&new_password).await?;
 
             user.password_hash = new_password_hash;
+            user.password_reset_required = false;
+            password_changed = true;
+            events.push(SecurityEventKind::PasswordChanged);
 
             if let Some(mailer) = &self.mailer {
                 let decrypted_email_bytes = self
                     .crypto
                     .symmetric_encryption()
-                    .decrypt_from_hex(&user.email_cipher)?;
+                    .decrypt_from_hex(&user.email_cipher)
+                    .await?;
                 let decrypted_email = String::from_utf8(decrypted_email_bytes)
                     .map_err(|_| DomainError::InvariantViolation)?;
                 let email = EmailAddress::parse(&decrypted_email)?;
@@ -155,35 +279,18 @@ impl UpdateUser for UpdateUserUseCase {
         }
 
         if let Some(keys) = payload.public_keys {
+            events.push(SecurityEventKind::KeysChanged);
             match keys {
                 TypedKeyDto::One(key) => {
-                    let pem = PemPublicKey::parse(key.clone())?;
-                    let id = pem.fingerprint()?;
-
-                    user.public_keys.push(PublicKeyDto {
-                        id: id.clone(),
-                        owner: user_id.to_string(),
-                        public_key_pem: key,
-                        created_at: chrono::Utc::now()
-                            .format("%Y-%m-%d")
-                            .to_string(),
-                    });
-                    updated_keys.push(id.as_str().to_string());
+                    let key = self.public_key(user_id, key)?;
+                    updated_keys.push(key.id.as_str().to_string());
+                    user.public_keys.push(key);
                 },
                 TypedKeyDto::Multiple(keys) => {
                     for key in keys {
-                        let pem = PemPublicKey::parse(key.clone())?;
-                        let id = pem.fingerprint()?;
-
-                        user.public_keys.push(PublicKeyDto {
-                            id: id.clone(),
-                            owner: user_id.to_string(),
-                            public_key_pem: key,
-                            created_at: chrono::Utc::now()
-                                .format("%Y-%m-%d")
-                                .to_string(),
-                        });
-                        updated_keys.push(id.as_str().to_string());
+                        let key = self.public_key(user_id, key)?;
+                        updated_keys.push(key.id.as_str().to_string());
+                        user.public_keys.push(key);
                     }
                 },
                 TypedKeyDto::Remove(key_id_to_remove) => {
@@ -195,6 +302,151 @@ impl UpdateUser for UpdateUserUseCase {
 
         self.account_repo.update(&user).await?;
 
+        if password_changed {
+            // Whoever knew the old password must not stay logged in.
+            self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+            self.denylist.bump_generation(user_id).await?;
+        }
+
+        let now = self.clock.now();
+        for kind in events {
+            let event = SecurityEventDto::new(user_id, kind, client, now);
+            audit(self.audit_log.as_deref(), event).await?;
+        }
+
         Ok(updated_keys)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        MemoryAccounts, MemoryDenylist, MemorySessions, TestClock, TestCrypto,
+        account, claims,
+    };
+    use crate::usecases::SENSITIVE_MAX_AGE;
+
+    const NOW: u64 = 10_000;
+    const PASSWORD: &str = "correct horse battery";
+    const KEY: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEAGb9ECWmEzf6FQbrBZ9w7lshQhqowtrbLDFw4rXAxZuE=
+-----END PUBLIC KEY-----";
+
+    fn usecase() -> UpdateUserUseCase {
+        let crypto = TestCrypto::new();
+        let alice = account(&crypto, "alice", "alice@example.com", PASSWORD);
+        UpdateUserUseCase::new(
+            MemoryAccounts::new([alice]),
+            crypto,
+            None,
+            MemorySessions::new(),
+            MemoryDenylist::new(),
+            TestClock::new(NOW),
+        )
+    }
+
+    fn payloads() -> [(&'static str, UpdateUserDto); 4] {
+        let payload = || UpdateUserDto {
+            username: None,
+            summary: None,
+            totp_secret: None,
+            totp_code: None,
+            public_keys: None,
+            email: None,
+            password: Some(PASSWORD.into()),
+            new_password: None,
+            disable_totp: false,
+        };
+
+        [
+            (
+                "email",
+                UpdateUserDto {
+                    email: Some("alice@example.org".into()),
+                    ..payload()
+                },
+            ),
+            (
+                "password",
+                UpdateUserDto {
+                    new_password: Some("battery staple horse".into()),
+                    ..payload()
+                },
+            ),
+            (
+                "key",
+                UpdateUserDto {
+                    public_keys: Some(TypedKeyDto::One(KEY.into())),
+                    ..payload()
+                },
+            ),
+            (
+                "totp",
+                UpdateUserDto {
+                    disable_totp: true,
+                    ..payload()
+                },
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_changes() {
+        let usecase = usecase();
+        let alice = UserId::parse("alice").unwrap();
+        let client = ClientContextDto::default();
+
+        let stale =
+            claims(&alice, &["pwd", "swk"], NOW - SENSITIVE_MAX_AGE - 1);
+        let password_only = claims(&alice, &["pwd"], NOW);
+        for claims in [stale, password_only] {
+            for (change, payload) in payloads() {
+                let err =
+                    usecase.update(&alice, &claims, payload, &client).await;
+                assert!(
+                    matches!(
+                        err,
+                        Err(ApplicationError::ReauthenticationRequired { .. })
+                    ),
+                    "{change} with {:?}",
+                    claims.amr
+                );
+            }
+        }
+
+        // Either a TOTP code or the refresh token of the session is enough.
+        for amr in [["pwd", "otp"], ["pwd", "swk"]] {
+            let usecase = self::usecase();
+            let elevated = claims(&alice, &amr, NOW - SENSITIVE_MAX_AGE);
+            for (change, payload) in payloads() {
+                let result =
+                    usecase.update(&alice, &elevated, payload, &client).await;
+                assert!(result.is_ok(), "{change} with {amr:?}: {result:?}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profile_changes() {
+        let usecase = usecase();
+        let alice = UserId::parse("alice").unwrap();
+        let stale = claims(&alice, &["pwd"], 0);
+
+        let payload = UpdateUserDto {
+            username: Some("Alice".into()),
+            summary: Some("Hello".into()),
+            totp_secret: None,
+            totp_code: None,
+            public_keys: None,
+            email: None,
+            password: None,
+            new_password: None,
+            disable_totp: false,
+        };
+        usecase
+            .update(&alice, &stale, payload, &Default::default())
+            .await
+            .unwrap();
+    }
+}
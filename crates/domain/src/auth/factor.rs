@@ -258,6 +258,9 @@ pub enum FactorType {
     Knowledge,
     Possession,
     Inherence,
+    /// Proof of holding a session, kept by the device rather than the user,
+    /// so neither a possession factor nor a part of `mfa`.
+    Session,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -295,6 +298,28 @@ impl VerifiedFactor {
     pub fn verified_at(&self) -> u64 {
         self.verified_at
     }
+
+    /// Rebuild a factor from its RFC 8176 authentication method reference.
+    ///
+    /// WebAuthn credential identifiers are not part of the reference and
+    /// are left empty.
+    pub fn from_amr(amr: &str, verified_at: u64) -> Option<Self> {
+        let (factor_type, method) = match amr {
+            "pwd" => (FactorType::Knowledge, FactorMethod::Password),
+            "otp" => (FactorType::Possession, FactorMethod::Totp),
+            "hwk" => (
+                FactorType::Possession,
+                FactorMethod::WebAuthn {
+                    credential_id: String::new(),
+                },
+            ),
+            "kba" => (FactorType::Knowledge, FactorMethod::RecoveryCode),
+            "swk" => (FactorType::Session, FactorMethod::RefreshToken),
+            _ => return None,
+        };
+
+        Some(Self::new(factor_type, method, verified_at))
+    }
 }
 
 /// Specific method used for authentication.
@@ -302,8 +327,25 @@ impl VerifiedFactor {
 pub enum FactorMethod {
     Password,
     Totp,
-    WebAuthn { credential_id: String },
+    WebAuthn {
+        credential_id: String,
+    },
     RecoveryCode,
+    /// Refresh token bound to the device session.
+    RefreshToken,
+}
+
+impl FactorMethod {
+    /// Returns the RFC 8176 authentication method reference.
+    pub fn amr(&self) -> &'static str {
+        match self {
+            FactorMethod::Password => "pwd",
+            FactorMethod::Totp => "otp",
+            FactorMethod::WebAuthn { .. } => "hwk",
+            FactorMethod::RecoveryCode => "kba",
+            FactorMethod::RefreshToken => "swk",
+        }
+    }
 }
 
 #[cfg(kani)]
@@ -53,6 +53,30 @@ pub fn validate_sensitive_operation(
     Ok(())
 }
 
+/// Validates that a sensitive operation on an account without a second
+/// factor follows a recent reauthentication, bound to the session or with
+/// a possession factor.
+#[inline]
+pub fn validate_session_reauthentication(
+    proof: &AuthenticationProof,
+    current_time: u64,
+    max_age_seconds: u64,
+) -> Result<()> {
+    validate_auth_freshness(
+        proof.authenticated_at(),
+        current_time,
+        max_age_seconds,
+    )?;
+
+    if !proof.has_factor_type(FactorType::Session) &&
+        !proof.has_factor_type(FactorType::Possession)
+    {
+        return Err(DomainError::InvariantViolation);
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,6 +156,45 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_session_reauthentication() {
+        let user_id = UserId::parse("user").unwrap();
+        let session = AuthenticationProof::new(
+            &user_id,
+            vec![
+                VerifiedFactor::new(
+                    FactorType::Knowledge,
+                    FactorMethod::Password,
+                    1000,
+                ),
+                VerifiedFactor::new(
+                    FactorType::Session,
+                    FactorMethod::RefreshToken,
+                    1000,
+                ),
+            ],
+            1000,
+        )
+        .unwrap();
+
+        // A session is not a possession factor.
+        assert!(validate_sensitive_operation(&session, 1100, 600).is_err());
+        assert!(
+            validate_session_reauthentication(&session, 1100, 600).is_ok()
+        );
+        assert!(
+            validate_session_reauthentication(&session, 2000, 600).is_err()
+        );
+
+        let mfa = create_proof_with_mfa(&user_id, 1000);
+        assert!(validate_session_reauthentication(&mfa, 1100, 600).is_ok());
+        let password_only = create_proof_with_password_only(&user_id, 1000);
+        assert!(
+            validate_session_reauthentication(&password_only, 1100, 600)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_sensitive_operation_requires_freshness() {
         let user_id = UserId::parse("user").unwrap();
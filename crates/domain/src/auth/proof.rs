@@ -22,6 +22,8 @@ pub struct AuthenticationProof<'a> {
     user_id: &'a UserId,
     verified_factors: Vec<VerifiedFactor>,
     authenticated_at: u64,
+    session_id: Option<&'a str>,
+    token_generation: u64,
 }
 
 impl<'a> AuthenticationProof<'a> {
@@ -45,6 +47,8 @@ impl<'a> AuthenticationProof<'a> {
             user_id,
             verified_factors,
             authenticated_at,
+            session_id: None,
+            token_generation: 0,
         })
     }
 
@@ -66,6 +70,56 @@ impl<'a> AuthenticationProof<'a> {
         self.authenticated_at
     }
 
+    /// Returns the session this proof was issued for, if any.
+    #[inline]
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id
+    }
+
+    /// Returns the token generation of the user when authenticating.
+    #[inline]
+    pub fn token_generation(&self) -> u64 {
+        self.token_generation
+    }
+
+    /// Returns the timestamp of the earliest verified factor, i.e. when the
+    /// user actually authenticated.
+    pub fn auth_time(&self) -> u64 {
+        self.verified_factors
+            .iter()
+            .map(VerifiedFactor::verified_at)
+            .min()
+            .unwrap_or(self.authenticated_at)
+    }
+
+    /// Returns the RFC 8176 authentication method references of this proof.
+    ///
+    /// `mfa` is added when the user verified factors of several
+    /// [`FactorType`]s. A [`FactorType::Session`] is held by the device
+    /// rather than the user, so it does not count towards `mfa`.
+    pub fn amr(&self) -> Vec<&'static str> {
+        let mut amr: Vec<&'static str> = Vec::new();
+        for factor in &self.verified_factors {
+            let method = factor.method().amr();
+            if !amr.contains(&method) {
+                amr.push(method);
+            }
+        }
+
+        let mut user_factors = self
+            .verified_factors
+            .iter()
+            .map(VerifiedFactor::factor_type)
+            .filter(|factor_type| *factor_type != FactorType::Session);
+        if let Some(first) = user_factors.next() &&
+            user_factors.any(|t| t != first)
+        {
+            amr.push("mfa");
+        }
+
+        amr
+    }
+
     /// Returns `true` if a factor of the specified [`FactorType`] is present
     /// in this proof.
     pub fn has_factor_type(&self, factor_type: FactorType) -> bool {
@@ -81,6 +135,8 @@ pub struct AuthenticationProofBuilder<'a> {
     user_id: Option<&'a UserId>,
     verified_factors: Vec<VerifiedFactor>,
     authenticated_at: Option<u64>,
+    session_id: Option<&'a str>,
+    token_generation: u64,
 }
 
 impl<'a> AuthenticationProofBuilder<'a> {
@@ -109,6 +165,18 @@ impl<'a> AuthenticationProofBuilder<'a> {
         self
     }
 
+    /// Sets the session the proof is issued for.
+    pub fn session_id(mut self, session_id: &'a str) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Sets the token generation tokens are issued for.
+    pub fn token_generation(mut self, generation: u64) -> Self {
+        self.token_generation = generation;
+        self
+    }
+
     /// Attempts to build the [`AuthenticationProof`].
     ///
     /// # Errors
@@ -128,11 +196,15 @@ impl<'a> AuthenticationProofBuilder<'a> {
                 message: "authenticated_at is required".into(),
             })?;
 
-        AuthenticationProof::new(
+        let mut proof = AuthenticationProof::new(
             user_id,
             self.verified_factors,
             authenticated_at,
-        )
+        )?;
+        proof.session_id = self.session_id;
+        proof.token_generation = self.token_generation;
+
+        Ok(proof)
     }
 }
 
@@ -280,3 +352,82 @@ mod proof {
         assert!(builder_no_time.build().is_err());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::factor::FactorMethod;
+
+    #[test]
+    fn test_amr_single_factor() {
+        let user_id = UserId::parse("user123").unwrap();
+        let proof = AuthenticationProofBuilder::default()
+            .user_id(&user_id)
+            .authenticated_at(1000)
+            .add_factor(VerifiedFactor::new(
+                FactorType::Knowledge,
+                FactorMethod::Password,
+                1000,
+            ))
+            .build()
+            .unwrap();
+
+        assert_eq!(proof.amr(), vec!["pwd"]);
+        assert_eq!(proof.auth_time(), 1000);
+    }
+
+    #[test]
+    fn test_amr_multi_factor() {
+        let user_id = UserId::parse("user123").unwrap();
+        let proof = AuthenticationProofBuilder::default()
+            .user_id(&user_id)
+            .authenticated_at(2000)
+            .add_factor(VerifiedFactor::new(
+                FactorType::Knowledge,
+                FactorMethod::Password,
+                1000,
+            ))
+            .add_factor(VerifiedFactor::new(
+                FactorType::Possession,
+                FactorMethod::Totp,
+                1010,
+            ))
+            .build()
+            .unwrap();
+
+        assert_eq!(proof.amr(), vec!["pwd", "otp", "mfa"]);
+        assert_eq!(proof.auth_time(), 1000);
+    }
+
+    #[test]
+    fn test_amr_refresh_token_is_not_mfa() {
+        let user_id = UserId::parse("user123").unwrap();
+        let proof = AuthenticationProofBuilder::default()
+            .user_id(&user_id)
+            .authenticated_at(1000)
+            .add_factor(VerifiedFactor::new(
+                FactorType::Knowledge,
+                FactorMethod::Password,
+                1000,
+            ))
+            .add_factor(VerifiedFactor::new(
+                FactorType::Session,
+                FactorMethod::RefreshToken,
+                1000,
+            ))
+            .build()
+            .unwrap();
+
+        assert_eq!(proof.amr(), vec!["pwd", "swk"]);
+        assert!(!proof.has_factor_type(FactorType::Possession));
+    }
+
+    #[test]
+    fn test_factor_from_amr_roundtrip() {
+        for amr in ["pwd", "otp", "hwk", "kba", "swk"] {
+            let factor = VerifiedFactor::from_amr(amr, 1000).unwrap();
+            assert_eq!(factor.method().amr(), amr);
+        }
+        assert!(VerifiedFactor::from_amr("mfa", 1000).is_none());
+    }
+}
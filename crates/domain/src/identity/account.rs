@@ -2,6 +2,7 @@
 
 use crate::auth::password::PasswordHash;
 use crate::identity::email::EmailAddress;
+use crate::identity::flags::UserFlags;
 use crate::identity::id::UserId;
 use crate::identity::ip::EncryptedIp;
 use crate::identity::user::User;
@@ -137,9 +138,11 @@ fn construct_user(
         totp_secret: None,
         summary: None,
         avatar: None,
-        flags: 0,
+        banner: None,
+        flags: UserFlags::empty(),
         created_at: chrono::Utc::now(),
         deleted_at: None,
+        suspension: None,
         public_keys: Vec::new(),
     }
 }
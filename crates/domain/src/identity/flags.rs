@@ -0,0 +1,92 @@
+//! Account flags.
+
+use std::fmt;
+
+use bitflags::bitflags;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{DomainError, Result};
+
+bitflags! {
+    /// Flags set on an account by staff.
+    ///
+    /// Flags are public and serialized as their numeric value. Unknown bits
+    /// are kept so that older versions do not drop newer flags.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct UserFlags: u32 {
+        /// Identity was verified by staff.
+        const VERIFIED = 1 << 0;
+        /// Member of the instance staff.
+        const STAFF = 1 << 1;
+        /// Automated account, which may not sign in with a password.
+        const BOT = 1 << 2;
+        /// Settings are frozen and cannot be changed by the user.
+        const LOCKED = 1 << 3;
+        /// Two-factor authentication cannot be disabled.
+        const MFA_ENFORCED = 1 << 4;
+    }
+}
+
+impl UserFlags {
+    /// Parse a flag from its `snake_case` name.
+    pub fn from_flag_name(name: &str) -> Result<Self> {
+        Self::from_name(&name.to_ascii_uppercase()).ok_or_else(|| {
+            DomainError::ValidationFailed {
+                field: "flags".into(),
+                message: format!("unknown flag {name:?}"),
+            }
+        })
+    }
+
+    /// Names of the known flags set, in `snake_case`.
+    pub fn names(&self) -> Vec<String> {
+        self.iter_names()
+            .map(|(name, _)| name.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+impl fmt::Display for UserFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.names().join(","))
+    }
+}
+
+impl Serialize for UserFlags {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for UserFlags {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(Self::from_bits_retain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_names() {
+        let flags = UserFlags::from_flag_name("mfa_enforced").unwrap() |
+            UserFlags::from_flag_name("BOT").unwrap();
+        assert_eq!(flags, UserFlags::BOT | UserFlags::MFA_ENFORCED);
+        assert_eq!(flags.names(), ["bot", "mfa_enforced"]);
+        assert!(UserFlags::from_flag_name("admin").is_err());
+    }
+
+    #[test]
+    fn test_unknown_bits_retained() {
+        let flags = UserFlags::from_bits_retain(1 << 31 | 1);
+        assert!(flags.contains(UserFlags::VERIFIED));
+        assert_eq!(flags.bits(), 1 << 31 | 1);
+        assert_eq!(flags.names(), ["verified"]);
+    }
+}
@@ -2,6 +2,9 @@
 
 pub mod account;
 pub mod email;
+pub mod flags;
 pub mod id;
 pub mod ip;
+pub mod role;
+pub mod suspension;
 pub mod user;
@@ -0,0 +1,73 @@
+//! Staff roles granting access to administration.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::DomainError;
+
+/// Role of a staff member, ordered by privilege.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Can review accounts, suspend them and end their sessions.
+    Moderator,
+    /// Can do anything a moderator can, and manage accounts, invitations
+    /// and roles.
+    Admin,
+}
+
+impl Role {
+    /// Returns the stable name of the role.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Returns `true` if this role grants what `required` grants.
+    #[inline]
+    pub fn includes(&self, required: Role) -> bool {
+        *self >= required
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(DomainError::ValidationFailed {
+                field: "role".into(),
+                message: format!("unknown role {s:?}"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_hierarchy() {
+        assert!(Role::Admin.includes(Role::Moderator));
+        assert!(Role::Admin.includes(Role::Admin));
+        assert!(!Role::Moderator.includes(Role::Admin));
+    }
+
+    #[test]
+    fn test_role_parsing() {
+        for role in [Role::Moderator, Role::Admin] {
+            assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+        }
+        assert!("root".parse::<Role>().is_err());
+    }
+}
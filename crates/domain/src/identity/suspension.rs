@@ -0,0 +1,125 @@
+//! Account suspension decided by a moderator.
+
+use crate::error::{DomainError, Result};
+
+/// Maximum length of a suspension reason, in characters.
+pub const MAX_REASON_LENGTH: usize = 512;
+
+/// Restriction preventing a user from authenticating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suspension {
+    reason: String,
+    since: u64,
+    until: Option<u64>,
+}
+
+impl Suspension {
+    /// Creates a new [`Suspension`] starting at `since`.
+    ///
+    /// A suspension without `until` lasts until it is lifted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::ValidationFailed`] if the reason is blank or
+    /// too long, or if `until` is not after `since`.
+    pub fn new(
+        reason: impl Into<String>,
+        since: u64,
+        until: Option<u64>,
+    ) -> Result<Self> {
+        let reason = Self::normalize_reason(reason)?;
+
+        if until.is_some_and(|until| until <= since) {
+            return Err(DomainError::ValidationFailed {
+                field: "until".into(),
+                message: "suspension must end in the future".into(),
+            });
+        }
+
+        Ok(Self {
+            reason,
+            since,
+            until,
+        })
+    }
+
+    /// Rebuilds a stored [`Suspension`] without validating it again.
+    pub fn from_parts(reason: String, since: u64, until: Option<u64>) -> Self {
+        Self {
+            reason,
+            since,
+            until,
+        }
+    }
+
+    /// Trims a moderation reason and checks its length.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::ValidationFailed`] if the reason is blank or
+    /// too long.
+    pub fn normalize_reason(reason: impl Into<String>) -> Result<String> {
+        let reason = reason.into().trim().to_string();
+
+        if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+            return Err(DomainError::ValidationFailed {
+                field: "reason".into(),
+                message: format!(
+                    "reason must be between 1 and {MAX_REASON_LENGTH} characters"
+                ),
+            });
+        }
+
+        Ok(reason)
+    }
+
+    /// Returns why the account was suspended.
+    #[inline]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Returns the Unix timestamp of when the suspension started.
+    #[inline]
+    pub fn since(&self) -> u64 {
+        self.since
+    }
+
+    /// Returns the Unix timestamp of when the suspension ends, if any.
+    #[inline]
+    pub fn until(&self) -> Option<u64> {
+        self.until
+    }
+
+    /// Returns `true` if the suspension still applies at `now`.
+    pub fn is_active(&self, now: u64) -> bool {
+        self.since <= now && self.until.is_none_or(|until| now < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspension_validation() {
+        assert!(Suspension::new("  ", 1000, None).is_err());
+        assert!(Suspension::new("x".repeat(513), 1000, None).is_err());
+        assert!(Suspension::new("spam", 1000, Some(1000)).is_err());
+
+        let suspension = Suspension::new(" spam ", 1000, Some(2000)).unwrap();
+        assert_eq!(suspension.reason(), "spam");
+    }
+
+    #[test]
+    fn test_suspension_activity() {
+        let temporary = Suspension::new("spam", 1000, Some(2000)).unwrap();
+        assert!(!temporary.is_active(999));
+        assert!(temporary.is_active(1000));
+        assert!(temporary.is_active(1999));
+        assert!(!temporary.is_active(2000));
+
+        let permanent = Suspension::new("spam", 1000, None).unwrap();
+        assert!(permanent.is_active(u64::MAX));
+    }
+}
@@ -4,8 +4,10 @@ use chrono::{DateTime, NaiveDate, Utc};
 
 use crate::auth::password::PasswordHash;
 use crate::identity::email::EmailAddress;
+use crate::identity::flags::UserFlags;
 use crate::identity::id::UserId;
 use crate::identity::ip::EncryptedIp;
+use crate::identity::suspension::Suspension;
 use crate::key::public_key::Key;
 
 /// Represents a registered user within the system domain.
@@ -18,11 +20,13 @@ pub struct User {
     pub locale: String,
     pub summary: Option<String>,
     pub avatar: Option<String>,
-    pub flags: i32,
+    pub banner: Option<String>,
+    pub flags: UserFlags,
     pub password: Option<PasswordHash>,
     pub ip: Option<EncryptedIp>,
     pub invite: Option<String>,
     pub created_at: DateTime<Utc>,
     pub deleted_at: Option<NaiveDate>,
+    pub suspension: Option<Suspension>,
     pub public_keys: Vec<Key>,
 }
@@ -0,0 +1,323 @@
+//! Algorithms of public keys, and which of them are accepted.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use p256::ecdsa::signature::Verifier;
+use rsa::sha2::Sha256;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, pkcs1v15};
+use spki::SubjectPublicKeyInfoOwned;
+use spki::der::Decode;
+use spki::der::asn1::ObjectIdentifier;
+
+use crate::error::Result;
+use crate::key::public_key::KeyError;
+
+pub(crate) const ED25519: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.101.112");
+pub(crate) const EC_PUBLIC_KEY: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+pub(crate) const SECP256R1: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+pub(crate) const SECP384R1: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.132.0.34");
+pub(crate) const RSA_ENCRYPTION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+
+/// Sizes of the RSA moduli whose signatures are verified, in bits.
+const RSA_VERIFY_BITS: RangeInclusive<usize> = 2048..=KeyPolicy::MAX_RSA_BITS;
+
+/// Algorithm of a public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAlgorithm {
+    Ed25519,
+    /// ECDSA on the NIST P-256 curve.
+    P256,
+    /// ECDSA on the NIST P-384 curve.
+    P384,
+    Rsa,
+}
+
+impl KeyAlgorithm {
+    /// Every supported algorithm, preferred first.
+    pub const ALL: [KeyAlgorithm; 4] = [
+        KeyAlgorithm::Ed25519,
+        KeyAlgorithm::P256,
+        KeyAlgorithm::P384,
+        KeyAlgorithm::Rsa,
+    ];
+
+    /// Returns the name of the algorithm, e.g. `P-256`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "Ed25519",
+            KeyAlgorithm::P256 => "P-256",
+            KeyAlgorithm::P384 => "P-384",
+            KeyAlgorithm::Rsa => "RSA",
+        }
+    }
+
+    /// Classify `spki`, returning its algorithm and size in bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::UnsupportedAlgorithm`] for other algorithms or
+    /// curves, and [`KeyError::InvalidFormat`] if the key does not match
+    /// its algorithm.
+    pub fn classify(
+        spki: &SubjectPublicKeyInfoOwned,
+    ) -> Result<(Self, usize)> {
+        let key = spki.subject_public_key.raw_bytes();
+        let parameters = spki.algorithm.parameters.as_ref();
+
+        let (algorithm, bits, valid) = match spki.algorithm.oid {
+            ED25519 => (Self::Ed25519, 256, key.len() == 32),
+            EC_PUBLIC_KEY => {
+                let curve = parameters
+                    .and_then(|parameters| {
+                        parameters.decode_as::<ObjectIdentifier>().ok()
+                    })
+                    .ok_or(KeyError::InvalidFormat)?;
+                // Only uncompressed points are accepted.
+                match curve {
+                    SECP256R1 => {
+                        (Self::P256, 256, key.len() == 65 && key[0] == 4)
+                    },
+                    SECP384R1 => {
+                        (Self::P384, 384, key.len() == 97 && key[0] == 4)
+                    },
+                    _ => return Err(KeyError::UnsupportedAlgorithm.into()),
+                }
+            },
+            RSA_ENCRYPTION => {
+                let modulus = pkcs1::RsaPublicKey::from_der(key)
+                    .map_err(|_| KeyError::InvalidFormat)?
+                    .modulus;
+                let modulus = modulus.as_bytes();
+                let bits = modulus.first().map_or(0, |first| {
+                    modulus.len() * 8 - first.leading_zeros() as usize
+                });
+                (Self::Rsa, bits, bits > 0)
+            },
+            _ => return Err(KeyError::UnsupportedAlgorithm.into()),
+        };
+
+        if valid {
+            Ok((algorithm, bits))
+        } else {
+            Err(KeyError::InvalidFormat.into())
+        }
+    }
+
+    /// Verify `signature` of `message` by `public_key`, the subject public
+    /// key of an SPKI.
+    ///
+    /// Ed25519 signatures are pure, ECDSA ones use SHA-256 on P-256 and
+    /// SHA-384 on P-384, either as ASN.1 or as fixed-length `r || s`, and
+    /// RSA ones are PKCS#1 v1.5 with SHA-256, by moduli of 2048 to 8192
+    /// bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::InvalidSignature`] if the signature is not
+    /// valid.
+    pub fn verify(
+        &self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<()> {
+        let verified = match self {
+            KeyAlgorithm::Ed25519 => {
+                verify_ed25519(public_key, message, signature)
+            },
+            KeyAlgorithm::P256 => verify_p256(public_key, message, signature),
+            KeyAlgorithm::P384 => verify_p384(public_key, message, signature),
+            KeyAlgorithm::Rsa => verify_rsa(public_key, message, signature),
+        };
+
+        verified.ok_or(KeyError::InvalidSignature.into())
+    }
+}
+
+fn verify_ed25519(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<()> {
+    let key = ed25519_dalek::VerifyingKey::try_from(public_key).ok()?;
+    let signature = ed25519_dalek::Signature::from_slice(signature).ok()?;
+    key.verify(message, &signature).ok()
+}
+
+fn verify_p256(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<()> {
+    use p256::ecdsa::{Signature, VerifyingKey};
+
+    let key = VerifyingKey::from_sec1_bytes(public_key).ok()?;
+    let signature = match signature.len() {
+        64 => Signature::from_slice(signature),
+        _ => Signature::from_der(signature),
+    }
+    .ok()?;
+    key.verify(message, &signature).ok()
+}
+
+fn verify_p384(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<()> {
+    use p384::ecdsa::{Signature, VerifyingKey};
+
+    let key = VerifyingKey::from_sec1_bytes(public_key).ok()?;
+    let signature = match signature.len() {
+        96 => Signature::from_slice(signature),
+        _ => Signature::from_der(signature),
+    }
+    .ok()?;
+    key.verify(message, &signature).ok()
+}
+
+fn verify_rsa(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<()> {
+    let key = pkcs1::RsaPublicKey::from_der(public_key).ok()?;
+    let key = rsa::RsaPublicKey::new_with_max_size(
+        BigUint::from_bytes_be(key.modulus.as_bytes()),
+        BigUint::from_bytes_be(key.public_exponent.as_bytes()),
+        *RSA_VERIFY_BITS.end(),
+    )
+    .ok()?;
+    if !RSA_VERIFY_BITS.contains(&key.n().bits()) {
+        return None;
+    }
+
+    let signature = pkcs1v15::Signature::try_from(signature).ok()?;
+    pkcs1v15::VerifyingKey::<Sha256>::new(key)
+        .verify(message, &signature)
+        .ok()
+}
+
+impl fmt::Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyAlgorithm {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(s))
+            .ok_or(KeyError::UnsupportedAlgorithm)
+    }
+}
+
+/// Algorithms and sizes of the public keys users may register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPolicy {
+    algorithms: Vec<KeyAlgorithm>,
+    min_rsa_bits: usize,
+}
+
+impl KeyPolicy {
+    /// Minimum RSA modulus size by default, in bits.
+    pub const DEFAULT_MIN_RSA_BITS: usize = 2048;
+    /// Maximum RSA modulus size, in bits. Signatures of larger keys are
+    /// never verified.
+    pub const MAX_RSA_BITS: usize = 8192;
+
+    /// Create a new [`KeyPolicy`] accepting `algorithms`, with RSA moduli
+    /// of at least `min_rsa_bits`.
+    pub fn new(algorithms: Vec<KeyAlgorithm>, min_rsa_bits: usize) -> Self {
+        Self {
+            algorithms,
+            min_rsa_bits,
+        }
+    }
+
+    /// Ensure a key of `algorithm` and `bits` is accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::AlgorithmNotAllowed`], [`KeyError::WeakKey`] or
+    /// [`KeyError::KeyTooLarge`] otherwise.
+    pub fn check(&self, algorithm: KeyAlgorithm, bits: usize) -> Result<()> {
+        if !self.algorithms.contains(&algorithm) {
+            return Err(KeyError::AlgorithmNotAllowed(algorithm).into());
+        }
+        if algorithm == KeyAlgorithm::Rsa && bits < self.min_rsa_bits {
+            return Err(KeyError::WeakKey {
+                bits,
+                min_bits: self.min_rsa_bits,
+            }
+            .into());
+        }
+        if algorithm == KeyAlgorithm::Rsa && bits > Self::MAX_RSA_BITS {
+            return Err(KeyError::KeyTooLarge {
+                bits,
+                max_bits: Self::MAX_RSA_BITS,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        Self::new(KeyAlgorithm::ALL.to_vec(), Self::DEFAULT_MIN_RSA_BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DomainError;
+
+    #[test]
+    fn test_names() {
+        for algorithm in KeyAlgorithm::ALL {
+            assert_eq!(
+                algorithm.as_str().parse::<KeyAlgorithm>().unwrap(),
+                algorithm
+            );
+        }
+        assert_eq!(
+            "p-384".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::P384
+        );
+        assert!("DSA".parse::<KeyAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = KeyPolicy::default();
+        assert!(policy.check(KeyAlgorithm::Ed25519, 256).is_ok());
+        assert!(policy.check(KeyAlgorithm::Rsa, 2048).is_ok());
+        assert!(policy.check(KeyAlgorithm::Rsa, 1024).is_err());
+        assert!(policy.check(KeyAlgorithm::Rsa, 8192).is_ok());
+        assert!(matches!(
+            policy.check(KeyAlgorithm::Rsa, 16384),
+            Err(DomainError::PublicKey(KeyError::KeyTooLarge {
+                bits: 16384,
+                max_bits: 8192,
+            }))
+        ));
+
+        let policy = KeyPolicy::new(vec![KeyAlgorithm::Ed25519], 3072);
+        assert!(policy.check(KeyAlgorithm::P256, 256).is_err());
+        assert!(policy.check(KeyAlgorithm::Rsa, 4096).is_err());
+    }
+}
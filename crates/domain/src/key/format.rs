@@ -0,0 +1,481 @@
+//! Formats public keys are registered and served in.
+//!
+//! Keys are accepted as SPKI PEM, PKCS#1 PEM, JWK (RFC 7517) or OpenSSH
+//! lines, and always decoded to an SPKI, so the RFC 7093 fingerprint of a
+//! key does not depend on the format it was sent in.
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use spki::der::asn1::{BitString, ObjectIdentifier, UintRef};
+use spki::der::{Any, Decode, DecodePem, Encode};
+use spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+
+use crate::error::Result;
+use crate::key::algorithm::{
+    EC_PUBLIC_KEY, ED25519, KeyAlgorithm, RSA_ENCRYPTION, SECP256R1, SECP384R1,
+};
+use crate::key::public_key::KeyError;
+
+const PKCS1_LABEL: &str = "RSA PUBLIC KEY";
+const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Format of a public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// PEM `SubjectPublicKeyInfo`, `BEGIN PUBLIC KEY`.
+    Spki,
+    /// PEM PKCS#1 RSA key, `BEGIN RSA PUBLIC KEY`.
+    Pkcs1,
+    /// JSON Web Key, as exported by WebCrypto.
+    Jwk,
+    /// OpenSSH line, e.g. `ssh-ed25519 AAAA... alice@example.com`.
+    OpenSsh,
+}
+
+impl KeyFormat {
+    /// Detect the format of `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::InvalidFormat`] if `key` looks like none of
+    /// them.
+    pub fn detect(key: &str) -> Result<Self> {
+        let key = key.trim_start();
+        if key.starts_with("-----BEGIN PUBLIC KEY-----") {
+            Ok(KeyFormat::Spki)
+        } else if key.starts_with("-----BEGIN RSA PUBLIC KEY-----") {
+            Ok(KeyFormat::Pkcs1)
+        } else if key.starts_with('{') {
+            Ok(KeyFormat::Jwk)
+        } else if key.starts_with("ssh-") || key.starts_with("ecdsa-sha2-") {
+            Ok(KeyFormat::OpenSsh)
+        } else {
+            Err(KeyError::InvalidFormat.into())
+        }
+    }
+
+    /// Decode `key`, written in this format, as an SPKI.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::InvalidFormat`] if `key` is malformed, and
+    /// [`KeyError::UnsupportedAlgorithm`] for unknown key types.
+    pub fn decode(&self, key: &str) -> Result<SubjectPublicKeyInfoOwned> {
+        match self {
+            KeyFormat::Spki => SubjectPublicKeyInfoOwned::from_pem(key)
+                .map_err(|_| KeyError::InvalidFormat.into()),
+            KeyFormat::Pkcs1 => {
+                let (label, der) =
+                    spki::der::pem::decode_vec(key.trim().as_bytes())
+                        .map_err(|_| KeyError::InvalidFormat)?;
+                if label != PKCS1_LABEL {
+                    return Err(KeyError::InvalidFormat.into());
+                }
+                pkcs1::RsaPublicKey::from_der(&der)
+                    .map_err(|_| KeyError::InvalidFormat)?;
+                spki(RSA_ENCRYPTION, Some(Any::null()), &der)
+            },
+            KeyFormat::Jwk => serde_json::from_str::<Jwk>(key)
+                .map_err(|_| KeyError::InvalidFormat)?
+                .to_spki(),
+            KeyFormat::OpenSsh => openssh(key),
+        }
+    }
+}
+
+/// Public JSON Web Key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    /// `OKP`, `EC` or `RSA`.
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// Private exponent or scalar, only read to refuse private keys.
+    #[serde(default, skip_serializing)]
+    d: Option<String>,
+}
+
+impl Jwk {
+    /// Create an empty [`Jwk`] of type `kty`.
+    fn of_type(kty: &str) -> Self {
+        Self {
+            kty: kty.to_string(),
+            crv: None,
+            x: None,
+            y: None,
+            n: None,
+            e: None,
+            d: None,
+        }
+    }
+
+    /// Create the [`Jwk`] of `public_key`, the subject public key of an
+    /// SPKI of `algorithm`.
+    pub fn new(algorithm: KeyAlgorithm, public_key: &[u8]) -> Result<Self> {
+        let encode = |bytes: &[u8]| Some(URL_SAFE_NO_PAD.encode(bytes));
+
+        Ok(match algorithm {
+            KeyAlgorithm::Ed25519 => Self {
+                crv: Some(algorithm.as_str().to_string()),
+                x: encode(public_key),
+                ..Self::of_type("OKP")
+            },
+            KeyAlgorithm::P256 | KeyAlgorithm::P384 => {
+                let (x, y) = coordinates(public_key)?;
+                Self {
+                    crv: Some(algorithm.as_str().to_string()),
+                    x: encode(x),
+                    y: encode(y),
+                    ..Self::of_type("EC")
+                }
+            },
+            KeyAlgorithm::Rsa => {
+                let key = pkcs1::RsaPublicKey::from_der(public_key)
+                    .map_err(|_| KeyError::InvalidFormat)?;
+                Self {
+                    n: encode(key.modulus.as_bytes()),
+                    e: encode(key.public_exponent.as_bytes()),
+                    ..Self::of_type("RSA")
+                }
+            },
+        })
+    }
+
+    /// Decode the key as an SPKI.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::InvalidFormat`] for private keys, which must
+    /// never be sent.
+    pub fn to_spki(&self) -> Result<SubjectPublicKeyInfoOwned> {
+        if self.d.is_some() {
+            return Err(KeyError::InvalidFormat.into());
+        }
+
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("OKP", Some("Ed25519")) => spki(ED25519, None, &field(&self.x)?),
+            ("EC", Some(crv @ ("P-256" | "P-384"))) => {
+                let curve = if crv == "P-256" { SECP256R1 } else { SECP384R1 };
+                let (x, y) = (field(&self.x)?, field(&self.y)?);
+                if x.len() != y.len() {
+                    return Err(KeyError::InvalidFormat.into());
+                }
+                spki(
+                    EC_PUBLIC_KEY,
+                    Some(curve.into()),
+                    &[&[4], &x[..], &y[..]].concat(),
+                )
+            },
+            ("RSA", _) => rsa(&field(&self.n)?, &field(&self.e)?),
+            _ => Err(KeyError::UnsupportedAlgorithm.into()),
+        }
+    }
+}
+
+/// Decode a base64url member of a JWK.
+fn field(value: &Option<String>) -> Result<Vec<u8>> {
+    value
+        .as_deref()
+        .and_then(|value| URL_SAFE_NO_PAD.decode(value).ok())
+        .ok_or_else(|| KeyError::InvalidFormat.into())
+}
+
+/// Coordinates of an uncompressed elliptic curve point.
+fn coordinates(point: &[u8]) -> Result<(&[u8], &[u8])> {
+    match point.split_first() {
+        Some((4, coordinates)) if coordinates.len() % 2 == 0 => {
+            Ok(coordinates.split_at(coordinates.len() / 2))
+        },
+        _ => Err(KeyError::InvalidFormat.into()),
+    }
+}
+
+/// Build the SPKI of `public_key`.
+fn spki(
+    oid: ObjectIdentifier,
+    parameters: Option<Any>,
+    public_key: &[u8],
+) -> Result<SubjectPublicKeyInfoOwned> {
+    Ok(SubjectPublicKeyInfoOwned {
+        algorithm: AlgorithmIdentifierOwned { oid, parameters },
+        subject_public_key: BitString::from_bytes(public_key)
+            .map_err(|_| KeyError::InvalidFormat)?,
+    })
+}
+
+/// Build the SPKI of the RSA key of big-endian `modulus` and `exponent`.
+fn rsa(modulus: &[u8], exponent: &[u8]) -> Result<SubjectPublicKeyInfoOwned> {
+    let key = pkcs1::RsaPublicKey {
+        modulus: UintRef::new(modulus).map_err(|_| KeyError::InvalidFormat)?,
+        public_exponent: UintRef::new(exponent)
+            .map_err(|_| KeyError::InvalidFormat)?,
+    };
+    let der = key.to_der().map_err(|_| KeyError::InvalidFormat)?;
+    spki(RSA_ENCRYPTION, Some(Any::null()), &der)
+}
+
+/// Length-prefixed strings of an SSH key blob, as defined on RFC4251.
+struct SshReader<'a>(&'a [u8]);
+
+impl<'a> SshReader<'a> {
+    fn string(&mut self) -> Result<&'a [u8]> {
+        let (length, rest) = self
+            .0
+            .split_first_chunk::<4>()
+            .ok_or(KeyError::InvalidFormat)?;
+        let length = u32::from_be_bytes(*length) as usize;
+        if rest.len() < length {
+            return Err(KeyError::InvalidFormat.into());
+        }
+
+        let (string, rest) = rest.split_at(length);
+        self.0 = rest;
+        Ok(string)
+    }
+}
+
+/// Decode an OpenSSH `<type> <base64 blob> [comment]` line.
+fn openssh(key: &str) -> Result<SubjectPublicKeyInfoOwned> {
+    let mut fields = key.split_whitespace();
+    let kind = fields.next().ok_or(KeyError::InvalidFormat)?;
+    let blob = fields
+        .next()
+        .and_then(|blob| STANDARD.decode(blob).ok())
+        .ok_or(KeyError::InvalidFormat)?;
+
+    let mut reader = SshReader(&blob);
+    if reader.string()? != kind.as_bytes() {
+        return Err(KeyError::InvalidFormat.into());
+    }
+    let spki = match kind {
+        "ssh-ed25519" => spki(ED25519, None, reader.string()?)?,
+        "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" => {
+            let (curve, name) = if kind.ends_with("256") {
+                (SECP256R1, "nistp256")
+            } else {
+                (SECP384R1, "nistp384")
+            };
+            if reader.string()? != name.as_bytes() {
+                return Err(KeyError::InvalidFormat.into());
+            }
+            spki(EC_PUBLIC_KEY, Some(curve.into()), reader.string()?)?
+        },
+        "ssh-rsa" => {
+            let exponent = reader.string()?;
+            rsa(reader.string()?, exponent)?
+        },
+        _ => return Err(KeyError::UnsupportedAlgorithm.into()),
+    };
+
+    if reader.0.is_empty() {
+        Ok(spki)
+    } else {
+        Err(KeyError::InvalidFormat.into())
+    }
+}
+
+/// Encode `public_key`, the subject public key of an SPKI of `algorithm`,
+/// as the `publicKeyMultibase` of a Multikey: base58btc of the key
+/// prefixed by its multicodec. Elliptic curve points are compressed.
+pub fn multibase(
+    algorithm: KeyAlgorithm,
+    public_key: &[u8],
+) -> Result<String> {
+    let key = match algorithm {
+        KeyAlgorithm::Ed25519 => [&[0xed, 0x01], public_key].concat(),
+        KeyAlgorithm::P256 | KeyAlgorithm::P384 => {
+            let (x, y) = coordinates(public_key)?;
+            let codec = if algorithm == KeyAlgorithm::P256 {
+                0x80
+            } else {
+                0x81
+            };
+            let parity = y.last().map_or(0, |last| last & 1);
+            [&[codec, 0x24, 2 + parity], x].concat()
+        },
+        KeyAlgorithm::Rsa => [&[0x85, 0x24], public_key].concat(),
+    };
+
+    Ok(format!("z{}", base58(&key)))
+}
+
+/// Encode `bytes` in base58, with the Bitcoin alphabet.
+fn base58(bytes: &[u8]) -> String {
+    // Little-endian digits.
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = u32::from(*byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| BASE58_ALPHABET[*digit as usize] as char),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DomainError;
+    use crate::key::pem::PemPublicKey;
+
+    const ED25519_SPKI: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEA7mv9TLuO5+Yx8h8cYCap10JpEtzDj6mFK7VjZk1g5uE=
+-----END PUBLIC KEY-----";
+    const ED25519_OPENSSH: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIO5r/Uy7jufmMfIfHGAmqddCaRLcw4+phSu1Y2ZNYObh alice@example.com";
+    const ED25519_JWK: &str = r#"{"kty": "OKP", "crv": "Ed25519", "x": "7mv9TLuO5-Yx8h8cYCap10JpEtzDj6mFK7VjZk1g5uE"}"#;
+
+    const P256_SPKI: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEMytvLTisxz2wecEjsU+IDmuj6kV0
+wZLOD/njPZ7uOxO3NfiJITIlVJ6T/pEg3EhJsfrO4Eoci7B7HPoZsximjg==
+-----END PUBLIC KEY-----";
+    const P256_OPENSSH: &str = "ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTYAAABBBDMrby04rMc9sHnBI7FPiA5ro+pFdMGSzg/54z2e7jsTtzX4iSEyJVSek/6RINxISbH6zuBKHIuwexz6GbMYpo4=";
+    const P256_JWK: &str = r#"{"kty": "EC", "crv": "P-256", "x": "MytvLTisxz2wecEjsU-IDmuj6kV0wZLOD_njPZ7uOxM", "y": "tzX4iSEyJVSek_6RINxISbH6zuBKHIuwexz6GbMYpo4", "key_ops": ["verify"], "ext": true}"#;
+
+    const RSA_SPKI: &str = "-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAxXqSTDvV9/08D+vHHOYp
+pL6sFEcDm2yKIUzkfcgH4CTPsPM1gyCh3bmhJUSvkGL4yNR/iQtp82mSeSSuGpQO
+YXAQ5zjJSClzk/B66blisKKotfLIKZmkh4qFeTH/KFbC7uU8kOTPj2Id3AvSJme7
+YBkgNCRStgM8VPdUSi3ImHjLpIlxAbdXLsD0RFVJ/IAwoIpFZJRQC4lijJAIvcB+
+2ffqwHuFcl2mDIrpVsG25tX8ZusmBngkb1izbs6yoMU8I1HWzAiQzmWFaYZmRPEH
+fQLl5TwEeRxJEd1eGuc3PQBuo71W07Gjl3DvtspyHNa0vP+UdFycKBuvT9VJbpgf
+zQIDAQAB
+-----END PUBLIC KEY-----";
+    const RSA_PKCS1: &str = "-----BEGIN RSA PUBLIC KEY-----
+MIIBCgKCAQEAxXqSTDvV9/08D+vHHOYppL6sFEcDm2yKIUzkfcgH4CTPsPM1gyCh
+3bmhJUSvkGL4yNR/iQtp82mSeSSuGpQOYXAQ5zjJSClzk/B66blisKKotfLIKZmk
+h4qFeTH/KFbC7uU8kOTPj2Id3AvSJme7YBkgNCRStgM8VPdUSi3ImHjLpIlxAbdX
+LsD0RFVJ/IAwoIpFZJRQC4lijJAIvcB+2ffqwHuFcl2mDIrpVsG25tX8ZusmBngk
+b1izbs6yoMU8I1HWzAiQzmWFaYZmRPEHfQLl5TwEeRxJEd1eGuc3PQBuo71W07Gj
+l3DvtspyHNa0vP+UdFycKBuvT9VJbpgfzQIDAQAB
+-----END RSA PUBLIC KEY-----
+";
+    const RSA_OPENSSH: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQDFepJMO9X3/TwP68cc5imkvqwURwObbIohTOR9yAfgJM+w8zWDIKHduaElRK+QYvjI1H+JC2nzaZJ5JK4alA5hcBDnOMlIKXOT8HrpuWKwoqi18sgpmaSHioV5Mf8oVsLu5TyQ5M+PYh3cC9ImZ7tgGSA0JFK2AzxU91RKLciYeMukiXEBt1cuwPREVUn8gDCgikVklFALiWKMkAi9wH7Z9+rAe4VyXaYMiulWwbbm1fxm6yYGeCRvWLNuzrKgxTwjUdbMCJDOZYVphmZE8Qd9AuXlPAR5HEkR3V4a5zc9AG6jvVbTsaOXcO+2ynIc1rS8/5R0XJwoG69P1UlumB/N alice@example.com";
+
+    fn parse(key: &str) -> PemPublicKey {
+        PemPublicKey::parse(key.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(KeyFormat::detect(RSA_SPKI).unwrap(), KeyFormat::Spki);
+        assert_eq!(KeyFormat::detect(RSA_PKCS1).unwrap(), KeyFormat::Pkcs1);
+        assert_eq!(KeyFormat::detect(P256_JWK).unwrap(), KeyFormat::Jwk);
+        assert_eq!(
+            KeyFormat::detect(&format!("\n{ED25519_OPENSSH}")).unwrap(),
+            KeyFormat::OpenSsh
+        );
+        assert!(KeyFormat::detect("AAAAC3NzaC1lZDI1NTE5").is_err());
+    }
+
+    #[test]
+    fn test_same_fingerprint() {
+        for (spki, others) in [
+            (ED25519_SPKI, &[ED25519_OPENSSH, ED25519_JWK][..]),
+            (P256_SPKI, &[P256_OPENSSH, P256_JWK]),
+            (RSA_SPKI, &[RSA_PKCS1, RSA_OPENSSH]),
+        ] {
+            let expected = parse(spki);
+            for other in others {
+                let key = parse(other);
+                assert_eq!(
+                    key.fingerprint().unwrap(),
+                    expected.fingerprint().unwrap()
+                );
+                assert_eq!(key.algorithm(), expected.algorithm());
+                // Other formats are stored as SPKI PEM.
+                assert_eq!(key.as_str().trim(), spki);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jwk() {
+        for key in [ED25519_SPKI, P256_SPKI, RSA_SPKI] {
+            let key = parse(key);
+            let jwk = serde_json::to_string(&key.to_jwk().unwrap()).unwrap();
+            assert_eq!(
+                parse(&jwk).fingerprint().unwrap(),
+                key.fingerprint().unwrap()
+            );
+        }
+        assert_eq!(
+            serde_json::to_value(parse(P256_SPKI).to_jwk().unwrap()).unwrap(),
+            serde_json::json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "MytvLTisxz2wecEjsU-IDmuj6kV0wZLOD_njPZ7uOxM",
+                "y": "tzX4iSEyJVSek_6RINxISbH6zuBKHIuwexz6GbMYpo4",
+            })
+        );
+    }
+
+    #[test]
+    fn test_multibase() {
+        assert_eq!(base58(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58(&[0, 0, 1]), "112");
+
+        assert_eq!(
+            parse(ED25519_SPKI).to_multibase().unwrap(),
+            "z6MkvVxapiLgiBEdATQZ8pFwWqk97B6sB9QzgGirtc89N78C"
+        );
+        assert_eq!(
+            parse(P256_SPKI).to_multibase().unwrap(),
+            "zDnaeTsiFatHoXZMFvZw6KedNa2K9ujrpVL3oZxEdxcGd8NYa"
+        );
+        assert!(parse(RSA_SPKI).to_multibase().unwrap().starts_with("z4MX"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let invalid = |key: &str| {
+            matches!(
+                PemPublicKey::parse(key.to_string()),
+                Err(DomainError::PublicKey(KeyError::InvalidFormat))
+            )
+        };
+
+        // Private keys are refused.
+        assert!(invalid(
+            r#"{"kty": "OKP", "crv": "Ed25519", "x": "7mv9TLuO5-Yx8h8cYCap10JpEtzDj6mFK7VjZk1g5uE", "d": "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A"}"#
+        ));
+        assert!(invalid(r#"{"kty": "EC", "crv": "P-256", "x": "AAAA"}"#));
+        // Type of the line does not match the one of the blob.
+        assert!(invalid(&ED25519_OPENSSH.replacen(
+            "ssh-ed25519",
+            "ssh-rsa",
+            1
+        )));
+        assert!(invalid("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5"));
+        assert!(invalid(&RSA_PKCS1.replace("RSA PUBLIC", "PUBLIC")));
+
+        assert!(matches!(
+            PemPublicKey::parse(r#"{"kty": "oct", "k": "AAAA"}"#.to_string()),
+            Err(DomainError::PublicKey(KeyError::UnsupportedAlgorithm))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
 //! Public key domain.
 
+pub mod algorithm;
+pub mod format;
 pub mod pem;
 pub mod public_key;
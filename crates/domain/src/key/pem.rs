@@ -5,10 +5,12 @@ use std::str::FromStr;
 
 use sha2::{Digest, Sha256};
 use spki::SubjectPublicKeyInfoOwned;
-use spki::der::{DecodePem, Encode};
+use spki::der::pem::LineEnding;
+use spki::der::{Encode, EncodePem};
 
 use crate::error::{DomainError, Result};
-use crate::key::public_key::KeyError;
+use crate::key::algorithm::KeyAlgorithm;
+use crate::key::format::{self, Jwk, KeyFormat};
 
 /// Value object of PEM fingerprint.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -44,20 +46,36 @@ impl fmt::Display for PemFingerprint {
 pub struct PemPublicKey {
     raw_pem: String,
     spki: SubjectPublicKeyInfoOwned,
+    algorithm: KeyAlgorithm,
+    bits: usize,
 }
 
 impl PemPublicKey {
     /// Converts a [`String`] into a valid [`PemPublicKey`].
     ///
+    /// The key may be in any [`KeyFormat`]. Keys not already in SPKI PEM
+    /// are converted to it.
+    ///
     /// # Errors
     ///
-    /// Returns `Err` if the string is not a valid RSA or ECDSA PEM
+    /// Returns `Err` if the string is not a valid Ed25519, ECDSA or RSA
     /// public key.
-    pub fn parse(pem: String) -> Result<Self> {
-        let spki = SubjectPublicKeyInfoOwned::from_pem(&pem)
-            .map_err(|_| KeyError::InvalidFormat)?;
+    pub fn parse(key: String) -> Result<Self> {
+        let format = KeyFormat::detect(&key)?;
+        let spki = format.decode(&key)?;
+        let (algorithm, bits) = KeyAlgorithm::classify(&spki)?;
+        let raw_pem = if format == KeyFormat::Spki {
+            key
+        } else {
+            spki.to_pem(LineEnding::LF).map_err(|_| DomainError::Der)?
+        };
 
-        Ok(Self { raw_pem: pem, spki })
+        Ok(Self {
+            raw_pem,
+            spki,
+            algorithm,
+            bits,
+        })
     }
 
     /// Calculate SPKI fingerprint as defined on RFC7093.
@@ -71,6 +89,40 @@ impl PemPublicKey {
         Ok(PemFingerprint(hex::encode(&hasher.finalize()[..20])))
     }
 
+    /// Returns the algorithm of the key.
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the size of the key, in bits.
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Verify `signature` of `message` by this key.
+    ///
+    /// See [`KeyAlgorithm::verify`] for the expected signature schemes.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        self.algorithm.verify(
+            self.spki.subject_public_key.raw_bytes(),
+            message,
+            signature,
+        )
+    }
+
+    /// Returns the key as a JSON Web Key.
+    pub fn to_jwk(&self) -> Result<Jwk> {
+        Jwk::new(self.algorithm, self.spki.subject_public_key.raw_bytes())
+    }
+
+    /// Returns the key as the `publicKeyMultibase` of a Multikey.
+    pub fn to_multibase(&self) -> Result<String> {
+        format::multibase(
+            self.algorithm,
+            self.spki.subject_public_key.raw_bytes(),
+        )
+    }
+
     /// Returns the decoded SPKI structure of raw key.
     pub fn spki(&self) -> &SubjectPublicKeyInfoOwned {
         &self.spki
@@ -113,7 +165,10 @@ impl TryFrom<String> for PemPublicKey {
 
 #[cfg(test)]
 mod tests {
+    use spki::der::asn1::ObjectIdentifier;
+
     use super::*;
+    use crate::key::public_key::KeyError;
 
     const TEST_RSA_PUB_KEY: &str = "-----BEGIN PUBLIC KEY-----
 MIGeMA0GCSqGSIb3DQEBAQUAA4GMADCBiAKBgHgX8gieCwHlUYtM3gcq9h/sDaqg
@@ -147,4 +202,114 @@ rfVXEFg2JmRE/rNm2RfhVp+fMjeHQNq6vLrEVg4r84vzUevkSVMvcZ0LxYtGMzVe
         assert_eq!(fp1.as_str().len(), 40);
         assert!(fp1.as_str().chars().all(|c| c.is_ascii_hexdigit()));
     }
+
+    /// RSA-2048 key, and its signature of `(request-target): post /inbox`.
+    const TEST_RSA_2048_PUB_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAr90Xb8sBh3sFGqeuWkxT
+uy2zQQ4QZAgkfuwTou8P/HvsN3nN/0ohuj2JNih6iAzJzdvL1nktJ4aYU7sdHfjW
+tlNo4/X8SJR6TIRZTABKmqjtHMflBxYmLPwn9P7jmIIxHR1v92qBJD7DBNjPamPv
+bIXAmYlcCHSYheXff9pfg11xNTr2tHeoq8ru5YzHxsdlLbpXmumAbucKm0NMztXQ
+jlbEFGCda48729vUef1aalCE0RNpO7TcSF7CdRHHl5taZGzUT/lIrFn6woW1xw/w
+iTuTyTEOLeOF47wnu8s/XP9dBkZiyIC5vKqq6gIHPYD9jCNX3sqSALN8PbVgUSrv
+7QIDAQAB
+-----END PUBLIC KEY-----";
+    const TEST_RSA_2048_SIGNATURE: &str = "abeTXwUu0eNgDrczbr1tG3K+AQncjAIB4IwbwgwV8995KpfNtzBFtmPqFHeDDYS5bcGjRS+RKTzsRs7AGyHxWo9plvpDU0dvcfrMGjyR+9OhNW884ZstptnjXSxC8GGufaDxCjsny8C1zYuGhAdn7KpRIcIym4IXTILSy+Jtw7mOiiP98x2U/KyLSubXkxfEHPJf2noiS1VS5A/mEzuQ4a5p0ZofVN7KEqQ5nEb0kZOyxbBW+ThYebgLg0DqOn8X59PPIFEzsgKAagKSxoUXNQFdq+fKTSvW4RaNgENIqfUFqbG0v5rfykHfODjJbi7hDMy5O+9MGGO0Ainb/rxHxw==";
+
+    /// SPKI PEM of `key`, of algorithm `oid` on `curve`.
+    fn pem(
+        oid: ObjectIdentifier,
+        curve: Option<ObjectIdentifier>,
+        key: &[u8],
+    ) -> String {
+        use spki::AlgorithmIdentifierOwned;
+        use spki::der::asn1::{Any, BitString};
+
+        SubjectPublicKeyInfoOwned {
+            algorithm: AlgorithmIdentifierOwned {
+                oid,
+                parameters: curve
+                    .map(|curve| Any::encode_from(&curve).unwrap()),
+            },
+            subject_public_key: BitString::from_bytes(key).unwrap(),
+        }
+        .to_pem(LineEnding::LF)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_algorithms() {
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD;
+        use p256::ecdsa::signature::Signer;
+
+        use crate::key::algorithm::{
+            EC_PUBLIC_KEY, ED25519, SECP256R1, SECP384R1,
+        };
+
+        let message = b"(request-target): post /inbox";
+
+        let ed25519 = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let key = PemPublicKey::parse(pem(
+            ED25519,
+            None,
+            ed25519.verifying_key().as_bytes(),
+        ))
+        .unwrap();
+        assert_eq!(key.algorithm(), KeyAlgorithm::Ed25519);
+        let signature = ed25519.sign(message).to_bytes();
+        assert!(key.verify(message, &signature).is_ok());
+        assert!(key.verify(b"tampered", &signature).is_err());
+
+        // P-256 signatures as ASN.1, P-384 ones as fixed-length `r || s`.
+        let p256 = p256::ecdsa::SigningKey::from_slice(&[7; 32]).unwrap();
+        let key = PemPublicKey::parse(pem(
+            EC_PUBLIC_KEY,
+            Some(SECP256R1),
+            p256.verifying_key().to_encoded_point(false).as_bytes(),
+        ))
+        .unwrap();
+        assert_eq!((key.algorithm(), key.bits()), (KeyAlgorithm::P256, 256));
+        let signature: p256::ecdsa::Signature = p256.sign(message);
+        let signature = signature.to_der();
+        assert!(key.verify(message, signature.as_bytes()).is_ok());
+        assert!(key.verify(b"tampered", signature.as_bytes()).is_err());
+
+        let p384 = p384::ecdsa::SigningKey::from_slice(&[7; 48]).unwrap();
+        let key = PemPublicKey::parse(pem(
+            EC_PUBLIC_KEY,
+            Some(SECP384R1),
+            p384.verifying_key().to_encoded_point(false).as_bytes(),
+        ))
+        .unwrap();
+        assert_eq!((key.algorithm(), key.bits()), (KeyAlgorithm::P384, 384));
+        let signature: p384::ecdsa::Signature = p384.sign(message);
+        let signature = signature.to_bytes();
+        assert!(key.verify(message, &signature).is_ok());
+        assert!(key.verify(b"tampered", &signature).is_err());
+
+        let key =
+            PemPublicKey::parse(TEST_RSA_2048_PUB_KEY.to_string()).unwrap();
+        assert_eq!((key.algorithm(), key.bits()), (KeyAlgorithm::Rsa, 2048));
+        let signature = STANDARD.decode(TEST_RSA_2048_SIGNATURE).unwrap();
+        assert!(key.verify(message, &signature).is_ok());
+        assert!(key.verify(b"tampered", &signature).is_err());
+
+        // The modulus of the test key starts with 0x78, so has 1023 bits,
+        // too few to verify signatures.
+        let weak = PemPublicKey::parse(TEST_RSA_PUB_KEY.to_string()).unwrap();
+        assert_eq!((weak.algorithm(), weak.bits()), (KeyAlgorithm::Rsa, 1023));
+        assert!(weak.verify(message, &signature[..128]).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_algorithm() {
+        // X25519 keys only agree on secrets.
+        let x25519 = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VuAyEAHEX1dAk3xdeDiUu+JFTcFnplZ+7xdwNd6F8TkaWbsVI=
+-----END PUBLIC KEY-----";
+        assert!(matches!(
+            PemPublicKey::parse(x25519.to_string()),
+            Err(DomainError::PublicKey(KeyError::UnsupportedAlgorithm))
+        ));
+    }
 }
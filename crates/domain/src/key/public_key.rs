@@ -4,13 +4,24 @@ use chrono::{DateTime, Utc};
 
 use crate::error::Result;
 use crate::identity::id::UserId;
+use crate::key::algorithm::KeyAlgorithm;
 use crate::key::pem::{PemFingerprint, PemPublicKey};
 
 /// Logical errors related to public keys.
 #[derive(Debug, thiserror::Error)]
 pub enum KeyError {
-    #[error("pem is not pkcs1 nor pkcs8")]
+    #[error("key is not a valid spki, pkcs1, jwk nor openssh public key")]
     InvalidFormat,
+    #[error("key algorithm is not supported")]
+    UnsupportedAlgorithm,
+    #[error("{0} keys are not allowed")]
+    AlgorithmNotAllowed(KeyAlgorithm),
+    #[error("key has {bits} bits, at least {min_bits} are required")]
+    WeakKey { bits: usize, min_bits: usize },
+    #[error("key has {bits} bits, at most {max_bits} are supported")]
+    KeyTooLarge { bits: usize, max_bits: usize },
+    #[error("signature is invalid")]
+    InvalidSignature,
 }
 
 /// Public key linked to a [`User`].